@@ -484,6 +484,44 @@ trait Foo {
 struct S;
 impl Foo for S {
     <|>fn valid(some: u32) -> bool { false }
+}",
+        )
+    }
+
+    #[test]
+    fn test_default_methods_are_reindented() {
+        check_assist(
+            add_missing_default_members,
+            "
+trait Foo {
+    fn foo(&self) {
+        if true {
+            bar();
+        }
+    }
+}
+mod m {
+    struct S;
+    impl super::Foo for S {<|>
+    }
+}",
+            "
+trait Foo {
+    fn foo(&self) {
+        if true {
+            bar();
+        }
+    }
+}
+mod m {
+    struct S;
+    impl super::Foo for S {
+        <|>fn foo(&self) {
+            if true {
+                bar();
+            }
+        }
+    }
 }",
         )
     }
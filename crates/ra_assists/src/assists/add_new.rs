@@ -1,10 +1,14 @@
 use format_buf::format;
 use hir::{db::HirDatabase, FromSource, InFile};
 use join_to_string::join;
+use ra_fmt::leading_indent;
 use ra_syntax::{
     ast::{
-        self, AstNode, NameOwner, StructKind, TypeAscriptionOwner, TypeParamsOwner, VisibilityOwner,
+        self, edit::IndentLevel, AstNode, NameOwner, StructKind, TypeAscriptionOwner,
+        TypeParamsOwner, VisibilityOwner,
     },
+    SourceFile,
+    SyntaxKind::WHITESPACE,
     TextUnit, T,
 };
 use std::fmt::Write;
@@ -29,7 +33,6 @@ use crate::{Assist, AssistCtx, AssistId};
 // impl<T: Clone> Ctx<T> {
 //     fn new(data: T) -> Self { Self { data } }
 // }
-//
 // ```
 pub(crate) fn add_new(ctx: AssistCtx<impl HirDatabase>) -> Option<Assist> {
     let strukt = ctx.find_node_at_offset::<ast::StructDef>()?;
@@ -43,64 +46,95 @@ pub(crate) fn add_new(ctx: AssistCtx<impl HirDatabase>) -> Option<Assist> {
     // Return early if we've found an existing new fn
     let impl_block = find_struct_impl(&ctx, &strukt)?;
 
-    ctx.add_assist(AssistId("add_new"), "add new fn", |edit| {
-        edit.target(strukt.syntax().text_range());
+    let mut buf = String::with_capacity(512);
 
-        let mut buf = String::with_capacity(512);
+    let vis = strukt.visibility().map(|v| format!("{} ", v.syntax()));
+    let vis = vis.as_ref().map(String::as_str).unwrap_or("");
+    write!(&mut buf, "{}fn new(", vis).unwrap();
 
-        if impl_block.is_some() {
-            buf.push('\n');
-        }
+    join(field_list.fields().filter_map(|f| {
+        Some(format!("{}: {}", f.name()?.syntax().text(), f.ascribed_type()?.syntax().text()))
+    }))
+    .separator(", ")
+    .to_buf(&mut buf);
 
-        let vis = strukt.visibility().map(|v| format!("{} ", v.syntax()));
-        let vis = vis.as_ref().map(String::as_str).unwrap_or("");
-        write!(&mut buf, "    {}fn new(", vis).unwrap();
+    buf.push_str(") -> Self { Self {");
 
-        join(field_list.fields().filter_map(|f| {
-            Some(format!("{}: {}", f.name()?.syntax().text(), f.ascribed_type()?.syntax().text()))
-        }))
+    join(field_list.fields().filter_map(|f| Some(f.name()?.syntax().text())))
         .separator(", ")
+        .surround_with(" ", " ")
         .to_buf(&mut buf);
 
-        buf.push_str(") -> Self { Self {");
-
-        join(field_list.fields().filter_map(|f| Some(f.name()?.syntax().text())))
-            .separator(", ")
-            .surround_with(" ", " ")
-            .to_buf(&mut buf);
-
-        buf.push_str("} }");
-
-        let (start_offset, end_offset) = impl_block
-            .and_then(|impl_block| {
-                buf.push('\n');
-                let start = impl_block
-                    .syntax()
-                    .descendants_with_tokens()
-                    .find(|t| t.kind() == T!['{'])?
-                    .text_range()
-                    .end();
-
-                Some((start, TextUnit::from_usize(1)))
-            })
-            .unwrap_or_else(|| {
-                buf = generate_impl_text(&strukt, &buf);
-                let start = strukt.syntax().text_range().end();
-
-                (start, TextUnit::from_usize(3))
-            });
+    buf.push_str("} }");
+
+    // The generated code is reformatted to the indentation of the impl, as the
+    // field types are copied verbatim.
+    let (start_offset, text, cursor_offset) = match &impl_block {
+        Some(impl_block) => {
+            let start = impl_block
+                .syntax()
+                .descendants_with_tokens()
+                .find(|t| t.kind() == T!['{'])?
+                .text_range()
+                .end();
+            let indent = leading_indent(impl_block.syntax()).unwrap_or_default();
+            let level = IndentLevel(IndentLevel::from_node(impl_block.syntax()).0 + 1);
+            let fn_def = level.reformat(parse::<ast::FnDef>(&buf)?);
+
+            let mut text = format!("\n{}    {}", indent, fn_def.syntax());
+            let cursor_offset = TextUnit::of_str(&text);
+            text.push('\n');
+            if impl_block.item_list()?.impl_items().next().is_none() {
+                text.push_str(&indent);
+            }
+            (start, text, cursor_offset)
+        }
+        None => {
+            let indent = leading_indent(strukt.syntax()).unwrap_or_default();
+            let level = IndentLevel::from_node(strukt.syntax());
+            let impl_def =
+                level.reformat(parse::<ast::ImplBlock>(&generate_impl_text(&strukt, &buf))?);
+            let fn_end = impl_def
+                .syntax()
+                .descendants()
+                .find_map(ast::FnDef::cast)?
+                .syntax()
+                .text_range()
+                .end();
+
+            let prefix = format!("\n\n{}", indent);
+            let cursor_offset =
+                TextUnit::of_str(&prefix) + fn_end - impl_def.syntax().text_range().start();
+            let mut text = format!("{}{}", prefix, impl_def.syntax());
+            let followed_by_newline = strukt
+                .syntax()
+                .last_token()
+                .and_then(|it| it.next_token())
+                .map_or(false, |it| it.kind() == WHITESPACE && it.text().starts_with('\n'));
+            if !followed_by_newline {
+                text.push('\n');
+            }
+            (strukt.syntax().text_range().end(), text, cursor_offset)
+        }
+    };
 
-        edit.set_cursor(start_offset + TextUnit::of_str(&buf) - end_offset);
-        edit.insert(start_offset, buf);
+    ctx.add_assist(AssistId("add_new"), "add new fn", |edit| {
+        edit.target(strukt.syntax().text_range());
+        edit.set_cursor(start_offset + cursor_offset);
+        edit.insert(start_offset, text);
     })
 }
 
+fn parse<N: AstNode>(text: &str) -> Option<N> {
+    SourceFile::parse(text).tree().syntax().descendants().find_map(N::cast)
+}
+
 // Generates the surrounding `impl Type { <code> }` including type and lifetime
 // parameters
 fn generate_impl_text(strukt: &ast::StructDef, code: &str) -> String {
     let type_params = strukt.type_param_list();
     let mut buf = String::with_capacity(code.len());
-    buf.push_str("\n\nimpl");
+    buf.push_str("impl");
     if let Some(type_params) = &type_params {
         format!(buf, "{}", type_params.syntax());
     }
@@ -116,7 +150,7 @@ fn generate_impl_text(strukt: &ast::StructDef, code: &str) -> String {
         join(lifetime_params.chain(type_params)).surround_with("<", ">").to_buf(&mut buf);
     }
 
-    format!(&mut buf, " {{\n{}\n}}\n", code);
+    format!(&mut buf, " {{\n{}\n}}", code);
 
     buf
 }
@@ -429,4 +463,57 @@ impl<T> Source<T> {
 "##,
         );
     }
+
+    #[test]
+    fn test_add_new_in_nested_module() {
+        check_assist(
+            add_new,
+            r"
+mod m {
+    struct Foo {
+        bar: (
+            i32,
+            u32,
+        ),<|>
+    }
+}
+",
+            r"
+mod m {
+    struct Foo {
+        bar: (
+            i32,
+            u32,
+        ),
+    }
+
+    impl Foo {
+        fn new(bar: (
+            i32,
+            u32,
+        )) -> Self { Self { bar } }<|>
+    }
+}
+",
+        );
+        check_assist(
+            add_new,
+            r"
+mod m {
+    struct Foo {<|>}
+
+    impl Foo {}
+}
+",
+            r"
+mod m {
+    struct Foo {}
+
+    impl Foo {
+        fn new() -> Self { Self {  } }<|>
+    }
+}
+",
+        );
+    }
 }
@@ -101,8 +101,8 @@ pub(crate) fn convert_to_guarded_return(ctx: AssistCtx<impl HirDatabase>) -> Opt
             None => {
                 // If.
                 let early_expression = &(early_expression.syntax().to_string() + ";");
-                let new_expr = if_indent_level
-                    .increase_indent(make::if_expression(&cond_expr, early_expression));
+                let new_expr =
+                    if_indent_level.reformat(make::if_expression(&cond_expr, early_expression));
                 replace(new_expr.syntax(), &then_block, &parent_block, &if_expr)
             }
             Some((path, bound_ident)) => {
@@ -132,7 +132,7 @@ pub(crate) fn convert_to_guarded_return(ctx: AssistCtx<impl HirDatabase>) -> Opt
                     make::bind_pat(make::name(&bound_ident.syntax().to_string())).into(),
                     Some(match_expr),
                 );
-                let let_stmt = if_indent_level.increase_indent(let_stmt);
+                let let_stmt = if_indent_level.reformat(let_stmt);
                 replace(let_stmt.syntax(), &then_block, &parent_block, &if_expr)
             }
         };
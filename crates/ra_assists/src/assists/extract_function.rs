@@ -0,0 +1,1114 @@
+use either::Either;
+use format_buf::format;
+use hir::{db::HirDatabase, HirDisplay, Mutability, SourceAnalyzer, Type, TypeRef};
+use itertools::Itertools;
+use ra_fmt::leading_indent;
+use ra_syntax::{
+    ast::{self, AstNode, NameOwner, SelfParamKind},
+    AstPtr, NodeOrToken,
+    SyntaxKind::{
+        BLOCK, BREAK_EXPR, CONTINUE_EXPR, EXPR_STMT, FIELD_EXPR, FN_DEF, FOR_EXPR, IMPL_BLOCK,
+        INDEX_EXPR, ITEM_LIST, LAMBDA_EXPR, LOOP_EXPR, METHOD_CALL_EXPR, RETURN_EXPR, TRAIT_DEF,
+        TRY_EXPR, WHILE_EXPR,
+    },
+    SyntaxNode, TextRange,
+};
+
+use crate::{Assist, AssistCtx, AssistId};
+
+// Assist: extract_function
+//
+// Extracts the selected statements or expression into a new function.
+//
+// ```
+// fn main() {
+//     let n = 1;
+//     <|>let m = n + 2;
+//     let k = m * n;<|>
+//     let g = 3;
+// }
+// ```
+// ->
+// ```
+// fn main() {
+//     let n = 1;
+//     fun_name(n);
+//     let g = 3;
+// }
+//
+// fn fun_name(n: i32) {
+//     let m = n + 2;
+//     let k = m * n;
+// }
+// ```
+pub(crate) fn extract_function(ctx: AssistCtx<impl HirDatabase>) -> Option<Assist> {
+    if ctx.frange.range.is_empty() {
+        return None;
+    }
+    let node = match ctx.covering_element() {
+        NodeOrToken::Node(it) => it,
+        NodeOrToken::Token(it) => it.parent(),
+    };
+    let body = FunctionBody::from_range(&node, ctx.frange.range)?;
+    let fn_def = node.ancestors().find_map(ast::FnDef::cast)?;
+    let root = fn_def.syntax().ancestors().last()?;
+
+    let db = ctx.db;
+    let analyzer = ctx.source_analyzer(fn_def.syntax(), None);
+    let range = body.text_range();
+
+    let self_param = fn_def.param_list().and_then(|it| it.self_param());
+    let self_usages: Vec<ast::PathExpr> =
+        body.descendants().filter_map(ast::PathExpr::cast).filter(is_self_path).collect();
+    let self_kind = match (&self_param, self_usages.is_empty()) {
+        (_, true) => None,
+        (Some(_), false) => {
+            if self_usages.iter().any(|it| is_mutated(db, &analyzer, it)) {
+                Some(SelfParamKind::MutRef)
+            } else {
+                Some(SelfParamKind::Ref)
+            }
+        }
+        (None, false) => return None,
+    };
+    // Associated functions without `self` are put after the impl or trait
+    let container = fn_def
+        .syntax()
+        .parent()
+        .filter(|it| it.kind() == ITEM_LIST)
+        .and_then(|it| it.parent())
+        .filter(|it| it.kind() == IMPL_BLOCK || it.kind() == TRAIT_DEF)
+        .filter(|_| self_kind.is_none());
+    let (generic_params, where_clause) = generics(&fn_def, container.as_ref());
+
+    let params = params(db, &analyzer, &root, &body)?;
+    let outputs = outputs(db, &analyzer, &body)?;
+    let mut exits = Vec::new();
+    for node in body.nodes() {
+        collect_exits(&node, false, &mut exits);
+    }
+    let flow = ControlFlow::of(&fn_def, &exits)?;
+
+    let value_ty = match body.tail() {
+        Some(tail) => {
+            let ty = analyzer.type_of(db, tail)?;
+            Some(type_text(db, &ty)?).filter(|it| it != "()")
+        }
+        None => match outputs.as_slice() {
+            [] => None,
+            [var] => Some(var.ty.clone()),
+            vars => Some(format!("({})", vars.iter().map(|it| &it.ty).join(", "))),
+        },
+    };
+    match (&flow, &body) {
+        (ControlFlow::None, _) | (ControlFlow::Try { .. }, _) => (),
+        (_, FunctionBody::Expr(_)) => return None,
+        _ if value_ty.is_some() => return None,
+        _ => (),
+    }
+
+    ctx.add_assist(AssistId("extract_function"), "extract into function", |edit| {
+        edit.target(range);
+
+        let text = root.text().to_string();
+        let replacements = body_replacements(&params, &exits, &flow);
+        let render = |range: TextRange| {
+            let mut buf = String::new();
+            let mut offset = range.start();
+            for (r, replacement) in replacements.iter().filter(|(r, _)| r.is_subrange(&range)) {
+                buf.push_str(&text[offset.to_usize()..r.start().to_usize()]);
+                buf.push_str(replacement);
+                offset = r.end();
+            }
+            buf.push_str(&text[offset.to_usize()..range.end().to_usize()]);
+            buf
+        };
+
+        let mut chunks = Vec::new();
+        let tail = body.tail().map(|it| render(it.syntax().text_range()));
+        if let FunctionBody::Statements { stmts, .. } = &body {
+            if let Some(last) = stmts.last() {
+                let end = last.syntax().text_range().end();
+                chunks.push(render(TextRange::from_to(range.start(), end)));
+            }
+        }
+        match &flow {
+            ControlFlow::Try { ok_wrapper, .. } => {
+                let value = match (tail, outputs.as_slice()) {
+                    (Some(tail), _) => tail,
+                    (None, []) => "()".to_string(),
+                    (None, [var]) => var.name.clone(),
+                    (None, vars) => format!("({})", vars.iter().map(|it| &it.name).join(", ")),
+                };
+                chunks.push(format!("{}({})", ok_wrapper, value));
+            }
+            ControlFlow::Return { ret_ty: Some(_) } => chunks.push("None".to_string()),
+            ControlFlow::Return { ret_ty: None } | ControlFlow::Break | ControlFlow::Continue => {
+                chunks.push("false".to_string())
+            }
+            ControlFlow::None => match (tail, outputs.as_slice()) {
+                (Some(tail), _) => chunks.push(tail),
+                (None, []) => (),
+                (None, [var]) => chunks.push(var.name.clone()),
+                (None, vars) => {
+                    chunks.push(format!("({})", vars.iter().map(|it| &it.name).join(", ")))
+                }
+            },
+        }
+
+        let anchor = container.clone().unwrap_or_else(|| fn_def.syntax().clone());
+        let indent = leading_indent(&anchor).unwrap_or_else(|| "".into());
+        let old_indent = leading_indent(&body.nodes()[0]).unwrap_or_else(|| "".into());
+        let new_indent = format!("{}    ", indent);
+
+        let mut fn_params = Vec::new();
+        match self_kind {
+            Some(SelfParamKind::MutRef) => fn_params.push("&mut self".to_string()),
+            Some(_) => fn_params.push("&self".to_string()),
+            None => (),
+        }
+        fn_params
+            .extend(params.iter().map(|it| format!("{}: {}{}", it.name, it.kind.prefix(), it.ty)));
+
+        let ret_ty = match &flow {
+            ControlFlow::Try { ok_wrapper: "Ok", err_ty } => Some(format!(
+                "Result<{}, {}>",
+                value_ty.as_ref().map_or("()", String::as_str),
+                err_ty
+            )),
+            ControlFlow::Try { .. } => {
+                Some(format!("Option<{}>", value_ty.as_ref().map_or("()", String::as_str)))
+            }
+            ControlFlow::Return { ret_ty: Some(ret_ty) } => Some(format!("Option<{}>", ret_ty)),
+            ControlFlow::Return { ret_ty: None } | ControlFlow::Break | ControlFlow::Continue => {
+                Some("bool".to_string())
+            }
+            ControlFlow::None => value_ty,
+        };
+
+        let mut buf = String::new();
+        format!(buf, "\n\n{}fn fun_name{}({})", indent, generic_params, fn_params.join(", "));
+        if let Some(ret_ty) = ret_ty {
+            format!(buf, " -> {}", ret_ty);
+        }
+        buf.push_str(&where_clause);
+        buf.push_str(" {\n");
+        for chunk in chunks {
+            for (i, line) in chunk.lines().enumerate() {
+                let line = if i == 0 { line } else { line.trim_start_matches(old_indent.as_str()) };
+                if !line.is_empty() {
+                    buf.push_str(&new_indent);
+                    buf.push_str(line);
+                }
+                buf.push('\n');
+            }
+        }
+        format!(buf, "{}}}", indent);
+        edit.insert(anchor.text_range().end(), buf);
+
+        let args = params.iter().map(|it| format!("{}{}", it.kind.prefix(), it.name)).join(", ");
+        let callee = if self_kind.is_some() { "self.fun_name" } else { "fun_name" };
+        let mut call = format!("{}({})", callee, args);
+        if let ControlFlow::Try { .. } = flow {
+            call.push('?');
+        }
+        let call = match (&flow, &body) {
+            (ControlFlow::Return { ret_ty: Some(_) }, _) => {
+                format!("if let Some(value) = {} {{ return value; }}", call)
+            }
+            (ControlFlow::Return { ret_ty: None }, _) => format!("if {} {{ return; }}", call),
+            (ControlFlow::Break, _) => format!("if {} {{ break; }}", call),
+            (ControlFlow::Continue, _) => format!("if {} {{ continue; }}", call),
+            (_, FunctionBody::Expr(_)) => call,
+            (_, FunctionBody::Statements { tail: Some(_), .. }) => call,
+            _ => match outputs.as_slice() {
+                [] => format!("{};", call),
+                [var] => format!("let {}{} = {};", var.mut_prefix(), var.name, call),
+                vars => format!(
+                    "let ({}) = {};",
+                    vars.iter().map(|it| format!("{}{}", it.mut_prefix(), it.name)).join(", "),
+                    call
+                ),
+            },
+        };
+        edit.replace(range, call);
+    })
+}
+
+/// The selected code: either a single expression or a run of statements of a
+/// block, possibly followed by the block's tail expression.
+enum FunctionBody {
+    Expr(ast::Expr),
+    Statements { stmts: Vec<ast::Stmt>, tail: Option<ast::Expr> },
+}
+
+impl FunctionBody {
+    fn from_range(node: &SyntaxNode, range: TextRange) -> Option<FunctionBody> {
+        let expr = node
+            .ancestors()
+            .take_while(|it| it.text_range() == range)
+            .filter_map(ast::Expr::cast)
+            .last();
+        // Whole statements and tail expressions are handled below, together
+        // with the rest of the block.
+        let is_in_block = |expr: &ast::Expr| match expr.syntax().parent() {
+            Some(parent) => parent.kind() == BLOCK || parent.kind() == EXPR_STMT,
+            None => false,
+        };
+        if let Some(expr) = expr.filter(|it| !is_in_block(it)) {
+            return match expr {
+                // There is nothing to gain from extracting these.
+                ast::Expr::Literal(_) | ast::Expr::PathExpr(_) => None,
+                _ => Some(FunctionBody::Expr(expr)),
+            };
+        }
+
+        let block = node.ancestors().find_map(ast::Block::cast)?;
+        let overlaps = |it: &SyntaxNode| {
+            let r = it.text_range();
+            r.start() < range.end() && range.start() < r.end()
+        };
+        let covered = |it: &SyntaxNode| it.text_range().is_subrange(&range);
+        let stmts: Vec<ast::Stmt> = block.statements().filter(|it| overlaps(it.syntax())).collect();
+        let tail = block.expr().filter(|it| overlaps(it.syntax()));
+        if !stmts
+            .iter()
+            .map(|it| it.syntax())
+            .chain(tail.as_ref().map(|it| it.syntax()))
+            .all(covered)
+        {
+            return None;
+        }
+        if stmts.is_empty() && tail.is_none() {
+            return None;
+        }
+        Some(FunctionBody::Statements { stmts, tail })
+    }
+
+    fn nodes(&self) -> Vec<SyntaxNode> {
+        match self {
+            FunctionBody::Expr(expr) => vec![expr.syntax().clone()],
+            FunctionBody::Statements { stmts, tail } => stmts
+                .iter()
+                .map(|it| it.syntax().clone())
+                .chain(tail.as_ref().map(|it| it.syntax().clone()))
+                .collect(),
+        }
+    }
+
+    fn descendants(&self) -> impl Iterator<Item = SyntaxNode> {
+        self.nodes().into_iter().flat_map(|it| it.descendants())
+    }
+
+    /// The expression whose value the selected code evaluates to.
+    fn tail(&self) -> Option<&ast::Expr> {
+        match self {
+            FunctionBody::Expr(expr) => Some(expr),
+            FunctionBody::Statements { tail, .. } => tail.as_ref(),
+        }
+    }
+
+    fn text_range(&self) -> TextRange {
+        let nodes = self.nodes();
+        let start = nodes[0].text_range().start();
+        let end = nodes[nodes.len() - 1].text_range().end();
+        TextRange::from_to(start, end)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ParamKind {
+    Value,
+    SharedRef,
+    MutRef,
+}
+
+impl ParamKind {
+    fn prefix(self) -> &'static str {
+        match self {
+            ParamKind::Value => "",
+            ParamKind::SharedRef => "&",
+            ParamKind::MutRef => "&mut ",
+        }
+    }
+}
+
+/// A local defined outside of the selection and used inside of it.
+struct Param {
+    name: String,
+    ty: String,
+    kind: ParamKind,
+    usages: Vec<ast::PathExpr>,
+}
+
+fn params(
+    db: &impl HirDatabase,
+    analyzer: &SourceAnalyzer,
+    root: &SyntaxNode,
+    body: &FunctionBody,
+) -> Option<Vec<Param>> {
+    let range = body.text_range();
+    let mut locals: Vec<(AstPtr<ast::Pat>, Vec<ast::PathExpr>)> = Vec::new();
+    for path_expr in body.descendants().filter_map(ast::PathExpr::cast) {
+        let name_ref = match path_expr.path().filter(|it| it.qualifier().is_none()) {
+            Some(path) => match path.segment().and_then(|it| it.name_ref()) {
+                Some(it) => it,
+                None => continue,
+            },
+            None => continue,
+        };
+        let ptr = match analyzer.resolve_local_name(&name_ref).map(|it| it.ptr()) {
+            Some(Either::Left(ptr)) => ptr,
+            _ => continue,
+        };
+        if ptr.syntax_node_ptr().range().is_subrange(&range) {
+            continue;
+        }
+        match locals.iter_mut().find(|(it, _)| *it == ptr) {
+            Some((_, usages)) => usages.push(path_expr),
+            None => locals.push((ptr, vec![path_expr])),
+        }
+    }
+
+    let mut res = Vec::new();
+    for (ptr, usages) in locals {
+        let bind_pat = match ptr.to_node(root) {
+            ast::Pat::BindPat(it) => it,
+            _ => return None,
+        };
+        let ty = analyzer.type_of_pat(db, &ast::Pat::from(bind_pat.clone()))?;
+        let kind = param_kind(db, analyzer, &bind_pat, &ty, &usages, range);
+        let name = bind_pat.name()?.text().to_string();
+        res.push(Param { name, ty: type_text(db, &ty)?, kind, usages });
+    }
+    Some(res)
+}
+
+fn param_kind(
+    db: &impl HirDatabase,
+    analyzer: &SourceAnalyzer,
+    bind_pat: &ast::BindPat,
+    ty: &Type,
+    usages: &[ast::PathExpr],
+    range: TextRange,
+) -> ParamKind {
+    if ty.is_mutable_reference() {
+        return ParamKind::Value;
+    }
+    if usages.iter().any(|it| is_mutated(db, analyzer, it)) {
+        return ParamKind::MutRef;
+    }
+    let used_after =
+        analyzer.find_all_refs(bind_pat).iter().any(|it| it.range.start() >= range.end());
+    if used_after && !ty.is_copy(db) {
+        ParamKind::SharedRef
+    } else {
+        ParamKind::Value
+    }
+}
+
+/// A local defined inside of the selection and used after it.
+struct OutputVar {
+    name: String,
+    ty: String,
+    is_mut: bool,
+}
+
+impl OutputVar {
+    fn mut_prefix(&self) -> &'static str {
+        if self.is_mut {
+            "mut "
+        } else {
+            ""
+        }
+    }
+}
+
+fn outputs(
+    db: &impl HirDatabase,
+    analyzer: &SourceAnalyzer,
+    body: &FunctionBody,
+) -> Option<Vec<OutputVar>> {
+    let range = body.text_range();
+    let mut res = Vec::new();
+    for bind_pat in body.descendants().filter_map(ast::BindPat::cast) {
+        let used_after =
+            analyzer.find_all_refs(&bind_pat).iter().any(|it| it.range.start() >= range.end());
+        if !used_after {
+            continue;
+        }
+        let ty = analyzer.type_of_pat(db, &ast::Pat::from(bind_pat.clone()))?;
+        let name = bind_pat.name()?.text().to_string();
+        res.push(OutputVar { name, ty: type_text(db, &ty)?, is_mut: bind_pat.is_mutable() });
+    }
+    Some(res)
+}
+
+/// How the selected code exits the enclosing function or loop early.
+enum ControlFlow {
+    None,
+    Return { ret_ty: Option<String> },
+    Break,
+    Continue,
+    Try { ok_wrapper: &'static str, err_ty: String },
+}
+
+impl ControlFlow {
+    fn of(fn_def: &ast::FnDef, exits: &[SyntaxNode]) -> Option<ControlFlow> {
+        let kind = match exits.first() {
+            Some(it) => it.kind(),
+            None => return Some(ControlFlow::None),
+        };
+        if exits.iter().any(|it| it.kind() != kind) {
+            return None;
+        }
+
+        let ret_ty = fn_def.ret_type().and_then(|it| it.type_ref());
+        let flow = match kind {
+            RETURN_EXPR => {
+                let returns_value = |it: &SyntaxNode| it.first_child().is_some();
+                if ret_ty.is_none() && exits.iter().any(returns_value) {
+                    return None;
+                }
+                ControlFlow::Return { ret_ty: ret_ty.map(|it| it.syntax().to_string()) }
+            }
+            BREAK_EXPR | CONTINUE_EXPR => {
+                // Labeled breaks and breaks with a value are not supported.
+                let is_plain = |it: &SyntaxNode| it.children_with_tokens().count() == 1;
+                if !exits.iter().all(is_plain) {
+                    return None;
+                }
+                if kind == BREAK_EXPR {
+                    ControlFlow::Break
+                } else {
+                    ControlFlow::Continue
+                }
+            }
+            TRY_EXPR => {
+                let path = match ret_ty? {
+                    ast::TypeRef::PathType(it) => it.path()?,
+                    _ => return None,
+                };
+                let segment = path.segment()?;
+                let mut type_args = segment.type_arg_list()?.type_args();
+                type_args.next()?;
+                match segment.name_ref()?.text().as_str() {
+                    "Result" => ControlFlow::Try {
+                        ok_wrapper: "Ok",
+                        err_ty: type_args.next()?.syntax().to_string(),
+                    },
+                    "Option" => ControlFlow::Try { ok_wrapper: "Some", err_ty: String::new() },
+                    _ => return None,
+                }
+            }
+            _ => unreachable!(),
+        };
+        Some(flow)
+    }
+}
+
+/// Collects `return`, `?`, and `break`/`continue` expressions which leave the
+/// selected code, skipping nested closures and functions.
+fn collect_exits(node: &SyntaxNode, in_loop: bool, acc: &mut Vec<SyntaxNode>) {
+    match node.kind() {
+        FN_DEF | LAMBDA_EXPR => return,
+        RETURN_EXPR | TRY_EXPR => acc.push(node.clone()),
+        BREAK_EXPR | CONTINUE_EXPR if !in_loop => acc.push(node.clone()),
+        _ => (),
+    }
+    let in_loop = in_loop
+        || match node.kind() {
+            LOOP_EXPR | WHILE_EXPR | FOR_EXPR => true,
+            _ => false,
+        };
+    for child in node.children() {
+        collect_exits(&child, in_loop, acc);
+    }
+}
+
+/// Computes the edits to the selected code which keep it valid inside the new
+/// function: dereferencing parameters passed by reference and turning early
+/// exits into returns.
+fn body_replacements(
+    params: &[Param],
+    exits: &[SyntaxNode],
+    flow: &ControlFlow,
+) -> Vec<(TextRange, String)> {
+    let mut res = Vec::new();
+    for param in params.iter().filter(|it| it.kind != ParamKind::Value) {
+        for usage in &param.usages {
+            let parent = match usage.syntax().parent() {
+                Some(it) => it,
+                None => continue,
+            };
+            let is_receiver = parent.first_child().as_ref() == Some(usage.syntax());
+            match parent.kind() {
+                METHOD_CALL_EXPR | FIELD_EXPR | INDEX_EXPR if is_receiver => {}
+                _ if ast::RefExpr::can_cast(parent.kind()) => {
+                    res.push((parent.text_range(), param.name.clone()))
+                }
+                _ => res.push((usage.syntax().text_range(), format!("*{}", param.name))),
+            }
+        }
+    }
+    for exit in exits {
+        match flow {
+            ControlFlow::Return { ret_ty: Some(_) } => match exit.first_child() {
+                Some(expr) => {
+                    let range = expr.text_range();
+                    res.push((TextRange::offset_len(range.start(), 0.into()), "Some(".into()));
+                    res.push((TextRange::offset_len(range.end(), 0.into()), ")".into()));
+                }
+                None => (),
+            },
+            ControlFlow::Return { ret_ty: None } | ControlFlow::Break | ControlFlow::Continue => {
+                res.push((exit.text_range(), "return true".into()))
+            }
+            ControlFlow::Try { .. } | ControlFlow::None => (),
+        }
+    }
+    res.sort_by_key(|(range, _)| (range.start(), range.end()));
+    res
+}
+
+/// The type as written in the signature of the new function, if it can be
+/// written at all.
+fn type_text(db: &impl HirDatabase, ty: &Type) -> Option<String> {
+    if !ty.is_nameable() {
+        return None;
+    }
+    Some(ty.display(db).to_string())
+}
+
+/// The generic parameters and the where clause of the new function: those of
+/// the enclosing function, and of the enclosing impl or trait if the new
+/// function is put after it.
+fn generics(fn_def: &ast::FnDef, container: Option<&SyntaxNode>) -> (String, String) {
+    let mut param_lists = Vec::new();
+    let mut where_clauses = Vec::new();
+    if let Some(container) = container {
+        if let Some(impl_block) = ast::ImplBlock::cast(container.clone()) {
+            param_lists.extend(impl_block.type_param_list());
+            where_clauses.extend(impl_block.where_clause());
+        }
+        if let Some(trait_def) = ast::TraitDef::cast(container.clone()) {
+            param_lists.extend(trait_def.type_param_list());
+            where_clauses.extend(trait_def.where_clause());
+        }
+    }
+    param_lists.extend(fn_def.type_param_list());
+    where_clauses.extend(fn_def.where_clause());
+
+    // Lifetimes have to come first
+    let lifetime_params = param_lists.iter().flat_map(|it| it.lifetime_params());
+    let type_params = param_lists.iter().flat_map(|it| it.type_params());
+    let params: Vec<String> = lifetime_params
+        .map(|it| it.syntax().to_string())
+        .chain(type_params.map(|it| it.syntax().to_string()))
+        .collect();
+    let predicates: Vec<String> = where_clauses
+        .iter()
+        .flat_map(|it| it.predicates())
+        .map(|it| it.syntax().to_string())
+        .collect();
+
+    let generic_params =
+        if params.is_empty() { String::new() } else { format!("<{}>", params.join(", ")) };
+    let where_clause = if predicates.is_empty() {
+        String::new()
+    } else {
+        format!(" where {}", predicates.join(", "))
+    };
+    (generic_params, where_clause)
+}
+
+fn is_self_path(path_expr: &ast::PathExpr) -> bool {
+    path_expr.syntax().text() == "self"
+}
+
+/// Checks whether the place `path_expr` refers to is assigned to or borrowed
+/// mutably, including by a method taking `&mut self`.
+fn is_mutated(db: &impl HirDatabase, analyzer: &SourceAnalyzer, path_expr: &ast::PathExpr) -> bool {
+    let mut place = path_expr.syntax().clone();
+    while let Some(parent) = place.parent() {
+        let is_projection = (parent.kind() == FIELD_EXPR || parent.kind() == INDEX_EXPR)
+            && parent.first_child().as_ref() == Some(&place);
+        if !is_projection {
+            break;
+        }
+        place = parent;
+    }
+    let parent = match place.parent() {
+        Some(it) => it,
+        None => return false,
+    };
+    if let Some(bin_expr) = ast::BinExpr::cast(parent.clone()) {
+        return bin_expr.op_kind().map_or(false, |it| it.is_assignment())
+            && bin_expr.lhs().map_or(false, |it| it.syntax() == &place);
+    }
+    if let Some(ref_expr) = ast::RefExpr::cast(parent.clone()) {
+        return ref_expr.is_mut();
+    }
+    let method_call = match ast::MethodCallExpr::cast(parent) {
+        Some(it) if it.expr().map_or(false, |it| it.syntax() == &place) => it,
+        _ => return false,
+    };
+    let method = match analyzer.resolve_method_call(&method_call) {
+        Some(it) if it.has_self_param(db) => it,
+        _ => return false,
+    };
+    match method.params(db).first() {
+        Some(TypeRef::Reference(_, Mutability::Mut)) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::helpers::{check_assist_range, check_assist_range_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn extract_expression() {
+        check_assist_range(
+            extract_function,
+            r#"
+fn foo() -> i32 {
+    let a = 1;
+    let b = 2;
+    <|>a + b<|>
+}
+"#,
+            r#"
+fn foo() -> i32 {
+    let a = 1;
+    let b = 2;
+    fun_name(a, b)
+}
+
+fn fun_name(a: i32, b: i32) -> i32 {
+    a + b
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn extract_argument_expression() {
+        check_assist_range(
+            extract_function,
+            r#"
+fn bar(x: i32) {}
+fn foo() {
+    let a = 1;
+    bar(<|>a * 2<|>);
+}
+"#,
+            r#"
+fn bar(x: i32) {}
+fn foo() {
+    let a = 1;
+    bar(fun_name(a));
+}
+
+fn fun_name(a: i32) -> i32 {
+    a * 2
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn extract_passes_mutated_locals_by_mut_ref() {
+        check_assist_range(
+            extract_function,
+            r#"
+fn foo() {
+    let mut n = 1;
+    <|>n += 1;<|>
+    let m = n + 1;
+}
+"#,
+            r#"
+fn foo() {
+    let mut n = 1;
+    fun_name(&mut n);
+    let m = n + 1;
+}
+
+fn fun_name(n: &mut i32) {
+    *n += 1;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn extract_passes_non_copy_locals_used_later_by_ref() {
+        check_assist_range(
+            extract_function,
+            r#"
+struct S;
+impl S {
+    fn get(&self) -> i32 { 1 }
+}
+fn foo() {
+    let s = S;
+    <|>let n = s.get();<|>
+    let m = s.get();
+}
+"#,
+            r#"
+struct S;
+impl S {
+    fn get(&self) -> i32 { 1 }
+}
+fn foo() {
+    let s = S;
+    fun_name(&s);
+    let m = s.get();
+}
+
+fn fun_name(s: &S) {
+    let n = s.get();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn extract_returns_locals_used_later() {
+        check_assist_range(
+            extract_function,
+            r#"
+fn foo() {
+    let n = 1;
+    <|>let k = n * n;
+    let mut m = k + 1;<|>
+    let g = k + m;
+}
+"#,
+            r#"
+fn foo() {
+    let n = 1;
+    let (k, mut m) = fun_name(n);
+    let g = k + m;
+}
+
+fn fun_name(n: i32) -> (i32, i32) {
+    let k = n * n;
+    let mut m = k + 1;
+    (k, m)
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn extract_with_return() {
+        check_assist_range(
+            extract_function,
+            r#"
+fn foo() -> i32 {
+    let n = 1;
+    <|>if n > 0 {
+        return n;
+    }<|>
+    0
+}
+"#,
+            r#"
+fn foo() -> i32 {
+    let n = 1;
+    if let Some(value) = fun_name(n) { return value; }
+    0
+}
+
+fn fun_name(n: i32) -> Option<i32> {
+    if n > 0 {
+        return Some(n);
+    }
+    None
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn extract_with_break() {
+        check_assist_range(
+            extract_function,
+            r#"
+fn foo() {
+    loop {
+        let n = 1;
+        <|>if n > 0 {
+            break;
+        }<|>
+        let m = 2;
+    }
+}
+"#,
+            r#"
+fn foo() {
+    loop {
+        let n = 1;
+        if fun_name(n) { break; }
+        let m = 2;
+    }
+}
+
+fn fun_name(n: i32) -> bool {
+    if n > 0 {
+        return true;
+    }
+    false
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn extract_with_try() {
+        check_assist_range(
+            extract_function,
+            r#"
+fn foo() -> Result<(), Error> {
+    <|>bar()?;
+    baz()?;<|>
+    Ok(())
+}
+"#,
+            r#"
+fn foo() -> Result<(), Error> {
+    fun_name()?;
+    Ok(())
+}
+
+fn fun_name() -> Result<(), Error> {
+    bar()?;
+    baz()?;
+    Ok(())
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn extract_method() {
+        check_assist_range(
+            extract_function,
+            r#"
+struct S { f: i32 }
+impl S {
+    fn foo(&self) -> i32 {
+        <|>self.f + 1<|>
+    }
+}
+"#,
+            r#"
+struct S { f: i32 }
+impl S {
+    fn foo(&self) -> i32 {
+        self.fun_name()
+    }
+
+    fn fun_name(&self) -> i32 {
+        self.f + 1
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn extract_function_from_impl_without_self() {
+        check_assist_range(
+            extract_function,
+            r#"
+struct S;
+impl S {
+    fn foo() -> i32 {
+        <|>1 + 1<|>
+    }
+}
+"#,
+            r#"
+struct S;
+impl S {
+    fn foo() -> i32 {
+        fun_name()
+    }
+}
+
+fn fun_name() -> i32 {
+    1 + 1
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn extract_passes_locals_mutated_by_methods_by_mut_ref() {
+        check_assist_range(
+            extract_function,
+            r#"
+struct V;
+impl V {
+    fn push(&mut self) {}
+    fn len(&self) -> i32 { 0 }
+}
+fn foo() {
+    let mut v = V;
+    <|>v.push();<|>
+    v.len();
+}
+"#,
+            r#"
+struct V;
+impl V {
+    fn push(&mut self) {}
+    fn len(&self) -> i32 { 0 }
+}
+fn foo() {
+    let mut v = V;
+    fun_name(&mut v);
+    v.len();
+}
+
+fn fun_name(v: &mut V) {
+    v.push();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn extract_passes_mut_locals_used_by_shared_methods_by_ref() {
+        check_assist_range(
+            extract_function,
+            r#"
+struct S;
+impl S {
+    fn get(&self) -> i32 { 1 }
+}
+fn foo() {
+    let mut s = S;
+    <|>let n = s.get();<|>
+    let m = s.get();
+}
+"#,
+            r#"
+struct S;
+impl S {
+    fn get(&self) -> i32 { 1 }
+}
+fn foo() {
+    let mut s = S;
+    fun_name(&s);
+    let m = s.get();
+}
+
+fn fun_name(s: &S) {
+    let n = s.get();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn extract_function_keeps_generics() {
+        check_assist_range(
+            extract_function,
+            r#"
+trait Clone {
+    fn clone(&self) -> Self;
+}
+fn foo<'a, T>(t: &'a T) -> T where T: Clone {
+    <|>t.clone()<|>
+}
+"#,
+            r#"
+trait Clone {
+    fn clone(&self) -> Self;
+}
+fn foo<'a, T>(t: &'a T) -> T where T: Clone {
+    fun_name(t)
+}
+
+fn fun_name<'a, T>(t: &T) -> T where T: Clone {
+    t.clone()
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn extract_function_from_generic_impl_without_self() {
+        check_assist_range(
+            extract_function,
+            r#"
+struct S<T>(T);
+impl<T> S<T> {
+    fn foo(t: T) -> S<T> {
+        <|>S(t)<|>
+    }
+}
+"#,
+            r#"
+struct S<T>(T);
+impl<T> S<T> {
+    fn foo(t: T) -> S<T> {
+        fun_name(t)
+    }
+}
+
+fn fun_name<T>(t: T) -> S<T> {
+    S(t)
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn extract_with_closure_types_not_applicable() {
+        check_assist_range_not_applicable(
+            extract_function,
+            r#"
+fn foo() -> i32 {
+    let f = |x: i32| x;
+    <|>f(1) + f(2)<|>
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn extract_partial_statements_not_applicable() {
+        check_assist_range_not_applicable(
+            extract_function,
+            r#"
+fn foo() {
+    let n = <|>1;
+    let m<|> = 2;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn extract_break_and_return_not_applicable() {
+        check_assist_range_not_applicable(
+            extract_function,
+            r#"
+fn foo() -> i32 {
+    loop {
+        <|>if true { break; }
+        if false { return 1; }<|>
+    }
+}
+"#,
+        );
+    }
+}
@@ -60,7 +60,7 @@ pub(crate) fn fill_match_arms(ctx: AssistCtx<impl HirDatabase>) -> Option<Assist
             let arms = variants
                 .filter_map(build_pat)
                 .map(|pat| make::match_arm(iter::once(pat), make::expr_unit()));
-            indent_level.increase_indent(make::match_arm_list(arms))
+            indent_level.reformat(make::match_arm_list(arms))
         };
 
         edit.target(match_expr.syntax().text_range());
@@ -103,7 +103,7 @@ fn build_pat(var: ast::EnumVariant) -> Option<ast::Pat> {
             make::tuple_struct_pat(path, pats).into()
         }
         ast::StructKind::Record(field_list) => {
-            let pats = field_list.fields().map(|f| make::bind_pat(f.name().unwrap()).into());
+            let pats = field_list.fields().map(|f| make::bind_pat(f.name().unwrap()));
             make::record_pat(path, pats).into()
         }
         ast::StructKind::Unit => make::path_pat(path).into(),
@@ -192,7 +192,6 @@ struct Ctx<T: Clone> {
 impl<T: Clone> Ctx<T> {
     fn new(data: T) -> Self { Self { data } }
 }
-
 "#####,
     )
 }
@@ -251,6 +250,33 @@ fn main() {
     )
 }
 
+#[test]
+fn doctest_extract_function() {
+    check(
+        "extract_function",
+        r#####"
+fn main() {
+    let n = 1;
+    <|>let m = n + 2;
+    let k = m * n;<|>
+    let g = 3;
+}
+"#####,
+        r#####"
+fn main() {
+    let n = 1;
+    fun_name(n);
+    let g = 3;
+}
+
+fn fun_name(n: i32) {
+    let m = n + 2;
+    let k = m * n;
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_fill_match_arms() {
     check(
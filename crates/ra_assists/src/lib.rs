@@ -116,6 +116,7 @@ mod assists {
     mod move_guard;
     mod move_bounds;
     mod early_return;
+    mod extract_function;
 
     pub(crate) fn all<DB: HirDatabase>() -> &'static [fn(AssistCtx<DB>) -> Option<Assist>] {
         &[
@@ -148,6 +149,7 @@ mod assists {
             raw_string::make_usual_string,
             raw_string::remove_hash,
             early_return::convert_to_guarded_return,
+            extract_function::extract_function,
         ]
     }
 }
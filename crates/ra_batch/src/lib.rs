@@ -5,8 +5,9 @@ use std::{collections::HashSet, error::Error, path::Path};
 use rustc_hash::FxHashMap;
 
 use crossbeam_channel::{unbounded, Receiver};
-use ra_db::{CrateGraph, FileId, SourceRootId};
+use ra_db::{CrateGraph, ExternSourceId, FileId, SourceRootId};
 use ra_ide::{AnalysisChange, AnalysisHost, FeatureFlags};
+use ra_proc_macro::ProcMacroClient;
 use ra_project_model::{get_rustc_cfg_options, PackageRoot, ProjectWorkspace};
 use ra_vfs::{RootEntry, Vfs, VfsChange, VfsTask, Watch};
 use ra_vfs_glob::RustPackageFilterBuilder;
@@ -50,12 +51,19 @@ pub fn load_cargo(root: &Path) -> Result<(AnalysisHost, FxHashMap<SourceRootId,
         opts
     };
 
-    let (crate_graph, _crate_names) =
-        ws.to_crate_graph(&default_cfg_options, &mut |path: &Path| {
+    let extern_source_roots =
+        roots.iter().map(|&r| (vfs.root2path(r), ExternSourceId(r.0))).collect();
+
+    let (crate_graph, _crate_names) = ws.to_crate_graph(
+        &default_cfg_options,
+        &extern_source_roots,
+        &ProcMacroClient::dummy(),
+        &mut |path: &Path| {
             let vfs_file = vfs.load(path);
             log::debug!("vfs file {:?} -> {:?}", path, vfs_file);
             vfs_file.map(vfs_file_to_id)
-        });
+        },
+    );
     log::debug!("crate graph: {:?}", crate_graph);
 
     let source_roots = roots
@@ -13,6 +13,8 @@ SUBCOMMANDS:
     analysis-stats
     highlight
     parse
+    query
+    ssr
     symbols";
 
 pub const ANALYSIS_BENCH_HELP: &str = "ra_cli-analysis-bench
@@ -57,6 +59,35 @@ FLAGS:
     -h, --help       Prints help information
     -r, --rainbow";
 
+pub const QUERY_HELP: &str = "ra-cli-query
+
+USAGE:
+    ra_cli query [FLAGS] [OPTIONS] <QUERY>
+
+FLAGS:
+    -h, --help    Prints help information
+
+OPTIONS:
+    --path <PATH>    Project to search, defaults to the current directory
+
+ARGS:
+    <QUERY>    A syntax tree query like `(METHOD_CALL_EXPR name_ref: \"unwrap\")`";
+
+pub const SSR_HELP: &str = "ra-cli-ssr
+
+USAGE:
+    ra_cli ssr [FLAGS] [OPTIONS] <RULE>
+
+FLAGS:
+    -h, --help    Prints help information
+        --apply   Writes the changes to disk instead of printing them
+
+OPTIONS:
+    --path <PATH>    Project to search, defaults to the current directory
+
+ARGS:
+    <RULE>    A rule like `foo($a, $b) ==>> bar($b, $a)`";
+
 pub const SYMBOLS_HELP: &str = "ra-cli-symbols
 
 USAGE:
@@ -4,6 +4,8 @@ mod analysis_stats;
 mod analysis_bench;
 mod help;
 mod progress_report;
+mod query;
+mod ssr;
 
 use std::{error::Error, fmt::Write, io::Read};
 
@@ -143,6 +145,39 @@ fn main() -> Result<()> {
             matches.finish().or_else(handle_extra_flags)?;
             analysis_bench::run(verbose, path.as_ref(), op)?;
         }
+        "ssr" => {
+            if matches.contains(["-h", "--help"]) {
+                eprintln!("{}", help::SSR_HELP);
+                return Ok(());
+            }
+            let apply = matches.contains("--apply");
+            let path: String = matches.opt_value_from_str("--path")?.unwrap_or_default();
+            let rule = {
+                let mut trailing = matches.free()?;
+                if trailing.len() != 1 {
+                    eprintln!("{}", help::SSR_HELP);
+                    Err("Invalid flags")?;
+                }
+                trailing.pop().unwrap()
+            };
+            ssr::run(&rule, path.as_ref(), apply)?;
+        }
+        "query" => {
+            if matches.contains(["-h", "--help"]) {
+                eprintln!("{}", help::QUERY_HELP);
+                return Ok(());
+            }
+            let path: String = matches.opt_value_from_str("--path")?.unwrap_or_default();
+            let query = {
+                let mut trailing = matches.free()?;
+                if trailing.len() != 1 {
+                    eprintln!("{}", help::QUERY_HELP);
+                    Err("Invalid flags")?;
+                }
+                trailing.pop().unwrap()
+            };
+            query::run(&query, path.as_ref())?;
+        }
         _ => eprintln!("{}", help::GLOBAL_HELP),
     }
     Ok(())
@@ -0,0 +1,42 @@
+//! Runs a syntax tree query over all files of a project, see `ra_syntax::Query`.
+
+use std::path::Path;
+
+use ra_db::{SourceDatabase, SourceDatabaseExt};
+use ra_syntax::{AstNode, Query};
+
+use crate::Result;
+
+pub(crate) fn run(query: &str, path: &Path) -> Result<()> {
+    let query = Query::parse(query)?;
+    let (host, roots) = ra_batch::load_cargo(path)?;
+    let db = host.raw_database();
+
+    for (source_root_id, project_root) in roots {
+        if !project_root.is_member() {
+            continue;
+        }
+        let source_root = db.source_root(source_root_id);
+        for file_id in source_root.walk() {
+            let parse = db.parse(file_id);
+            let matches = query.matches(parse.tree().syntax());
+            if matches.is_empty() {
+                continue;
+            }
+            let path = db.file_relative_path(file_id).to_path(project_root.path());
+            let text = db.file_text(file_id);
+            let text = text.as_str();
+            for m in matches {
+                println!("{} {:?} {}", path.display(), m.range, first_line(&text[m.range]));
+                for (name, range) in m.captures {
+                    println!("    @{} {:?} {}", name, range, first_line(&text[range]));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn first_line(text: &str) -> &str {
+    text.lines().next().unwrap_or_default()
+}
@@ -0,0 +1,31 @@
+//! Applies a structural search replace rule to a whole project.
+
+use std::{fs, path::Path};
+
+use ra_db::SourceDatabaseExt;
+
+use crate::Result;
+
+pub(crate) fn run(rule: &str, path: &Path, apply: bool) -> Result<()> {
+    let (host, roots) = ra_batch::load_cargo(path)?;
+    let db = host.raw_database();
+    let change = host.analysis().structural_search_replace(rule)??;
+
+    for edit in change.source_file_edits {
+        let source_root = db.file_source_root(edit.file_id);
+        let rel_path = db.file_relative_path(edit.file_id);
+        let path = match roots.get(&source_root) {
+            Some(root) => rel_path.to_path(root.path()),
+            None => continue,
+        };
+        let text = db.file_text(edit.file_id);
+        let new_text = edit.edit.apply(&text);
+        if apply {
+            fs::write(&path, new_text)?;
+        } else {
+            println!("--- {}", path.display());
+            println!("{}", new_text);
+        }
+    }
+    Ok(())
+}
@@ -6,12 +6,18 @@
 //! actual IO. See `vfs` and `project_model` in the `ra_lsp_server` crate for how
 //! actual IO is done and lowered to input.
 
-use std::{fmt, str::FromStr};
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
 
 use ra_cfg::CfgOptions;
 use ra_syntax::SmolStr;
 use rustc_hash::FxHashMap;
 use rustc_hash::FxHashSet;
+use tt::TokenExpander;
 
 use crate::{RelativePath, RelativePathBuf};
 
@@ -83,13 +89,53 @@ pub struct CrateGraph {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct CrateId(pub u32);
 
+/// Identifies a source root which lies outside of the crate's own sources,
+/// like the `OUT_DIR` of a build script.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ExternSourceId(pub u32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ProcMacroId(pub u32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProcMacroKind {
+    CustomDerive,
+    FuncLike,
+    Attr,
+}
+
+/// A procedural macro exported by a proc-macro crate.
+///
+/// The actual expansion happens outside of rust-analyzer (see the
+/// `ra_proc_macro` crate), `expander` is just a handle to it.
+#[derive(Debug, Clone)]
+pub struct ProcMacro {
+    pub name: SmolStr,
+    pub kind: ProcMacroKind,
+    pub expander: Arc<dyn TokenExpander>,
+}
+
+impl Eq for ProcMacro {}
+impl PartialEq for ProcMacro {
+    fn eq(&self, other: &ProcMacro) -> bool {
+        self.name == other.name
+            && self.kind == other.kind
+            && Arc::ptr_eq(&self.expander, &other.expander)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct CrateData {
     file_id: FileId,
     edition: Edition,
+    /// The name the crate is compiled with, if known. Dependents may refer to
+    /// it by another name, see `Dependency::name`.
+    name: Option<SmolStr>,
     cfg_options: CfgOptions,
     env: Env,
+    extern_source: ExternSource,
     dependencies: Vec<Dependency>,
+    proc_macro: Vec<ProcMacro>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -103,6 +149,13 @@ pub struct Env {
     entries: FxHashMap<String, String>,
 }
 
+/// Absolute paths a crate may refer to (with `include!` and friends), along
+/// with the source roots they are loaded into.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct ExternSource {
+    extern_paths: FxHashMap<PathBuf, ExternSourceId>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Dependency {
     pub crate_id: CrateId,
@@ -114,10 +167,14 @@ impl CrateGraph {
         &mut self,
         file_id: FileId,
         edition: Edition,
+        name: Option<SmolStr>,
         cfg_options: CfgOptions,
         env: Env,
+        extern_source: ExternSource,
+        proc_macro: Vec<ProcMacro>,
     ) -> CrateId {
-        let data = CrateData::new(file_id, edition, cfg_options, env);
+        let data =
+            CrateData::new(file_id, edition, name, cfg_options, env, extern_source, proc_macro);
         let crate_id = CrateId(self.arena.len() as u32);
         let prev = self.arena.insert(crate_id, data);
         assert!(prev.is_none());
@@ -128,6 +185,20 @@ impl CrateGraph {
         &self.arena[&crate_id].cfg_options
     }
 
+    /// Environment variables visible to the crate at compile time, via `env!`.
+    pub fn env(&self, crate_id: CrateId) -> &Env {
+        &self.arena[&crate_id].env
+    }
+
+    pub fn extern_source(&self, crate_id: CrateId) -> &ExternSource {
+        &self.arena[&crate_id].extern_source
+    }
+
+    /// Procedural macros defined by the crate, indexed by `ProcMacroId`.
+    pub fn proc_macros(&self, crate_id: CrateId) -> &[ProcMacro] {
+        &self.arena[&crate_id].proc_macro
+    }
+
     pub fn add_dep(
         &mut self,
         from: CrateId,
@@ -157,6 +228,10 @@ impl CrateGraph {
         self.arena[&crate_id].edition
     }
 
+    pub fn crate_name(&self, crate_id: CrateId) -> Option<&SmolStr> {
+        self.arena[&crate_id].name.as_ref()
+    }
+
     // FIXME: this only finds one crate with the given root; we could have multiple
     pub fn crate_id_for_crate_root(&self, file_id: FileId) -> Option<CrateId> {
         let (&crate_id, _) = self.arena.iter().find(|(_crate_id, data)| data.file_id == file_id)?;
@@ -213,8 +288,25 @@ impl CrateId {
 }
 
 impl CrateData {
-    fn new(file_id: FileId, edition: Edition, cfg_options: CfgOptions, env: Env) -> CrateData {
-        CrateData { file_id, edition, dependencies: Vec::new(), cfg_options, env }
+    fn new(
+        file_id: FileId,
+        edition: Edition,
+        name: Option<SmolStr>,
+        cfg_options: CfgOptions,
+        env: Env,
+        extern_source: ExternSource,
+        proc_macro: Vec<ProcMacro>,
+    ) -> CrateData {
+        CrateData {
+            file_id,
+            edition,
+            name,
+            dependencies: Vec::new(),
+            cfg_options,
+            env,
+            extern_source,
+            proc_macro,
+        }
     }
 
     fn add_dep(&mut self, name: SmolStr, crate_id: CrateId) {
@@ -244,6 +336,32 @@ impl fmt::Display for Edition {
     }
 }
 
+impl Env {
+    pub fn set(&mut self, env: &str, value: String) {
+        self.entries.insert(env.to_owned(), value);
+    }
+
+    pub fn get(&self, env: &str) -> Option<String> {
+        self.entries.get(env).cloned()
+    }
+}
+
+impl ExternSource {
+    /// Splits an absolute `path` into the extern source root containing it
+    /// and the path relative to that root.
+    pub fn extern_path(&self, path: &Path) -> Option<(ExternSourceId, RelativePathBuf)> {
+        self.extern_paths.iter().find_map(|(root_path, id)| {
+            let rel_path = path.strip_prefix(root_path).ok()?;
+            let rel_path = RelativePathBuf::from_path(rel_path).ok()?;
+            Some((*id, rel_path))
+        })
+    }
+
+    pub fn set_extern_path(&mut self, root_path: &Path, root: ExternSourceId) {
+        self.extern_paths.insert(root_path.to_path_buf(), root);
+    }
+}
+
 impl Dependency {
     pub fn crate_id(&self) -> CrateId {
         self.crate_id
@@ -273,12 +391,33 @@ mod tests {
     #[test]
     fn it_should_panic_because_of_cycle_dependencies() {
         let mut graph = CrateGraph::default();
-        let crate1 =
-            graph.add_crate_root(FileId(1u32), Edition2018, CfgOptions::default(), Env::default());
-        let crate2 =
-            graph.add_crate_root(FileId(2u32), Edition2018, CfgOptions::default(), Env::default());
-        let crate3 =
-            graph.add_crate_root(FileId(3u32), Edition2018, CfgOptions::default(), Env::default());
+        let crate1 = graph.add_crate_root(
+            FileId(1u32),
+            Edition2018,
+            None,
+            CfgOptions::default(),
+            Env::default(),
+            Default::default(),
+            Vec::new(),
+        );
+        let crate2 = graph.add_crate_root(
+            FileId(2u32),
+            Edition2018,
+            None,
+            CfgOptions::default(),
+            Env::default(),
+            Default::default(),
+            Vec::new(),
+        );
+        let crate3 = graph.add_crate_root(
+            FileId(3u32),
+            Edition2018,
+            None,
+            CfgOptions::default(),
+            Env::default(),
+            Default::default(),
+            Vec::new(),
+        );
         assert!(graph.add_dep(crate1, SmolStr::new("crate2"), crate2).is_ok());
         assert!(graph.add_dep(crate2, SmolStr::new("crate3"), crate3).is_ok());
         assert!(graph.add_dep(crate3, SmolStr::new("crate1"), crate1).is_err());
@@ -287,12 +426,33 @@ mod tests {
     #[test]
     fn it_works() {
         let mut graph = CrateGraph::default();
-        let crate1 =
-            graph.add_crate_root(FileId(1u32), Edition2018, CfgOptions::default(), Env::default());
-        let crate2 =
-            graph.add_crate_root(FileId(2u32), Edition2018, CfgOptions::default(), Env::default());
-        let crate3 =
-            graph.add_crate_root(FileId(3u32), Edition2018, CfgOptions::default(), Env::default());
+        let crate1 = graph.add_crate_root(
+            FileId(1u32),
+            Edition2018,
+            None,
+            CfgOptions::default(),
+            Env::default(),
+            Default::default(),
+            Vec::new(),
+        );
+        let crate2 = graph.add_crate_root(
+            FileId(2u32),
+            Edition2018,
+            None,
+            CfgOptions::default(),
+            Env::default(),
+            Default::default(),
+            Vec::new(),
+        );
+        let crate3 = graph.add_crate_root(
+            FileId(3u32),
+            Edition2018,
+            None,
+            CfgOptions::default(),
+            Env::default(),
+            Default::default(),
+            Vec::new(),
+        );
         assert!(graph.add_dep(crate1, SmolStr::new("crate2"), crate2).is_ok());
         assert!(graph.add_dep(crate2, SmolStr::new("crate3"), crate3).is_ok());
     }
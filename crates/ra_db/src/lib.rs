@@ -10,7 +10,10 @@ use ra_syntax::{ast, Parse, SourceFile, TextRange, TextUnit};
 
 pub use crate::{
     cancellation::Canceled,
-    input::{CrateGraph, CrateId, Dependency, Edition, Env, FileId, SourceRoot, SourceRootId},
+    input::{
+        CrateGraph, CrateId, Dependency, Edition, Env, ExternSource, ExternSourceId, FileId,
+        ProcMacro, ProcMacroId, ProcMacroKind, SourceRoot, SourceRootId,
+    },
 };
 pub use relative_path::{RelativePath, RelativePathBuf};
 pub use salsa;
@@ -85,6 +88,21 @@ pub trait FileLoader {
     fn resolve_relative_path(&self, anchor: FileId, relative_path: &RelativePath)
         -> Option<FileId>;
     fn relevant_crates(&self, file_id: FileId) -> Arc<Vec<CrateId>>;
+    /// Resolves a path relative to the root of an extern source, see
+    /// `ExternSource`.
+    fn resolve_extern_path(
+        &self,
+        extern_id: ExternSourceId,
+        relative_path: &RelativePath,
+    ) -> Option<FileId>;
+    /// Syntax tree of the file with the given `text`, incrementally reparsed
+    /// from the previous version of the file, if such a tree is available.
+    ///
+    /// The tree is the same as parsing `text` from scratch, so salsa doesn't
+    /// need to track this as a dependency of `parse`.
+    fn reparsed_file(&self, _file_id: FileId, _text: &Arc<String>) -> Option<Parse<SourceFile>> {
+        None
+    }
 }
 
 /// Database which stores all significant input facts: source code and project
@@ -103,6 +121,9 @@ pub trait SourceDatabase: CheckCanceled + FileLoader + std::fmt::Debug {
 fn parse_query(db: &impl SourceDatabase, file_id: FileId) -> Parse<ast::SourceFile> {
     let _p = profile("parse_query");
     let text = db.file_text(file_id);
+    if let Some(parse) = db.reparsed_file(file_id, &text) {
+        return parse;
+    }
     SourceFile::parse(&*text)
 }
 
@@ -162,4 +183,13 @@ impl<T: SourceDatabaseExt> FileLoader for FileLoaderDelegate<&'_ T> {
         let source_root = self.0.file_source_root(file_id);
         self.0.source_root_crates(source_root)
     }
+
+    fn resolve_extern_path(
+        &self,
+        extern_id: ExternSourceId,
+        relative_path: &RelativePath,
+    ) -> Option<FileId> {
+        let source_root = self.0.source_root(SourceRootId(extern_id.0));
+        source_root.file_by_relative_path(relative_path)
+    }
 }
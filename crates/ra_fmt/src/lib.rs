@@ -4,7 +4,7 @@ use std::iter::successors;
 
 use itertools::Itertools;
 use ra_syntax::{
-    ast::{self, AstNode, AstToken},
+    ast::{self, edit::IndentLevel, AstNode, AstToken},
     SmolStr, SyntaxKind,
     SyntaxKind::*,
     SyntaxNode, SyntaxToken, T,
@@ -15,6 +15,13 @@ pub fn reindent(text: &str, indent: &str) -> String {
     text.lines().intersperse(&indent).collect()
 }
 
+/// Re-indents a freshly built or edited `node`, so that it can be placed at
+/// `indent` level. See `IndentLevel::reformat`, which `ast::edit` uses for
+/// the nodes it inserts.
+pub fn reformat<N: AstNode>(node: N, indent: IndentLevel) -> N {
+    indent.reformat(node)
+}
+
 /// If the node is on the beginning of the line, calculate indent.
 pub fn leading_indent(node: &SyntaxNode) -> Option<SmolStr> {
     for token in prev_tokens(node.first_token()?) {
@@ -70,3 +77,35 @@ pub fn compute_ws(left: SyntaxKind, right: SyntaxKind) -> &'static str {
     }
     " "
 }
+
+#[cfg(test)]
+mod tests {
+    use ra_syntax::SourceFile;
+
+    use super::*;
+
+    fn check_reformat(before: &str, indent: u8, after: &str) {
+        let file = SourceFile::parse(before).tree();
+        let item = file.syntax().descendants().find_map(ast::ModuleItem::cast).unwrap();
+        let actual = reformat(item, IndentLevel(indent)).syntax().to_string();
+        assert_eq!(actual, after);
+    }
+
+    #[test]
+    fn reformat_nested_blocks() {
+        check_reformat(
+            "fn foo() {\n  if true {\n bar(\n1,\n      2,\n  )\n        }\n}",
+            1,
+            "fn foo() {\n        if true {\n            bar(\n                1,\n                2,\n            )\n        }\n    }",
+        );
+    }
+
+    #[test]
+    fn reformat_keeps_comments_and_literals() {
+        check_reformat(
+            "fn foo() {   \n\n\n\n// comment\n    let s = \"a\n  b\";\nx\n.bar()\n}",
+            0,
+            "fn foo() {\n\n    // comment\n    let s = \"a\n  b\";\n    x\n        .bar()\n}",
+        );
+    }
+}
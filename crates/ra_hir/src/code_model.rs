@@ -6,14 +6,17 @@ use hir_def::{
     adt::VariantData,
     builtin_type::BuiltinType,
     docs::Documentation,
-    expr::{BindingAnnotation, Pat, PatId},
+    expr::{BindingAnnotation, ExprId, Pat, PatId},
+    item_scope::ItemInNs,
+    lang_item::LangItemTarget,
     nameres::ModuleSource,
+    path::ModPath,
     per_ns::PerNs,
     resolver::HasResolver,
     type_ref::{Mutability, TypeRef},
-    AdtId, ConstId, DefWithBodyId, EnumId, FunctionId, HasModule, ImplId, LocalEnumVariantId,
-    LocalModuleId, LocalStructFieldId, Lookup, ModuleId, StaticId, StructId, TraitId, TypeAliasId,
-    TypeParamId, UnionId,
+    AdtId, AssocContainerId, ConstId, DefWithBodyId, EnumId, FunctionId, HasModule, ImplId,
+    LocalEnumVariantId, LocalModuleId, LocalStructFieldId, Lookup, ModuleId, StaticId, StructId,
+    TraitId, TypeAliasId, TypeParamId, UnionId,
 };
 use hir_expand::{
     diagnostics::DiagnosticSink,
@@ -21,8 +24,9 @@ use hir_expand::{
     MacroDefId,
 };
 use hir_ty::{
-    autoderef, display::HirFormatter, expr::ExprValidator, ApplicationTy, Canonical, InEnvironment,
-    TraitEnvironment, Ty, TyDefId, TypeCtor, TypeWalk,
+    autoderef, display::HirFormatter, expr::ExprValidator, traits::FnTrait, ApplicationTy,
+    Canonical, CaptureKind, InEnvironment, Obligation, Substs, TraitEnvironment, TraitRef, Ty,
+    TyDefId, TypeCtor, TypeWalk,
 };
 use ra_db::{CrateId, Edition, FileId};
 use ra_prof::profile;
@@ -195,6 +199,7 @@ impl Module {
         for decl in self.declarations(db) {
             match decl {
                 crate::ModuleDef::Function(f) => f.diagnostics(db, sink),
+                crate::ModuleDef::Adt(adt) => adt.diagnostics(db, sink),
                 crate::ModuleDef::Module(m) => {
                     // Only add diagnostics from inline modules
                     if let ModuleSource::Module(_) = m.definition_source(db).value {
@@ -224,6 +229,18 @@ impl Module {
         def_map[self.id.local_id].scope.impls().map(ImplBlock::from).collect()
     }
 
+    /// Finds a path that can be used to refer to the given item from within
+    /// this module, if possible.
+    pub fn find_use_path(self, db: &impl DefDatabase, item: ModuleDef) -> Option<ModPath> {
+        let item = match item {
+            ModuleDef::Function(_) | ModuleDef::Const(_) | ModuleDef::Static(_) => {
+                ItemInNs::Values(item.into())
+            }
+            _ => ItemInNs::Types(item.into()),
+        };
+        hir_def::find_path::find_path(db, item, self.into())
+    }
+
     pub(crate) fn with_module_id(self, module_id: LocalModuleId) -> Module {
         Module::new(self.krate(), module_id)
     }
@@ -426,6 +443,17 @@ impl Adt {
     pub fn krate(self, db: &impl HirDatabase) -> Option<Crate> {
         Some(self.module(db).krate())
     }
+
+    pub fn diagnostics(self, db: &impl HirDatabase, sink: &mut DiagnosticSink) {
+        let variants = match self {
+            Adt::Struct(it) => vec![VariantDef::Struct(it)],
+            Adt::Union(it) => vec![VariantDef::Union(it)],
+            Adt::Enum(it) => it.variants(db).into_iter().map(VariantDef::EnumVariant).collect(),
+        };
+        for variant in variants {
+            hir_def::adt::add_field_diagnostics(db, variant.into(), sink);
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -510,6 +538,8 @@ impl Function {
 
     pub fn diagnostics(self, db: &impl HirDatabase, sink: &mut DiagnosticSink) {
         let _p = profile("Function::diagnostics");
+        let (_, source_map) = db.body_with_source_map(self.id.into());
+        source_map.add_diagnostics(sink);
         let infer = db.infer(self.id.into());
         infer.add_diagnostics(db, self.id, sink);
         let mut validator = ExprValidator::new(self.id, infer, sink);
@@ -623,6 +653,12 @@ pub enum AssocItem {
 // casting them, and somehow making the constructors private, which would be annoying.
 impl_froms!(AssocItem: Function, Const, TypeAlias);
 
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum AssocItemContainer {
+    Trait(Trait),
+    ImplBlock(ImplBlock),
+}
+
 impl AssocItem {
     pub fn module(self, db: &impl DefDatabase) -> Module {
         match self {
@@ -631,6 +667,27 @@ impl AssocItem {
             AssocItem::TypeAlias(t) => t.module(db),
         }
     }
+
+    pub fn container(self, db: &impl DefDatabase) -> AssocItemContainer {
+        let container = match self {
+            AssocItem::Function(it) => it.id.lookup(db).container,
+            AssocItem::Const(it) => it.id.lookup(db).container,
+            AssocItem::TypeAlias(it) => it.id.lookup(db).container,
+        };
+        match container {
+            AssocContainerId::TraitId(id) => AssocItemContainer::Trait(id.into()),
+            AssocContainerId::ImplId(id) => AssocItemContainer::ImplBlock(id.into()),
+            AssocContainerId::ContainerId(_) => panic!("invalid AssocItem"),
+        }
+    }
+
+    pub fn name(self, db: &impl HirDatabase) -> Option<Name> {
+        match self {
+            AssocItem::Function(it) => Some(it.name(db)),
+            AssocItem::Const(it) => it.name(db),
+            AssocItem::TypeAlias(it) => Some(it.name(db)),
+        }
+    }
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
@@ -705,6 +762,39 @@ impl Local {
         self.parent.module(db)
     }
 
+    /// If this local holds a closure, returns the most permissive `Fn*` trait
+    /// the closure implements.
+    pub fn closure_kind(self, db: &impl HirDatabase) -> Option<FnTrait> {
+        let (owner, closure) = self.closure(db)?;
+        db.infer(owner).closure_kind(closure)
+    }
+
+    /// If this local holds a closure, returns the variables of the enclosing
+    /// body the closure captures.
+    pub fn closure_captures(self, db: &impl HirDatabase) -> Option<Vec<ClosureCapture>> {
+        let (owner, closure) = self.closure(db)?;
+        let captures = db
+            .infer(owner)
+            .closure_captures(closure)
+            .iter()
+            .map(|it| ClosureCapture {
+                local: Local { parent: owner.into(), pat_id: it.local },
+                kind: it.kind,
+            })
+            .collect();
+        Some(captures)
+    }
+
+    fn closure(self, db: &impl HirDatabase) -> Option<(DefWithBodyId, ExprId)> {
+        let infer = db.infer(self.parent.into());
+        match &infer[self.pat_id] {
+            Ty::Apply(ApplicationTy { ctor: TypeCtor::Closure { def, expr }, .. }) => {
+                Some((*def, *expr))
+            }
+            _ => None,
+        }
+    }
+
     pub fn ty(self, db: &impl HirDatabase) -> Type {
         let def = DefWithBodyId::from(self.parent);
         let infer = db.infer(def);
@@ -725,6 +815,23 @@ impl Local {
     }
 }
 
+/// A variable captured by a closure, and how it is captured.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ClosureCapture {
+    local: Local,
+    kind: CaptureKind,
+}
+
+impl ClosureCapture {
+    pub fn local(&self) -> Local {
+        self.local
+    }
+
+    pub fn kind(&self) -> CaptureKind {
+        self.kind
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct TypeParam {
     pub(crate) id: TypeParamId,
@@ -764,6 +871,11 @@ impl ImplBlock {
         db.impl_data(self.id).target_type.clone()
     }
 
+    /// The trait this block implements, if it is a trait impl.
+    pub fn target_trait_def(&self, db: &impl HirDatabase) -> Option<Trait> {
+        db.impl_trait(self.id).map(|it| it.trait_.into())
+    }
+
     pub fn target_ty(&self, db: &impl HirDatabase) -> Type {
         let impl_data = db.impl_data(self.id);
         let resolver = self.id.resolver(db);
@@ -834,6 +946,40 @@ impl Type {
         }
     }
 
+    /// Checks whether values of this type are copied rather than moved.
+    pub fn is_copy(&self, db: &impl HirDatabase) -> bool {
+        if let Ty::Apply(a_ty) = &self.ty.value {
+            match a_ty.ctor {
+                TypeCtor::Bool
+                | TypeCtor::Char
+                | TypeCtor::Int(_)
+                | TypeCtor::Float(_)
+                | TypeCtor::Never
+                | TypeCtor::RawPtr(_)
+                | TypeCtor::Ref(Mutability::Shared)
+                | TypeCtor::FnDef(_)
+                | TypeCtor::FnPtr { .. } => return true,
+                TypeCtor::Ref(Mutability::Mut) | TypeCtor::Str | TypeCtor::Slice => return false,
+                TypeCtor::Tuple { .. } => {
+                    return a_ty.parameters.iter().all(|ty| self.derived(ty.clone()).is_copy(db))
+                }
+                _ => {}
+            }
+        }
+
+        let copy_trait = match db.lang_item(self.krate, "copy".into()) {
+            Some(LangItemTarget::TraitId(it)) => it,
+            _ => return false,
+        };
+        let trait_ref =
+            TraitRef { trait_: copy_trait, substs: Substs::single(self.ty.value.clone()) };
+        let goal = Canonical {
+            value: InEnvironment::new(self.ty.environment.clone(), Obligation::Trait(trait_ref)),
+            num_vars: 0,
+        };
+        db.trait_solve(self.krate, goal).is_some()
+    }
+
     pub fn is_unknown(&self) -> bool {
         match &self.ty.value {
             Ty::Unknown => true,
@@ -858,6 +1004,25 @@ impl Type {
         }
     }
 
+    /// Whether the type can be written down in source code, like in the
+    /// signature of a new function. Closure and function item types, and types
+    /// which are not fully known, can't.
+    pub fn is_nameable(&self) -> bool {
+        return go(&self.ty.value);
+
+        fn go(ty: &Ty) -> bool {
+            match ty {
+                Ty::Apply(a_ty) => match a_ty.ctor {
+                    TypeCtor::Closure { .. } | TypeCtor::FnDef(_) => false,
+                    _ => a_ty.parameters.iter().all(go),
+                },
+                Ty::Projection(p_ty) => p_ty.parameters.iter().all(go),
+                Ty::Unknown | Ty::Infer(_) | Ty::Bound(_) => false,
+                Ty::Param { .. } | Ty::Dyn(_) | Ty::Opaque(_) => true,
+            }
+        }
+    }
+
     pub fn fields(&self, db: &impl HirDatabase) -> Vec<(StructField, Type)> {
         if let Ty::Apply(a_ty) = &self.ty.value {
             match a_ty.ctor {
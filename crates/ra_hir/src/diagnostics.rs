@@ -1,4 +1,7 @@
 //! FIXME: write short doc here
-pub use hir_def::diagnostics::UnresolvedModule;
+pub use hir_def::diagnostics::{InactiveCode, UnresolvedImport, UnresolvedModule};
 pub use hir_expand::diagnostics::{AstDiagnostic, Diagnostic, DiagnosticSink};
-pub use hir_ty::diagnostics::{MissingFields, MissingOkInTailExpr, NoSuchField};
+pub use hir_ty::diagnostics::{
+    InconsistentBinding, MissingFields, MissingMatchArms, MissingOkInTailExpr, NoSuchField,
+    TypeMismatch, UnreachablePattern, UnresolvedField, UnresolvedMethodCall, UnresolvedPath,
+};
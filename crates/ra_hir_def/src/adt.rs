@@ -4,17 +4,23 @@ use std::sync::Arc;
 
 use either::Either;
 use hir_expand::{
+    diagnostics::DiagnosticSink,
+    hygiene::Hygiene,
     name::{AsName, Name},
-    InFile,
+    HirFileId, InFile,
 };
 use ra_arena::{map::ArenaMap, Arena};
+use ra_db::{CrateGraph, CrateId};
 use ra_prof::profile;
-use ra_syntax::ast::{self, NameOwner, TypeAscriptionOwner, VisibilityOwner};
+use ra_syntax::{
+    ast::{self, AttrsOwner, NameOwner, TypeAscriptionOwner, VisibilityOwner},
+    AstNode, SyntaxNodePtr,
+};
 
 use crate::{
-    db::DefDatabase, src::HasChildSource, src::HasSource, trace::Trace, type_ref::TypeRef,
-    visibility::RawVisibility, EnumId, LocalEnumVariantId, LocalStructFieldId, Lookup, StructId,
-    UnionId, VariantId,
+    attr::Attrs, db::DefDatabase, diagnostics::InactiveCode, src::HasChildSource, src::HasSource,
+    trace::Trace, type_ref::TypeRef, visibility::RawVisibility, EnumId, HasModule,
+    LocalEnumVariantId, LocalStructFieldId, Lookup, StructId, UnionId, VariantId,
 };
 
 /// Note that we use `StructData` for unions as well!
@@ -55,15 +61,18 @@ impl StructData {
     pub(crate) fn struct_data_query(db: &impl DefDatabase, id: StructId) -> Arc<StructData> {
         let src = id.lookup(db).source(db);
         let name = src.value.name().map_or_else(Name::missing, |n| n.as_name());
-        let variant_data = VariantData::new(db, src.map(|s| s.kind()));
+        let krate = id.lookup(db).container.module(db).krate;
+        let variant_data = VariantData::new(db, krate, src.map(|s| s.kind()));
         let variant_data = Arc::new(variant_data);
         Arc::new(StructData { name, variant_data })
     }
     pub(crate) fn union_data_query(db: &impl DefDatabase, id: UnionId) -> Arc<StructData> {
         let src = id.lookup(db).source(db);
         let name = src.value.name().map_or_else(Name::missing, |n| n.as_name());
+        let krate = id.lookup(db).container.module(db).krate;
         let variant_data = VariantData::new(
             db,
+            krate,
             src.map(|s| {
                 s.record_field_def_list()
                     .map(ast::StructKind::Record)
@@ -80,8 +89,9 @@ impl EnumData {
         let _p = profile("enum_data_query");
         let src = e.lookup(db).source(db);
         let name = src.value.name().map_or_else(Name::missing, |n| n.as_name());
+        let krate = e.lookup(db).container.module(db).krate;
         let mut trace = Trace::new_for_arena();
-        lower_enum(db, &mut trace, &src);
+        lower_enum(db, &mut trace, krate, &src);
         Arc::new(EnumData { name, variants: trace.into_arena() })
     }
 
@@ -96,8 +106,9 @@ impl HasChildSource for EnumId {
     type Value = ast::EnumVariant;
     fn child_source(&self, db: &impl DefDatabase) -> InFile<ArenaMap<Self::ChildId, Self::Value>> {
         let src = self.lookup(db).source(db);
+        let krate = self.lookup(db).container.module(db).krate;
         let mut trace = Trace::new_for_map();
-        lower_enum(db, &mut trace, &src);
+        lower_enum(db, &mut trace, krate, &src);
         src.with_value(trace.into_map())
     }
 }
@@ -105,6 +116,7 @@ impl HasChildSource for EnumId {
 fn lower_enum(
     db: &impl DefDatabase,
     trace: &mut Trace<LocalEnumVariantId, EnumVariantData, ast::EnumVariant>,
+    krate: CrateId,
     ast: &InFile<ast::EnumDef>,
 ) {
     for var in ast.value.variant_list().into_iter().flat_map(|it| it.variants()) {
@@ -112,16 +124,16 @@ fn lower_enum(
             || var.clone(),
             || EnumVariantData {
                 name: var.name().map_or_else(Name::missing, |it| it.as_name()),
-                variant_data: Arc::new(VariantData::new(db, ast.with_value(var.kind()))),
+                variant_data: Arc::new(VariantData::new(db, krate, ast.with_value(var.kind()))),
             },
         );
     }
 }
 
 impl VariantData {
-    fn new(db: &impl DefDatabase, flavor: InFile<ast::StructKind>) -> Self {
+    fn new(db: &impl DefDatabase, krate: CrateId, flavor: InFile<ast::StructKind>) -> Self {
         let mut trace = Trace::new_for_arena();
-        match lower_struct(db, &mut trace, &flavor) {
+        match lower_struct(db, &mut trace, krate, &flavor) {
             StructKind::Tuple => VariantData::Tuple(trace.into_arena()),
             StructKind::Record => VariantData::Record(trace.into_arena()),
             StructKind::Unit => VariantData::Unit,
@@ -153,26 +165,52 @@ impl HasChildSource for VariantId {
     type Value = Either<ast::TupleFieldDef, ast::RecordFieldDef>;
 
     fn child_source(&self, db: &impl DefDatabase) -> InFile<ArenaMap<Self::ChildId, Self::Value>> {
-        let src = match self {
-            VariantId::EnumVariantId(it) => {
-                // I don't really like the fact that we call into parent source
-                // here, this might add to more queries then necessary.
-                let src = it.parent.child_source(db);
-                src.map(|map| map[it.local_id].kind())
-            }
-            VariantId::StructId(it) => it.lookup(db).source(db).map(|it| it.kind()),
-            VariantId::UnionId(it) => it.lookup(db).source(db).map(|it| {
-                it.record_field_def_list()
-                    .map(ast::StructKind::Record)
-                    .unwrap_or(ast::StructKind::Unit)
-            }),
-        };
+        let src = variant_source(db, *self);
         let mut trace = Trace::new_for_map();
-        lower_struct(db, &mut trace, &src);
+        lower_struct(db, &mut trace, self.module(db).krate, &src);
         src.with_value(trace.into_map())
     }
 }
 
+/// Reports the fields of `variant` which are disabled by `#[cfg]`.
+pub fn add_field_diagnostics(db: &impl DefDatabase, variant: VariantId, sink: &mut DiagnosticSink) {
+    let src = variant_source(db, variant);
+    let fields: Vec<_> = match &src.value {
+        ast::StructKind::Tuple(fl) => fl.fields().map(Either::Left).collect(),
+        ast::StructKind::Record(fl) => fl.fields().map(Either::Right).collect(),
+        ast::StructKind::Unit => return,
+    };
+    let cfg = FieldCfg::new(db, variant.module(db).krate, src.file_id);
+    for field in fields {
+        let field: &dyn AttrsOwner = match &field {
+            Either::Left(it) => it,
+            Either::Right(it) => it,
+        };
+        if let Some(disabled_by) = cfg.disabling_cfg(field) {
+            sink.push(InactiveCode::new(
+                src.file_id,
+                SyntaxNodePtr::new(field.syntax()),
+                &disabled_by,
+            ));
+        }
+    }
+}
+
+fn variant_source(db: &impl DefDatabase, variant: VariantId) -> InFile<ast::StructKind> {
+    match variant {
+        VariantId::EnumVariantId(it) => {
+            // I don't really like the fact that we call into parent source
+            // here, this might add to more queries then necessary.
+            let src = it.parent.child_source(db);
+            src.map(|map| map[it.local_id].kind())
+        }
+        VariantId::StructId(it) => it.lookup(db).source(db).map(|it| it.kind()),
+        VariantId::UnionId(it) => it.lookup(db).source(db).map(|it| {
+            it.record_field_def_list().map(ast::StructKind::Record).unwrap_or(ast::StructKind::Unit)
+        }),
+    }
+}
+
 enum StructKind {
     Tuple,
     Record,
@@ -186,11 +224,15 @@ fn lower_struct(
         StructFieldData,
         Either<ast::TupleFieldDef, ast::RecordFieldDef>,
     >,
+    krate: CrateId,
     ast: &InFile<ast::StructKind>,
 ) -> StructKind {
+    let cfg = FieldCfg::new(db, krate, ast.file_id);
     match &ast.value {
         ast::StructKind::Tuple(fl) => {
-            for (i, fd) in fl.fields().enumerate() {
+            // Disabled fields don't count when numbering the others
+            let fields = fl.fields().filter(|fd| cfg.disabling_cfg(fd).is_none());
+            for (i, fd) in fields.enumerate() {
                 trace.alloc(
                     || Either::Left(fd.clone()),
                     || StructFieldData {
@@ -203,7 +245,7 @@ fn lower_struct(
             StructKind::Tuple
         }
         ast::StructKind::Record(fl) => {
-            for fd in fl.fields() {
+            for fd in fl.fields().filter(|fd| cfg.disabling_cfg(fd).is_none()) {
                 trace.alloc(
                     || Either::Right(fd.clone()),
                     || StructFieldData {
@@ -218,3 +260,24 @@ fn lower_struct(
         ast::StructKind::Unit => StructKind::Unit,
     }
 }
+
+/// Evaluates `#[cfg]`s on the fields of a struct or variant.
+struct FieldCfg {
+    crate_graph: Arc<CrateGraph>,
+    krate: CrateId,
+    hygiene: Hygiene,
+}
+
+impl FieldCfg {
+    fn new(db: &impl DefDatabase, krate: CrateId, file_id: HirFileId) -> FieldCfg {
+        FieldCfg { crate_graph: db.crate_graph(), krate, hygiene: Hygiene::new(db, file_id) }
+    }
+
+    fn disabling_cfg(&self, field: &dyn AttrsOwner) -> Option<tt::Subtree> {
+        let cfg_options = self.crate_graph.cfg_options(self.krate);
+        Attrs::new(field, &self.hygiene)
+            .expand_cfg_attr(cfg_options)
+            .disabling_cfg(cfg_options)
+            .cloned()
+    }
+}
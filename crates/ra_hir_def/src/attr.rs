@@ -3,8 +3,10 @@
 use std::{ops, sync::Arc};
 
 use either::Either;
-use hir_expand::{hygiene::Hygiene, AstId, InFile};
+use hir_expand::{builtin_macro::unquote_str, hygiene::Hygiene, AstId, InFile};
 use mbe::ast_to_token_tree;
+use ra_cfg::CfgOptions;
+use ra_db::CrateId;
 use ra_syntax::{
     ast::{self, AstNode, AttrsOwner},
     SmolStr,
@@ -12,7 +14,8 @@ use ra_syntax::{
 use tt::Subtree;
 
 use crate::{
-    db::DefDatabase, path::ModPath, src::HasChildSource, src::HasSource, AdtId, AttrDefId, Lookup,
+    db::DefDatabase, path::ModPath, src::HasChildSource, src::HasSource, AdtId, AttrDefId,
+    HasModule, Lookup,
 };
 
 #[derive(Default, Debug, Clone, PartialEq, Eq)]
@@ -33,6 +36,14 @@ impl ops::Deref for Attrs {
 
 impl Attrs {
     pub(crate) fn attrs_query(db: &impl DefDatabase, def: AttrDefId) -> Attrs {
+        let attrs = Attrs::unexpanded(db, def);
+        match krate_of(db, def) {
+            Some(krate) => attrs.expand_cfg_attr(db.crate_graph().cfg_options(krate)),
+            None => attrs,
+        }
+    }
+
+    fn unexpanded(db: &impl DefDatabase, def: AttrDefId) -> Attrs {
         match def {
             AttrDefId::ModuleId(module) => {
                 let def_map = db.crate_def_map(module.krate);
@@ -77,7 +88,11 @@ impl Attrs {
     }
 
     pub(crate) fn new(owner: &dyn AttrsOwner, hygiene: &Hygiene) -> Attrs {
-        let mut attrs = owner.attrs().peekable();
+        Attrs::from_ast(owner.attrs(), hygiene)
+    }
+
+    pub(crate) fn from_ast(attrs: impl Iterator<Item = ast::Attr>, hygiene: &Hygiene) -> Attrs {
+        let mut attrs = attrs.peekable();
         let entries = if attrs.peek().is_none() {
             // Avoid heap allocation
             None
@@ -87,6 +102,29 @@ impl Attrs {
         Attrs { entries }
     }
 
+    /// Replaces each `#[cfg_attr(pred, attr1, attr2)]` with `#[attr1]` and
+    /// `#[attr2]` if `pred` holds, and drops it otherwise.
+    pub(crate) fn expand_cfg_attr(&self, cfg_options: &CfgOptions) -> Attrs {
+        if !self.iter().any(Attr::is_cfg_attr) {
+            return self.clone();
+        }
+        let mut entries = Vec::new();
+        for attr in self.iter() {
+            attr.expand_cfg_attr(cfg_options, &mut entries);
+        }
+        Attrs { entries: if entries.is_empty() { None } else { Some(entries.into()) } }
+    }
+
+    /// The first `#[cfg]` predicate which doesn't hold, if any. `cfg_attr`s
+    /// should be expanded first.
+    pub(crate) fn disabling_cfg(&self, cfg_options: &CfgOptions) -> Option<&Subtree> {
+        self.by_key("cfg").tt_values().find(|tt| cfg_options.is_cfg_enabled(tt) == Some(false))
+    }
+
+    pub(crate) fn is_cfg_enabled(&self, cfg_options: &CfgOptions) -> bool {
+        self.disabling_cfg(cfg_options).is_none()
+    }
+
     pub fn by_key(&self, key: &'static str) -> AttrQuery<'_> {
         AttrQuery { attrs: self, key }
     }
@@ -110,9 +148,7 @@ impl Attr {
         let input = match ast.input() {
             None => None,
             Some(ast::AttrInput::Literal(lit)) => {
-                // FIXME: escape? raw string?
-                let value = lit.syntax().first_token()?.text().trim_matches('"').into();
-                Some(AttrInput::Literal(value))
+                Some(AttrInput::Literal(literal_value(lit.syntax().first_token()?.text())))
             }
             Some(ast::AttrInput::TokenTree(tt)) => {
                 Some(AttrInput::TokenTree(ast_to_token_tree(&tt)?.0))
@@ -121,6 +157,61 @@ impl Attr {
 
         Some(Attr { path, input })
     }
+
+    /// Parses an attribute from the tokens inside of `#[]`, like the
+    /// `derive(Debug)` produced by `cfg_attr`.
+    fn from_tts(tts: &[tt::TokenTree]) -> Option<Attr> {
+        let path_len = tts
+            .iter()
+            .position(|tt| match tt {
+                tt::TokenTree::Leaf(tt::Leaf::Ident(_)) => false,
+                tt::TokenTree::Leaf(tt::Leaf::Punct(p)) => p.char != ':',
+                _ => true,
+            })
+            .unwrap_or(tts.len());
+        let path = ModPath::from_tt_path(&tts[..path_len])?;
+        let input = match &tts[path_len..] {
+            [] => None,
+            [tt::TokenTree::Leaf(tt::Leaf::Punct(eq)), tt::TokenTree::Leaf(tt::Leaf::Literal(lit))]
+                if eq.char == '=' =>
+            {
+                Some(AttrInput::Literal(literal_value(&lit.text)))
+            }
+            [tt::TokenTree::Subtree(subtree)] => Some(AttrInput::TokenTree(subtree.clone())),
+            _ => return None,
+        };
+        Some(Attr { path, input })
+    }
+
+    fn is_cfg_attr(&self) -> bool {
+        self.path.as_ident().map_or(false, |name| name.to_string() == "cfg_attr")
+    }
+
+    fn expand_cfg_attr(&self, cfg_options: &CfgOptions, acc: &mut Vec<Attr>) {
+        if !self.is_cfg_attr() {
+            acc.push(self.clone());
+            return;
+        }
+        let subtree = match &self.input {
+            Some(AttrInput::TokenTree(it)) => it,
+            _ => return,
+        };
+        let mut parts = subtree.token_trees.split(|tt| match tt {
+            tt::TokenTree::Leaf(tt::Leaf::Punct(p)) => p.char == ',',
+            _ => false,
+        });
+        let pred = match parts.next() {
+            Some(it) => Subtree { delimiter: None, token_trees: it.to_vec() },
+            None => return,
+        };
+        if cfg_options.is_cfg_enabled(&pred) != Some(true) {
+            return;
+        }
+        for attr in parts.filter(|it| !it.is_empty()).filter_map(Attr::from_tts) {
+            // `cfg_attr`s can be nested
+            attr.expand_cfg_attr(cfg_options, acc);
+        }
+    }
 }
 
 pub struct AttrQuery<'a> {
@@ -155,6 +246,32 @@ impl<'a> AttrQuery<'a> {
     }
 }
 
+/// The value of the literal in `#[attr = lit]`: strings are unescaped, other
+/// literals are kept as written.
+fn literal_value(text: &str) -> SmolStr {
+    match unquote_str(text) {
+        Some(it) => it.into(),
+        None => text.into(),
+    }
+}
+
+fn krate_of(db: &impl DefDatabase, def: AttrDefId) -> Option<CrateId> {
+    let module = match def {
+        AttrDefId::ModuleId(it) => it,
+        AttrDefId::StructFieldId(it) => it.parent.module(db),
+        AttrDefId::AdtId(it) => it.module(db),
+        AttrDefId::FunctionId(it) => it.lookup(db).module(db),
+        AttrDefId::EnumVariantId(it) => it.parent.lookup(db).container.module(db),
+        AttrDefId::StaticId(it) => it.lookup(db).module(db),
+        AttrDefId::ConstId(it) => it.lookup(db).module(db),
+        AttrDefId::TraitId(it) => it.lookup(db).container.module(db),
+        AttrDefId::TypeAliasId(it) => it.lookup(db).module(db),
+        AttrDefId::ImplId(it) => it.lookup(db).container.module(db),
+        AttrDefId::MacroDefId(it) => return it.krate,
+    };
+    Some(module.krate)
+}
+
 fn attrs_from_ast<D, N>(src: AstId<N>, db: &D) -> Attrs
 where
     N: ast::AttrsOwner,
@@ -1,5 +1,6 @@
 //! Defines `Body`: a lowered representation of bodies of functions, statics and
 //! consts.
+mod diagnostics;
 mod lower;
 pub mod scope;
 
@@ -8,14 +9,18 @@ use std::{mem, ops::Index, sync::Arc};
 use drop_bomb::DropBomb;
 use either::Either;
 use hir_expand::{
-    ast_id_map::AstIdMap, hygiene::Hygiene, AstId, HirFileId, InFile, MacroCallKind, MacroDefId,
+    ast_id_map::AstIdMap, diagnostics::DiagnosticSink, hygiene::Hygiene, AstId, HirFileId, InFile,
+    MacroCallKind, MacroDefId,
 };
 use ra_arena::{map::ArenaMap, Arena};
+use ra_db::CrateGraph;
 use ra_prof::profile;
 use ra_syntax::{ast, AstNode, AstPtr};
 use rustc_hash::FxHashMap;
+use tt::Subtree;
 
 use crate::{
+    attr::Attrs,
     db::DefDatabase,
     expr::{Expr, ExprId, Pat, PatId},
     item_scope::BuiltinShadowMode,
@@ -28,6 +33,7 @@ use crate::{
 
 pub(crate) struct Expander {
     crate_def_map: Arc<CrateDefMap>,
+    crate_graph: Arc<CrateGraph>,
     current_file_id: HirFileId,
     hygiene: Hygiene,
     ast_id_map: Arc<AstIdMap>,
@@ -41,9 +47,10 @@ impl Expander {
         module: ModuleId,
     ) -> Expander {
         let crate_def_map = db.crate_def_map(module.krate);
+        let crate_graph = db.crate_graph();
         let hygiene = Hygiene::new(db, current_file_id);
         let ast_id_map = db.ast_id_map(current_file_id);
-        Expander { crate_def_map, current_file_id, hygiene, ast_id_map, module }
+        Expander { crate_def_map, crate_graph, current_file_id, hygiene, ast_id_map, module }
     }
 
     pub(crate) fn enter_expand<T: ast::AstNode, DB: DefDatabase>(
@@ -95,6 +102,13 @@ impl Expander {
         InFile { file_id: self.current_file_id, value }
     }
 
+    /// The first `#[cfg]` among `attrs` which doesn't hold, if any.
+    fn disabling_cfg(&self, attrs: impl Iterator<Item = ast::Attr>) -> Option<Subtree> {
+        let cfg_options = self.crate_graph.cfg_options(self.module.krate);
+        let attrs = Attrs::from_ast(attrs, &self.hygiene).expand_cfg_attr(cfg_options);
+        attrs.disabling_cfg(cfg_options).cloned()
+    }
+
     fn parse_path(&mut self, path: ast::Path) -> Option<Path> {
         Path::from_src(path, &self.hygiene)
     }
@@ -164,6 +178,7 @@ pub struct BodySourceMap {
     pat_map_back: ArenaMap<PatId, PatSource>,
     field_map: FxHashMap<(ExprId, usize), AstPtr<ast::RecordField>>,
     expansions: FxHashMap<InFile<AstPtr<ast::MacroCall>>, HirFileId>,
+    diagnostics: Vec<diagnostics::BodyDiagnostic>,
 }
 
 impl Body {
@@ -260,4 +275,8 @@ impl BodySourceMap {
     pub fn field_syntax(&self, expr: ExprId, field: usize) -> AstPtr<ast::RecordField> {
         self.field_map[&(expr, field)]
     }
+
+    pub fn add_diagnostics(&self, sink: &mut DiagnosticSink) {
+        self.diagnostics.iter().for_each(|it| it.add_to(sink))
+    }
 }
@@ -0,0 +1,23 @@
+//! Diagnostics emitted while lowering a body.
+
+use hir_expand::{diagnostics::DiagnosticSink, InFile};
+use ra_syntax::SyntaxNodePtr;
+
+use crate::diagnostics::InactiveCode;
+
+#[derive(Debug, PartialEq, Eq)]
+pub(super) enum BodyDiagnostic {
+    /// A statement, expression or item which is disabled by the `cfg`
+    /// attribute `#[cfg(cfg)]`.
+    InactiveCode { node: InFile<SyntaxNodePtr>, cfg: tt::Subtree },
+}
+
+impl BodyDiagnostic {
+    pub(super) fn add_to(&self, sink: &mut DiagnosticSink) {
+        match self {
+            BodyDiagnostic::InactiveCode { node, cfg } => {
+                sink.push(InactiveCode::new(node.file_id, node.value, cfg))
+            }
+        }
+    }
+}
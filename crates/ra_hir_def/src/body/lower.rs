@@ -7,15 +7,15 @@ use hir_expand::name::{name, AsName, Name};
 use ra_arena::Arena;
 use ra_syntax::{
     ast::{
-        self, ArgListOwner, ArrayExprKind, LiteralKind, LoopBodyOwner, ModuleItemOwner, NameOwner,
-        TypeAscriptionOwner,
+        self, ArgListOwner, ArrayExprKind, AttrsOwner, LiteralKind, LoopBodyOwner, ModuleItemOwner,
+        NameOwner, SlicePatComponents, TypeAscriptionOwner,
     },
-    AstNode, AstPtr,
+    AstNode, AstPtr, SyntaxKind, SyntaxNodePtr, T,
 };
 use test_utils::tested_by;
 
 use crate::{
-    body::{Body, BodySourceMap, Expander, PatPtr},
+    body::{diagnostics::BodyDiagnostic, Body, BodySourceMap, Expander, PatPtr},
     builtin_type::{BuiltinFloat, BuiltinInt},
     db::DefDatabase,
     expr::{
@@ -136,6 +136,19 @@ where
         self.body.exprs.alloc(Expr::Missing)
     }
 
+    /// Checks the `#[cfg]`s among the attributes of `node`, recording `node`
+    /// as inactive code if one of them doesn't hold.
+    fn check_cfg(&mut self, node: &impl AstNode, attrs: impl Iterator<Item = ast::Attr>) -> bool {
+        match self.expander.disabling_cfg(attrs) {
+            None => true,
+            Some(cfg) => {
+                let node = self.expander.to_source(SyntaxNodePtr::new(node.syntax()));
+                self.source_map.diagnostics.push(BodyDiagnostic::InactiveCode { node, cfg });
+                false
+            }
+        }
+    }
+
     fn missing_pat(&mut self) -> PatId {
         self.body.pats.alloc(Pat::Missing)
     }
@@ -247,8 +260,11 @@ where
             ast::Expr::MatchExpr(e) => {
                 let expr = self.collect_expr_opt(e.expr());
                 let arms = if let Some(match_arm_list) = e.match_arm_list() {
-                    match_arm_list
+                    let arms: Vec<_> = match_arm_list
                         .arms()
+                        .filter(|arm| self.check_cfg(arm, arm.attrs()))
+                        .collect();
+                    arms.into_iter()
                         .map(|arm| MatchArm {
                             pats: arm.pats().map(|p| self.collect_pat(p)).collect(),
                             expr: self.collect_expr_opt(arm.expr()),
@@ -294,8 +310,10 @@ where
                 let path = e.path().and_then(|path| self.expander.parse_path(path));
                 let mut field_ptrs = Vec::new();
                 let record_lit = if let Some(nfl) = e.record_field_list() {
-                    let fields = nfl
-                        .fields()
+                    let fields: Vec<_> =
+                        nfl.fields().filter(|field| self.check_cfg(field, field.attrs())).collect();
+                    let fields = fields
+                        .into_iter()
                         .inspect(|field| field_ptrs.push(AstPtr::new(field)))
                         .map(|field| RecordLitField {
                             name: field
@@ -374,7 +392,11 @@ where
                 }
                 let ret_type = e.ret_type().and_then(|r| r.type_ref()).map(TypeRef::from_ast);
                 let body = self.collect_expr_opt(e.body());
-                self.alloc_expr(Expr::Lambda { args, arg_types, ret_type, body }, syntax_ptr)
+                let is_move = e.is_move();
+                self.alloc_expr(
+                    Expr::Lambda { args, arg_types, ret_type, body, is_move },
+                    syntax_ptr,
+                )
             }
             ast::Expr::BinExpr(e) => {
                 let lhs = self.collect_expr_opt(e.lhs());
@@ -425,7 +447,7 @@ where
                     LiteralKind::ByteString => Literal::ByteString(Default::default()),
                     LiteralKind::String => Literal::String(Default::default()),
                     LiteralKind::Byte => Literal::Int(Default::default(), Some(BuiltinInt::U8)),
-                    LiteralKind::Bool => Literal::Bool(Default::default()),
+                    LiteralKind::Bool => Literal::Bool(e.token().kind() == T![true]),
                     LiteralKind::Char => Literal::Char(Default::default()),
                 };
                 self.alloc_expr(Expr::Literal(lit), syntax_ptr)
@@ -481,8 +503,15 @@ where
             None => return self.alloc_expr(Expr::Missing, syntax_node_ptr),
         };
         self.collect_block_items(&block);
-        let statements = block
+        let statements: Vec<_> = block
             .statements()
+            .filter(|s| match s {
+                ast::Stmt::LetStmt(stmt) => self.check_cfg(stmt, stmt.attrs()),
+                ast::Stmt::ExprStmt(stmt) => self.check_cfg(stmt, stmt.attrs()),
+            })
+            .collect();
+        let statements = statements
+            .into_iter()
             .map(|s| match s {
                 ast::Stmt::LetStmt(stmt) => {
                     let pat = self.collect_pat_opt(stmt.pat());
@@ -493,13 +522,19 @@ where
                 ast::Stmt::ExprStmt(stmt) => Statement::Expr(self.collect_expr_opt(stmt.expr())),
             })
             .collect();
-        let tail = block.expr().map(|e| self.collect_expr(e));
+        let tail = block
+            .expr()
+            .filter(|e| self.check_cfg(e, outer_attrs(e)))
+            .map(|e| self.collect_expr(e));
         self.alloc_expr(Expr::Block { statements, tail }, syntax_node_ptr)
     }
 
     fn collect_block_items(&mut self, block: &ast::Block) {
         let container = ContainerId::DefWithBodyId(self.def);
         for item in block.items() {
+            if !self.check_cfg(&item, item.attrs()) {
+                continue;
+            }
             let (def, name): (ModuleDefId, Option<ast::Name>) = match item {
                 ast::ModuleItem::FnDef(def) => {
                     let ast_id = self.expander.ast_id(&def);
@@ -591,6 +626,18 @@ where
                 let args = p.args().map(|p| self.collect_pat(p)).collect();
                 Pat::Tuple(args)
             }
+            ast::Pat::OrPat(p) => {
+                let pats = p.pats().map(|p| self.collect_pat(p)).collect();
+                Pat::Or(pats)
+            }
+            ast::Pat::ParenPat(p) => {
+                let inner = self.collect_pat_opt(p.pat());
+                // make the paren pat point to the inner pattern as well
+                let ptr = AstPtr::new(&pat);
+                let src = self.expander.to_source(Either::Left(ptr));
+                self.source_map.pat_map.insert(src, inner);
+                return inner;
+            }
             ast::Pat::PlaceholderPat(_) => Pat::Wild,
             ast::Pat::RecordPat(p) => {
                 let path = p.path().and_then(|path| self.expander.parse_path(path));
@@ -617,11 +664,24 @@ where
                 Pat::Record { path, args: fields }
             }
 
+            ast::Pat::LiteralPat(p) => match p.literal() {
+                Some(literal) => Pat::Lit(self.collect_expr(ast::Expr::Literal(literal))),
+                None => Pat::Missing,
+            },
+            ast::Pat::SlicePat(p) => {
+                let SlicePatComponents { prefix, slice, suffix } = p.components();
+
+                Pat::Slice {
+                    prefix: prefix.into_iter().map(|p| self.collect_pat(p)).collect(),
+                    rest: slice.map(|p| self.collect_pat(p)),
+                    suffix: suffix.into_iter().map(|p| self.collect_pat(p)).collect(),
+                }
+            }
+
             // FIXME: implement
             ast::Pat::DotDotPat(_) => Pat::Missing,
             ast::Pat::BoxPat(_) => Pat::Missing,
-            ast::Pat::LiteralPat(_) => Pat::Missing,
-            ast::Pat::SlicePat(_) | ast::Pat::RangePat(_) => Pat::Missing,
+            ast::Pat::RangePat(_) => Pat::Missing,
         };
         let ptr = AstPtr::new(&pat);
         self.alloc_pat(pattern, Either::Left(ptr))
@@ -679,3 +739,14 @@ impl From<ast::BinOp> for BinaryOp {
         }
     }
 }
+
+/// The outer attributes of an expression in statement position, like the
+/// `#[cfg(test)]` of a block's tail `#[cfg(test)] foo()`. Unlike `attrs()`,
+/// this skips attributes on nested elements, like in `[#[cfg(test)] 1]`.
+fn outer_attrs(expr: &ast::Expr) -> impl Iterator<Item = ast::Attr> {
+    expr.syntax()
+        .children_with_tokens()
+        .take_while(|it| it.kind() == SyntaxKind::ATTR || it.kind().is_trivia())
+        .filter_map(|it| it.into_node())
+        .filter_map(ast::Attr::cast)
+}
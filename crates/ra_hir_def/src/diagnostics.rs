@@ -26,3 +26,49 @@ impl Diagnostic for UnresolvedModule {
         self
     }
 }
+
+#[derive(Debug)]
+pub struct UnresolvedImport {
+    pub file: HirFileId,
+    pub node: AstPtr<ast::UseTree>,
+}
+
+impl Diagnostic for UnresolvedImport {
+    fn message(&self) -> String {
+        "unresolved import".to_string()
+    }
+    fn source(&self) -> InFile<SyntaxNodePtr> {
+        InFile { file_id: self.file, value: self.node.into() }
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+}
+
+#[derive(Debug)]
+pub struct InactiveCode {
+    pub file: HirFileId,
+    pub node: SyntaxNodePtr,
+    /// The `cfg` predicate which disables the code.
+    pub cfg: String,
+}
+
+impl InactiveCode {
+    pub(crate) fn new(file: HirFileId, node: SyntaxNodePtr, cfg: &tt::Subtree) -> InactiveCode {
+        // Print the predicate without the parentheses of `cfg(...)`
+        let cfg = tt::Subtree { delimiter: None, token_trees: cfg.token_trees.clone() };
+        InactiveCode { file, node, cfg: cfg.to_string() }
+    }
+}
+
+impl Diagnostic for InactiveCode {
+    fn message(&self) -> String {
+        format!("code is inactive due to #[cfg] directives: {} is disabled", self.cfg)
+    }
+    fn source(&self) -> InFile<SyntaxNodePtr> {
+        InFile { file_id: self.file, value: self.node }
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+}
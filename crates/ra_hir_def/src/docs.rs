@@ -25,7 +25,7 @@ impl Into<String> for Documentation {
 }
 
 impl Documentation {
-    fn new(s: &str) -> Documentation {
+    pub fn new(s: &str) -> Documentation {
         Documentation(s.into())
     }
 
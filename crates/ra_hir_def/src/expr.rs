@@ -145,6 +145,8 @@ pub enum Expr {
         arg_types: Vec<Option<TypeRef>>,
         ret_type: Option<TypeRef>,
         body: ExprId,
+        /// Whether this is a `move` closure, which captures everything by value.
+        is_move: bool,
     },
     Tuple {
         exprs: Vec<ExprId>,
@@ -382,6 +384,7 @@ pub enum Pat {
     Missing,
     Wild,
     Tuple(Vec<PatId>),
+    Or(Vec<PatId>),
     Record {
         path: Option<Path>,
         args: Vec<RecordFieldPat>,
@@ -420,7 +423,7 @@ impl Pat {
             Pat::Bind { subpat, .. } => {
                 subpat.iter().copied().for_each(f);
             }
-            Pat::Tuple(args) | Pat::TupleStruct { args, .. } => {
+            Pat::Tuple(args) | Pat::TupleStruct { args, .. } | Pat::Or(args) => {
                 args.iter().copied().for_each(f);
             }
             Pat::Ref { pat, .. } => f(*pat),
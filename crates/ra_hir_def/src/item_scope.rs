@@ -104,6 +104,18 @@ impl ItemScope {
         }
     }
 
+    /// Finds the name under which `item` is visible in this scope, if any.
+    pub(crate) fn name_of(&self, item: ItemInNs) -> Option<(&Name, Visibility)> {
+        self.visible.iter().find_map(|(name, def)| {
+            match item {
+                ItemInNs::Types(it) => def.types.filter(|(def, _)| *def == it),
+                ItemInNs::Values(it) => def.values.filter(|(def, _)| *def == it),
+                ItemInNs::Macros(it) => def.macros.filter(|(def, _)| *def == it),
+            }
+            .map(|(_, vis)| (name, vis))
+        })
+    }
+
     pub(crate) fn traits<'a>(&'a self) -> impl Iterator<Item = TraitId> + 'a {
         self.visible.values().filter_map(|def| match def.take_types() {
             Some(ModuleDefId::TraitId(t)) => Some(t),
@@ -156,6 +168,23 @@ impl ItemScope {
     }
 }
 
+/// An item together with the namespace it lives in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ItemInNs {
+    Types(ModuleDefId),
+    Values(ModuleDefId),
+    Macros(MacroDefId),
+}
+
+impl ItemInNs {
+    pub fn as_module_def_id(self) -> Option<ModuleDefId> {
+        match self {
+            ItemInNs::Types(id) | ItemInNs::Values(id) => Some(id),
+            ItemInNs::Macros(_) => None,
+        }
+    }
+}
+
 impl PerNs {
     pub(crate) fn from_def(def: ModuleDefId, v: Visibility) -> PerNs {
         match def {
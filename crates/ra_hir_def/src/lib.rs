@@ -37,6 +37,7 @@ pub mod src;
 pub mod child_by_source;
 
 pub mod visibility;
+pub mod find_path;
 
 #[cfg(test)]
 mod test_db;
@@ -383,6 +384,17 @@ impl HasModule for AdtId {
     }
 }
 
+impl HasModule for VariantId {
+    fn module(&self, db: &impl db::DefDatabase) -> ModuleId {
+        match self {
+            VariantId::EnumVariantId(it) => it.parent.lookup(db).container,
+            VariantId::StructId(it) => it.lookup(db).container,
+            VariantId::UnionId(it) => it.lookup(db).container,
+        }
+        .module(db)
+    }
+}
+
 impl HasModule for DefWithBodyId {
     fn module(&self, db: &impl db::DefDatabase) -> ModuleId {
         match self {
@@ -49,7 +49,6 @@
 
 pub(crate) mod raw;
 mod collector;
-mod mod_resolution;
 mod path_resolution;
 
 #[cfg(test)]
@@ -278,11 +277,18 @@ impl ModuleSource {
 }
 
 mod diagnostics {
-    use hir_expand::diagnostics::DiagnosticSink;
+    use either::Either;
+    use hir_expand::{diagnostics::DiagnosticSink, hygiene::Hygiene, InFile};
     use ra_db::RelativePathBuf;
-    use ra_syntax::{ast, AstPtr};
+    use ra_syntax::{ast, AstNode, AstPtr, SyntaxNodePtr};
 
-    use crate::{db::DefDatabase, diagnostics::UnresolvedModule, nameres::LocalModuleId, AstId};
+    use crate::{
+        db::DefDatabase,
+        diagnostics::{InactiveCode, UnresolvedImport, UnresolvedModule},
+        nameres::LocalModuleId,
+        path::ModPath,
+        AstId,
+    };
 
     #[derive(Debug, PartialEq, Eq)]
     pub(super) enum DefDiagnostic {
@@ -291,6 +297,15 @@ mod diagnostics {
             declaration: AstId<ast::Module>,
             candidate: RelativePathBuf,
         },
+        /// An item or macro call which is disabled by the `cfg` attribute
+        /// `#[cfg(cfg)]`.
+        InactiveCode {
+            module: LocalModuleId,
+            ast: Either<AstId<ast::ModuleItem>, AstId<ast::MacroCall>>,
+            cfg: tt::Subtree,
+        },
+        /// The `index`-th use tree of `use_item` could not be resolved.
+        UnresolvedImport { module: LocalModuleId, use_item: AstId<ast::UseItem>, index: usize },
     }
 
     impl DefDiagnostic {
@@ -312,6 +327,39 @@ mod diagnostics {
                         candidate: candidate.clone(),
                     })
                 }
+                DefDiagnostic::InactiveCode { module, ast, cfg } => {
+                    if *module != target_module {
+                        return;
+                    }
+                    let (file, node) = match ast {
+                        Either::Left(it) => {
+                            (it.file_id, SyntaxNodePtr::new(it.to_node(db).syntax()))
+                        }
+                        Either::Right(it) => {
+                            (it.file_id, SyntaxNodePtr::new(it.to_node(db).syntax()))
+                        }
+                    };
+                    sink.push(InactiveCode::new(file, node, cfg))
+                }
+                DefDiagnostic::UnresolvedImport { module, use_item, index } => {
+                    if *module != target_module {
+                        return;
+                    }
+                    let item = use_item.to_node(db);
+                    let hygiene = Hygiene::new(db, use_item.file_id);
+                    let mut use_trees = Vec::new();
+                    ModPath::expand_use_item(
+                        InFile::new(use_item.file_id, item),
+                        &hygiene,
+                        |_, use_tree, _, _| use_trees.push(use_tree.clone()),
+                    );
+                    if let Some(use_tree) = use_trees.get(*index) {
+                        sink.push(UnresolvedImport {
+                            file: use_item.file_id,
+                            node: AstPtr::new(use_tree),
+                        })
+                    }
+                }
             }
         }
     }
@@ -3,14 +3,17 @@
 //! `DefCollector::collect` contains the fixed-point iteration loop which
 //! resolves imports and expands macros.
 
+use either::Either;
 use hir_expand::{
     builtin_derive::find_builtin_derive,
     builtin_macro::find_builtin_macro,
+    mod_resolution::ModDir,
     name::{name, AsName, Name},
+    proc_macro::ProcMacroExpander,
     HirFileId, MacroCallId, MacroCallKind, MacroDefId, MacroDefKind,
 };
 use ra_cfg::CfgOptions;
-use ra_db::{CrateId, FileId};
+use ra_db::{CrateId, FileId, ProcMacroId};
 use ra_syntax::ast;
 use rustc_hash::FxHashMap;
 use test_utils::tested_by;
@@ -19,8 +22,8 @@ use crate::{
     attr::Attrs,
     db::DefDatabase,
     nameres::{
-        diagnostics::DefDiagnostic, mod_resolution::ModDir, path_resolution::ReachedFixedPoint,
-        raw, BuiltinShadowMode, CrateDefMap, ModuleData, ModuleOrigin, ResolveMode,
+        diagnostics::DefDiagnostic, path_resolution::ReachedFixedPoint, raw, BuiltinShadowMode,
+        CrateDefMap, ModuleData, ModuleOrigin, ResolveMode,
     },
     path::{ModPath, PathKind},
     per_ns::PerNs,
@@ -91,6 +94,7 @@ impl PartialResolvedImport {
 #[derive(Clone, Debug, Eq, PartialEq)]
 struct ImportDirective {
     module_id: LocalModuleId,
+    file_id: HirFileId,
     import_id: raw::Import,
     import: raw::ImportData,
     status: PartialResolvedImport,
@@ -104,6 +108,24 @@ struct MacroDirective {
     legacy: Option<MacroCallId>,
 }
 
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct AttrMacroDirective {
+    module_id: LocalModuleId,
+    ast_id: AstId<ast::ModuleItem>,
+    path: ModPath,
+    /// `true` for a trait in `#[derive(..)]`, `false` for an attribute macro
+    /// invocation like `#[tokio::main]`.
+    is_derive: bool,
+}
+
+enum AttrResolution {
+    Macro(MacroDefId),
+    /// The path doesn't resolve yet, but might once more imports or macros
+    /// are resolved.
+    Unresolved,
+    NotAMacro,
+}
+
 /// Walks the tree of module recursively
 struct DefCollector<'a, DB> {
     db: &'a DB,
@@ -112,7 +134,7 @@ struct DefCollector<'a, DB> {
     unresolved_imports: Vec<ImportDirective>,
     resolved_imports: Vec<ImportDirective>,
     unexpanded_macros: Vec<MacroDirective>,
-    unexpanded_attribute_macros: Vec<(LocalModuleId, AstId<ast::ModuleItem>, ModPath)>,
+    unexpanded_attribute_macros: Vec<AttrMacroDirective>,
     mod_dirs: FxHashMap<LocalModuleId, ModDir>,
     cfg_options: &'a CfgOptions,
 }
@@ -127,6 +149,7 @@ where
         let raw_items = self.db.raw_items(file_id.into());
         let module_id = self.def_map.root;
         self.def_map.modules[module_id].origin = ModuleOrigin::CrateRoot { definition: file_id };
+        self.define_proc_macros();
         ModCollector {
             def_collector: &mut *self,
             module_id,
@@ -152,6 +175,10 @@ where
             }
         }
 
+        // Attributes which still don't resolve can't be expanded; we keep the
+        // item as it is written, like for an unknown inert attribute.
+        self.unexpanded_attribute_macros.clear();
+
         // Resolve all indeterminate resolved imports again
         // As some of the macros will expand newly import shadowing partial resolved imports
         // FIXME: We maybe could skip this, if we handle the Indetermine imports in `resolve_imports`
@@ -171,7 +198,37 @@ where
         let unresolved_imports = std::mem::replace(&mut self.unresolved_imports, Vec::new());
         // show unresolved imports in completion, etc
         for directive in unresolved_imports {
-            self.record_resolved_import(&directive)
+            self.record_resolved_import(&directive);
+            if let raw::ImportSource::UseTree { use_item, index } = directive.import.source {
+                self.def_map.diagnostics.push(DefDiagnostic::UnresolvedImport {
+                    module: directive.module_id,
+                    use_item: AstId::new(directive.file_id, use_item),
+                    index,
+                });
+            }
+        }
+    }
+
+    /// Define the procedural macros of a proc-macro crate.
+    ///
+    /// They are not defined by any item in the source code, so we inject them
+    /// into the crate root, where they are visible like `#[macro_export]`ed
+    /// macros.
+    fn define_proc_macros(&mut self) {
+        let krate = self.def_map.krate;
+        let crate_graph = self.db.crate_graph();
+        for (idx, proc_macro) in crate_graph.proc_macros(krate).iter().enumerate() {
+            let expander = ProcMacroExpander::new(krate, ProcMacroId(idx as u32));
+            let macro_id = MacroDefId {
+                krate: Some(krate),
+                ast_id: None,
+                kind: MacroDefKind::ProcMacro(expander),
+            };
+            self.update(
+                self.def_map.root,
+                &[(proc_macro.as_name(), PerNs::macros(macro_id, Visibility::Public))],
+                Visibility::Public,
+            );
         }
     }
 
@@ -528,17 +585,23 @@ where
 
             true
         });
-        attribute_macros.retain(|(module_id, ast_id, path)| {
-            let resolved_res = self.resolve_attribute_macro(path);
-
-            if let Some(def) = resolved_res {
-                let call_id = def.as_call_id(self.db, MacroCallKind::Attr(*ast_id));
-                resolved.push((*module_id, call_id));
-                res = ReachedFixedPoint::No;
-                return false;
-            }
-
-            true
+        attribute_macros.retain(|directive| {
+            let def = match self.resolve_attribute_macro(directive) {
+                AttrResolution::Macro(def) => def,
+                AttrResolution::Unresolved => return true,
+                // The path names something else, like an inert attribute
+                // defined by a tool, so there's nothing to expand.
+                AttrResolution::NotAMacro => return false,
+            };
+            let name = if directive.is_derive {
+                directive.path.segments.last().map(|it| it.to_string()).unwrap_or_default()
+            } else {
+                attr_path_text(&directive.path)
+            };
+            let call_id = def.as_call_id(self.db, MacroCallKind::Attr(directive.ast_id, name));
+            resolved.push((directive.module_id, call_id));
+            res = ReachedFixedPoint::No;
+            false
         });
 
         self.unexpanded_macros = macros;
@@ -551,18 +614,36 @@ where
         res
     }
 
-    fn resolve_attribute_macro(&self, path: &ModPath) -> Option<MacroDefId> {
-        // FIXME this is currently super hacky, just enough to support the
-        // built-in derives
-        if let Some(name) = path.as_ident() {
-            // FIXME this should actually be handled with the normal name
-            // resolution; the std lib defines built-in stubs for the derives,
-            // but these are new-style `macro`s, which we don't support yet
-            if let Some(def_id) = find_builtin_derive(name) {
-                return Some(def_id);
+    fn resolve_attribute_macro(&self, directive: &AttrMacroDirective) -> AttrResolution {
+        if directive.is_derive {
+            if let Some(name) = directive.path.as_ident() {
+                // FIXME this should actually be handled with the normal name
+                // resolution; the std lib defines built-in stubs for the derives,
+                // but these are new-style `macro`s, which we don't support yet
+                if let Some(def_id) = find_builtin_derive(name) {
+                    return AttrResolution::Macro(def_id);
+                }
             }
         }
-        None
+
+        // Everything else can only be a procedural macro: `macro_rules!` can't
+        // be invoked as an attribute.
+        let resolved_res = self.def_map.resolve_path_fp_with_macro(
+            self.db,
+            ResolveMode::Other,
+            directive.module_id,
+            &directive.path,
+            BuiltinShadowMode::Module,
+        );
+        if resolved_res.resolved_def.is_none() {
+            return AttrResolution::Unresolved;
+        }
+        match resolved_res.resolved_def.take_macros() {
+            Some(def @ MacroDefId { kind: MacroDefKind::ProcMacro(_), .. }) => {
+                AttrResolution::Macro(def)
+            }
+            _ => AttrResolution::NotAMacro,
+        }
     }
 
     fn collect_macro_expansion(&mut self, module_id: LocalModuleId, macro_call_id: MacroCallId) {
@@ -614,10 +695,12 @@ where
         // `#[macro_use] extern crate` is hoisted to imports macros before collecting
         // any other items.
         for item in items {
-            if self.is_cfg_enabled(&item.attrs) {
+            let attrs = item.attrs.expand_cfg_attr(self.def_collector.cfg_options);
+            if self.is_cfg_enabled(&attrs) {
                 if let raw::RawItemKind::Import(import_id) = item.kind {
                     let import = self.raw_items[import_id].clone();
-                    if import.is_extern_crate && import.is_macro_use {
+                    let is_macro_use = import.is_macro_use || attrs.by_key("macro_use").exists();
+                    if import.is_extern_crate && is_macro_use {
                         self.def_collector.import_macros_from_extern_crate(self.module_id, &import);
                     }
                 }
@@ -625,22 +708,20 @@ where
         }
 
         for item in items {
-            if self.is_cfg_enabled(&item.attrs) {
+            let attrs = item.attrs.expand_cfg_attr(self.def_collector.cfg_options);
+            if self.is_cfg_enabled(&attrs) {
                 match item.kind {
-                    raw::RawItemKind::Module(m) => {
-                        self.collect_module(&self.raw_items[m], &item.attrs)
-                    }
+                    raw::RawItemKind::Module(m) => self.collect_module(&self.raw_items[m], &attrs),
                     raw::RawItemKind::Import(import_id) => {
                         self.def_collector.unresolved_imports.push(ImportDirective {
                             module_id: self.module_id,
+                            file_id: self.file_id,
                             import_id,
                             import: self.raw_items[import_id].clone(),
                             status: PartialResolvedImport::Unresolved,
                         })
                     }
-                    raw::RawItemKind::Def(def) => {
-                        self.define_def(&self.raw_items[def], &item.attrs)
-                    }
+                    raw::RawItemKind::Def(def) => self.define_def(&self.raw_items[def], &attrs),
                     raw::RawItemKind::Macro(mac) => self.collect_macro(&self.raw_items[mac]),
                     raw::RawItemKind::Impl(imp) => {
                         let module = ModuleId {
@@ -657,6 +738,8 @@ where
                             .define_impl(impl_id)
                     }
                 }
+            } else {
+                self.emit_inactive_code_diagnostic(item, &attrs);
             }
         }
     }
@@ -756,10 +839,9 @@ where
 
     fn define_def(&mut self, def: &raw::DefData, attrs: &Attrs) {
         let module = ModuleId { krate: self.def_collector.def_map.krate, local_id: self.module_id };
-        // FIXME: check attrs to see if this is an attribute macro invocation;
-        // in which case we don't add the invocation, just a single attribute
-        // macro invocation
-
+        // FIXME: if an attribute macro is applied to the item, we should only
+        // define the items the macro expands to, not the item itself.
+        self.collect_attr_macros(attrs, def);
         self.collect_derives(attrs, def);
 
         let name = def.name.clone();
@@ -820,21 +902,40 @@ where
 
     fn collect_derives(&mut self, attrs: &Attrs, def: &raw::DefData) {
         for derive_subtree in attrs.by_key("derive").tt_values() {
-            // for #[derive(Copy, Clone)], `derive_subtree` is the `(Copy, Clone)` subtree
-            for tt in &derive_subtree.token_trees {
-                let ident = match &tt {
-                    tt::TokenTree::Leaf(tt::Leaf::Ident(ident)) => ident,
-                    tt::TokenTree::Leaf(tt::Leaf::Punct(_)) => continue, // , is ok
-                    _ => continue, // anything else would be an error (which we currently ignore)
+            // for #[derive(Copy, serde::Serialize)], `derive_subtree` is the
+            // `(Copy, serde::Serialize)` subtree
+            for path in derive_subtree.token_trees.split(is_comma) {
+                let path = match ModPath::from_tt_path(path) {
+                    Some(it) => it,
+                    None => continue, // an error (which we currently ignore)
                 };
-                let path = ModPath::from_tt_ident(ident);
 
                 let ast_id = AstId::new(self.file_id, def.kind.ast_id());
-                self.def_collector.unexpanded_attribute_macros.push((self.module_id, ast_id, path));
+                self.def_collector.unexpanded_attribute_macros.push(AttrMacroDirective {
+                    module_id: self.module_id,
+                    ast_id,
+                    path,
+                    is_derive: true,
+                });
             }
         }
     }
 
+    fn collect_attr_macros(&mut self, attrs: &Attrs, def: &raw::DefData) {
+        for attr in attrs.iter() {
+            if is_builtin_or_tool_attr(&attr.path) {
+                continue;
+            }
+            let ast_id = AstId::new(self.file_id, def.kind.ast_id());
+            self.def_collector.unexpanded_attribute_macros.push(AttrMacroDirective {
+                module_id: self.module_id,
+                ast_id,
+                path: attr.path.clone(),
+                is_derive: false,
+            });
+        }
+    }
+
     fn collect_macro(&mut self, mac: &raw::MacroData) {
         let ast_id = AstId::new(self.file_id, mac.ast_id);
 
@@ -907,11 +1008,39 @@ where
     }
 
     fn is_cfg_enabled(&self, attrs: &Attrs) -> bool {
-        // FIXME: handle cfg_attr :-)
-        attrs
-            .by_key("cfg")
-            .tt_values()
-            .all(|tt| self.def_collector.cfg_options.is_cfg_enabled(tt) != Some(false))
+        attrs.is_cfg_enabled(self.def_collector.cfg_options)
+    }
+
+    fn emit_inactive_code_diagnostic(&mut self, item: &raw::RawItem, attrs: &Attrs) {
+        let cfg = match attrs.disabling_cfg(self.def_collector.cfg_options) {
+            Some(it) => it.clone(),
+            None => return,
+        };
+        let ast_id = match item.kind {
+            raw::RawItemKind::Def(def) => Either::Left(self.raw_items[def].kind.ast_id()),
+            raw::RawItemKind::Module(m) => match &self.raw_items[m] {
+                raw::ModuleData::Definition { ast_id, .. }
+                | raw::ModuleData::Declaration { ast_id, .. } => Either::Left(ast_id.upcast()),
+            },
+            raw::RawItemKind::Impl(imp) => Either::Left(self.raw_items[imp].ast_id.upcast()),
+            raw::RawItemKind::Import(import_id) => match self.raw_items[import_id].source {
+                // All trees of a `use` item share its attributes, report it once
+                raw::ImportSource::UseTree { use_item, index: 0 } => {
+                    Either::Left(use_item.upcast())
+                }
+                raw::ImportSource::UseTree { .. } => return,
+                raw::ImportSource::ExternCrate(ast_id) => Either::Left(ast_id.upcast()),
+            },
+            raw::RawItemKind::Macro(mac) => Either::Right(self.raw_items[mac].ast_id),
+        };
+        let file_id = self.file_id;
+        let ast =
+            ast_id.map_left(|it| AstId::new(file_id, it)).map_right(|it| AstId::new(file_id, it));
+        self.def_collector.def_map.diagnostics.push(DefDiagnostic::InactiveCode {
+            module: self.module_id,
+            ast,
+            cfg,
+        });
     }
 }
 
@@ -919,6 +1048,126 @@ fn is_macro_rules(path: &ModPath) -> bool {
     path.as_ident() == Some(&name![macro_rules])
 }
 
+fn is_comma(tt: &tt::TokenTree) -> bool {
+    match tt {
+        tt::TokenTree::Leaf(tt::Leaf::Punct(p)) => p.char == ',',
+        _ => false,
+    }
+}
+
+/// Attributes which are handled by the compiler or by external tools, and
+/// thus can never refer to an attribute macro. Internal `rustc_*` attributes
+/// are recognized by their prefix.
+const BUILTIN_ATTRS: &[&str] = &[
+    "allow",
+    "allow_internal_unsafe",
+    "allow_internal_unstable",
+    "automatically_derived",
+    "bench",
+    "cfg",
+    "cfg_attr",
+    "cold",
+    "compiler_builtins",
+    "const_trait_impl",
+    "crate_name",
+    "crate_type",
+    "default_lib_allocator",
+    "deny",
+    "deprecated",
+    "derive",
+    "doc",
+    "export_name",
+    "feature",
+    "ffi_const",
+    "ffi_pure",
+    "ffi_returns_twice",
+    "forbid",
+    "fundamental",
+    "global_allocator",
+    "global_asm",
+    "ignore",
+    "inline",
+    "instruction_set",
+    "lang",
+    "link",
+    "link_args",
+    "link_name",
+    "link_ordinal",
+    "link_section",
+    "linkage",
+    "macro_export",
+    "macro_use",
+    "main",
+    "marker",
+    "may_dangle",
+    "must_use",
+    "naked",
+    "needs_allocator",
+    "needs_panic_runtime",
+    "no_builtins",
+    "no_core",
+    "no_debug",
+    "no_implicit_prelude",
+    "no_link",
+    "no_main",
+    "no_mangle",
+    "no_sanitize",
+    "no_std",
+    "non_exhaustive",
+    "optimize",
+    "panic_handler",
+    "panic_runtime",
+    "path",
+    "plugin",
+    "plugin_registrar",
+    "proc_macro",
+    "proc_macro_attribute",
+    "proc_macro_derive",
+    "profiler_runtime",
+    "recursion_limit",
+    "reexport_test_harness_main",
+    "register_attr",
+    "register_tool",
+    "repr",
+    "should_panic",
+    "stable",
+    "start",
+    "structural_match",
+    "target_feature",
+    "test",
+    "test_runner",
+    "thread_local",
+    "track_caller",
+    "type_length_limit",
+    "unsafe_destructor_blind_to_params",
+    "unstable",
+    "unwind",
+    "used",
+    "warn",
+    "windows_subsystem",
+];
+
+const TOOL_ATTR_NAMESPACES: &[&str] = &["rustfmt", "clippy"];
+
+fn is_builtin_or_tool_attr(path: &ModPath) -> bool {
+    if path.kind != PathKind::Plain {
+        return false;
+    }
+    match path.segments.as_slice() {
+        [name] => {
+            let name = name.to_string();
+            name.starts_with("rustc_") || BUILTIN_ATTRS.contains(&name.as_str())
+        }
+        [namespace, ..] => TOOL_ATTR_NAMESPACES.contains(&namespace.to_string().as_str()),
+        [] => true,
+    }
+}
+
+/// Renders the path of an attribute macro, like `tokio::main`.
+fn attr_path_text(path: &ModPath) -> String {
+    path.segments.iter().map(|it| it.to_string()).collect::<Vec<_>>().join("::")
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{db::DefDatabase, test_db::TestDB};
@@ -151,6 +151,19 @@ pub struct ImportData {
     pub(super) is_extern_crate: bool,
     pub(super) is_macro_use: bool,
     pub(super) visibility: RawVisibility,
+    pub(super) source: ImportSource,
+}
+
+/// Where in the source an import comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum ImportSource {
+    /// The `index`-th use tree of a `use` item, counting in the order in which
+    /// `ModPath::expand_use_item` visits them.
+    UseTree {
+        use_item: FileAstId<ast::UseItem>,
+        index: usize,
+    },
+    ExternCrate(FileAstId<ast::ExternCrateItem>),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -321,6 +334,7 @@ impl RawItemsCollector {
         let is_prelude = use_item.has_atom_attr("prelude_import");
         let attrs = self.parse_attrs(&use_item);
         let visibility = RawVisibility::from_ast_with_hygiene(use_item.visibility(), &self.hygiene);
+        let ast_id = self.source_ast_id_map.ast_id(&use_item);
 
         let mut buf = Vec::new();
         ModPath::expand_use_item(
@@ -335,6 +349,7 @@ impl RawItemsCollector {
                     is_extern_crate: false,
                     is_macro_use: false,
                     visibility: visibility.clone(),
+                    source: ImportSource::UseTree { use_item: ast_id, index: buf.len() },
                 };
                 buf.push(import_data);
             },
@@ -357,6 +372,7 @@ impl RawItemsCollector {
             let attrs = self.parse_attrs(&extern_crate);
             // FIXME: cfg_attr
             let is_macro_use = extern_crate.has_atom_attr("macro_use");
+            let ast_id = self.source_ast_id_map.ast_id(&extern_crate);
             let import_data = ImportData {
                 path,
                 alias,
@@ -365,6 +381,7 @@ impl RawItemsCollector {
                 is_extern_crate: true,
                 is_macro_use,
                 visibility,
+                source: ImportSource::ExternCrate(ast_id),
             };
             self.push_import(current_module, attrs, import_data);
         }
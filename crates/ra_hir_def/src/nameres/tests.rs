@@ -187,6 +187,45 @@ fn use_trees() {
     "###);
 }
 
+#[test]
+fn unresolved_import_diagnostics() {
+    let map = compute_crate_def_map(
+        "
+        //- /lib.rs
+        use foo::{Bar, Baz};
+
+        mod foo {
+            pub struct Bar;
+        }
+        ",
+    );
+    insta::assert_debug_snapshot!(map.diagnostics, @r###"
+    [
+        UnresolvedImport {
+            module: LocalModuleId(
+                0,
+            ),
+            use_item: InFile {
+                file_id: HirFileId(
+                    FileId(
+                        FileId(
+                            0,
+                        ),
+                    ),
+                ),
+                value: FileAstId {
+                    raw: ErasedFileAstId(
+                        0,
+                    ),
+                    _ty: PhantomData,
+                },
+            },
+            index: 1,
+        },
+    ]
+    "###);
+}
+
 #[test]
 fn re_exports() {
     let map = def_map(
@@ -554,6 +593,57 @@ fn cfg_test() {
     "###);
 }
 
+#[test]
+fn cfg_attr_test() {
+    let map = def_map(
+        r#"
+        //- /main.rs crate:main cfg:test,feature=foo
+        #[cfg_attr(test, path = "real.rs")]
+        mod foo;
+        #[cfg_attr(feature = "foo", cfg(not(test)))]
+        pub struct Disabled;
+        #[cfg_attr(feature = "bar", cfg(not(test)))]
+        pub struct Enabled;
+        #[cfg_attr(test, cfg_attr(feature = "foo", cfg(feature = "bar")))]
+        pub struct Nested;
+
+        //- /real.rs
+        pub struct Real;
+        "#,
+    );
+
+    assert_snapshot!(map, @r###"
+        ⋮crate
+        ⋮Enabled: t v
+        ⋮foo: t
+        ⋮
+        ⋮crate::foo
+        ⋮Real: t v
+    "###);
+}
+
+#[test]
+fn cfg_attr_unescapes_values() {
+    let map = def_map(
+        r#"
+        //- /main.rs crate:main cfg:test
+        #[cfg_attr(test, path = "re\x61l.rs")]
+        mod foo;
+
+        //- /real.rs
+        pub struct Real;
+        "#,
+    );
+
+    assert_snapshot!(map, @r###"
+        ⋮crate
+        ⋮foo: t
+        ⋮
+        ⋮crate::foo
+        ⋮Real: t v
+    "###);
+}
+
 #[test]
 fn infer_multiple_namespace() {
     let map = def_map(
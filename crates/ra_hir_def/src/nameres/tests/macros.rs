@@ -1,3 +1,9 @@
+use ra_cfg::CfgOptions;
+use ra_db::{
+    fixture::WORKSPACE, CrateGraph, Edition, Env, FileId, ProcMacro, ProcMacroKind,
+    SourceDatabaseExt, SourceRoot,
+};
+
 use super::*;
 
 #[test]
@@ -624,3 +630,128 @@ fn expand_multiple_derive() {
     );
     assert_eq!(map.modules[map.root].scope.impls().len(), 2);
 }
+
+/// A proc macro which ignores its input and expands to fixed items.
+#[derive(Debug)]
+struct ExpandTo(&'static str);
+
+impl tt::TokenExpander for ExpandTo {
+    fn expand(
+        &self,
+        _subtree: &tt::Subtree,
+        _attrs: Option<&tt::Subtree>,
+    ) -> Result<tt::Subtree, tt::ExpansionError> {
+        let file = ra_syntax::SourceFile::parse(self.0).tree();
+        Ok(mbe::ast_to_token_tree(&file).unwrap().0)
+    }
+}
+
+fn proc_macro(name: &str, kind: ProcMacroKind, expansion: &'static str) -> ProcMacro {
+    ProcMacro { name: name.into(), kind, expander: Arc::new(ExpandTo(expansion)) }
+}
+
+/// Like `def_map`, but `main` depends on a proc-macro crate `pm` defining
+/// `proc_macros`. Fixtures can't describe those, so the crate graph is built
+/// by hand.
+fn def_map_with_proc_macros(main: &str, proc_macros: Vec<ProcMacro>) -> String {
+    let mut db = TestDB::default();
+    let mut source_root = SourceRoot::new_local();
+    let mut crate_graph = CrateGraph::default();
+    let mut crates = Vec::new();
+    for (idx, (path, text, proc_macros)) in
+        vec![("/main.rs", main, Vec::new()), ("/pm.rs", "", proc_macros)].into_iter().enumerate()
+    {
+        let file_id = FileId(idx as u32);
+        db.set_file_text(file_id, Arc::new(text.to_string()));
+        db.set_file_relative_path(file_id, path.into());
+        db.set_file_source_root(file_id, WORKSPACE);
+        source_root.insert_file(path.into(), file_id);
+        crates.push(crate_graph.add_crate_root(
+            file_id,
+            Edition::Edition2018,
+            None,
+            CfgOptions::default(),
+            Env::default(),
+            Default::default(),
+            proc_macros,
+        ));
+    }
+    crate_graph.add_dep(crates[0], "pm".into(), crates[1]).unwrap();
+    db.set_source_root(WORKSPACE, Arc::new(source_root));
+    db.set_crate_graph(Arc::new(crate_graph));
+
+    render_crate_def_map(&db.crate_def_map(crates[0]))
+}
+
+#[test]
+fn expand_attribute_proc_macro() {
+    let map = def_map_with_proc_macros(
+        "
+        #[pm::generate]
+        fn foo() {}
+        ",
+        vec![proc_macro("generate", ProcMacroKind::Attr, "struct Generated;")],
+    );
+    assert_snapshot!(map, @r###"
+   ⋮crate
+   ⋮Generated: t v
+   ⋮foo: v
+    "###);
+}
+
+#[test]
+fn expand_imported_derive_proc_macro() {
+    let map = def_map_with_proc_macros(
+        "
+        use pm::Derived;
+
+        #[derive(Clone, Derived)]
+        struct Foo;
+        ",
+        vec![proc_macro("Derived", ProcMacroKind::CustomDerive, "struct FromDerive;")],
+    );
+    assert_snapshot!(map, @r###"
+   ⋮crate
+   ⋮Derived: m
+   ⋮Foo: t v
+   ⋮FromDerive: t v
+    "###);
+}
+
+#[test]
+fn inert_and_unresolved_attributes_are_not_expanded() {
+    let map = def_map_with_proc_macros(
+        "
+        mod generate {}
+
+        #[rustfmt::skip]
+        #[must_use]
+        #[repr(C)]
+        #[generate]
+        #[unknown]
+        #[pm::unknown]
+        struct Foo;
+        ",
+        vec![proc_macro("generate", ProcMacroKind::Attr, "struct Generated;")],
+    );
+    assert_snapshot!(map, @r###"
+   ⋮crate
+   ⋮Foo: t v
+   ⋮generate: t
+    "###);
+}
+
+#[test]
+fn function_like_proc_macro_is_not_an_attribute() {
+    let map = def_map_with_proc_macros(
+        "
+        #[pm::generate]
+        struct Foo;
+        ",
+        vec![proc_macro("generate", ProcMacroKind::FuncLike, "struct Generated;")],
+    );
+    assert_snapshot!(map, @r###"
+   ⋮crate
+   ⋮Foo: t v
+    "###);
+}
@@ -1,7 +1,7 @@
 //! A desugared representation of paths like `crate::foo` or `<Type as Trait>::bar`.
 mod lower;
 
-use std::{iter, sync::Arc};
+use std::{fmt, iter, sync::Arc};
 
 use hir_expand::{
     hygiene::Hygiene,
@@ -47,9 +47,31 @@ impl ModPath {
         name_ref.as_name().into()
     }
 
-    /// Converts an `tt::Ident` into a single-identifier `Path`.
-    pub(crate) fn from_tt_ident(ident: &tt::Ident) -> ModPath {
-        ident.as_name().into()
+    /// Converts a sequence of `tt::Ident`s separated by `::`, like the
+    /// `serde::Serialize` in `#[derive(serde::Serialize)]`, into a `Path`.
+    pub(crate) fn from_tt_path(tts: &[tt::TokenTree]) -> Option<ModPath> {
+        let mut kind = PathKind::Plain;
+        let mut segments = Vec::new();
+        let mut colons = 0;
+        for tt in tts {
+            match tt {
+                tt::TokenTree::Leaf(tt::Leaf::Punct(p)) if p.char == ':' => colons += 1,
+                tt::TokenTree::Leaf(tt::Leaf::Ident(ident)) => {
+                    match (segments.is_empty(), colons) {
+                        (true, 2) => kind = PathKind::Abs,
+                        (true, 0) | (false, 2) => (),
+                        _ => return None,
+                    }
+                    colons = 0;
+                    segments.push(ident.as_name());
+                }
+                _ => return None,
+            }
+        }
+        if segments.is_empty() || colons != 0 {
+            return None;
+        }
+        Some(ModPath::from_simple_segments(kind, segments))
     }
 
     /// Calls `cb` with all paths, represented by this use item.
@@ -248,6 +270,39 @@ impl From<Name> for ModPath {
     }
 }
 
+impl fmt::Display for ModPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut first_segment = true;
+        let mut add_segment = |s| -> fmt::Result {
+            if !first_segment {
+                f.write_str("::")?;
+            }
+            first_segment = false;
+            f.write_str(s)
+        };
+        match self.kind {
+            PathKind::Plain => {}
+            PathKind::Super(0) => add_segment("self")?,
+            PathKind::Super(n) => {
+                for _ in 0..n {
+                    add_segment("super")?;
+                }
+            }
+            PathKind::Crate => add_segment("crate")?,
+            PathKind::Abs => add_segment("")?,
+            PathKind::DollarCrate(_) => add_segment("$crate")?,
+        }
+        for segment in &self.segments {
+            if !first_segment {
+                f.write_str("::")?;
+            }
+            first_segment = false;
+            write!(f, "{}", segment)?;
+        }
+        Ok(())
+    }
+}
+
 pub use hir_expand::name as __name;
 
 #[macro_export]
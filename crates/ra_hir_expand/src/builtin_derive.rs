@@ -245,7 +245,10 @@ mod tests {
 
         let loc = MacroCallLoc {
             def,
-            kind: MacroCallKind::Attr(AstId::new(file_id.into(), ast_id_map.ast_id(&items[0]))),
+            kind: MacroCallKind::Attr(
+                AstId::new(file_id.into(), ast_id_map.ast_id(&items[0])),
+                String::new(),
+            ),
         };
 
         let id = db.intern_macro(loc);
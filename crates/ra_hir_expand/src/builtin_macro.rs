@@ -1,8 +1,15 @@
 //! Builtin macro
+use std::{collections::HashSet, path::Path};
+
+use ra_db::{FileId, RelativePath};
+use ra_syntax::SmolStr;
+
 use crate::db::AstDatabase;
 use crate::{
-    ast::{self, AstNode},
-    name, AstId, CrateId, HirFileId, MacroCallId, MacroDefId, MacroDefKind, TextUnit,
+    ast::{self, AstNode, AttrsOwner, NameOwner},
+    mod_resolution::ModDir,
+    name::{self, AsName, Name},
+    AstId, CrateId, HirFileId, MacroCallId, MacroDefId, MacroDefKind, TextUnit,
 };
 
 use crate::quote;
@@ -56,6 +63,13 @@ register_builtin! {
     (format_args, FormatArgs) => format_args_expand,
     (env, Env) => env_expand,
     (option_env, OptionEnv) => option_env_expand,
+    (concat, Concat) => concat_expand,
+    (include, Include) => include_expand,
+    (include_str, IncludeStr) => include_str_expand,
+    (include_bytes, IncludeBytes) => include_bytes_expand,
+    (assert, Assert) => assert_expand,
+    (cfg, Cfg) => cfg_expand,
+    (module_path, ModulePath) => module_path_expand,
     // format_args_nl only differs in that it adds a newline in the end,
     // so we use the same stub expansion for now
     (format_args_nl, FormatArgsNl) => format_args_expand
@@ -128,12 +142,14 @@ fn stringify_expand(
 }
 
 fn env_expand(
-    _db: &dyn AstDatabase,
-    _id: MacroCallId,
-    _tt: &tt::Subtree,
+    db: &dyn AstDatabase,
+    id: MacroCallId,
+    tt: &tt::Subtree,
 ) -> Result<tt::Subtree, mbe::ExpandError> {
-    // dummy implementation for type-checking purposes
-    let expanded = quote! { "" };
+    // Unknown variables expand to "" rather than to an error, so that we can
+    // still type-check the surrounding code.
+    let value = env_str(db, id, tt).unwrap_or_default();
+    let expanded = quote! { #value };
 
     Ok(expanded)
 }
@@ -231,19 +247,316 @@ fn compile_error_expand(
     Err(mbe::ExpandError::BindingError("Must be a string".into()))
 }
 
-fn format_args_expand(
+fn concat_expand(
+    db: &dyn AstDatabase,
+    id: MacroCallId,
+    tt: &tt::Subtree,
+) -> Result<tt::Subtree, mbe::ExpandError> {
+    let text = concat_str(db, id, tt)?;
+    let literal =
+        tt::Literal { text: format!("{:?}", text).into(), id: tt::TokenId::unspecified() };
+    let expanded = quote! { #literal };
+
+    Ok(expanded)
+}
+
+fn include_expand(
+    db: &dyn AstDatabase,
+    id: MacroCallId,
+    tt: &tt::Subtree,
+) -> Result<tt::Subtree, mbe::ExpandError> {
+    let file_id = resolve_include_path(db, id, tt)?;
+    // `parse` depends on the text of the included file, so this expansion is
+    // invalidated whenever the file changes.
+    let parse = db.parse(file_id);
+    let (mut subtree, _token_map) = mbe::syntax_node_to_token_tree(parse.tree().syntax())
+        .ok_or_else(|| mbe::ExpandError::ConversionError)?;
+    // Token ids are relative to the macro call, and the included tokens are
+    // not part of it.
+    clear_token_ids(&mut subtree);
+
+    Ok(subtree)
+}
+
+fn include_str_expand(
+    db: &dyn AstDatabase,
+    id: MacroCallId,
+    tt: &tt::Subtree,
+) -> Result<tt::Subtree, mbe::ExpandError> {
+    let file_id = resolve_include_path(db, id, tt)?;
+    let text = db.file_text(file_id);
+    let literal =
+        tt::Literal { text: format!("{:?}", text).into(), id: tt::TokenId::unspecified() };
+    let expanded = quote! { #literal };
+
+    Ok(expanded)
+}
+
+fn include_bytes_expand(
+    db: &dyn AstDatabase,
+    id: MacroCallId,
+    tt: &tt::Subtree,
+) -> Result<tt::Subtree, mbe::ExpandError> {
+    let file_id = resolve_include_path(db, id, tt)?;
+    let text = db.file_text(file_id);
+    let bytes = text.bytes().flat_map(std::ascii::escape_default).map(char::from);
+    let literal = tt::Literal {
+        text: format!("b\"{}\"", bytes.collect::<String>()).into(),
+        id: tt::TokenId::unspecified(),
+    };
+    let expanded = quote! { #literal };
+
+    Ok(expanded)
+}
+
+fn assert_expand(
     _db: &dyn AstDatabase,
     _id: MacroCallId,
     tt: &tt::Subtree,
 ) -> Result<tt::Subtree, mbe::ExpandError> {
-    // We expand `format_args!("", a1, a2)` to
+    // We expand `assert!(cond, arg1, arg2)` to
     // ```
-    // std::fmt::Arguments::new_v1(&[], &[
-    //   std::fmt::ArgumentV1::new(&arg1,std::fmt::Display::fmt),
-    //   std::fmt::ArgumentV1::new(&arg2,std::fmt::Display::fmt),
-    // ])
+    // {if !cond { std::panic!(arg1, arg2); }}
     // ```,
-    // which is still not really correct, but close enough for now
+    // which is wrong but useful.
+    let mut args = split_args(tt).into_iter();
+    let cond = args.next().ok_or_else(|| mbe::ExpandError::NoMatchingRule)?;
+    let mut panic_args = Vec::new();
+    for arg in args {
+        if !panic_args.is_empty() {
+            panic_args.extend(quote! { , }.token_trees);
+        }
+        panic_args.extend(arg);
+    }
+    let expanded = quote! {
+        { if !(##cond) { std::panic!(##panic_args); } }
+    };
+
+    Ok(expanded)
+}
+
+fn cfg_expand(
+    db: &dyn AstDatabase,
+    id: MacroCallId,
+    tt: &tt::Subtree,
+) -> Result<tt::Subtree, mbe::ExpandError> {
+    let krate = calling_crate(db, id).ok_or_else(|| mbe::ExpandError::ConversionError)?;
+    let enabled = db.crate_graph().cfg_options(krate).is_cfg_enabled(tt) == Some(true);
+    let ident = tt::Ident {
+        text: if enabled { "true" } else { "false" }.into(),
+        id: tt::TokenId::unspecified(),
+    };
+    let expanded = quote! { #ident };
+
+    Ok(expanded)
+}
+
+fn module_path_expand(
+    db: &dyn AstDatabase,
+    id: MacroCallId,
+    _tt: &tt::Subtree,
+) -> Result<tt::Subtree, mbe::ExpandError> {
+    let module_path = module_path(db, id).ok_or_else(|| mbe::ExpandError::ConversionError)?;
+    let expanded = quote! { #module_path };
+
+    Ok(expanded)
+}
+
+/// The path of the module containing the macro call, like `krate::foo::bar`.
+///
+/// Modules are only known to `ra_hir_def`, so this follows the `mod`
+/// declarations from the crate root to the file with the call.
+fn module_path(db: &dyn AstDatabase, id: MacroCallId) -> Option<String> {
+    // The call may come from the expansion of another macro
+    let mut call = db.lookup_intern_macro(id).kind.node(db);
+    while let Some(node) = call.file_id.call_node(db) {
+        call = node;
+    }
+    let file_id = call.file_id.original_file(db);
+
+    let crate_graph = db.crate_graph();
+    let (krate, mut segments) = db.relevant_crates(file_id).iter().find_map(|&krate| {
+        let segments = file_module_path(db, crate_graph.crate_root(krate), file_id)?;
+        Some((krate, segments))
+    })?;
+    let inline_modules: Option<Vec<Name>> = call
+        .value
+        .ancestors()
+        .skip(1)
+        .filter_map(ast::Module::cast)
+        .map(|it| Some(it.name()?.as_name()))
+        .collect();
+    segments.extend(inline_modules?.into_iter().rev());
+
+    let mut path = crate_graph.crate_name(krate)?.to_string();
+    for segment in segments {
+        path.push_str("::");
+        path.push_str(&segment.to_string());
+    }
+    Some(path)
+}
+
+/// Names of the modules leading from `crate_root` to the module defined by
+/// `file_id`.
+fn file_module_path(
+    db: &dyn AstDatabase,
+    crate_root: FileId,
+    file_id: FileId,
+) -> Option<Vec<Name>> {
+    let mut files = vec![(crate_root, ModDir::root(), Vec::new())];
+    let mut visited = HashSet::new();
+    while let Some((current, mod_dir, names)) = files.pop() {
+        if current == file_id {
+            return Some(names);
+        }
+        // `#[path]` can create cycles
+        if !visited.insert(current) {
+            continue;
+        }
+        let source_file = db.parse(current).tree();
+        let declarations = source_file
+            .syntax()
+            .descendants()
+            .filter_map(ast::Module::cast)
+            .filter(|it| it.item_list().is_none());
+        for declaration in declarations {
+            files.extend(resolve_module_file(db, current, &mod_dir, &names, &declaration));
+        }
+    }
+    None
+}
+
+/// Resolves a `mod foo;` in `file_id` to the file of the module, along with
+/// the names of the modules leading to it.
+fn resolve_module_file(
+    db: &dyn AstDatabase,
+    file_id: FileId,
+    mod_dir: &ModDir,
+    names: &[Name],
+    declaration: &ast::Module,
+) -> Option<(FileId, ModDir, Vec<Name>)> {
+    let mut mod_dir = mod_dir.clone();
+    let mut names = names.to_vec();
+    // The declaration may be nested in inline modules
+    let inline_modules: Vec<_> =
+        declaration.syntax().ancestors().skip(1).filter_map(ast::Module::cast).collect();
+    for module in inline_modules.iter().rev() {
+        let name = module.name()?.as_name();
+        mod_dir = mod_dir.descend_into_definition(&name, path_attr(module).as_ref());
+        names.push(name);
+    }
+    let name = declaration.name()?.as_name();
+    let attr_path = path_attr(declaration);
+    let (file_id, mod_dir) =
+        mod_dir.resolve_declaration(db, file_id.into(), &name, attr_path.as_ref()).ok()?;
+    names.push(name);
+    Some((file_id, mod_dir, names))
+}
+
+/// The value of the `#[path = "..."]` attribute of a module.
+fn path_attr(module: &ast::Module) -> Option<SmolStr> {
+    module.attrs().find_map(|attr| match attr.as_simple_key_value()? {
+        (key, value) if key == "path" => Some(value),
+        _ => None,
+    })
+}
+
+/// The crate containing the macro call.
+///
+/// Note that `MacroDefId::krate` of a builtin macro is the crate where the
+/// macro is *declared*, usually `core`.
+fn calling_crate(db: &dyn AstDatabase, id: MacroCallId) -> Option<CrateId> {
+    let file_id = db.lookup_intern_macro(id).kind.file_id().original_file(db);
+    db.relevant_crates(file_id).first().copied()
+}
+
+/// Resolves the path argument of `include!` and friends: relative paths are
+/// relative to the file with the macro call, absolute ones (usually produced
+/// by `concat!(env!("OUT_DIR"), ..)`) are looked up in the crate's extern
+/// sources.
+fn resolve_include_path(
+    db: &dyn AstDatabase,
+    id: MacroCallId,
+    tt: &tt::Subtree,
+) -> Result<FileId, mbe::ExpandError> {
+    let path = eval_str(db, id, &tt.token_trees)?;
+    let file_id = if Path::new(&path).is_absolute() {
+        let krate = calling_crate(db, id);
+        krate.and_then(|krate| {
+            let crate_graph = db.crate_graph();
+            let (extern_id, relative_path) =
+                crate_graph.extern_source(krate).extern_path(Path::new(&path))?;
+            db.resolve_extern_path(extern_id, &relative_path)
+        })
+    } else {
+        let call_file = db.lookup_intern_macro(id).kind.file_id().original_file(db);
+        db.resolve_relative_path(call_file, RelativePath::new(&path))
+    };
+    file_id.ok_or_else(|| mbe::ExpandError::ConversionError)
+}
+
+/// Evaluates a macro argument which should be a string, eagerly expanding
+/// the string-valued builtin macros in it, so that things like
+/// `include!(concat!(env!("OUT_DIR"), "/gen.rs"))` work.
+fn eval_str(
+    db: &dyn AstDatabase,
+    id: MacroCallId,
+    tts: &[tt::TokenTree],
+) -> Result<String, mbe::ExpandError> {
+    match tts {
+        [tt::TokenTree::Leaf(tt::Leaf::Literal(lit))] => {
+            unquote_str(&lit.text).ok_or_else(|| mbe::ExpandError::UnexpectedToken)
+        }
+        [tt::TokenTree::Leaf(tt::Leaf::Ident(name)), tt::TokenTree::Leaf(tt::Leaf::Punct(bang)), tt::TokenTree::Subtree(args)]
+            if bang.char == '!' =>
+        {
+            match name.text.as_str() {
+                "concat" => concat_str(db, id, args),
+                "env" => env_str(db, id, args).ok_or_else(|| mbe::ExpandError::ConversionError),
+                _ => Err(mbe::ExpandError::UnexpectedToken),
+            }
+        }
+        _ => Err(mbe::ExpandError::UnexpectedToken),
+    }
+}
+
+fn concat_str(
+    db: &dyn AstDatabase,
+    id: MacroCallId,
+    tt: &tt::Subtree,
+) -> Result<String, mbe::ExpandError> {
+    let mut text = String::new();
+    for arg in split_args(tt) {
+        match arg.as_slice() {
+            [tt::TokenTree::Leaf(tt::Leaf::Literal(lit))] => {
+                match unquote_str(&lit.text).or_else(|| unquote_char(&lit.text)) {
+                    Some(s) => text.push_str(&s),
+                    // integer and float literals
+                    None => text.push_str(&lit.text),
+                }
+            }
+            [tt::TokenTree::Leaf(tt::Leaf::Ident(ident))]
+                if ident.text == "true" || ident.text == "false" =>
+            {
+                text.push_str(&ident.text)
+            }
+            _ => text.push_str(&eval_str(db, id, &arg)?),
+        }
+    }
+    Ok(text)
+}
+
+fn env_str(db: &dyn AstDatabase, id: MacroCallId, tt: &tt::Subtree) -> Option<String> {
+    let key = match split_args(tt).first()?.as_slice() {
+        [tt::TokenTree::Leaf(tt::Leaf::Literal(lit))] => unquote_str(&lit.text)?,
+        _ => return None,
+    };
+    let krate = calling_crate(db, id)?;
+    db.crate_graph().env(krate).get(&key)
+}
+
+/// Splits macro arguments on top-level commas, ignoring a trailing one.
+fn split_args(tt: &tt::Subtree) -> Vec<Vec<tt::TokenTree>> {
     let mut args = Vec::new();
     let mut current = Vec::new();
     for tt in tt.token_trees.iter().cloned() {
@@ -260,6 +573,104 @@ fn format_args_expand(
     if !current.is_empty() {
         args.push(current);
     }
+    args
+}
+
+/// The value of the string literal `lit`, with escapes resolved.
+pub fn unquote_str(lit: &str) -> Option<String> {
+    if lit.starts_with('r') {
+        let lit = lit[1..].trim_matches('#');
+        if lit.len() < 2 || !lit.starts_with('"') || !lit.ends_with('"') {
+            return None;
+        }
+        return Some(lit[1..lit.len() - 1].to_string());
+    }
+    if lit.len() < 2 || !lit.starts_with('"') || !lit.ends_with('"') {
+        return None;
+    }
+    unescape(&lit[1..lit.len() - 1])
+}
+
+fn unquote_char(lit: &str) -> Option<String> {
+    if lit.len() < 2 || !lit.starts_with('\'') || !lit.ends_with('\'') {
+        return None;
+    }
+    unescape(&lit[1..lit.len() - 1])
+}
+
+fn unescape(text: &str) -> Option<String> {
+    let mut res = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            res.push(c);
+            continue;
+        }
+        let unescaped = match chars.next()? {
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            '0' => '\0',
+            c @ '\\' | c @ '\'' | c @ '"' => c,
+            'x' => {
+                let digits: String = chars.by_ref().take(2).collect();
+                match u8::from_str_radix(&digits, 16) {
+                    Ok(byte) if digits.len() == 2 && byte <= 0x7F => byte as char,
+                    _ => return None,
+                }
+            }
+            'u' => {
+                if chars.next()? != '{' {
+                    return None;
+                }
+                let mut digits = String::new();
+                loop {
+                    match chars.next()? {
+                        '}' => break,
+                        '_' => (),
+                        c => digits.push(c),
+                    }
+                }
+                if digits.is_empty() || digits.len() > 6 {
+                    return None;
+                }
+                std::char::from_u32(u32::from_str_radix(&digits, 16).ok()?)?
+            }
+            _ => return None,
+        };
+        res.push(unescaped);
+    }
+    Some(res)
+}
+
+fn clear_token_ids(subtree: &mut tt::Subtree) {
+    if let Some(delimiter) = &mut subtree.delimiter {
+        delimiter.id = tt::TokenId::unspecified();
+    }
+    for tt in subtree.token_trees.iter_mut() {
+        match tt {
+            tt::TokenTree::Subtree(subtree) => clear_token_ids(subtree),
+            tt::TokenTree::Leaf(tt::Leaf::Literal(it)) => it.id = tt::TokenId::unspecified(),
+            tt::TokenTree::Leaf(tt::Leaf::Punct(it)) => it.id = tt::TokenId::unspecified(),
+            tt::TokenTree::Leaf(tt::Leaf::Ident(it)) => it.id = tt::TokenId::unspecified(),
+        }
+    }
+}
+
+fn format_args_expand(
+    _db: &dyn AstDatabase,
+    _id: MacroCallId,
+    tt: &tt::Subtree,
+) -> Result<tt::Subtree, mbe::ExpandError> {
+    // We expand `format_args!("", a1, a2)` to
+    // ```
+    // std::fmt::Arguments::new_v1(&[], &[
+    //   std::fmt::ArgumentV1::new(&arg1,std::fmt::Display::fmt),
+    //   std::fmt::ArgumentV1::new(&arg2,std::fmt::Display::fmt),
+    // ])
+    // ```,
+    // which is still not really correct, but close enough for now
+    let mut args = split_args(tt);
     if args.is_empty() {
         return Err(mbe::ExpandError::NoMatchingRule);
     }
@@ -282,6 +693,15 @@ mod tests {
 
     fn expand_builtin_macro(s: &str) -> String {
         let (db, file_id) = TestDB::with_single_file(&s);
+        expand_builtin_macro_in_file(&db, file_id)
+    }
+
+    fn expand_builtin_macro_with_files(fixture: &str) -> String {
+        let (db, pos) = TestDB::with_position(&fixture);
+        expand_builtin_macro_in_file(&db, pos.file_id)
+    }
+
+    fn expand_builtin_macro_in_file(db: &TestDB, file_id: FileId) -> String {
         let parsed = db.parse(file_id);
         let macro_calls: Vec<_> =
             parsed.syntax_node().descendants().filter_map(|it| ast::MacroCall::cast(it)).collect();
@@ -424,4 +844,132 @@ mod tests {
             r#"std::fmt::Arguments::new_v1(&[] ,&[std::fmt::ArgumentV1::new(&(arg1(a,b,c)),std::fmt::Display::fmt),std::fmt::ArgumentV1::new(&(arg2),std::fmt::Display::fmt),])"#
         );
     }
+
+    #[test]
+    fn test_concat_expand() {
+        let expanded = expand_builtin_macro(
+            r#"
+            #[rustc_builtin_macro]
+            macro_rules! concat {}
+            concat!("a", concat!("b", 'c'), 1, true);
+            "#,
+        );
+
+        assert_eq!(expanded, r#""abc1true""#);
+    }
+
+    #[test]
+    fn test_concat_expand_escapes() {
+        let expanded = expand_builtin_macro(
+            r#"
+            #[rustc_builtin_macro]
+            macro_rules! concat {}
+            concat!("\x41\t", "\u{e9}");
+            "#,
+        );
+
+        assert_eq!(expanded, r#""A\té""#);
+    }
+
+    #[test]
+    fn test_unescape_byte_escapes() {
+        assert_eq!(unescape(r"\x41\x7f"), Some("A\x7f".to_string()));
+        assert_eq!(unescape(r"\x80"), None);
+        assert_eq!(unescape(r"\x4"), None);
+        assert_eq!(unescape(r"\x4g"), None);
+    }
+
+    #[test]
+    fn test_unescape_unicode_escapes() {
+        assert_eq!(unescape(r"\u{41}\u{1F6_00}"), Some("A\u{1F600}".to_string()));
+        assert_eq!(unescape(r"\u{D800}"), None);
+        assert_eq!(unescape(r"\u{}"), None);
+        assert_eq!(unescape(r"\u{41"), None);
+        assert_eq!(unescape(r"\u41"), None);
+    }
+
+    #[test]
+    fn test_include_expand() {
+        let expanded = expand_builtin_macro_with_files(
+            r#"
+            //- /main.rs
+            #[rustc_builtin_macro]
+            macro_rules! include {}
+            include!("foo.rs");<|>
+
+            //- /foo.rs
+            fn foo() {}
+            "#,
+        );
+
+        assert_eq!(expanded, "fnfoo(){}");
+    }
+
+    #[test]
+    fn test_assert_expand() {
+        let expanded = expand_builtin_macro(
+            r#"
+            #[rustc_builtin_macro]
+            macro_rules! assert {
+                ($cond:expr) => ({ /* compiler built-in */ });
+                ($cond:expr, $($args:tt)*) => ({ /* compiler built-in */ })
+            }
+            assert!(true, "{} {:?}", arg1(a, b, c), arg2);
+            "#,
+        );
+
+        assert_eq!(expanded, r#"{if!(true){std::panic!("{} {:?}",arg1(a,b,c),arg2);}}"#);
+    }
+
+    #[test]
+    fn test_cfg_expand() {
+        let expanded = expand_builtin_macro(
+            r#"
+            #[rustc_builtin_macro]
+            macro_rules! cfg {}
+            cfg!(test)
+            "#,
+        );
+
+        assert_eq!(expanded, "false");
+    }
+
+    #[test]
+    fn test_module_path_expand() {
+        let expanded = expand_builtin_macro_with_files(
+            r#"
+            //- /lib.rs crate:foo
+            mod bar;
+
+            //- /bar.rs
+            #[rustc_builtin_macro]
+            macro_rules! module_path {}
+            mod baz {
+                fn f() { module_path!()<|>; }
+            }
+            "#,
+        );
+
+        assert_eq!(expanded, r#""foo::bar::baz""#);
+    }
+
+    #[test]
+    fn test_module_path_expand_path_attr() {
+        let expanded = expand_builtin_macro_with_files(
+            r#"
+            //- /lib.rs crate:foo
+            mod bar {
+                #[path = "qux.rs"]
+                mod baz;
+            }
+
+            //- /bar/qux.rs
+            #[rustc_builtin_macro]
+            macro_rules! module_path {}
+            module_path!()<|>;
+            "#,
+        );
+
+        assert_eq!(expanded, r#""foo::bar::baz""#);
+    }
 }
@@ -28,6 +28,7 @@ impl Hygiene {
                     MacroDefKind::Declarative => loc.def.krate,
                     MacroDefKind::BuiltIn(_) => None,
                     MacroDefKind::BuiltInDerive(_) => None,
+                    MacroDefKind::ProcMacro(_) => None,
                 }
             }
         };
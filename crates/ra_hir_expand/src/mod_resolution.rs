@@ -1,12 +1,11 @@
 //! This module resolves `mod foo;` declaration to file.
-use hir_expand::name::Name;
 use ra_db::{FileId, RelativePathBuf};
 use ra_syntax::SmolStr;
 
-use crate::{db::DefDatabase, HirFileId};
+use crate::{db::AstDatabase, name::Name, HirFileId};
 
 #[derive(Clone, Debug)]
-pub(super) struct ModDir {
+pub struct ModDir {
     /// `.` for `mod.rs`, `lib.rs`
     /// `./foo` for `foo.rs`
     /// `./foo/bar` for `mod bar { mod x; }` nested in `foo.rs`
@@ -16,15 +15,11 @@ pub(super) struct ModDir {
 }
 
 impl ModDir {
-    pub(super) fn root() -> ModDir {
+    pub fn root() -> ModDir {
         ModDir { path: RelativePathBuf::default(), root_non_dir_owner: false }
     }
 
-    pub(super) fn descend_into_definition(
-        &self,
-        name: &Name,
-        attr_path: Option<&SmolStr>,
-    ) -> ModDir {
+    pub fn descend_into_definition(&self, name: &Name, attr_path: Option<&SmolStr>) -> ModDir {
         let mut path = self.path.clone();
         match attr_to_path(attr_path) {
             None => path.push(&name.to_string()),
@@ -38,9 +33,9 @@ impl ModDir {
         ModDir { path, root_non_dir_owner: false }
     }
 
-    pub(super) fn resolve_declaration(
+    pub fn resolve_declaration(
         &self,
-        db: &impl DefDatabase,
+        db: &dyn AstDatabase,
         file_id: HirFileId,
         name: &Name,
         attr_path: Option<&SmolStr>,
@@ -107,6 +107,12 @@ impl AsName for ra_db::Dependency {
     }
 }
 
+impl AsName for ra_db::ProcMacro {
+    fn as_name(&self) -> Name {
+        Name::new_text(self.name.clone())
+    }
+}
+
 pub mod known {
     macro_rules! known_names {
         ($($ident:ident),* $(,)?) => {
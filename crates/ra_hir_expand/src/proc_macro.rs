@@ -0,0 +1,165 @@
+//! Proc Macro Expander stuff
+//!
+//! Procedural macros are never expanded in-process: the crate graph only
+//! stores a handle (`ra_db::ProcMacro`) to an expander which talks to an
+//! external server. This module glues those handles to the `MacroDefKind`
+//! machinery and prepares the input token tree the way rustc does.
+
+use ra_db::{CrateId, ProcMacroId, ProcMacroKind};
+
+use crate::{db::AstDatabase, MacroCallId, MacroCallKind};
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct ProcMacroExpander {
+    krate: CrateId,
+    proc_macro_id: ProcMacroId,
+}
+
+macro_rules! err {
+    ($fmt:literal, $($tt:tt),*) => {
+        mbe::ExpandError::ProcMacroError(tt::ExpansionError::Unknown(format!($fmt, $($tt),*)))
+    };
+    ($fmt:literal) => {
+        mbe::ExpandError::ProcMacroError(tt::ExpansionError::Unknown($fmt.to_string()))
+    }
+}
+
+impl ProcMacroExpander {
+    pub fn new(krate: CrateId, proc_macro_id: ProcMacroId) -> ProcMacroExpander {
+        ProcMacroExpander { krate, proc_macro_id }
+    }
+
+    pub fn expand(
+        &self,
+        db: &dyn AstDatabase,
+        id: MacroCallId,
+        tt: &tt::Subtree,
+    ) -> Result<tt::Subtree, mbe::ExpandError> {
+        let krate_graph = db.crate_graph();
+        let proc_macro = krate_graph
+            .proc_macros(self.krate)
+            .get(self.proc_macro_id.0 as usize)
+            .ok_or_else(|| err!("No proc macro {:?} found", self.proc_macro_id))?;
+
+        let loc = db.lookup_intern_macro(id);
+        match (&loc.kind, proc_macro.kind) {
+            (MacroCallKind::FnLike(_), ProcMacroKind::FuncLike) => {
+                proc_macro.expander.expand(tt, None).map_err(mbe::ExpandError::from)
+            }
+            (MacroCallKind::Attr(_, _), ProcMacroKind::CustomDerive) => {
+                let tt = remove_attrs(tt, |path| path == "derive");
+                proc_macro.expander.expand(&tt, None).map_err(mbe::ExpandError::from)
+            }
+            (MacroCallKind::Attr(_, name), ProcMacroKind::Attr) => {
+                let attr = find_attr_input(tt, name);
+                let tt = remove_attrs(tt, |path| path == name);
+                proc_macro.expander.expand(&tt, attr.as_ref()).map_err(mbe::ExpandError::from)
+            }
+            (kind, _) => Err(err!("Proc macro {} can't be invoked as {:?}", proc_macro.name, kind)),
+        }
+    }
+}
+
+/// Removes the outer attributes (`#[path ...]`) whose path satisfies `pred`.
+///
+/// rustc hands the annotated item to a proc macro without the attribute which
+/// invoked it (and, for derives, without any `#[derive]`), otherwise the
+/// expansion would invoke the macro again.
+fn remove_attrs(tt: &tt::Subtree, pred: impl Fn(&str) -> bool) -> tt::Subtree {
+    let mut token_trees = Vec::with_capacity(tt.token_trees.len());
+    let mut iter = tt.token_trees.iter().peekable();
+    while let Some(tree) = iter.next() {
+        if let tt::TokenTree::Leaf(tt::Leaf::Punct(tt::Punct { char: '#', .. })) = tree {
+            if let Some(tt::TokenTree::Subtree(attr)) = iter.peek() {
+                if attr.delimiter_kind() == Some(tt::DelimiterKind::Bracket)
+                    && attr_path(attr).map_or(false, |path| pred(&path))
+                {
+                    iter.next();
+                    continue;
+                }
+            }
+        }
+        token_trees.push(tree.clone());
+    }
+    tt::Subtree { delimiter: tt.delimiter, token_trees }
+}
+
+/// Finds the `(...)` argument of the outer `#[name(...)]` attribute.
+fn find_attr_input(tt: &tt::Subtree, name: &str) -> Option<tt::Subtree> {
+    let attr =
+        outer_attrs(tt).find(|attr| attr_path(attr).as_ref().map(String::as_str) == Some(name))?;
+    attr.token_trees.iter().find_map(|it| match it {
+        tt::TokenTree::Subtree(it) => Some(tt::Subtree { delimiter: None, ..it.clone() }),
+        _ => None,
+    })
+}
+
+/// Iterates over the `[...]` parts of the top-level `#[...]` attributes.
+fn outer_attrs(tt: &tt::Subtree) -> impl Iterator<Item = &tt::Subtree> {
+    tt.token_trees.windows(2).filter_map(|pair| match pair {
+        [tt::TokenTree::Leaf(tt::Leaf::Punct(tt::Punct { char: '#', .. })), tt::TokenTree::Subtree(attr)]
+            if attr.delimiter_kind() == Some(tt::DelimiterKind::Bracket) =>
+        {
+            Some(attr)
+        }
+        _ => None,
+    })
+}
+
+/// Returns the path of an attribute (`tokio::main` for `[tokio::main]`) as text.
+fn attr_path(attr: &tt::Subtree) -> Option<String> {
+    let mut path = String::new();
+    for tt in attr.token_trees.iter() {
+        match tt {
+            tt::TokenTree::Leaf(tt::Leaf::Ident(ident)) => path.push_str(&ident.text),
+            tt::TokenTree::Leaf(tt::Leaf::Punct(tt::Punct { char: ':', .. })) => path.push(':'),
+            _ => break,
+        }
+    }
+    if path.is_empty() {
+        None
+    } else {
+        Some(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ra_syntax::{ast, AstNode};
+
+    use super::*;
+
+    fn parse(text: &str) -> tt::Subtree {
+        let source_file = ast::SourceFile::parse(&format!("m!{{{}}}", text)).tree();
+        let token_tree = source_file.syntax().descendants().find_map(ast::TokenTree::cast).unwrap();
+        let (subtree, _) = mbe::ast_to_token_tree(&token_tree).unwrap();
+        tt::Subtree { delimiter: None, ..subtree }
+    }
+
+    #[test]
+    fn test_remove_derive_attrs() {
+        let tt = parse(
+            r#"
+            #[allow(unused)]
+            #[derive(Serialize, Debug)]
+            struct S;
+            "#,
+        );
+        let tt = remove_attrs(&tt, |path| path == "derive");
+        assert_eq!(tt.to_string(), "# [allow (unused)] struct S ;");
+    }
+
+    #[test]
+    fn test_find_attr_input() {
+        let tt = parse(
+            r#"
+            #[tokio::main(basic_scheduler)]
+            async fn main() {}
+            "#,
+        );
+        let input = find_attr_input(&tt, "tokio::main").unwrap();
+        assert_eq!(input.to_string(), "basic_scheduler");
+        let tt = remove_attrs(&tt, |path| path == "tokio::main");
+        assert_eq!(tt.to_string(), "async fn main () {}");
+    }
+}
@@ -98,8 +98,10 @@ macro_rules! __quote {
     // FIXME: Not all puncts are handled
     ( -> ) => {$crate::__quote!(@PUNCT '-', '>')};
     ( & ) => {$crate::__quote!(@PUNCT '&')};
+    ( ! ) => {$crate::__quote!(@PUNCT '!')};
     ( , ) => {$crate::__quote!(@PUNCT ',')};
     ( : ) => {$crate::__quote!(@PUNCT ':')};
+    ( ; ) => {$crate::__quote!(@PUNCT ';')};
     ( :: ) => {$crate::__quote!(@PUNCT ':', ':')};
     ( . ) => {$crate::__quote!(@PUNCT '.')};
     ( < ) => {$crate::__quote!(@PUNCT '<')};
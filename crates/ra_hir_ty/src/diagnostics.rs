@@ -62,6 +62,30 @@ impl AstDiagnostic for MissingFields {
     }
 }
 
+#[derive(Debug)]
+pub struct TypeMismatch {
+    pub file: HirFileId,
+    pub expr: AstPtr<ast::Expr>,
+    pub expected: String,
+    pub actual: String,
+    /// Whether neither type contains unknown, not yet inferred or
+    /// trait-dependent parts. Mismatches between partially known types are
+    /// often artifacts of unimplemented inference features.
+    pub fully_known: bool,
+}
+
+impl Diagnostic for TypeMismatch {
+    fn message(&self) -> String {
+        format!("expected {}, found {}", self.expected, self.actual)
+    }
+    fn source(&self) -> InFile<SyntaxNodePtr> {
+        InFile { file_id: self.file, value: self.expr.into() }
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+}
+
 #[derive(Debug)]
 pub struct MissingOkInTailExpr {
     pub file: HirFileId,
@@ -89,3 +113,150 @@ impl AstDiagnostic for MissingOkInTailExpr {
         ast::Expr::cast(node).unwrap()
     }
 }
+
+#[derive(Debug)]
+pub struct UnresolvedPath {
+    pub file: HirFileId,
+    pub expr: AstPtr<ast::Expr>,
+    pub path: String,
+}
+
+impl Diagnostic for UnresolvedPath {
+    fn message(&self) -> String {
+        format!("unresolved path `{}`", self.path)
+    }
+    fn source(&self) -> InFile<SyntaxNodePtr> {
+        InFile { file_id: self.file, value: self.expr.into() }
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+}
+
+impl AstDiagnostic for UnresolvedPath {
+    type AST = ast::Expr;
+
+    fn ast(&self, db: &impl AstDatabase) -> Self::AST {
+        let root = db.parse_or_expand(self.file).unwrap();
+        let node = self.source().value.to_node(&root);
+        ast::Expr::cast(node).unwrap()
+    }
+}
+
+#[derive(Debug)]
+pub struct UnresolvedField {
+    pub file: HirFileId,
+    pub name_ref: AstPtr<ast::NameRef>,
+    pub name: String,
+    pub receiver: String,
+}
+
+impl Diagnostic for UnresolvedField {
+    fn message(&self) -> String {
+        format!("no field `{}` on type `{}`", self.name, self.receiver)
+    }
+    fn source(&self) -> InFile<SyntaxNodePtr> {
+        InFile { file_id: self.file, value: self.name_ref.into() }
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+}
+
+#[derive(Debug)]
+pub struct UnresolvedMethodCall {
+    pub file: HirFileId,
+    pub name_ref: AstPtr<ast::NameRef>,
+    pub name: String,
+    pub receiver: String,
+}
+
+impl Diagnostic for UnresolvedMethodCall {
+    fn message(&self) -> String {
+        format!("no method `{}` on type `{}`", self.name, self.receiver)
+    }
+    fn source(&self) -> InFile<SyntaxNodePtr> {
+        InFile { file_id: self.file, value: self.name_ref.into() }
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+}
+
+#[derive(Debug)]
+pub struct MissingMatchArms {
+    pub file: HirFileId,
+    pub match_expr: AstPtr<ast::Expr>,
+    pub arms: AstPtr<ast::MatchArmList>,
+    /// Patterns which are not covered by any arm, as source code.
+    pub missed_patterns: Vec<String>,
+}
+
+impl Diagnostic for MissingMatchArms {
+    fn message(&self) -> String {
+        const MAX_LISTED: usize = 3;
+        let mut listed = self
+            .missed_patterns
+            .iter()
+            .take(MAX_LISTED)
+            .map(|pat| format!("`{}`", pat))
+            .collect::<Vec<_>>()
+            .join(", ");
+        if self.missed_patterns.len() > MAX_LISTED {
+            listed += &format!(" and {} more", self.missed_patterns.len() - MAX_LISTED);
+        }
+        format!("missing match arms: {} not covered", listed)
+    }
+    fn source(&self) -> InFile<SyntaxNodePtr> {
+        InFile { file_id: self.file, value: self.match_expr.into() }
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+}
+
+impl AstDiagnostic for MissingMatchArms {
+    type AST = ast::MatchArmList;
+
+    fn ast(&self, db: &impl AstDatabase) -> Self::AST {
+        let root = db.parse_or_expand(self.file).unwrap();
+        self.arms.to_node(&root)
+    }
+}
+
+#[derive(Debug)]
+pub struct InconsistentBinding {
+    pub file: HirFileId,
+    pub pat: AstPtr<ast::Pat>,
+    pub name: Name,
+}
+
+impl Diagnostic for InconsistentBinding {
+    fn message(&self) -> String {
+        format!("variable `{}` is not bound in all patterns", self.name)
+    }
+    fn source(&self) -> InFile<SyntaxNodePtr> {
+        InFile { file_id: self.file, value: self.pat.into() }
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+}
+
+#[derive(Debug)]
+pub struct UnreachablePattern {
+    pub file: HirFileId,
+    pub pat: AstPtr<ast::Pat>,
+}
+
+impl Diagnostic for UnreachablePattern {
+    fn message(&self) -> String {
+        "unreachable pattern".to_string()
+    }
+    fn source(&self) -> InFile<SyntaxNodePtr> {
+        InFile { file_id: self.file, value: self.pat.into() }
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+}
@@ -3,9 +3,9 @@
 use std::sync::Arc;
 
 use hir_def::{
-    path::{path, Path},
-    resolver::HasResolver,
-    AdtId, FunctionId,
+    path::{path, ModPath, Path, PathKind},
+    resolver::{resolver_for_expr, HasResolver, Resolver, ValueNs},
+    AdtId, FunctionId, HasModule, Lookup,
 };
 use hir_expand::{diagnostics::DiagnosticSink, name::Name};
 use ra_syntax::ast;
@@ -14,7 +14,12 @@ use rustc_hash::FxHashSet;
 
 use crate::{
     db::HirDatabase,
-    diagnostics::{MissingFields, MissingOkInTailExpr},
+    diagnostics::{
+        InconsistentBinding, MissingFields, MissingMatchArms, MissingOkInTailExpr, TypeMismatch,
+        UnreachablePattern,
+    },
+    display::HirDisplay,
+    match_check::MatchCheckCtx,
     ApplicationTy, InferenceResult, Ty, TypeCtor,
 };
 
@@ -48,14 +53,80 @@ impl<'a, 'b> ExprValidator<'a, 'b> {
         let body = db.body(self.func.into());
 
         for e in body.exprs.iter() {
-            if let (id, Expr::RecordLit { path, fields, spread }) = e {
-                self.validate_record_literal(id, path, fields, *spread, db);
+            match e {
+                (id, Expr::RecordLit { path, fields, spread }) => {
+                    self.validate_record_literal(id, path, fields, *spread, db);
+                }
+                (id, Expr::Match { expr, arms }) => {
+                    for arm in arms {
+                        self.validate_or_bindings(&arm.pats, &body, db);
+                    }
+                    self.validate_match(id, *expr, arms, &body, db);
+                }
+                _ => (),
+            }
+        }
+        for (_, pat) in body.pats.iter() {
+            if let Pat::Or(alternatives) = pat {
+                self.validate_or_bindings(alternatives, &body, db);
             }
         }
 
         let body_expr = &body[body.body_expr];
+        let mut missing_ok_in = None;
         if let Expr::Block { statements: _, tail: Some(t) } = body_expr {
-            self.validate_results_in_tail_expr(body.body_expr, *t, db);
+            if self.validate_results_in_tail_expr(body.body_expr, *t, db) {
+                missing_ok_in = Some(*t);
+            }
+        }
+
+        self.validate_type_mismatches(&body, missing_ok_in, db);
+    }
+
+    fn validate_type_mismatches(
+        &mut self,
+        body: &Body,
+        missing_ok_in: Option<ExprId>,
+        db: &impl HirDatabase,
+    ) {
+        let (_, source_map) = db.body_with_source_map(self.func.into());
+
+        for (id, mismatch) in self.infer.type_mismatches.iter() {
+            if Some(id) == missing_ok_in {
+                continue;
+            }
+            match &body[id] {
+                // A block coerces its tail expression, so the mismatch of the
+                // tail is reported instead.
+                Expr::Block { tail: Some(tail), .. } => {
+                    if Some(*tail) == missing_ok_in
+                        || self.infer.type_mismatch_for_expr(*tail).is_some()
+                    {
+                        continue;
+                    }
+                }
+                _ => (),
+            }
+
+            let expected = mismatch.expected.display(db).to_string();
+            let actual = mismatch.actual.display(db).to_string();
+            if expected == actual {
+                continue;
+            }
+            let fully_known =
+                mismatch.expected.is_fully_known() && mismatch.actual.is_fully_known();
+
+            if let Some(source_ptr) = source_map.expr_syntax(id) {
+                if let Some(expr) = source_ptr.value.left() {
+                    self.sink.push(TypeMismatch {
+                        file: source_ptr.file_id,
+                        expr,
+                        expected,
+                        actual,
+                        fully_known,
+                    });
+                }
+            }
         }
     }
 
@@ -112,16 +183,118 @@ impl<'a, 'b> ExprValidator<'a, 'b> {
         }
     }
 
+    fn validate_match(
+        &mut self,
+        id: ExprId,
+        scrutinee: ExprId,
+        arms: &[MatchArm],
+        body: &Body,
+        db: &impl HirDatabase,
+    ) {
+        let scrutinee_ty = &self.infer[scrutinee];
+        if !scrutinee_ty.is_fully_known() {
+            return;
+        }
+        let ctx = MatchCheckCtx {
+            db,
+            body,
+            infer: &self.infer,
+            resolver: resolver_for_expr(db, self.func.into(), id),
+            module: self.func.lookup(db).module(db),
+        };
+        let result = match ctx.check_match(scrutinee_ty, arms) {
+            Some(it) => it,
+            None => return,
+        };
+        let (_, source_map) = db.body_with_source_map(self.func.into());
+
+        for pat in result.unreachable {
+            if let Some(source_ptr) = source_map.pat_syntax(pat) {
+                if let Some(pat_ptr) = source_ptr.value.left() {
+                    self.sink.push(UnreachablePattern { file: source_ptr.file_id, pat: pat_ptr });
+                }
+            }
+        }
+
+        if result.missing.is_empty() {
+            return;
+        }
+        if let Some(source_ptr) = source_map.expr_syntax(id) {
+            if let Some(expr) = source_ptr.value.left() {
+                let root = source_ptr.file_syntax(db);
+                if let ast::Expr::MatchExpr(match_expr) = expr.to_node(&root) {
+                    if let (Some(scrutinee), Some(arms)) =
+                        (match_expr.expr(), match_expr.match_arm_list())
+                    {
+                        self.sink.push(MissingMatchArms {
+                            file: source_ptr.file_id,
+                            match_expr: AstPtr::new(&scrutinee),
+                            arms: AstPtr::new(&arms),
+                            missed_patterns: result.missing,
+                        })
+                    }
+                }
+            }
+        }
+    }
+
+    /// Checks that every alternative of an or-pattern binds the same variables.
+    fn validate_or_bindings(&mut self, alternatives: &[PatId], body: &Body, db: &impl HirDatabase) {
+        if alternatives.len() < 2 {
+            return;
+        }
+        let resolver = self.func.resolver(db);
+        let bindings = alternatives
+            .iter()
+            .map(|&pat| {
+                let mut names = Vec::new();
+                collect_bindings(db, &resolver, body, pat, &mut names);
+                names
+            })
+            .collect::<Vec<_>>();
+        let mut all_names: Vec<&Name> = Vec::new();
+        for name in bindings.iter().flatten() {
+            if !all_names.contains(&name) {
+                all_names.push(name);
+            }
+        }
+        if all_names.is_empty() {
+            return;
+        }
+
+        let (_, source_map) = db.body_with_source_map(self.func.into());
+        for (&pat, names) in alternatives.iter().zip(bindings.iter()) {
+            let source_ptr = match source_map.pat_syntax(pat) {
+                Some(it) => it,
+                None => continue,
+            };
+            let pat_ptr = match source_ptr.value.left() {
+                Some(it) => it,
+                None => continue,
+            };
+            for &name in all_names.iter() {
+                if !names.contains(name) {
+                    self.sink.push(InconsistentBinding {
+                        file: source_ptr.file_id,
+                        pat: pat_ptr,
+                        name: name.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Returns `true` if a `MissingOkInTailExpr` diagnostic was reported.
     fn validate_results_in_tail_expr(
         &mut self,
         body_id: ExprId,
         id: ExprId,
         db: &impl HirDatabase,
-    ) {
+    ) -> bool {
         // the mismatch will be on the whole block currently
         let mismatch = match self.infer.type_mismatch_for_expr(body_id) {
             Some(m) => m,
-            None => return,
+            None => return false,
         };
 
         let std_result_path = path![std::result::Result];
@@ -129,13 +302,13 @@ impl<'a, 'b> ExprValidator<'a, 'b> {
         let resolver = self.func.resolver(db);
         let std_result_enum = match resolver.resolve_known_enum(db, &std_result_path) {
             Some(it) => it,
-            _ => return,
+            _ => return false,
         };
 
         let std_result_ctor = TypeCtor::Adt(AdtId::EnumId(std_result_enum));
         let params = match &mismatch.expected {
             Ty::Apply(ApplicationTy { ctor, parameters }) if ctor == &std_result_ctor => parameters,
-            _ => return,
+            _ => return false,
         };
 
         if params.len() == 2 && &params[0] == &mismatch.actual {
@@ -144,8 +317,48 @@ impl<'a, 'b> ExprValidator<'a, 'b> {
             if let Some(source_ptr) = source_map.expr_syntax(id) {
                 if let Some(expr) = source_ptr.value.left() {
                     self.sink.push(MissingOkInTailExpr { file: source_ptr.file_id, expr });
+                    return true;
                 }
             }
         }
+        false
+    }
+}
+
+/// Collects the names bound by `pat`. A bare identifier which resolves to a
+/// unit struct, a unit variant or a constant is not a binding.
+fn collect_bindings(
+    db: &impl HirDatabase,
+    resolver: &Resolver,
+    body: &Body,
+    pat: PatId,
+    acc: &mut Vec<Name>,
+) {
+    match &body[pat] {
+        Pat::Bind { mode, name, subpat } => {
+            if let (BindingAnnotation::Unannotated, None) = (mode, subpat) {
+                let path = ModPath::from_simple_segments(PathKind::Plain, vec![name.clone()]);
+                match resolver.resolve_path_in_value_ns_fully(db, &path) {
+                    Some(ValueNs::EnumVariantId(_))
+                    | Some(ValueNs::StructId(_))
+                    | Some(ValueNs::ConstId(_)) => return,
+                    _ => (),
+                }
+            }
+            if !acc.contains(name) {
+                acc.push(name.clone());
+            }
+            if let Some(subpat) = subpat {
+                collect_bindings(db, resolver, body, *subpat, acc);
+            }
+        }
+        // Alternatives bind the same variables, or a diagnostic is reported
+        // for them, so the first one is enough.
+        Pat::Or(alternatives) => {
+            if let Some(&first) = alternatives.first() {
+                collect_bindings(db, resolver, body, first, acc);
+            }
+        }
+        p => p.walk_child_pats(|pat| collect_bindings(db, resolver, body, pat, acc)),
     }
 }
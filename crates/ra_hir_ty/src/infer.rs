@@ -38,7 +38,7 @@ use test_utils::tested_by;
 
 use super::{
     primitive::{FloatTy, IntTy},
-    traits::{Guidance, Obligation, ProjectionPredicate, Solution},
+    traits::{FnTrait, Guidance, Obligation, ProjectionPredicate, Solution},
     ApplicationTy, GenericPredicate, InEnvironment, ProjectionTy, Substs, TraitEnvironment,
     TraitRef, Ty, TypeCtor, TypeWalk, Uncertain,
 };
@@ -60,6 +60,7 @@ mod path;
 mod expr;
 mod pat;
 mod coerce;
+mod closure;
 
 /// The entry point of type inference.
 pub fn do_infer_query(db: &impl HirDatabase, def: DefWithBodyId) -> Arc<InferenceResult> {
@@ -75,7 +76,9 @@ pub fn do_infer_query(db: &impl HirDatabase, def: DefWithBodyId) -> Arc<Inferenc
 
     ctx.infer_body();
 
-    Arc::new(ctx.resolve_all())
+    let mut result = ctx.resolve_all();
+    closure::infer_closure_captures(db, def, &mut result);
+    Arc::new(result)
 }
 
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
@@ -110,6 +113,20 @@ impl Default for BindingMode {
     }
 }
 
+/// How a closure captures a variable from its environment.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum CaptureKind {
+    ByRef(Mutability),
+    ByValue,
+}
+
+/// A variable of the enclosing body which is used inside of a closure.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CapturedItem {
+    pub local: PatId,
+    pub kind: CaptureKind,
+}
+
 /// A mismatch between an expected and an inferred type.
 #[derive(Clone, PartialEq, Eq, Debug, Hash)]
 pub struct TypeMismatch {
@@ -130,6 +147,10 @@ pub struct InferenceResult {
     variant_resolutions: FxHashMap<ExprOrPatId, VariantId>,
     /// For each associated item record what it resolves to
     assoc_resolutions: FxHashMap<ExprOrPatId, AssocItemId>,
+    /// For each closure, the most permissive `Fn` trait it implements.
+    closure_kinds: FxHashMap<ExprId, FnTrait>,
+    /// For each closure, the variables it captures, in order of first use.
+    closure_captures: FxHashMap<ExprId, Vec<CapturedItem>>,
     diagnostics: Vec<InferenceDiagnostic>,
     pub type_of_expr: ArenaMap<ExprId, Ty>,
     pub type_of_pat: ArenaMap<PatId, Ty>,
@@ -161,13 +182,19 @@ impl InferenceResult {
     pub fn type_mismatch_for_expr(&self, expr: ExprId) -> Option<&TypeMismatch> {
         self.type_mismatches.get(expr)
     }
+    pub fn closure_kind(&self, closure: ExprId) -> Option<FnTrait> {
+        self.closure_kinds.get(&closure).copied()
+    }
+    pub fn closure_captures(&self, closure: ExprId) -> &[CapturedItem] {
+        self.closure_captures.get(&closure).map_or(&[], |it| it.as_slice())
+    }
     pub fn add_diagnostics(
         &self,
         db: &impl HirDatabase,
         owner: FunctionId,
         sink: &mut DiagnosticSink,
     ) {
-        self.diagnostics.iter().for_each(|it| it.add_to(db, owner, sink))
+        self.diagnostics.iter().for_each(|it| it.add_to(db, owner, self, sink))
     }
 }
 
@@ -240,6 +267,12 @@ impl<'a, D: HirDatabase> InferenceContext<'a, D> {
             let resolved = self.table.resolve_ty_completely(mem::replace(ty, Ty::Unknown));
             *ty = resolved;
         }
+        for mismatch in result.type_mismatches.values_mut() {
+            mismatch.expected =
+                self.table.resolve_ty_completely(mem::replace(&mut mismatch.expected, Ty::Unknown));
+            mismatch.actual =
+                self.table.resolve_ty_completely(mem::replace(&mut mismatch.actual, Ty::Unknown));
+        }
         result
     }
 
@@ -622,12 +655,22 @@ impl Expectation {
 mod diagnostics {
     use hir_def::{expr::ExprId, src::HasSource, FunctionId, Lookup};
     use hir_expand::diagnostics::DiagnosticSink;
-
-    use crate::{db::HirDatabase, diagnostics::NoSuchField};
+    use ra_syntax::{ast, AstNode, AstPtr};
+
+    use super::InferenceResult;
+    use crate::{
+        db::HirDatabase,
+        diagnostics::{NoSuchField, UnresolvedField, UnresolvedMethodCall, UnresolvedPath},
+        display::HirDisplay,
+        Ty,
+    };
 
     #[derive(Debug, PartialEq, Eq, Clone)]
     pub(super) enum InferenceDiagnostic {
         NoSuchField { expr: ExprId, field: usize },
+        UnresolvedPath { expr: ExprId },
+        UnresolvedField { expr: ExprId, receiver: ExprId },
+        UnresolvedMethodCall { expr: ExprId, receiver: ExprId },
     }
 
     impl InferenceDiagnostic {
@@ -635,6 +678,7 @@ mod diagnostics {
             &self,
             db: &impl HirDatabase,
             owner: FunctionId,
+            infer: &InferenceResult,
             sink: &mut DiagnosticSink,
         ) {
             match self {
@@ -644,7 +688,74 @@ mod diagnostics {
                     let field = source_map.field_syntax(*expr, *field);
                     sink.push(NoSuchField { file, field })
                 }
+                InferenceDiagnostic::UnresolvedPath { expr } => {
+                    let (_, source_map) = db.body_with_source_map(owner.into());
+                    if let Some(source) = source_map.expr_syntax(*expr) {
+                        if let Some(expr) = source.value.left() {
+                            let root = source.file_syntax(db);
+                            let path = expr.to_node(&root).syntax().text().to_string();
+                            sink.push(UnresolvedPath { file: source.file_id, expr, path })
+                        }
+                    }
+                }
+                InferenceDiagnostic::UnresolvedField { expr, receiver } => {
+                    let receiver_ty = &infer[*receiver];
+                    if !is_known_receiver(receiver_ty) {
+                        return;
+                    }
+                    let (_, source_map) = db.body_with_source_map(owner.into());
+                    let source = match source_map.expr_syntax(*expr) {
+                        Some(it) => it,
+                        None => return,
+                    };
+                    let root = source.file_syntax(db);
+                    let name_ref = match source.value.left().map(|it| it.to_node(&root)) {
+                        Some(ast::Expr::FieldExpr(it)) => it.name_ref(),
+                        _ => None,
+                    };
+                    if let Some(name_ref) = name_ref {
+                        sink.push(UnresolvedField {
+                            file: source.file_id,
+                            name_ref: AstPtr::new(&name_ref),
+                            name: name_ref.text().to_string(),
+                            receiver: receiver_ty.display(db).to_string(),
+                        })
+                    }
+                }
+                InferenceDiagnostic::UnresolvedMethodCall { expr, receiver } => {
+                    let receiver_ty = &infer[*receiver];
+                    if !is_known_receiver(receiver_ty) {
+                        return;
+                    }
+                    let (_, source_map) = db.body_with_source_map(owner.into());
+                    let source = match source_map.expr_syntax(*expr) {
+                        Some(it) => it,
+                        None => return,
+                    };
+                    let root = source.file_syntax(db);
+                    let name_ref = match source.value.left().map(|it| it.to_node(&root)) {
+                        Some(ast::Expr::MethodCallExpr(it)) => it.name_ref(),
+                        _ => None,
+                    };
+                    if let Some(name_ref) = name_ref {
+                        sink.push(UnresolvedMethodCall {
+                            file: source.file_id,
+                            name_ref: AstPtr::new(&name_ref),
+                            name: name_ref.text().to_string(),
+                            receiver: receiver_ty.display(db).to_string(),
+                        })
+                    }
+                }
             }
         }
     }
+
+    /// Field and method lookup on partially inferred types or type parameters
+    /// is not reliable enough to report failures.
+    fn is_known_receiver(ty: &Ty) -> bool {
+        match ty {
+            Ty::Apply(_) => ty.is_fully_known(),
+            _ => false,
+        }
+    }
 }
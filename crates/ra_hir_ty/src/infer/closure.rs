@@ -0,0 +1,472 @@
+//! Closure-specific parts of type inference: deducing the signature of a
+//! closure from the type it is expected to have, and working out which
+//! variables of the enclosing body a closure captures and how.
+
+use hir_def::{
+    body::{
+        scope::{ExprScopes, ScopeId},
+        Body,
+    },
+    expr::{BinaryOp, BindingAnnotation, Expr, ExprId, Pat, PatId, Statement, UnaryOp},
+    path::Path,
+    resolver::{HasResolver, Resolver},
+    type_ref::{Mutability, TypeRef},
+    DefWithBodyId, HasModule, TraitId, TypeAliasId,
+};
+use hir_expand::name::name;
+use ra_db::CrateId;
+
+use super::{CaptureKind, CapturedItem, InferTy, InferenceContext, InferenceResult};
+use crate::{
+    db::HirDatabase, method_resolution::implements_trait, traits::FnTrait, ApplicationTy,
+    Canonical, FnSig, GenericPredicate, Obligation, Ty, TypeCtor,
+};
+
+impl<'a, D: HirDatabase> InferenceContext<'a, D> {
+    /// Tries to find out the signature of a closure with `num_args` parameters
+    /// from the type it is expected to have, before its body is inferred. This
+    /// works for function pointers, `dyn`/`impl` types with an `Fn*` bound, and
+    /// type variables with pending `Fn*` obligations, like the `F` in
+    /// `fn map<B, F: FnMut(Self::Item) -> B>(self, f: F)`.
+    pub(super) fn deduce_closure_signature(
+        &mut self,
+        expected: &Ty,
+        num_args: usize,
+    ) -> Option<FnSig> {
+        let expected = self.resolve_ty_shallow(expected).into_owned();
+        let sig = match &expected {
+            ty_app!(TypeCtor::FnPtr { .. }) | ty_app!(TypeCtor::Closure { .. }) => {
+                expected.callable_sig(self.db)?
+            }
+            Ty::Dyn(predicates) | Ty::Opaque(predicates) => {
+                // The predicates are about the bound variable standing for the
+                // `dyn`/`impl` type itself.
+                self.sig_from_fn_bounds(predicates, |_, self_ty| *self_ty == Ty::Bound(0))?
+            }
+            Ty::Infer(InferTy::TypeVar(var)) => {
+                let var = *var;
+                let predicates: Vec<_> = self
+                    .obligations
+                    .iter()
+                    .map(|obligation| match obligation {
+                        Obligation::Trait(tr) => GenericPredicate::Implemented(tr.clone()),
+                        Obligation::Projection(pred) => GenericPredicate::Projection(pred.clone()),
+                    })
+                    .collect();
+                self.sig_from_fn_bounds(&predicates, |ctx, self_ty| {
+                    match &*ctx.resolve_ty_shallow(self_ty) {
+                        Ty::Infer(InferTy::TypeVar(other)) => {
+                            let table = &mut ctx.table.var_unification_table;
+                            table.find(*other) == table.find(var)
+                        }
+                        _ => false,
+                    }
+                })?
+            }
+            _ => return None,
+        };
+        if sig.params().len() != num_args {
+            return None;
+        }
+        let params = sig.params().iter().map(|ty| self.insert_type_vars(ty.clone())).collect();
+        let ret = self.insert_type_vars(sig.ret().clone());
+        Some(FnSig::from_params_and_return(params, ret))
+    }
+
+    fn sig_from_fn_bounds(
+        &mut self,
+        predicates: &[GenericPredicate],
+        mut is_self_ty: impl FnMut(&mut Self, &Ty) -> bool,
+    ) -> Option<FnSig> {
+        let fn_once_output = self.resolve_fn_once_output();
+        let mut params = None;
+        let mut ret = None;
+        for pred in predicates {
+            match pred {
+                GenericPredicate::Implemented(trait_ref) => {
+                    if !self.is_fn_trait(trait_ref.trait_)
+                        || trait_ref.substs.len() != 2
+                        || !is_self_ty(self, trait_ref.self_ty())
+                    {
+                        continue;
+                    }
+                    if let ty_app!(TypeCtor::Tuple { .. }, arg_tys) =
+                        &*self.resolve_ty_shallow(&trait_ref.substs[1])
+                    {
+                        params = Some(arg_tys.iter().cloned().collect::<Vec<_>>());
+                    }
+                }
+                GenericPredicate::Projection(pred) => {
+                    if Some(pred.projection_ty.associated_ty) == fn_once_output
+                        && is_self_ty(self, &pred.projection_ty.parameters[0])
+                    {
+                        ret = Some(pred.ty.clone());
+                    }
+                }
+                GenericPredicate::Error => {}
+            }
+        }
+        let ret = ret.unwrap_or_else(|| self.table.new_type_var());
+        Some(FnSig::from_params_and_return(params?, ret))
+    }
+
+    fn is_fn_trait(&self, trait_: TraitId) -> bool {
+        [FnTrait::FnOnce, FnTrait::FnMut, FnTrait::Fn].iter().any(|fn_trait| {
+            self.resolve_lang_item(fn_trait.lang_item_name()).and_then(|it| it.as_trait())
+                == Some(trait_)
+        })
+    }
+
+    fn resolve_fn_once_output(&self) -> Option<TypeAliasId> {
+        let trait_ = self.resolve_lang_item(FnTrait::FnOnce.lang_item_name())?.as_trait()?;
+        self.db.trait_data(trait_).associated_type_by_name(&name![Output])
+    }
+}
+
+/// Finds the captured variables and the `Fn*` kind of every closure in the
+/// body of `owner`, using the already inferred types.
+pub(super) fn infer_closure_captures(
+    db: &impl HirDatabase,
+    owner: DefWithBodyId,
+    result: &mut InferenceResult,
+) {
+    let body = db.body(owner);
+    let closures: Vec<_> = body
+        .exprs
+        .iter()
+        .filter_map(|(id, expr)| match expr {
+            Expr::Lambda { .. } => Some(id),
+            _ => None,
+        })
+        .collect();
+    if closures.is_empty() {
+        return;
+    }
+    let scopes = db.expr_scopes(owner);
+    let resolver = owner.resolver(db);
+    let krate = owner.module(db).krate;
+    let copy_trait = db.lang_item(krate, "copy".into()).and_then(|it| it.as_trait());
+
+    // Closures nested in (or defined before) another closure come first in the
+    // arena, so their kinds are known by the time a call to them is seen.
+    for closure in closures {
+        let (closure_body, is_move) = match &body[closure] {
+            Expr::Lambda { body, is_move, .. } => (*body, *is_move),
+            _ => unreachable!(),
+        };
+        let mut collector = CaptureCollector {
+            db,
+            owner,
+            body: &body,
+            scopes: &scopes,
+            result,
+            resolver: &resolver,
+            krate,
+            copy_trait,
+            closure_scope: scopes.scope_for(closure_body),
+            captures: Vec::new(),
+        };
+        collector.consume(closure_body);
+        let captures = collector.captures;
+
+        let kind = match captures.iter().map(|(_, usage)| *usage).max() {
+            Some(Usage::Move) => FnTrait::FnOnce,
+            Some(Usage::Mut) => FnTrait::FnMut,
+            Some(Usage::Shared) | None => FnTrait::Fn,
+        };
+        let captures = captures
+            .into_iter()
+            .map(|(local, usage)| {
+                let kind = match usage {
+                    _ if is_move => CaptureKind::ByValue,
+                    Usage::Shared => CaptureKind::ByRef(Mutability::Shared),
+                    Usage::Mut => CaptureKind::ByRef(Mutability::Mut),
+                    Usage::Move => CaptureKind::ByValue,
+                };
+                CapturedItem { local, kind }
+            })
+            .collect();
+        result.closure_kinds.insert(closure, kind);
+        result.closure_captures.insert(closure, captures);
+    }
+}
+
+/// How a captured variable is used in a closure body, from least to most
+/// demanding.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Usage {
+    Shared,
+    Mut,
+    Move,
+}
+
+impl From<Mutability> for Usage {
+    fn from(mutability: Mutability) -> Usage {
+        match mutability {
+            Mutability::Shared => Usage::Shared,
+            Mutability::Mut => Usage::Mut,
+        }
+    }
+}
+
+struct CaptureCollector<'a, DB: HirDatabase> {
+    db: &'a DB,
+    owner: DefWithBodyId,
+    body: &'a Body,
+    scopes: &'a ExprScopes,
+    result: &'a InferenceResult,
+    resolver: &'a Resolver,
+    krate: CrateId,
+    copy_trait: Option<TraitId>,
+    closure_scope: Option<ScopeId>,
+    captures: Vec<(PatId, Usage)>,
+}
+
+impl<'a, DB: HirDatabase> CaptureCollector<'a, DB> {
+    /// `expr` is evaluated and its value used.
+    fn consume(&mut self, expr: ExprId) {
+        if self.is_place(expr) {
+            let usage = if self.is_copy(&self.result[expr]) { Usage::Shared } else { Usage::Move };
+            self.use_place(expr, usage);
+        } else {
+            self.walk_expr(expr);
+        }
+    }
+
+    /// `expr` is borrowed, explicitly or by autoref.
+    fn borrow(&mut self, expr: ExprId, mutability: Mutability) {
+        self.use_place(expr, mutability.into());
+    }
+
+    fn is_place(&self, expr: ExprId) -> bool {
+        match &self.body[expr] {
+            Expr::Path(_) | Expr::Field { .. } | Expr::Index { .. } => true,
+            Expr::UnaryOp { op: UnaryOp::Deref, .. } => true,
+            _ => false,
+        }
+    }
+
+    fn use_place(&mut self, expr: ExprId, usage: Usage) {
+        match &self.body[expr] {
+            Expr::Path(path) => {
+                if let Some(local) = self.captured_local(expr, path) {
+                    self.record(local, usage);
+                }
+            }
+            Expr::Field { expr: base, .. } => self.use_place(*base, usage),
+            Expr::Index { base, index } => {
+                // Indexing goes through a reference, values can't be moved out
+                self.use_place(*base, usage.min(Usage::Mut));
+                self.consume(*index);
+            }
+            Expr::UnaryOp { expr: inner, op: UnaryOp::Deref } => {
+                self.use_place(*inner, usage.min(Usage::Mut))
+            }
+            _ => self.walk_expr(expr),
+        }
+    }
+
+    fn walk_expr(&mut self, expr: ExprId) {
+        let body = self.body;
+        match &body[expr] {
+            Expr::Ref { expr, mutability } => self.borrow(*expr, *mutability),
+            Expr::BinaryOp { lhs, rhs, op: Some(BinaryOp::Assignment { .. }) } => {
+                self.use_place(*lhs, Usage::Mut);
+                self.consume(*rhs);
+            }
+            Expr::BinaryOp { lhs, rhs, op: Some(BinaryOp::CmpOp(_)) } => {
+                // Comparison operators take their operands by reference
+                self.borrow(*lhs, Mutability::Shared);
+                self.borrow(*rhs, Mutability::Shared);
+            }
+            Expr::MethodCall { receiver, args, .. } => {
+                match self.receiver_mutability(expr) {
+                    Some(mutability) => self.borrow(*receiver, mutability),
+                    None => self.consume(*receiver),
+                }
+                args.iter().for_each(|&arg| self.consume(arg));
+            }
+            Expr::Call { callee, args } => {
+                match self.callee_kind(*callee) {
+                    Some(FnTrait::FnOnce) => self.consume(*callee),
+                    Some(FnTrait::FnMut) => self.borrow(*callee, Mutability::Mut),
+                    Some(FnTrait::Fn) | None => self.borrow(*callee, Mutability::Shared),
+                }
+                args.iter().for_each(|&arg| self.consume(arg));
+            }
+            Expr::Match { expr: scrutinee, arms } => {
+                let usage = arms
+                    .iter()
+                    .flat_map(|arm| arm.pats.iter())
+                    .map(|&pat| self.pat_usage(pat))
+                    .max()
+                    .unwrap_or(Usage::Shared);
+                self.use_place(*scrutinee, usage);
+                for arm in arms {
+                    if let Some(guard) = arm.guard {
+                        self.consume(guard);
+                    }
+                    self.consume(arm.expr);
+                }
+            }
+            Expr::Block { statements, tail } => {
+                for stmt in statements {
+                    match stmt {
+                        Statement::Let { pat, initializer, .. } => {
+                            if let Some(initializer) = initializer {
+                                let usage = self.pat_usage(*pat);
+                                self.use_place(*initializer, usage);
+                            }
+                        }
+                        Statement::Expr(expr) => self.consume(*expr),
+                    }
+                }
+                if let Some(tail) = tail {
+                    self.consume(*tail);
+                }
+            }
+            e => e.walk_child_exprs(|child| self.consume(child)),
+        }
+    }
+
+    /// How binding a value to `pat` uses that value.
+    fn pat_usage(&self, pat: PatId) -> Usage {
+        match &self.body[pat] {
+            Pat::Bind { mode, subpat, .. } => {
+                let usage = match mode {
+                    BindingAnnotation::Ref => Usage::Shared,
+                    BindingAnnotation::RefMut => Usage::Mut,
+                    BindingAnnotation::Unannotated | BindingAnnotation::Mutable => {
+                        if self.is_copy(&self.result[pat]) {
+                            Usage::Shared
+                        } else {
+                            Usage::Move
+                        }
+                    }
+                };
+                match subpat {
+                    Some(subpat) => usage.max(self.pat_usage(*subpat)),
+                    None => usage,
+                }
+            }
+            p => {
+                let mut usage = Usage::Shared;
+                p.walk_child_pats(|child| usage = usage.max(self.pat_usage(child)));
+                usage
+            }
+        }
+    }
+
+    /// If `path` refers to a local variable declared outside of the closure,
+    /// returns that variable.
+    fn captured_local(&self, expr: ExprId, path: &Path) -> Option<PatId> {
+        let name = path.mod_path().as_ident()?;
+        let mut inside_closure = true;
+        for scope in self.scopes.scope_chain(self.scopes.scope_for(expr)) {
+            if let Some(entry) = self.scopes.entries(scope).iter().find(|it| it.name() == name) {
+                return if inside_closure { None } else { Some(entry.pat()) };
+            }
+            if Some(scope) == self.closure_scope {
+                inside_closure = false;
+            }
+        }
+        None
+    }
+
+    fn record(&mut self, local: PatId, usage: Usage) {
+        match self.captures.iter_mut().find(|(it, _)| *it == local) {
+            Some((_, it)) => *it = usage.max(*it),
+            None => self.captures.push((local, usage)),
+        }
+    }
+
+    /// For a method call, whether the method takes `self` by reference.
+    fn receiver_mutability(&self, method_call: ExprId) -> Option<Mutability> {
+        let func = self.result.method_resolution(method_call)?;
+        let data = self.db.function_data(func);
+        if !data.has_self_param {
+            return None;
+        }
+        match data.params.first()? {
+            TypeRef::Reference(_, mutability) => Some(*mutability),
+            _ => None,
+        }
+    }
+
+    /// Which `Fn*` trait a call of `callee` goes through, and so how the
+    /// callee is used by the call.
+    fn callee_kind(&self, callee: ExprId) -> Option<FnTrait> {
+        self.fn_trait_of(&self.result[callee])
+    }
+
+    fn fn_trait_of(&self, ty: &Ty) -> Option<FnTrait> {
+        match ty {
+            Ty::Apply(ApplicationTy { ctor: TypeCtor::Closure { def, expr }, .. }) => {
+                if *def == self.owner {
+                    self.result.closure_kind(*expr)
+                } else {
+                    None
+                }
+            }
+            ty_app!(TypeCtor::FnPtr { .. }) | ty_app!(TypeCtor::FnDef(_)) => Some(FnTrait::Fn),
+            // Calling through `&F` copies the reference, through `&mut F` it
+            // reborrows it
+            ty_app!(TypeCtor::Ref(Mutability::Shared)) => Some(FnTrait::Fn),
+            ty_app!(TypeCtor::Ref(Mutability::Mut)) => Some(FnTrait::FnMut),
+            Ty::Unknown | Ty::Infer(_) => None,
+            _ => [FnTrait::Fn, FnTrait::FnMut, FnTrait::FnOnce]
+                .iter()
+                .copied()
+                .find(|&fn_trait| self.implements_fn_trait(ty, fn_trait)),
+        }
+    }
+
+    fn is_copy(&self, ty: &Ty) -> bool {
+        match ty {
+            Ty::Apply(a_ty) => match a_ty.ctor {
+                TypeCtor::Bool
+                | TypeCtor::Char
+                | TypeCtor::Int(_)
+                | TypeCtor::Float(_)
+                | TypeCtor::Never
+                | TypeCtor::RawPtr(_)
+                | TypeCtor::Ref(Mutability::Shared)
+                | TypeCtor::FnPtr { .. }
+                | TypeCtor::FnDef(_) => true,
+                TypeCtor::Tuple { .. } | TypeCtor::Array => {
+                    a_ty.parameters.iter().all(|ty| self.is_copy(ty))
+                }
+                TypeCtor::Ref(Mutability::Mut)
+                | TypeCtor::Str
+                | TypeCtor::Slice
+                | TypeCtor::Closure { .. } => false,
+                TypeCtor::Adt(_) | TypeCtor::AssociatedType(_) => self.implements_copy(ty),
+            },
+            // We can't tell, and wrongly assuming a move would make the closure `FnOnce`
+            Ty::Unknown => true,
+            _ => self.implements_copy(ty),
+        }
+    }
+
+    fn implements_fn_trait(&self, ty: &Ty, fn_trait: FnTrait) -> bool {
+        let trait_ = match self
+            .db
+            .lang_item(self.krate, fn_trait.lang_item_name().into())
+            .and_then(|it| it.as_trait())
+        {
+            Some(it) => it,
+            None => return false,
+        };
+        let canonical = Canonical { value: ty.clone(), num_vars: 0 };
+        implements_trait(&canonical, self.db, self.resolver, self.krate, trait_)
+    }
+
+    fn implements_copy(&self, ty: &Ty) -> bool {
+        let copy_trait = match self.copy_trait {
+            Some(it) => it,
+            None => return false,
+        };
+        let canonical = Canonical { value: ty.clone(), num_vars: 0 };
+        implements_trait(&canonical, self.db, self.resolver, self.krate, copy_trait)
+    }
+}
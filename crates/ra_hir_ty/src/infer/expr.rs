@@ -6,8 +6,8 @@ use std::sync::Arc;
 use hir_def::{
     builtin_type::Signedness,
     expr::{Array, BinaryOp, Expr, ExprId, Literal, Statement, UnaryOp},
-    path::{GenericArg, GenericArgs},
-    resolver::resolver_for_expr,
+    path::{GenericArg, GenericArgs, ModPath, Path, PathKind},
+    resolver::{resolver_for_expr, Resolver},
     AdtId, AssocContainerId, Lookup, StructFieldId,
 };
 use hir_expand::name::{name, Name};
@@ -102,14 +102,18 @@ impl<'a, D: HirDatabase> InferenceContext<'a, D> {
                 self.infer_expr(*body, &Expectation::has_type(Ty::unit()));
                 Ty::unit()
             }
-            Expr::Lambda { body, args, ret_type, arg_types } => {
+            Expr::Lambda { body, args, ret_type, arg_types, .. } => {
                 assert_eq!(args.len(), arg_types.len());
 
+                let expected_sig = self.deduce_closure_signature(&expected.ty, args.len());
+
                 let mut sig_tys = Vec::new();
 
-                for (arg_pat, arg_type) in args.iter().zip(arg_types.iter()) {
+                for (i, (arg_pat, arg_type)) in args.iter().zip(arg_types.iter()).enumerate() {
                     let expected = if let Some(type_ref) = arg_type {
                         self.make_ty(type_ref)
+                    } else if let Some(sig) = &expected_sig {
+                        sig.params()[i].clone()
                     } else {
                         Ty::Unknown
                     };
@@ -118,9 +122,10 @@ impl<'a, D: HirDatabase> InferenceContext<'a, D> {
                 }
 
                 // add return type
-                let ret_ty = match ret_type {
-                    Some(type_ref) => self.make_ty(type_ref),
-                    None => self.table.new_type_var(),
+                let ret_ty = match (ret_type, expected_sig) {
+                    (Some(type_ref), _) => self.make_ty(type_ref),
+                    (None, Some(sig)) => sig.ret().clone(),
+                    (None, None) => self.table.new_type_var(),
                 };
                 sig_tys.push(ret_ty.clone());
                 let sig_ty = Ty::apply(
@@ -187,7 +192,17 @@ impl<'a, D: HirDatabase> InferenceContext<'a, D> {
             Expr::Path(p) => {
                 // FIXME this could be more efficient...
                 let resolver = resolver_for_expr(self.db, self.owner.into(), tgt_expr);
-                self.infer_path(&resolver, p, tgt_expr.into()).unwrap_or(Ty::Unknown)
+                match self.infer_path(&resolver, p, tgt_expr.into()) {
+                    Some(ty) => ty,
+                    None => {
+                        if self.is_unresolved_path(&resolver, p) {
+                            self.push_diagnostic(InferenceDiagnostic::UnresolvedPath {
+                                expr: tgt_expr,
+                            });
+                        }
+                        Ty::Unknown
+                    }
+                }
             }
             Expr::Continue => Ty::simple(TypeCtor::Never),
             Expr::Break { expr } => {
@@ -248,6 +263,7 @@ impl<'a, D: HirDatabase> InferenceContext<'a, D> {
             Expr::Field { expr, name } => {
                 let receiver_ty = self.infer_expr_inner(*expr, &Expectation::none());
                 let canonicalized = self.canonicalizer().canonicalize_ty(receiver_ty);
+                let mut saw_union = false;
                 let ty = autoderef::autoderef(
                     self.db,
                     self.resolver.krate(),
@@ -256,27 +272,43 @@ impl<'a, D: HirDatabase> InferenceContext<'a, D> {
                         environment: self.trait_env.clone(),
                     },
                 )
-                .find_map(|derefed_ty| match canonicalized.decanonicalize_ty(derefed_ty.value) {
-                    Ty::Apply(a_ty) => match a_ty.ctor {
-                        TypeCtor::Tuple { .. } => name
-                            .as_tuple_index()
-                            .and_then(|idx| a_ty.parameters.0.get(idx).cloned()),
-                        TypeCtor::Adt(AdtId::StructId(s)) => {
-                            self.db.struct_data(s).variant_data.field(name).map(|local_id| {
-                                let field = StructFieldId { parent: s.into(), local_id }.into();
-                                self.write_field_resolution(tgt_expr, field);
-                                self.db.field_types(s.into())[field.local_id]
-                                    .clone()
-                                    .subst(&a_ty.parameters)
-                            })
-                        }
-                        // FIXME:
-                        TypeCtor::Adt(AdtId::UnionId(_)) => None,
+                .find_map(|derefed_ty| {
+                    match canonicalized.decanonicalize_ty(derefed_ty.value) {
+                        Ty::Apply(a_ty) => match a_ty.ctor {
+                            TypeCtor::Tuple { .. } => name
+                                .as_tuple_index()
+                                .and_then(|idx| a_ty.parameters.0.get(idx).cloned()),
+                            TypeCtor::Adt(AdtId::StructId(s)) => {
+                                self.db.struct_data(s).variant_data.field(name).map(|local_id| {
+                                    let field = StructFieldId { parent: s.into(), local_id }.into();
+                                    self.write_field_resolution(tgt_expr, field);
+                                    self.db.field_types(s.into())[field.local_id]
+                                        .clone()
+                                        .subst(&a_ty.parameters)
+                                })
+                            }
+                            // FIXME:
+                            TypeCtor::Adt(AdtId::UnionId(_)) => {
+                                saw_union = true;
+                                None
+                            }
+                            _ => None,
+                        },
                         _ => None,
-                    },
-                    _ => None,
-                })
-                .unwrap_or(Ty::Unknown);
+                    }
+                });
+                let ty = match ty {
+                    Some(ty) => ty,
+                    None => {
+                        if !saw_union {
+                            self.push_diagnostic(InferenceDiagnostic::UnresolvedField {
+                                expr: tgt_expr,
+                                receiver: *expr,
+                            });
+                        }
+                        Ty::Unknown
+                    }
+                };
                 let ty = self.insert_type_vars(ty);
                 self.normalize_associated_types_in(ty)
             }
@@ -581,7 +613,15 @@ impl<'a, D: HirDatabase> InferenceContext<'a, D> {
                 self.write_method_resolution(tgt_expr, func);
                 (ty, self.db.value_ty(func.into()), Some(generics(self.db, func.into())))
             }
-            None => (receiver_ty, Ty::Unknown, None),
+            None => {
+                if !self.trait_in_scope_has_method(method_name) {
+                    self.push_diagnostic(InferenceDiagnostic::UnresolvedMethodCall {
+                        expr: tgt_expr,
+                        receiver,
+                    });
+                }
+                (receiver_ty, Ty::Unknown, None)
+            }
         };
         let substs = self.substs_for_method_call(def_generics, generic_args, &derefed_receiver_ty);
         let method_ty = method_ty.apply_substs(substs);
@@ -635,6 +675,36 @@ impl<'a, D: HirDatabase> InferenceContext<'a, D> {
         }
     }
 
+    /// Whether a path which failed to resolve refers to a name which is not in
+    /// scope at all, as opposed to an associated item we failed to find.
+    fn is_unresolved_path(&self, resolver: &Resolver, path: &Path) -> bool {
+        if path.type_anchor().is_some() || *path.kind() != PathKind::Plain {
+            return false;
+        }
+        let first_segment = match path.segments().first() {
+            Some(it) => it.name.clone(),
+            None => return false,
+        };
+        if first_segment == name![Self] {
+            return false;
+        }
+        if path.segments().len() == 1 {
+            return resolver.resolve_path_in_value_ns(self.db, path.mod_path()).is_none();
+        }
+        let first_segment = ModPath::from_simple_segments(PathKind::Plain, vec![first_segment]);
+        resolver.resolve_path_in_type_ns(self.db, &first_segment).is_none()
+            && resolver.resolve_module_path_in_items(self.db, &first_segment).is_none()
+    }
+
+    /// Trait solving is incomplete, so a method is only considered to be
+    /// unresolved if no trait in scope has a method with that name.
+    fn trait_in_scope_has_method(&self, name: &Name) -> bool {
+        self.resolver
+            .traits_in_scope(self.db)
+            .into_iter()
+            .any(|t| self.db.trait_data(t).items.iter().any(|(item_name, _)| item_name == name))
+    }
+
     fn substs_for_method_call(
         &mut self,
         def_generics: Option<Generics>,
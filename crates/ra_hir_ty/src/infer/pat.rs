@@ -11,7 +11,7 @@ use hir_def::{
 use hir_expand::name::Name;
 use test_utils::tested_by;
 
-use super::{BindingMode, InferenceContext};
+use super::{BindingMode, Expectation, InferenceContext};
 use crate::{db::HirDatabase, utils::variant_data, Substs, Ty, TypeCtor, TypeWalk};
 
 impl<'a, D: HirDatabase> InferenceContext<'a, D> {
@@ -21,9 +21,13 @@ impl<'a, D: HirDatabase> InferenceContext<'a, D> {
         subpats: &[PatId],
         expected: &Ty,
         default_bm: BindingMode,
+        id: PatId,
     ) -> Ty {
         let (ty, def) = self.resolve_variant(path);
         let var_data = def.map(|it| variant_data(self.db, it));
+        if let Some(variant) = def {
+            self.write_variant_resolution(id.into(), variant);
+        }
         self.unify(&ty, expected);
 
         let substs = ty.substs().unwrap_or_else(Substs::empty);
@@ -89,7 +93,8 @@ impl<'a, D: HirDatabase> InferenceContext<'a, D> {
             | Pat::Slice { .. } => true,
             // FIXME: Path/Lit might actually evaluate to ref, but inference is unimplemented.
             Pat::Path(..) | Pat::Lit(..) => true,
-            Pat::Wild | Pat::Bind { .. } | Pat::Ref { .. } | Pat::Missing => false,
+            // Each alternative of an or-pattern is adjusted on its own.
+            Pat::Wild | Pat::Bind { .. } | Pat::Ref { .. } | Pat::Or(..) | Pat::Missing => false,
         };
         if is_non_ref_pat {
             while let Some((inner, mutability)) = expected.as_reference() {
@@ -127,6 +132,17 @@ impl<'a, D: HirDatabase> InferenceContext<'a, D> {
 
                 Ty::apply(TypeCtor::Tuple { cardinality: args.len() as u16 }, Substs(inner_tys))
             }
+            Pat::Or(pats) => {
+                if let Some((first_pat, rest)) = pats.split_first() {
+                    let ty = self.infer_pat(*first_pat, expected, default_bm);
+                    for pat in rest {
+                        self.infer_pat(*pat, expected, default_bm);
+                    }
+                    ty
+                } else {
+                    Ty::Unknown
+                }
+            }
             Pat::Ref { pat, mutability } => {
                 let expectation = match expected.as_reference() {
                     Some((inner_ty, exp_mut)) => {
@@ -141,7 +157,7 @@ impl<'a, D: HirDatabase> InferenceContext<'a, D> {
                 Ty::apply_one(TypeCtor::Ref(*mutability), subty)
             }
             Pat::TupleStruct { path: p, args: subpats } => {
-                self.infer_tuple_struct_pat(p.as_ref(), subpats, expected, default_bm)
+                self.infer_tuple_struct_pat(p.as_ref(), subpats, expected, default_bm, pat)
             }
             Pat::Record { path: p, args: fields } => {
                 self.infer_record_pat(p.as_ref(), fields, expected, default_bm, pat)
@@ -174,6 +190,25 @@ impl<'a, D: HirDatabase> InferenceContext<'a, D> {
                 self.write_pat_ty(pat, bound_ty);
                 return inner_ty;
             }
+            Pat::Slice { prefix, rest, suffix } => {
+                let (container_ty, elem_ty) = match &expected {
+                    ty_app!(TypeCtor::Array, st) => (TypeCtor::Array, st.as_single().clone()),
+                    ty_app!(TypeCtor::Slice, st) => (TypeCtor::Slice, st.as_single().clone()),
+                    _ => (TypeCtor::Slice, Ty::Unknown),
+                };
+
+                for pat_id in prefix.iter().chain(suffix) {
+                    self.infer_pat(*pat_id, &elem_ty, default_bm);
+                }
+
+                let pat_ty = Ty::apply_one(container_ty, elem_ty);
+                if let Some(slice_pat_id) = rest {
+                    self.infer_pat(*slice_pat_id, &pat_ty, default_bm);
+                }
+
+                pat_ty
+            }
+            Pat::Lit(expr) => self.infer_expr(*expr, &Expectation::none()),
             _ => Ty::Unknown,
         };
         // use a new type variable if we got Ty::Unknown here
@@ -27,6 +27,7 @@ pub mod method_resolution;
 mod op;
 mod lower;
 mod infer;
+mod match_check;
 pub mod display;
 pub(crate) mod utils;
 pub mod db;
@@ -58,7 +59,7 @@ use crate::{
 use display::{HirDisplay, HirFormatter};
 
 pub use autoderef::autoderef;
-pub use infer::{do_infer_query, InferTy, InferenceResult};
+pub use infer::{do_infer_query, CaptureKind, CapturedItem, InferTy, InferenceResult};
 pub use lower::CallableDef;
 pub use lower::{callable_item_sig, TyDefId, ValueTyDefId};
 pub use traits::{InEnvironment, Obligation, ProjectionPredicate, TraitEnvironment};
@@ -698,6 +699,23 @@ impl Ty {
             _ => None,
         }
     }
+
+    /// Whether the type is completely inferred. Types which still contain
+    /// inference holes, or whose equality depends on trait solving, are not
+    /// reliable enough to report errors against.
+    pub fn is_fully_known(&self) -> bool {
+        let mut known = true;
+        self.walk(&mut |ty| match ty {
+            Ty::Unknown
+            | Ty::Infer(_)
+            | Ty::Projection(_)
+            | Ty::Bound(_)
+            | Ty::Dyn(_)
+            | Ty::Opaque(_) => known = false,
+            Ty::Apply(_) | Ty::Param { .. } => (),
+        });
+        known
+    }
 }
 
 /// This allows walking structures that contain types to do something with those
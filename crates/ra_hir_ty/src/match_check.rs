@@ -0,0 +1,618 @@
+//! Exhaustiveness and usefulness checking for `match` expressions.
+//!
+//! This is an implementation of the algorithm from "Warnings for pattern
+//! matching" by Luc Maranget. A row of patterns is *useful* with respect to a
+//! matrix of patterns if some value is matched by the row, but by no row of
+//! the matrix. A `match` is exhaustive if the wildcard is not useful with
+//! respect to its arms, and an arm is unreachable if it is not useful with
+//! respect to the arms before it.
+//!
+//! Patterns are first lowered into a simplified form, where every pattern is
+//! either a wildcard or a constructor applied to sub-patterns. References are
+//! transparent, as they have a single constructor. Or-patterns are expanded
+//! into their alternatives, each of which becomes a row of its own. Literals other than `bool`s
+//! are opaque: they only match themselves and never cover their type.
+//!
+//! The check is conservative: if some pattern can't be lowered (ranges,
+//! unresolved paths, `..` in tuples), no diagnostics are reported at all.
+
+use std::iter;
+
+use hir_def::{
+    adt::VariantData,
+    body::Body,
+    expr::{BindingAnnotation, Expr, Literal, MatchArm, Pat, PatId},
+    find_path::find_path,
+    item_scope::ItemInNs,
+    path::{ModPath, PathKind},
+    resolver::{Resolver, ValueNs},
+    AdtId, EnumVariantId, ModuleDefId, ModuleId, StructId, VariantId,
+};
+
+use crate::{db::HirDatabase, utils::variant_data, ApplicationTy, InferenceResult, Ty, TypeCtor};
+
+/// Computing all the missing patterns can be exponential, and nobody wants to
+/// read through hundreds of them anyway.
+const MAX_WITNESSES: usize = 32;
+
+/// Nested or-patterns multiply: `(A | B, C | D)` has four alternatives. Past
+/// this many, the check gives up.
+const MAX_ALTERNATIVES: usize = 256;
+
+pub(crate) struct MatchCheckResult {
+    /// Patterns, rendered as source code, which are not covered by any arm.
+    pub(crate) missing: Vec<String>,
+    pub(crate) unreachable: Vec<PatId>,
+}
+
+pub(crate) struct MatchCheckCtx<'a, D: HirDatabase> {
+    pub(crate) db: &'a D,
+    pub(crate) body: &'a Body,
+    pub(crate) infer: &'a InferenceResult,
+    /// Used to tell bindings apart from unit structs and variants.
+    pub(crate) resolver: Resolver,
+    /// The module of the `match`, from which missing patterns are named.
+    pub(crate) module: ModuleId,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SliceLen {
+    /// `[a, b]` matches slices with exactly two elements.
+    Fixed(usize),
+    /// `[a, .., b]` matches slices with at least two elements.
+    Var { prefix: usize, suffix: usize },
+}
+
+impl SliceLen {
+    fn arity(self) -> usize {
+        match self {
+            SliceLen::Fixed(len) => len,
+            SliceLen::Var { prefix, suffix } => prefix + suffix,
+        }
+    }
+
+    fn covers(self, other: SliceLen) -> bool {
+        match (self, other) {
+            (SliceLen::Fixed(len), SliceLen::Fixed(other_len)) => len == other_len,
+            (SliceLen::Fixed(_), SliceLen::Var { .. }) => false,
+            (SliceLen::Var { prefix, suffix }, SliceLen::Fixed(len)) => prefix + suffix <= len,
+            (
+                SliceLen::Var { prefix, suffix },
+                SliceLen::Var { prefix: other_prefix, suffix: other_suffix },
+            ) => prefix <= other_prefix && suffix <= other_suffix,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Constructor {
+    Bool(bool),
+    Variant(EnumVariantId),
+    Struct(StructId),
+    Tuple(usize),
+    Slice {
+        len: SliceLen,
+        is_array: bool,
+    },
+    /// A literal which we can't compare with other literals.
+    Opaque,
+}
+
+impl Constructor {
+    /// Whether a pattern with this constructor matches every value built with
+    /// `other`.
+    fn covers(&self, other: &Constructor) -> bool {
+        match (self, other) {
+            (Constructor::Opaque, _) => false,
+            (Constructor::Slice { len, .. }, Constructor::Slice { len: other_len, .. }) => {
+                len.covers(*other_len)
+            }
+            _ => self == other,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Pattern {
+    Wild,
+    Ctor(Constructor, Vec<Pattern>),
+}
+
+impl Pattern {
+    fn ctor(&self) -> Option<&Constructor> {
+        match self {
+            Pattern::Wild => None,
+            Pattern::Ctor(ctor, _) => Some(ctor),
+        }
+    }
+
+    fn wilds(arity: usize) -> Vec<Pattern> {
+        iter::repeat(Pattern::Wild).take(arity).collect()
+    }
+}
+
+impl<'a, D: HirDatabase> MatchCheckCtx<'a, D> {
+    /// Returns `None` if some pattern is not supported by the check.
+    pub(crate) fn check_match(
+        &self,
+        scrutinee_ty: &Ty,
+        arms: &[MatchArm],
+    ) -> Option<MatchCheckResult> {
+        let mut matrix = Vec::new();
+        let mut unreachable = Vec::new();
+        for arm in arms {
+            for &pat in arm.pats.iter() {
+                let rows = self.lower_pat(pat)?.into_iter().map(|it| vec![it]).collect::<Vec<_>>();
+                if !rows.iter().any(|row| self.is_useful(&matrix, row)) {
+                    unreachable.push(pat);
+                }
+                // An arm with a guard might not match, so it can't make any
+                // other arm unreachable.
+                if arm.guard.is_none() {
+                    matrix.extend(rows);
+                }
+            }
+        }
+
+        let missing = if arms.is_empty() {
+            self.wildcard_witnesses(scrutinee_ty)
+        } else {
+            self.missing_patterns(&matrix, 1).into_iter().flatten().collect()
+        };
+        let missing = missing.iter().map(|pat| self.render(pat)).collect();
+
+        Some(MatchCheckResult { missing, unreachable })
+    }
+
+    /// Lowers `pat` into its alternatives, which together match the same
+    /// values as `pat`.
+    fn lower_pat(&self, pat: PatId) -> Option<Vec<Pattern>> {
+        let pattern = match &self.body[pat] {
+            Pat::Wild => Pattern::Wild,
+            Pat::Bind { mode: BindingAnnotation::Unannotated, name, subpat: None } => {
+                let path = ModPath::from_simple_segments(PathKind::Plain, vec![name.clone()]);
+                match self.resolver.resolve_path_in_value_ns_fully(self.db, &path) {
+                    Some(ValueNs::EnumVariantId(it)) => self.lower_unit(it.into())?,
+                    Some(ValueNs::StructId(it)) => self.lower_unit(it.into())?,
+                    Some(ValueNs::ConstId(_)) => Pattern::Ctor(Constructor::Opaque, Vec::new()),
+                    _ => Pattern::Wild,
+                }
+            }
+            Pat::Bind { subpat: Some(subpat), .. } => return self.lower_pat(*subpat),
+            Pat::Bind { .. } => Pattern::Wild,
+            Pat::Ref { pat, .. } => return self.lower_pat(*pat),
+            Pat::Tuple(args) => {
+                return self.lower_ctor(Constructor::Tuple(args.len()), self.lower_pats(args)?);
+            }
+            Pat::Or(pats) => {
+                let mut alternatives = Vec::new();
+                for &pat in pats {
+                    alternatives.extend(self.lower_pat(pat)?);
+                }
+                if alternatives.len() > MAX_ALTERNATIVES {
+                    return None;
+                }
+                return Some(alternatives);
+            }
+            Pat::Lit(expr) => match &self.body[*expr] {
+                Expr::Literal(Literal::Bool(value)) => {
+                    Pattern::Ctor(Constructor::Bool(*value), Vec::new())
+                }
+                _ => Pattern::Ctor(Constructor::Opaque, Vec::new()),
+            },
+            Pat::Path(path) => {
+                match self.resolver.resolve_path_in_value_ns_fully(self.db, path.mod_path())? {
+                    ValueNs::EnumVariantId(it) => self.lower_unit(it.into())?,
+                    ValueNs::StructId(it) => self.lower_unit(it.into())?,
+                    ValueNs::ConstId(_) => Pattern::Ctor(Constructor::Opaque, Vec::new()),
+                    _ => return None,
+                }
+            }
+            Pat::TupleStruct { args, .. } => {
+                let variant = self.infer.variant_resolution_for_pat(pat)?;
+                if args.len() != variant_data(self.db, variant).fields().len() {
+                    return None;
+                }
+                return self.lower_ctor(variant_constructor(variant)?, self.lower_pats(args)?);
+            }
+            Pat::Record { args, .. } => {
+                let variant = self.infer.variant_resolution_for_pat(pat)?;
+                let fields = variant_data(self.db, variant)
+                    .fields()
+                    .iter()
+                    .map(|(_, field)| match args.iter().find(|arg| arg.name == field.name) {
+                        Some(arg) => self.lower_pat(arg.pat),
+                        None => Some(vec![Pattern::Wild]),
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                return self.lower_ctor(variant_constructor(variant)?, fields);
+            }
+            Pat::Slice { prefix, rest, suffix } => {
+                let mut ty = &self.infer[pat];
+                while let Some((inner, _)) = ty.as_reference() {
+                    ty = inner;
+                }
+                let is_array = match ty {
+                    Ty::Apply(ApplicationTy { ctor: TypeCtor::Array, .. }) => true,
+                    Ty::Apply(ApplicationTy { ctor: TypeCtor::Slice, .. }) => false,
+                    _ => return None,
+                };
+                let len = match rest {
+                    Some(_) => SliceLen::Var { prefix: prefix.len(), suffix: suffix.len() },
+                    None => SliceLen::Fixed(prefix.len()),
+                };
+                let args = prefix.iter().chain(suffix).copied().collect::<Vec<_>>();
+                return self
+                    .lower_ctor(Constructor::Slice { len, is_array }, self.lower_pats(&args)?);
+            }
+            Pat::Range { .. } | Pat::Missing => return None,
+        };
+        Some(vec![pattern])
+    }
+
+    fn lower_pats(&self, pats: &[PatId]) -> Option<Vec<Vec<Pattern>>> {
+        pats.iter().map(|pat| self.lower_pat(*pat)).collect()
+    }
+
+    /// Applies `ctor` to every combination of the alternatives of its
+    /// arguments.
+    fn lower_ctor(&self, ctor: Constructor, args: Vec<Vec<Pattern>>) -> Option<Vec<Pattern>> {
+        let mut combinations = vec![Vec::new()];
+        for alternatives in args {
+            combinations = combinations
+                .into_iter()
+                .flat_map(|prefix| {
+                    alternatives.iter().map(move |alternative| {
+                        let mut combination = prefix.clone();
+                        combination.push(alternative.clone());
+                        combination
+                    })
+                })
+                .collect();
+            if combinations.len() > MAX_ALTERNATIVES {
+                return None;
+            }
+        }
+        Some(combinations.into_iter().map(|args| Pattern::Ctor(ctor.clone(), args)).collect())
+    }
+
+    fn lower_unit(&self, variant: VariantId) -> Option<Pattern> {
+        let arity = variant_data(self.db, variant).fields().len();
+        Some(Pattern::Ctor(variant_constructor(variant)?, Pattern::wilds(arity)))
+    }
+
+    fn arity(&self, ctor: &Constructor) -> usize {
+        match ctor {
+            Constructor::Bool(_) | Constructor::Opaque => 0,
+            Constructor::Variant(it) => variant_data(self.db, (*it).into()).fields().len(),
+            Constructor::Struct(it) => self.db.struct_data(*it).variant_data.fields().len(),
+            Constructor::Tuple(arity) => *arity,
+            Constructor::Slice { len, .. } => len.arity(),
+        }
+    }
+
+    /// All the constructors of the type of a column, given the constructors
+    /// appearing in it. Returns `None` if there are infinitely many.
+    ///
+    /// Slices of any length are split into a finite number of classes: each
+    /// length up to the longest one which is mentioned explicitly, and one
+    /// class for all the longer slices, which are treated alike by every
+    /// pattern in the column.
+    fn all_constructors(&self, heads: &[&Constructor]) -> Option<Vec<Constructor>> {
+        let ctors = match heads.first()? {
+            Constructor::Bool(_) => vec![Constructor::Bool(true), Constructor::Bool(false)],
+            Constructor::Variant(it) => {
+                let parent = it.parent;
+                let enum_data = self.db.enum_data(parent);
+                enum_data
+                    .variants
+                    .iter()
+                    .map(|(local_id, _)| Constructor::Variant(EnumVariantId { parent, local_id }))
+                    .collect()
+            }
+            Constructor::Struct(_) | Constructor::Tuple(_) => vec![heads[0].clone()],
+            Constructor::Slice { is_array, .. } => {
+                let is_array = *is_array;
+                let mut max_fixed_len = None;
+                let (mut max_prefix, mut max_suffix) = (0, 0);
+                for head in heads {
+                    match head {
+                        Constructor::Slice { len: SliceLen::Fixed(len), .. } => {
+                            max_fixed_len = max_fixed_len.max(Some(*len))
+                        }
+                        Constructor::Slice { len: SliceLen::Var { prefix, suffix }, .. } => {
+                            max_prefix = max_prefix.max(*prefix);
+                            max_suffix = max_suffix.max(*suffix);
+                        }
+                        _ => (),
+                    }
+                }
+                let lens = match max_fixed_len {
+                    // Arrays only have a single length.
+                    Some(len) if is_array => vec![SliceLen::Fixed(len)],
+                    _ if is_array => {
+                        vec![SliceLen::Var { prefix: max_prefix, suffix: max_suffix }]
+                    }
+                    _ => {
+                        let var_len =
+                            max_fixed_len.map_or(0, |len| len + 1).max(max_prefix + max_suffix);
+                        (0..var_len)
+                            .map(SliceLen::Fixed)
+                            .chain(iter::once(SliceLen::Var {
+                                prefix: var_len - max_suffix,
+                                suffix: max_suffix,
+                            }))
+                            .collect()
+                    }
+                };
+                lens.into_iter().map(|len| Constructor::Slice { len, is_array }).collect()
+            }
+            Constructor::Opaque => return None,
+        };
+        Some(ctors)
+    }
+
+    /// Returns all the constructors of the column, if each of them is covered
+    /// by some pattern in it.
+    fn complete_constructors(&self, heads: &[&Constructor]) -> Option<Vec<Constructor>> {
+        let all = self.all_constructors(heads)?;
+        if all.iter().all(|ctor| heads.iter().any(|head| head.covers(ctor))) {
+            Some(all)
+        } else {
+            None
+        }
+    }
+
+    fn is_useful(&self, matrix: &[Vec<Pattern>], row: &[Pattern]) -> bool {
+        let head = match row.first() {
+            Some(it) => it,
+            None => return matrix.is_empty(),
+        };
+        match head {
+            Pattern::Ctor(ctor @ Constructor::Slice { .. }, _) => {
+                let heads = column_heads(matrix).chain(iter::once(ctor)).collect::<Vec<_>>();
+                self.all_constructors(&heads)
+                    .unwrap_or_default()
+                    .iter()
+                    .filter(|it| ctor.covers(it))
+                    .any(|it| self.is_useful_specialized(matrix, row, it))
+            }
+            Pattern::Ctor(ctor, args) => {
+                let matrix = specialize_matrix(matrix, ctor, args.len());
+                let row = args.iter().chain(&row[1..]).cloned().collect::<Vec<_>>();
+                self.is_useful(&matrix, &row)
+            }
+            Pattern::Wild => {
+                let heads = column_heads(matrix).collect::<Vec<_>>();
+                match self.complete_constructors(&heads) {
+                    Some(all) => all.iter().any(|it| self.is_useful_specialized(matrix, row, it)),
+                    None => self.is_useful(&default_matrix(matrix), &row[1..]),
+                }
+            }
+        }
+    }
+
+    fn is_useful_specialized(
+        &self,
+        matrix: &[Vec<Pattern>],
+        row: &[Pattern],
+        ctor: &Constructor,
+    ) -> bool {
+        let arity = self.arity(ctor);
+        let matrix = specialize_matrix(matrix, ctor, arity);
+        match specialize(row, ctor, arity) {
+            Some(row) => self.is_useful(&matrix, &row),
+            None => false,
+        }
+    }
+
+    /// Computes rows of `width` patterns which are not matched by the matrix.
+    fn missing_patterns(&self, matrix: &[Vec<Pattern>], width: usize) -> Vec<Vec<Pattern>> {
+        if width == 0 {
+            return if matrix.is_empty() { vec![Vec::new()] } else { Vec::new() };
+        }
+
+        let heads = column_heads(matrix).collect::<Vec<_>>();
+        let mut res = Vec::new();
+        match self.complete_constructors(&heads) {
+            Some(all) => {
+                for ctor in all {
+                    let arity = self.arity(&ctor);
+                    let specialized = specialize_matrix(matrix, &ctor, arity);
+                    for witness in self.missing_patterns(&specialized, arity + width - 1) {
+                        let (args, rest) = witness.split_at(arity);
+                        let head = Pattern::Ctor(ctor.clone(), args.to_vec());
+                        res.push(iter::once(head).chain(rest.iter().cloned()).collect());
+                        if res.len() >= MAX_WITNESSES {
+                            return res;
+                        }
+                    }
+                }
+            }
+            None => {
+                let witnesses = self.missing_patterns(&default_matrix(matrix), width - 1);
+                if witnesses.is_empty() {
+                    return witnesses;
+                }
+                let missing_heads = match self.all_constructors(&heads) {
+                    Some(all) => all
+                        .into_iter()
+                        .filter(|ctor| !heads.iter().any(|head| head.covers(ctor)))
+                        .map(|ctor| {
+                            let arity = self.arity(&ctor);
+                            Pattern::Ctor(ctor, Pattern::wilds(arity))
+                        })
+                        .collect(),
+                    None => vec![Pattern::Wild],
+                };
+                for witness in witnesses {
+                    for head in missing_heads.iter() {
+                        res.push(iter::once(head.clone()).chain(witness.iter().cloned()).collect());
+                        if res.len() >= MAX_WITNESSES {
+                            return res;
+                        }
+                    }
+                }
+            }
+        }
+        res
+    }
+
+    /// The values not matched by a `match` without arms.
+    fn wildcard_witnesses(&self, ty: &Ty) -> Vec<Pattern> {
+        let ctors = match ty {
+            Ty::Apply(ApplicationTy { ctor: TypeCtor::Never, .. }) => Vec::new(),
+            Ty::Apply(ApplicationTy { ctor: TypeCtor::Bool, .. }) => {
+                vec![Constructor::Bool(true), Constructor::Bool(false)]
+            }
+            Ty::Apply(ApplicationTy { ctor: TypeCtor::Adt(AdtId::EnumId(parent)), .. }) => {
+                let parent = *parent;
+                self.db
+                    .enum_data(parent)
+                    .variants
+                    .iter()
+                    .map(|(local_id, _)| Constructor::Variant(EnumVariantId { parent, local_id }))
+                    .collect()
+            }
+            _ => return vec![Pattern::Wild],
+        };
+        ctors
+            .into_iter()
+            .map(|ctor| {
+                let arity = self.arity(&ctor);
+                Pattern::Ctor(ctor, Pattern::wilds(arity))
+            })
+            .collect()
+    }
+
+    fn render(&self, pat: &Pattern) -> String {
+        let (ctor, args) = match pat {
+            Pattern::Wild => return "_".to_string(),
+            Pattern::Ctor(ctor, args) => (ctor, args),
+        };
+        match ctor {
+            Constructor::Bool(value) => value.to_string(),
+            Constructor::Opaque => "_".to_string(),
+            Constructor::Tuple(1) => format!("({},)", self.render(&args[0])),
+            Constructor::Tuple(_) => format!("({})", self.render_list(args.iter())),
+            Constructor::Slice { len: SliceLen::Fixed(_), .. } => {
+                format!("[{}]", self.render_list(args.iter()))
+            }
+            Constructor::Slice { len: SliceLen::Var { prefix, .. }, .. } => {
+                let (prefix, suffix) = args.split_at(*prefix);
+                let rendered = prefix
+                    .iter()
+                    .map(|it| self.render(it))
+                    .chain(iter::once("..".to_string()))
+                    .chain(suffix.iter().map(|it| self.render(it)))
+                    .collect::<Vec<_>>();
+                format!("[{}]", rendered.join(", "))
+            }
+            Constructor::Variant(it) => {
+                let enum_data = self.db.enum_data(it.parent);
+                let variant = &enum_data.variants[it.local_id];
+                // Variants are rarely imported, so they are usually named
+                // through their enum
+                let name = self.path_to(ModuleDefId::EnumVariantId(*it)).unwrap_or_else(|| {
+                    let enum_path = self
+                        .path_to(ModuleDefId::AdtId(it.parent.into()))
+                        .unwrap_or_else(|| enum_data.name.to_string());
+                    format!("{}::{}", enum_path, variant.name)
+                });
+                self.render_fields(name, &variant.variant_data, args)
+            }
+            Constructor::Struct(it) => {
+                let struct_data = self.db.struct_data(*it);
+                let name = self
+                    .path_to(ModuleDefId::AdtId((*it).into()))
+                    .unwrap_or_else(|| struct_data.name.to_string());
+                self.render_fields(name, &struct_data.variant_data, args)
+            }
+        }
+    }
+
+    /// The path naming `def` in the module of the `match`.
+    fn path_to(&self, def: ModuleDefId) -> Option<String> {
+        find_path(self.db, ItemInNs::Types(def), self.module).map(|it| it.to_string())
+    }
+
+    fn render_list<'p>(&self, pats: impl Iterator<Item = &'p Pattern>) -> String {
+        pats.map(|it| self.render(it)).collect::<Vec<_>>().join(", ")
+    }
+
+    fn render_fields(&self, name: String, data: &VariantData, args: &[Pattern]) -> String {
+        match data {
+            VariantData::Unit => name,
+            VariantData::Tuple(_) => format!("{}({})", name, self.render_list(args.iter())),
+            VariantData::Record(fields) => {
+                let rendered = fields
+                    .iter()
+                    .zip(args)
+                    .filter(|(_, pat)| pat.ctor().is_some())
+                    .map(|((_, field), pat)| format!("{}: {}", field.name, self.render(pat)))
+                    .collect::<Vec<_>>();
+                if rendered.is_empty() {
+                    format!("{} {{ .. }}", name)
+                } else if rendered.len() == args.len() {
+                    format!("{} {{ {} }}", name, rendered.join(", "))
+                } else {
+                    format!("{} {{ {}, .. }}", name, rendered.join(", "))
+                }
+            }
+        }
+    }
+}
+
+fn variant_constructor(variant: VariantId) -> Option<Constructor> {
+    match variant {
+        VariantId::EnumVariantId(it) => Some(Constructor::Variant(it)),
+        VariantId::StructId(it) => Some(Constructor::Struct(it)),
+        VariantId::UnionId(_) => None,
+    }
+}
+
+fn column_heads(matrix: &[Vec<Pattern>]) -> impl Iterator<Item = &Constructor> {
+    matrix.iter().filter_map(|row| row[0].ctor())
+}
+
+/// The rows which match values built with `ctor`, with their first pattern
+/// replaced by `arity` patterns for the fields of the value.
+fn specialize_matrix(
+    matrix: &[Vec<Pattern>],
+    ctor: &Constructor,
+    arity: usize,
+) -> Vec<Vec<Pattern>> {
+    matrix.iter().filter_map(|row| specialize(row, ctor, arity)).collect()
+}
+
+fn specialize(row: &[Pattern], ctor: &Constructor, arity: usize) -> Option<Vec<Pattern>> {
+    let (head, rest) = row.split_first()?;
+    let mut res = match head {
+        Pattern::Wild => Pattern::wilds(arity),
+        Pattern::Ctor(head_ctor, args) => {
+            if !head_ctor.covers(ctor) {
+                return None;
+            }
+            match head_ctor {
+                // The elements between the prefix and the suffix are matched
+                // by the `..`.
+                Constructor::Slice { len: SliceLen::Var { prefix, suffix }, .. } => {
+                    let (prefix_pats, suffix_pats) = args.split_at(*prefix);
+                    prefix_pats
+                        .iter()
+                        .cloned()
+                        .chain(Pattern::wilds(arity - prefix - suffix))
+                        .chain(suffix_pats.iter().cloned())
+                        .collect()
+                }
+                _ => args.clone(),
+            }
+        }
+    };
+    res.extend(rest.iter().cloned());
+    Some(res)
+}
+
+fn default_matrix(matrix: &[Vec<Pattern>]) -> Vec<Vec<Pattern>> {
+    matrix.iter().filter(|row| row[0].ctor().is_none()).map(|row| row[1..].to_vec()).collect()
+}
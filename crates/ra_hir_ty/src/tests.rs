@@ -315,3 +315,209 @@ fn no_such_field_diagnostics() {
     "###
     );
 }
+
+#[test]
+fn type_mismatch_diagnostics() {
+    let diagnostics = TestDB::with_files(
+        r"
+        //- /lib.rs
+        struct S;
+        fn foo() -> S {
+            let x: u32 = S;
+            let y: (u32, u32) = (1, S);
+            92
+        }
+        ",
+    )
+    .diagnostics();
+
+    assert_snapshot!(diagnostics, @r###"
+    "S": expected u32, found S
+    "S": expected u32, found S
+    "92": expected S, found i32
+    "###
+    );
+}
+
+#[test]
+fn unresolved_path_diagnostics() {
+    let diagnostics = TestDB::with_files(
+        r"
+        //- /lib.rs
+        mod foo { pub fn bar() {} }
+        fn baz(x: u32) {
+            x;
+            foo::bar();
+            unknown();
+            foo::unknown();
+            missing::bar();
+        }
+        ",
+    )
+    .diagnostics();
+
+    assert_snapshot!(diagnostics, @r###"
+    "unknown": unresolved path `unknown`
+    "missing::bar": unresolved path `missing::bar`
+    "###
+    );
+}
+
+#[test]
+fn unresolved_field_and_method_diagnostics() {
+    let diagnostics = TestDB::with_files(
+        r"
+        //- /lib.rs
+        struct S { foo: u32 }
+        impl S { fn method(&self) {} }
+        fn f(s: S, u: Unknown) {
+            s.foo;
+            s.bar;
+            s.method();
+            s.frobnicate();
+            u.anything;
+            u.anything();
+        }
+        ",
+    )
+    .diagnostics();
+
+    assert_snapshot!(diagnostics, @r###"
+    "bar": no field `bar` on type `S`
+    "frobnicate": no method `frobnicate` on type `S`
+    "###
+    );
+}
+
+#[test]
+fn missing_match_arms_diagnostics() {
+    let diagnostics = TestDB::with_files(
+        r"
+        //- /lib.rs
+        enum Either { A, B(bool), C { x: u32, y: bool } }
+        fn f(e: Either, t: (bool, bool), s: &[u32]) {
+            match e {
+                Either::A => (),
+                Either::B(true) => (),
+            }
+            match t {
+                (true, _) => (),
+                (_, true) => (),
+            }
+            match s {
+                [] => (),
+                [_, ..] => (),
+            }
+            match s {
+                [x] => (),
+                [.., 1] => (),
+            }
+        }
+        ",
+    )
+    .diagnostics();
+
+    assert_snapshot!(diagnostics, @r###"
+    "e": missing match arms: `Either::C { .. }` not covered
+    "t": missing match arms: `(false, false)` not covered
+    "s": missing match arms: `[]` not covered
+    "###
+    );
+}
+
+#[test]
+fn missing_match_arms_are_named_from_the_match() {
+    let diagnostics = TestDB::with_files(
+        r"
+        //- /lib.rs
+        mod m {
+            pub enum E { A, B }
+        }
+        mod n {
+            use crate::m::E::{self, B};
+            fn f(b: E) {
+                match b {
+                    E::A => (),
+                }
+            }
+        }
+        fn f(a: m::E) {
+            match a {
+                m::E::A => (),
+            }
+        }
+        ",
+    )
+    .diagnostics();
+
+    assert_snapshot!(diagnostics, @r###"
+    "a": missing match arms: `m::E::B` not covered
+    "b": missing match arms: `B` not covered
+    "###
+    );
+}
+
+#[test]
+fn unreachable_pattern_diagnostics() {
+    let diagnostics = TestDB::with_files(
+        r"
+        //- /lib.rs
+        enum E { A, B }
+        fn f(e: E, b: bool) {
+            match e {
+                E::A => (),
+                _ => (),
+                E::B => (),
+            }
+            match b {
+                true => (),
+                false => (),
+                _ => (),
+            }
+            match e {
+                E::A | E::A => (),
+                E::B if b => (),
+                E::B => (),
+            }
+        }
+        ",
+    )
+    .diagnostics();
+
+    assert_snapshot!(diagnostics, @r###"
+    "E::B": unreachable pattern
+    "_": unreachable pattern
+    "E::A": unreachable pattern
+    "###
+    );
+}
+
+#[test]
+fn or_pattern_diagnostics() {
+    let diagnostics = TestDB::with_files(
+        r"
+        //- /lib.rs
+        enum E { A(u32), B(u32), C }
+        fn f(e: E, o: (bool, bool)) {
+            match e {
+                E::A(x) | E::B(x) => (),
+                E::C => (),
+            }
+            match e {
+                E::A(x) | E::C => (),
+                _ => (),
+            }
+            match o {
+                (true | false, true) => (),
+            }
+        }
+        ",
+    )
+    .diagnostics();
+
+    assert_snapshot!(diagnostics, @r###"
+    "E::C": variable `x` is not bound in all patterns
+    "o": missing match arms: `(true, false)`, `(false, false)` not covered
+    "###
+    );
+}
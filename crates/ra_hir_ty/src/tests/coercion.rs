@@ -275,12 +275,14 @@ fn test(i: i32) {
     [70; 147) 'match ...     }': &[i32]
     [76; 77) 'i': i32
     [88; 89) '2': i32
+    [88; 89) '2': i32
     [93; 96) 'foo': fn foo<i32>(&[T]) -> &[T]
     [93; 102) 'foo(&[2])': &[i32]
     [97; 101) '&[2]': &[i32;_]
     [98; 101) '[2]': [i32;_]
     [99; 100) '2': i32
     [112; 113) '1': i32
+    [112; 113) '1': i32
     [117; 121) '&[1]': &[i32;_]
     [118; 121) '[1]': [i32;_]
     [119; 120) '1': i32
@@ -316,10 +318,12 @@ fn test(i: i32) {
     [70; 147) 'match ...     }': &[i32]
     [76; 77) 'i': i32
     [88; 89) '1': i32
+    [88; 89) '1': i32
     [93; 97) '&[1]': &[i32;_]
     [94; 97) '[1]': [i32;_]
     [95; 96) '1': i32
     [107; 108) '2': i32
+    [107; 108) '2': i32
     [112; 115) 'foo': fn foo<i32>(&[T]) -> &[T]
     [112; 121) 'foo(&[2])': &[i32]
     [116; 120) '&[2]': &[i32;_]
@@ -357,9 +361,11 @@ fn test() {
     [45; 142) 'match ...     }': *const i32
     [51; 52) '1': i32
     [63; 64) '1': i32
+    [63; 64) '1': i32
     [68; 69) 't': &mut i32
     [68; 81) 't as *mut i32': *mut i32
     [91; 92) '2': i32
+    [91; 92) '2': i32
     [96; 97) 't': &mut i32
     [96; 105) 't as &i32': &i32
     [115; 116) '_': i32
@@ -43,6 +43,37 @@ impl S {
     assert_eq!("(i32, {unknown}, i32, {unknown})", type_at_pos(&db, pos));
 }
 
+#[test]
+fn cfg_fields_statements_and_arms() {
+    let (db, pos) = TestDB::with_position(
+        r#"
+//- /main.rs crate:main cfg:test
+struct S {
+    #[cfg(not(test))]
+    a: u32,
+    #[cfg(test)]
+    a: bool,
+}
+struct T(#[cfg(not(test))] u32, i64);
+
+fn test(s: S, t: T) {
+    #[cfg(not(test))]
+    let x = 1u32;
+    #[cfg(test)]
+    let x = "";
+    let m = match () {
+        #[cfg(not(test))]
+        () => 1u8,
+        () => 'c',
+    };
+    let r = (s.a, t.0, x, m);
+    r<|>;
+}
+"#,
+    );
+    assert_eq!("(bool, i64, &str, char)", type_at_pos(&db, pos));
+}
+
 #[test]
 fn infer_macros_expanded() {
     assert_snapshot!(
@@ -338,6 +369,44 @@ fn main() {
     );
 }
 
+#[test]
+fn infer_builtin_macros_include() {
+    let (db, pos) = TestDB::with_position(
+        r#"
+//- /main.rs
+#[rustc_builtin_macro]
+macro_rules! include {() => {}}
+
+include!("foo.rs");
+
+fn main() {
+    bar()<|>;
+}
+
+//- /foo.rs
+fn bar() -> u32 {0}
+"#,
+    );
+    assert_eq!("u32", type_at_pos(&db, pos));
+}
+
+#[test]
+fn infer_builtin_macros_concat() {
+    let (db, pos) = TestDB::with_position(
+        r#"
+//- /main.rs
+#[rustc_builtin_macro]
+macro_rules! concat {() => {}}
+
+fn main() {
+    let x = concat!("foo", 42);
+    x<|>;
+}
+"#,
+    );
+    assert_eq!("&str", type_at_pos(&db, pos));
+}
+
 #[test]
 fn infer_derive_clone_simple() {
     let (db, pos) = TestDB::with_position(
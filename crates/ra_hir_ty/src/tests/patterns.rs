@@ -53,7 +53,7 @@ fn test(x: &i32) {
     [140; 141) 'g': {unknown}
     [144; 145) 'e': {unknown}
     [158; 205) 'if let...     }': ()
-    [165; 170) '[val]': {unknown}
+    [165; 170) '[val]': [{unknown}]
     [173; 176) 'opt': {unknown}
     [177; 205) '{     ...     }': ()
     [191; 192) 'h': {unknown}
@@ -236,3 +236,46 @@ fn test(a1: A<u32>, o: Option<u64>) {
     "###
     );
 }
+
+#[test]
+fn infer_or_pattern() {
+    assert_snapshot!(
+        infer(r#"
+enum Result<T, E> {
+    Ok(T),
+    Err(E),
+}
+
+fn test(r: Result<u64, u64>) {
+    let (Result::Ok(y) | Result::Err(y)) = r;
+    y;
+    match r {
+        Result::Ok(1 | 2) => {}
+        _ => {}
+    }
+}
+"#),
+    @r###"
+    [55; 56) 'r': Result<u64, u64>
+    [76; 200) '{     ...   } }': ()
+    [87; 100) 'Result::Ok(y)': Result<u64, u64>
+    [87; 117) 'Result...Err(y)': Result<u64, u64>
+    [98; 99) 'y': u64
+    [103; 117) 'Result::Err(y)': Result<u64, u64>
+    [115; 116) 'y': u64
+    [121; 122) 'r': Result<u64, u64>
+    [128; 129) 'y': u64
+    [135; 198) 'match ...     }': ()
+    [141; 142) 'r': Result<u64, u64>
+    [153; 170) 'Result...1 | 2)': Result<u64, u64>
+    [164; 165) '1': u64
+    [164; 165) '1': u64
+    [164; 169) '1 | 2': u64
+    [168; 169) '2': u64
+    [168; 169) '2': u64
+    [174; 176) '{}': ()
+    [185; 186) '_': Result<u64, u64>
+    [190; 192) '{}': ()
+    "###
+    );
+}
@@ -886,6 +886,7 @@ fn foo() {
     [165; 247) 'match ...     }': i32
     [171; 175) 'true': bool
     [186; 190) 'true': bool
+    [186; 190) 'true': bool
     [194; 195) '3': i32
     [205; 206) '_': bool
     [210; 241) '{     ...     }': !
@@ -894,6 +895,7 @@ fn foo() {
     [263; 320) 'match ...     }': i32
     [269; 273) 'true': bool
     [284; 288) 'true': bool
+    [284; 288) 'true': bool
     [292; 293) '4': i32
     [303; 304) '_': bool
     [308; 314) 'return': !
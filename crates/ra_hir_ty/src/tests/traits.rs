@@ -1477,6 +1477,103 @@ fn test() {
     );
 }
 
+#[test]
+fn closure_signature_from_dyn_fn_expectation() {
+    assert_snapshot!(
+        infer(r#"
+#[lang = "fn_once"]
+trait FnOnce<Args> {
+    type Output;
+}
+
+struct S;
+impl S {
+    fn method(self) -> u64;
+}
+
+fn foo(f: &dyn FnOnce(S) -> u64) {}
+
+fn test() {
+    foo(&|s| s.method());
+}
+"#),
+        @r###"
+    [95; 99) 'self': S
+    [115; 116) 'f': &dyn FnOnce<(S,), Output = u64>
+    [145; 147) '{}': ()
+    [159; 188) '{     ...()); }': ()
+    [165; 168) 'foo': fn foo(&dyn FnOnce<(S,), Output = u64>) -> ()
+    [165; 185) 'foo(&|...hod())': ()
+    [169; 184) '&|s| s.method()': &|S| -> u64
+    [170; 184) '|s| s.method()': |S| -> u64
+    [171; 172) 's': S
+    [174; 175) 's': S
+    [174; 184) 's.method()': u64
+    "###
+    );
+}
+
+#[test]
+fn closure_signature_from_generic_fn_bound() {
+    let t = type_at(
+        r#"
+//- /main.rs
+#[lang = "fn_once"]
+trait FnOnce<Args> {
+    type Output;
+}
+#[lang = "fn_mut"]
+trait FnMut<Args>: FnOnce<Args> {}
+
+struct S;
+impl S {
+    fn method(self) -> u64;
+}
+
+struct Iter<T>(T);
+impl<T> Iter<T> {
+    fn map<B, F: FnMut(T) -> B>(self, f: F) -> Iter<B>;
+}
+
+fn test(iter: Iter<S>) {
+    iter.map(|x| x<|>.method());
+}
+"#,
+    );
+    assert_eq!(t, "S");
+}
+
+#[test]
+fn closure_return_type_from_generic_fn_bound() {
+    let t = type_at(
+        r#"
+//- /main.rs
+#[lang = "fn_once"]
+trait FnOnce<Args> {
+    type Output;
+}
+#[lang = "fn_mut"]
+trait FnMut<Args>: FnOnce<Args> {}
+
+struct S;
+impl S {
+    fn method(self) -> u64;
+}
+
+struct Iter<T>(T);
+impl<T> Iter<T> {
+    fn map<B, F: FnMut(T) -> B>(self, f: F) -> Iter<B>;
+}
+
+fn test(iter: Iter<S>) {
+    let mapped = iter.map(|x| x.method());
+    mapped<|>;
+}
+"#,
+    );
+    assert_eq!(t, "Iter<u64>");
+}
+
 #[test]
 fn unselected_projection_in_trait_env_1() {
     let t = type_at(
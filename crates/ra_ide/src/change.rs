@@ -8,7 +8,8 @@ use ra_db::{
     SourceRootId,
 };
 use ra_prof::{memory_usage, profile, Bytes};
-use ra_syntax::SourceFile;
+use ra_syntax::{AstNode, Parse, SourceFile};
+use ra_text_edit::AtomTextEdit;
 #[cfg(not(feature = "wasm"))]
 use rayon::prelude::*;
 use rustc_hash::FxHashMap;
@@ -22,7 +23,7 @@ use crate::{
 pub struct AnalysisChange {
     new_roots: Vec<(SourceRootId, bool)>,
     roots_changed: FxHashMap<SourceRootId, RootChange>,
-    files_changed: Vec<(FileId, Arc<String>)>,
+    files_changed: Vec<(FileId, Arc<String>, Option<Vec<AtomTextEdit>>)>,
     libraries_added: Vec<LibraryData>,
     crate_graph: Option<CrateGraph>,
     debug_data: DebugData,
@@ -71,7 +72,19 @@ impl AnalysisChange {
     }
 
     pub fn change_file(&mut self, file_id: FileId, new_text: Arc<String>) {
-        self.files_changed.push((file_id, new_text))
+        self.files_changed.push((file_id, new_text, None))
+    }
+
+    /// Like `change_file`, but also records the `edits` which turn the old
+    /// text of the file into `new_text`, so that the file can be reparsed
+    /// incrementally.
+    pub fn change_file_with_edits(
+        &mut self,
+        file_id: FileId,
+        new_text: Arc<String>,
+        edits: Vec<AtomTextEdit>,
+    ) {
+        self.files_changed.push((file_id, new_text, Some(edits)))
     }
 
     pub fn remove_file(&mut self, root_id: SourceRootId, file_id: FileId, path: RelativePathBuf) {
@@ -190,7 +203,20 @@ impl RootDatabase {
         for (root_id, root_change) in change.roots_changed {
             self.apply_root_change(root_id, root_change);
         }
-        for (file_id, text) in change.files_changed {
+        if !change.files_changed.is_empty() {
+            // Trees reparsed by earlier changes are either memoized by the
+            // `parse` query by now, or evicted from it, in which case parsing
+            // from scratch is fine. Dropping them keeps the table small.
+            let mut reparsed_files = FxHashMap::default();
+            for (file_id, text, edits) in change.files_changed.iter() {
+                let reparsed = edits.as_ref().and_then(|edits| self.reparse(*file_id, text, edits));
+                if let Some(parse) = reparsed {
+                    reparsed_files.insert(*file_id, (text.clone(), parse));
+                }
+            }
+            self.reparsed_files = Arc::new(reparsed_files);
+        }
+        for (file_id, text, _edits) in change.files_changed {
             let source_root_id = self.file_source_root(file_id);
             let source_root = self.source_root(source_root_id);
             let durability = durability(&source_root);
@@ -235,12 +261,31 @@ impl RootDatabase {
             source_root.insert_file(add_file.path, add_file.file_id);
         }
         for remove_file in root_change.removed {
+            Arc::make_mut(&mut self.reparsed_files).remove(&remove_file.file_id);
             self.set_file_text_with_durability(remove_file.file_id, Default::default(), durability);
             source_root.remove_file(&remove_file.path);
         }
         self.set_source_root_with_durability(root_id, Arc::new(source_root), durability);
     }
 
+    /// Applies `edits` to the current syntax tree of the file, reusing the
+    /// unchanged parts of the tree. Returns `None` if the edits don't produce
+    /// `new_text`.
+    fn reparse(
+        &self,
+        file_id: FileId,
+        new_text: &str,
+        edits: &[AtomTextEdit],
+    ) -> Option<Parse<SourceFile>> {
+        let _p = profile("RootDatabase::reparse");
+        let parse = edits.iter().fold(self.parse(file_id), |parse, edit| parse.reparse(edit));
+        if parse.tree().syntax().text() != new_text {
+            log::error!("incremental reparse of {:?} is out of sync with the file text", file_id);
+            return None;
+        }
+        Some(parse)
+    }
+
     pub(crate) fn maybe_collect_garbage(&mut self) {
         if cfg!(feature = "wasm") {
             return;
@@ -351,3 +396,46 @@ fn durability(source_root: &SourceRoot) -> Durability {
         Durability::LOW
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use ra_syntax::{AstNode, SourceFile, TextRange};
+    use ra_text_edit::AtomTextEdit;
+
+    use crate::{mock_analysis::MockAnalysis, AnalysisChange};
+
+    fn check_change_with_edits(before: &str, edits: Vec<AtomTextEdit>, after: &str) {
+        let mut analysis = MockAnalysis::new();
+        let file_id = analysis.add_file("/main.rs", before);
+        let mut host = analysis.analysis_host();
+        host.analysis().parse(file_id).unwrap();
+
+        let mut change = AnalysisChange::new();
+        change.change_file_with_edits(file_id, Arc::new(after.to_string()), edits);
+        host.apply_change(change);
+
+        let reparsed = host.analysis().parse(file_id).unwrap();
+        let expected = SourceFile::parse(after).tree();
+        assert_eq!(format!("{:#?}", reparsed.syntax()), format!("{:#?}", expected.syntax()));
+    }
+
+    #[test]
+    fn change_file_with_edits_reparses_block() {
+        check_change_with_edits(
+            "fn foo() {\n    1 + 1\n}\n",
+            vec![AtomTextEdit::replace(TextRange::from_to(15.into(), 20.into()), "92".to_string())],
+            "fn foo() {\n    92\n}\n",
+        );
+    }
+
+    #[test]
+    fn change_file_with_out_of_sync_edits() {
+        check_change_with_edits(
+            "fn foo() {}\n",
+            vec![AtomTextEdit::insert(0.into(), "pub ".to_string())],
+            "struct S;\n",
+        );
+    }
+}
@@ -16,7 +16,8 @@ mod complete_scope;
 mod complete_postfix;
 mod complete_macro_in_item_position;
 
-use ra_db::SourceDatabase;
+use ra_db::{FileRange, SourceDatabase};
+use ra_syntax::SmolStr;
 
 #[cfg(test)]
 use crate::completion::completion_item::do_completion;
@@ -29,7 +30,7 @@ use crate::{
 };
 
 pub use crate::completion::completion_item::{
-    CompletionItem, CompletionItemKind, InsertTextFormat,
+    CompletionItem, CompletionItemKind, InsertTextFormat, ResolvedCompletion,
 };
 
 /// Main entry point for completion. We run completion as a two-phase process.
@@ -57,21 +58,49 @@ pub use crate::completion::completion_item::{
 pub(crate) fn completions(db: &db::RootDatabase, position: FilePosition) -> Option<Completions> {
     let original_parse = db.parse(position.file_id);
     let ctx = CompletionContext::new(db, &original_parse, position)?;
+    let mut acc = collect_completions(&ctx);
+    acc.resolve_all(db, position);
+    Some(acc)
+}
 
+/// Like `completions`, but leaves out the parts of the items which are
+/// expensive to compute: documentation, signatures of functions and the `use`s
+/// to add. They are computed for a single item by `resolve_completion`, from
+/// the item's `import_to_add` and `details_source`.
+pub(crate) fn unresolved_completions(
+    db: &db::RootDatabase,
+    position: FilePosition,
+) -> Option<Completions> {
+    let original_parse = db.parse(position.file_id);
+    let ctx = CompletionContext::new(db, &original_parse, position)?;
+    Some(collect_completions(&ctx))
+}
+
+/// Computes what `unresolved_completions` left out of an item at `position`.
+pub(crate) fn resolve_completion(
+    db: &db::RootDatabase,
+    position: FilePosition,
+    import_to_add: Option<&[SmolStr]>,
+    details_source: Option<FileRange>,
+) -> ResolvedCompletion {
+    ResolvedCompletion::compute(db, position, import_to_add, details_source)
+}
+
+fn collect_completions(ctx: &CompletionContext) -> Completions {
     let mut acc = Completions::default();
 
-    complete_fn_param::complete_fn_param(&mut acc, &ctx);
-    complete_keyword::complete_expr_keyword(&mut acc, &ctx);
-    complete_keyword::complete_use_tree_keyword(&mut acc, &ctx);
-    complete_snippet::complete_expr_snippet(&mut acc, &ctx);
-    complete_snippet::complete_item_snippet(&mut acc, &ctx);
-    complete_path::complete_path(&mut acc, &ctx);
-    complete_scope::complete_scope(&mut acc, &ctx);
-    complete_dot::complete_dot(&mut acc, &ctx);
-    complete_record_literal::complete_record_literal(&mut acc, &ctx);
-    complete_record_pattern::complete_record_pattern(&mut acc, &ctx);
-    complete_pattern::complete_pattern(&mut acc, &ctx);
-    complete_postfix::complete_postfix(&mut acc, &ctx);
-    complete_macro_in_item_position::complete_macro_in_item_position(&mut acc, &ctx);
-    Some(acc)
+    complete_fn_param::complete_fn_param(&mut acc, ctx);
+    complete_keyword::complete_expr_keyword(&mut acc, ctx);
+    complete_keyword::complete_use_tree_keyword(&mut acc, ctx);
+    complete_snippet::complete_expr_snippet(&mut acc, ctx);
+    complete_snippet::complete_item_snippet(&mut acc, ctx);
+    complete_path::complete_path(&mut acc, ctx);
+    complete_scope::complete_scope(&mut acc, ctx);
+    complete_dot::complete_dot(&mut acc, ctx);
+    complete_record_literal::complete_record_literal(&mut acc, ctx);
+    complete_record_pattern::complete_record_pattern(&mut acc, ctx);
+    complete_pattern::complete_pattern(&mut acc, ctx);
+    complete_postfix::complete_postfix(&mut acc, ctx);
+    complete_macro_in_item_position::complete_macro_in_item_position(&mut acc, ctx);
+    acc
 }
@@ -1,11 +1,14 @@
-//! FIXME: write short doc here
+//! Completion of names from the current scope, e.g. locals and imported items.
 
-use ra_assists::auto_import_text_edit;
+use hir::{AttrDef, ModPath, ModuleDef, PathKind};
 use ra_syntax::{ast, AstNode, SmolStr};
-use ra_text_edit::TextEditBuilder;
-use rustc_hash::FxHashMap;
 
-use crate::completion::{CompletionContext, CompletionItem, CompletionKind, Completions};
+use crate::{
+    completion::{
+        CompletionContext, CompletionItem, CompletionItemKind, CompletionKind, Completions,
+    },
+    imports_locator::ImportsLocator,
+};
 
 pub(super) fn complete_scope(acc: &mut Completions, ctx: &CompletionContext) {
     if !ctx.is_trivial_path {
@@ -18,116 +21,99 @@ pub(super) fn complete_scope(acc: &mut Completions, ctx: &CompletionContext) {
 
     // auto-import
     // We fetch ident from the original file, because we need to pre-filter auto-imports
-    if ast::NameRef::cast(ctx.token.parent()).is_some() {
-        let import_resolver = ImportResolver::new();
-        let import_names = import_resolver.all_names(ctx.token.text());
-        import_names.into_iter().for_each(|(name, path)| {
-            let edit = {
-                let mut builder = TextEditBuilder::default();
-                builder.replace(ctx.source_range(), name.to_string());
-                auto_import_text_edit(
-                    &ctx.token.parent(),
-                    &ctx.token.parent(),
-                    &path,
-                    &mut builder,
-                );
-                builder.finish()
-            };
-
-            // Hack: copied this check form conv.rs beacause auto import can produce edits
-            // that invalidate assert in conv_with.
-            if edit
-                .as_atoms()
-                .iter()
-                .filter(|atom| !ctx.source_range().is_subrange(&atom.delete))
-                .all(|atom| ctx.source_range().intersection(&atom.delete).is_none())
-            {
-                CompletionItem::new(
-                    CompletionKind::Reference,
-                    ctx.source_range(),
-                    build_import_label(&name, &path),
-                )
-                .text_edit(edit)
-                .add_to(acc);
-            }
-        });
+    if ctx.db.feature_flags.get("completion.enable-auto-import")
+        && ast::NameRef::cast(ctx.token.parent()).is_some()
+    {
+        complete_unimported_items(acc, ctx);
     }
 }
 
-fn build_import_label(name: &str, path: &[SmolStr]) -> String {
-    let mut buf = String::with_capacity(64);
-    buf.push_str(name);
-    buf.push_str(" (");
-    fmt_import_path(path, &mut buf);
-    buf.push_str(")");
-    buf
-}
-
-fn fmt_import_path(path: &[SmolStr], buf: &mut String) {
-    let mut segments = path.iter();
-    if let Some(s) = segments.next() {
-        buf.push_str(&s);
+/// Offers items from the whole crate graph which are not in scope yet. The
+/// `use` for the item is added to the edit when the item is resolved.
+fn complete_unimported_items(acc: &mut Completions, ctx: &CompletionContext) {
+    let module = match ctx.module {
+        Some(it) => it,
+        None => return,
+    };
+    let name_fragment = ctx.token.text();
+    // Searching the whole index for a single letter is too slow and too noisy.
+    if name_fragment.len() < 2 {
+        return;
     }
-    for s in segments {
-        buf.push_str("::");
-        buf.push_str(&s);
+
+    let mut candidates = ImportsLocator::new(ctx.db)
+        .find_similar_imports(name_fragment, 40)
+        .into_iter()
+        .filter_map(|def| Some((module.find_use_path(ctx.db, def)?, def)))
+        // Single segment paths are already in scope
+        .filter(|(path, _)| path.segments.len() > 1 || path.kind != PathKind::Plain)
+        .collect::<Vec<_>>();
+    candidates.sort_by_key(|(path, _)| path.to_string());
+    candidates.dedup_by_key(|(path, _)| path.to_string());
+
+    for (path, def) in candidates {
+        let name = match path.segments.last() {
+            Some(it) => it.to_string(),
+            None => continue,
+        };
+        let segments = path.to_string().split("::").map(SmolStr::new).collect::<Vec<_>>();
+        let mut item =
+            CompletionItem::new(CompletionKind::Reference, ctx.source_range(), import_label(&path))
+                .lookup_by(name.clone())
+                .insert_text(name)
+                .import_to_add(segments)
+                .set_details_from(ctx, details_source(def));
+        if let Some(kind) = completion_item_kind(def) {
+            item = item.kind(kind);
+        }
+        item.add_to(acc);
     }
 }
 
-#[derive(Debug, Clone, Default)]
-pub(crate) struct ImportResolver {
-    // todo: use fst crate or something like that
-    dummy_names: Vec<(SmolStr, Vec<SmolStr>)>,
+fn import_label(path: &ModPath) -> String {
+    let name = path.segments.last().map(|it| it.to_string()).unwrap_or_default();
+    format!("{} ({})", name, path)
 }
 
-impl ImportResolver {
-    pub(crate) fn new() -> Self {
-        let dummy_names = vec![
-            (SmolStr::new("fmt"), vec![SmolStr::new("std"), SmolStr::new("fmt")]),
-            (SmolStr::new("io"), vec![SmolStr::new("std"), SmolStr::new("io")]),
-            (SmolStr::new("iter"), vec![SmolStr::new("std"), SmolStr::new("iter")]),
-            (SmolStr::new("hash"), vec![SmolStr::new("std"), SmolStr::new("hash")]),
-            (
-                SmolStr::new("Debug"),
-                vec![SmolStr::new("std"), SmolStr::new("fmt"), SmolStr::new("Debug")],
-            ),
-            (
-                SmolStr::new("Display"),
-                vec![SmolStr::new("std"), SmolStr::new("fmt"), SmolStr::new("Display")],
-            ),
-            (
-                SmolStr::new("Hash"),
-                vec![SmolStr::new("std"), SmolStr::new("hash"), SmolStr::new("Hash")],
-            ),
-            (
-                SmolStr::new("Hasher"),
-                vec![SmolStr::new("std"), SmolStr::new("hash"), SmolStr::new("Hasher")],
-            ),
-            (
-                SmolStr::new("Iterator"),
-                vec![SmolStr::new("std"), SmolStr::new("iter"), SmolStr::new("Iterator")],
-            ),
-        ];
-
-        ImportResolver { dummy_names }
-    }
-
-    // Returns a map of importable items filtered by name.
-    // The map associates item name with its full path.
-    // todo: should return Resolutions
-    pub(crate) fn all_names(&self, name: &str) -> FxHashMap<SmolStr, Vec<SmolStr>> {
-        if name.len() > 1 {
-            self.dummy_names.iter().filter(|(n, _)| n.contains(name)).cloned().collect()
-        } else {
-            FxHashMap::default()
+fn completion_item_kind(def: ModuleDef) -> Option<CompletionItemKind> {
+    let kind = match def {
+        ModuleDef::Module(_) => CompletionItemKind::Module,
+        ModuleDef::Function(_) => CompletionItemKind::Function,
+        ModuleDef::Adt(hir::Adt::Struct(_)) | ModuleDef::Adt(hir::Adt::Union(_)) => {
+            CompletionItemKind::Struct
         }
-    }
+        ModuleDef::Adt(hir::Adt::Enum(_)) => CompletionItemKind::Enum,
+        ModuleDef::EnumVariant(_) => CompletionItemKind::EnumVariant,
+        ModuleDef::Const(_) => CompletionItemKind::Const,
+        ModuleDef::Static(_) => CompletionItemKind::Static,
+        ModuleDef::Trait(_) => CompletionItemKind::Trait,
+        ModuleDef::TypeAlias(_) => CompletionItemKind::TypeAlias,
+        ModuleDef::BuiltinType(_) => return None,
+    };
+    Some(kind)
+}
+
+fn details_source(def: ModuleDef) -> Option<AttrDef> {
+    let def = match def {
+        ModuleDef::Module(it) => it.into(),
+        ModuleDef::Function(it) => it.into(),
+        ModuleDef::Adt(it) => it.into(),
+        ModuleDef::EnumVariant(it) => it.into(),
+        ModuleDef::Const(it) => it.into(),
+        ModuleDef::Static(it) => it.into(),
+        ModuleDef::Trait(it) => it.into(),
+        ModuleDef::TypeAlias(it) => it.into(),
+        ModuleDef::BuiltinType(_) => return None,
+    };
+    Some(def)
 }
 
 #[cfg(test)]
 mod tests {
     use crate::completion::{do_completion, CompletionItem, CompletionKind};
+    use crate::mock_analysis::single_file_with_position;
     use insta::assert_debug_snapshot;
+    use test_utils::{assert_eq_text, extract_offset};
 
     fn do_reference_completion(code: &str) -> Vec<CompletionItem> {
         do_completion(code, CompletionKind::Reference)
@@ -910,4 +896,94 @@ mod tests {
         "###
         )
     }
+
+    #[test]
+    fn completes_unimported_items_with_import() {
+        let code = r"
+mod collections {
+    pub struct HashMap;
+}
+
+fn main() {
+    HashMa<|>
+}
+";
+        let completions = do_reference_completion(code);
+        let item = completions
+            .iter()
+            .find(|it| it.label() == "HashMap (collections::HashMap)")
+            .expect("no completion for unimported item");
+        assert_eq!(item.lookup(), "HashMap");
+        assert!(item.requires_import());
+
+        let (_, before) = extract_offset(code);
+        assert_eq_text!(
+            &item.text_edit().apply(&before),
+            r"
+use collections::HashMap;
+
+mod collections {
+    pub struct HashMap;
+}
+
+fn main() {
+    HashMap
+}
+"
+        );
+    }
+
+    #[test]
+    fn adds_import_on_resolve() {
+        let code = r"
+mod collections {
+    pub struct HashMap;
+}
+
+fn main() {
+    HashMa<|>
+}
+";
+        let (analysis, position) = single_file_with_position(code);
+        let items = analysis.unresolved_completions(position).unwrap().unwrap();
+        let item =
+            items.iter().find(|it| it.lookup() == "HashMap" && it.requires_import()).unwrap();
+        assert_eq!(item.text_edit().as_atoms().len(), 1);
+
+        let resolved = analysis
+            .resolve_completion(position, item.import_to_add(), item.details_source())
+            .unwrap();
+        let (_, before) = extract_offset(code);
+        assert_eq_text!(
+            &resolved.import_edit.unwrap().apply(&before),
+            r"
+use collections::HashMap;
+
+mod collections {
+    pub struct HashMap;
+}
+
+fn main() {
+    HashMa
+}
+"
+        );
+    }
+
+    #[test]
+    fn does_not_offer_to_import_items_in_scope() {
+        let completions = do_reference_completion(
+            r"
+            mod collections {
+                pub struct HashMap;
+            }
+            use collections::HashMap;
+
+            fn main() {
+                HashMa<|>
+            }
+            ",
+        );
+        assert!(completions.iter().all(|it| !it.requires_import()));
+    }
 }
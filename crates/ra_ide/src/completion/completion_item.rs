@@ -2,9 +2,21 @@
 
 use std::fmt;
 
-use hir::Documentation;
-use ra_syntax::TextRange;
-use ra_text_edit::TextEdit;
+use hir::{Adt, AttrDef, Docs, Documentation, FieldSource, HasSource, InFile};
+use ra_assists::auto_import_text_edit;
+use ra_db::{FileRange, SourceDatabase};
+use ra_syntax::{
+    algo::find_covering_element,
+    ast::{self, DocCommentsOwner},
+    match_ast, AstNode, NodeOrToken, SmolStr,
+    SyntaxKind::IDENT,
+    SyntaxNode, TextRange,
+};
+use ra_text_edit::{TextEdit, TextEditBuilder};
+
+use crate::{
+    completion::CompletionContext, db::RootDatabase, display::function_label, FilePosition,
+};
 
 /// `CompletionItem` describes a single completion variant in the editor pop-up.
 /// It is basically a POD with various properties. To construct a
@@ -47,6 +59,14 @@ pub struct CompletionItem {
 
     /// Whether this item is marked as deprecated
     deprecated: bool,
+
+    /// Path of the item, if it is not in scope yet. Resolving the item adds a
+    /// `use` for it. Such items are ranked below the ones already in scope.
+    import_to_add: Option<Vec<SmolStr>>,
+
+    /// Definition from which the documentation (and, for functions, the
+    /// signature) is computed when the item is resolved.
+    details_source: Option<FileRange>,
 }
 
 // We use custom debug for CompletionItem to make `insta`'s diffs more readable.
@@ -76,6 +96,9 @@ impl fmt::Debug for CompletionItem {
         if self.deprecated {
             s.field("deprecated", &true);
         }
+        if self.requires_import() {
+            s.field("requires_import", &true);
+        }
         s.finish()
     }
 }
@@ -139,6 +162,8 @@ impl CompletionItem {
             kind: None,
             text_edit: None,
             deprecated: None,
+            import_to_add: None,
+            details_source: None,
         }
     }
     /// What user sees in pop-up in the UI.
@@ -177,6 +202,145 @@ impl CompletionItem {
     pub fn deprecated(&self) -> bool {
         self.deprecated
     }
+
+    pub fn requires_import(&self) -> bool {
+        self.import_to_add.is_some()
+    }
+
+    /// Path of the item to add a `use` for when the item is resolved.
+    pub fn import_to_add(&self) -> Option<&[SmolStr]> {
+        self.import_to_add.as_ref().map(|it| it.as_slice())
+    }
+
+    /// Definition to take the documentation and the signature from when the
+    /// item is resolved.
+    pub fn details_source(&self) -> Option<FileRange> {
+        self.details_source
+    }
+
+    /// Fills in the parts of the item left out of the initial completion list.
+    fn apply(&mut self, resolved: ResolvedCompletion) {
+        if self.documentation.is_none() {
+            self.documentation = resolved.documentation;
+        }
+        if self.detail.is_none() {
+            self.detail = resolved.detail;
+        }
+        if let Some(import_edit) = resolved.import_edit {
+            let mut builder = TextEditBuilder::default();
+            for atom in self.text_edit.as_atoms().iter().chain(import_edit.as_atoms()) {
+                builder.replace(atom.delete, atom.insert.clone());
+            }
+            self.text_edit = builder.finish();
+        }
+    }
+}
+
+/// The parts of a `CompletionItem` which are expensive to compute, and so are
+/// only computed for the item the user looks at.
+#[derive(Debug, Default)]
+pub struct ResolvedCompletion {
+    pub documentation: Option<Documentation>,
+    pub detail: Option<String>,
+    /// Adds the `use` for an item which is not in scope yet.
+    pub import_edit: Option<TextEdit>,
+}
+
+impl ResolvedCompletion {
+    pub(crate) fn compute(
+        db: &RootDatabase,
+        position: FilePosition,
+        import_to_add: Option<&[SmolStr]>,
+        details_source: Option<FileRange>,
+    ) -> ResolvedCompletion {
+        let mut res = ResolvedCompletion::default();
+        if let Some(node) = details_source.and_then(|it| definition_node(db, it)) {
+            res.documentation = doc_comment_text(&node).map(|it| Documentation::new(&it));
+            res.detail = ast::FnDef::cast(node).map(|it| function_label(&it));
+        }
+        if let Some(path) = import_to_add {
+            res.import_edit = import_edit(db, position, path);
+        }
+        res
+    }
+}
+
+fn definition_node(db: &RootDatabase, frange: FileRange) -> Option<SyntaxNode> {
+    let file = db.parse(frange.file_id).tree();
+    let node = match find_covering_element(file.syntax(), frange.range) {
+        NodeOrToken::Node(it) => it,
+        NodeOrToken::Token(it) => it.parent(),
+    };
+    // The file may have changed since the completion list was computed
+    node.ancestors().find(|it| it.text_range() == frange.range)
+}
+
+fn doc_comment_text(node: &SyntaxNode) -> Option<String> {
+    match_ast! {
+        match node {
+            ast::Module(it) => { it.doc_comment_text() },
+            ast::RecordFieldDef(it) => { it.doc_comment_text() },
+            ast::StructDef(it) => { it.doc_comment_text() },
+            ast::EnumDef(it) => { it.doc_comment_text() },
+            ast::UnionDef(it) => { it.doc_comment_text() },
+            ast::FnDef(it) => { it.doc_comment_text() },
+            ast::EnumVariant(it) => { it.doc_comment_text() },
+            ast::StaticDef(it) => { it.doc_comment_text() },
+            ast::ConstDef(it) => { it.doc_comment_text() },
+            ast::TraitDef(it) => { it.doc_comment_text() },
+            ast::TypeAliasDef(it) => { it.doc_comment_text() },
+            _ => { None },
+        }
+    }
+}
+
+fn import_edit(db: &RootDatabase, position: FilePosition, path: &[SmolStr]) -> Option<TextEdit> {
+    let file = db.parse(position.file_id).tree();
+    let token = file.syntax().token_at_offset(position.offset).left_biased()?;
+    // Same as `CompletionContext::source_range`
+    let source_range = match token.kind() {
+        IDENT => token.text_range(),
+        _ => TextRange::offset_len(position.offset, 0.into()),
+    };
+    let mut builder = TextEditBuilder::default();
+    auto_import_text_edit(&token.parent(), &token.parent(), path, &mut builder);
+    let edit = builder.finish();
+
+    // Hack: copied this check form conv.rs beacause auto import can produce edits
+    // that invalidate assert in conv_with.
+    if edit.as_atoms().iter().any(|atom| source_range.intersection(&atom.delete).is_some()) {
+        return None;
+    }
+    Some(edit)
+}
+
+/// Where the documentation of `def` is written: on the definition, or on the
+/// declaration for modules. `None` if it can't be found again by its range,
+/// like definitions from macro expansions.
+fn details_source(db: &RootDatabase, def: AttrDef) -> Option<FileRange> {
+    let node = match def {
+        AttrDef::Module(it) => it.declaration_source(db)?.map(|it| it.syntax().clone()),
+        AttrDef::StructField(it) => match it.source(db) {
+            InFile { file_id, value: FieldSource::Named(it) } => {
+                InFile::new(file_id, it.syntax().clone())
+            }
+            InFile { value: FieldSource::Pos(_), .. } => return None,
+        },
+        AttrDef::Adt(Adt::Struct(it)) => it.source(db).map(|it| it.syntax().clone()),
+        AttrDef::Adt(Adt::Enum(it)) => it.source(db).map(|it| it.syntax().clone()),
+        AttrDef::Adt(Adt::Union(it)) => it.source(db).map(|it| it.syntax().clone()),
+        AttrDef::Function(it) => it.source(db).map(|it| it.syntax().clone()),
+        AttrDef::EnumVariant(it) => it.source(db).map(|it| it.syntax().clone()),
+        AttrDef::Static(it) => it.source(db).map(|it| it.syntax().clone()),
+        AttrDef::Const(it) => it.source(db).map(|it| it.syntax().clone()),
+        AttrDef::Trait(it) => it.source(db).map(|it| it.syntax().clone()),
+        AttrDef::TypeAlias(it) => it.source(db).map(|it| it.syntax().clone()),
+        AttrDef::MacroDef(_) => return None,
+    };
+    if node.file_id.call_node(db).is_some() {
+        return None;
+    }
+    Some(FileRange { file_id: node.file_id.original_file(db), range: node.value.text_range() })
 }
 
 /// A helper to make `CompletionItem`s.
@@ -193,6 +357,8 @@ pub(crate) struct Builder {
     kind: Option<CompletionItemKind>,
     text_edit: Option<TextEdit>,
     deprecated: Option<bool>,
+    import_to_add: Option<Vec<SmolStr>>,
+    details_source: Option<FileRange>,
 }
 
 impl Builder {
@@ -221,6 +387,8 @@ impl Builder {
             kind: self.kind,
             completion_kind: self.completion_kind,
             deprecated: self.deprecated.unwrap_or(false),
+            import_to_add: self.import_to_add,
+            details_source: self.details_source,
         }
     }
     pub(crate) fn lookup_by(mut self, lookup: impl Into<String>) -> Builder {
@@ -271,6 +439,33 @@ impl Builder {
         self.deprecated = Some(deprecated);
         self
     }
+    pub(crate) fn import_to_add(mut self, path: Vec<SmolStr>) -> Builder {
+        self.import_to_add = Some(path);
+        self
+    }
+    pub(crate) fn details_from(self, ctx: &CompletionContext, def: impl Into<AttrDef>) -> Builder {
+        self.set_details_from(ctx, Some(def.into()))
+    }
+    /// Documentation and signatures which can't be found again when the item
+    /// is resolved are computed right away.
+    pub(crate) fn set_details_from(
+        mut self,
+        ctx: &CompletionContext,
+        def: Option<AttrDef>,
+    ) -> Builder {
+        let def = match def {
+            Some(it) => it,
+            None => return self,
+        };
+        self.details_source = details_source(ctx.db, def);
+        if self.details_source.is_none() {
+            self.documentation = def.docs(ctx.db);
+            if let AttrDef::Function(func) = def {
+                self.detail = Some(function_label(&func.source(ctx.db).value));
+            }
+        }
+        self
+    }
 }
 
 impl<'a> Into<CompletionItem> for Builder {
@@ -296,6 +491,17 @@ impl Completions {
     {
         items.into_iter().for_each(|item| self.add(item.into()))
     }
+    pub(crate) fn resolve_all(&mut self, db: &RootDatabase, position: FilePosition) {
+        for item in self.buf.iter_mut() {
+            let resolved = ResolvedCompletion::compute(
+                db,
+                position,
+                item.import_to_add(),
+                item.details_source(),
+            );
+            item.apply(resolved);
+        }
+    }
 }
 
 impl Into<Vec<CompletionItem>> for Completions {
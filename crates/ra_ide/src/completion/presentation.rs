@@ -9,7 +9,7 @@ use crate::completion::{
     CompletionContext, CompletionItem, CompletionItemKind, CompletionKind, Completions,
 };
 
-use crate::display::{const_label, function_label, macro_label, type_label};
+use crate::display::{const_label, macro_label, type_label};
 
 impl Completions {
     pub(crate) fn add_field(
@@ -26,7 +26,7 @@ impl Completions {
         )
         .kind(CompletionItemKind::Field)
         .detail(ty.display(ctx.db).to_string())
-        .set_documentation(field.docs(ctx.db))
+        .details_from(ctx, field)
         .set_deprecated(is_deprecated)
         .add_to(self);
     }
@@ -83,14 +83,14 @@ impl Completions {
             }
         };
 
-        let docs = match resolution {
-            ScopeDef::ModuleDef(Module(it)) => it.docs(ctx.db),
-            ScopeDef::ModuleDef(Adt(it)) => it.docs(ctx.db),
-            ScopeDef::ModuleDef(EnumVariant(it)) => it.docs(ctx.db),
-            ScopeDef::ModuleDef(Const(it)) => it.docs(ctx.db),
-            ScopeDef::ModuleDef(Static(it)) => it.docs(ctx.db),
-            ScopeDef::ModuleDef(Trait(it)) => it.docs(ctx.db),
-            ScopeDef::ModuleDef(TypeAlias(it)) => it.docs(ctx.db),
+        let details_source: Option<hir::AttrDef> = match resolution {
+            ScopeDef::ModuleDef(Module(it)) => Some((*it).into()),
+            ScopeDef::ModuleDef(Adt(it)) => Some((*it).into()),
+            ScopeDef::ModuleDef(EnumVariant(it)) => Some((*it).into()),
+            ScopeDef::ModuleDef(Const(it)) => Some((*it).into()),
+            ScopeDef::ModuleDef(Static(it)) => Some((*it).into()),
+            ScopeDef::ModuleDef(Trait(it)) => Some((*it).into()),
+            ScopeDef::ModuleDef(TypeAlias(it)) => Some((*it).into()),
             _ => None,
         };
 
@@ -122,7 +122,7 @@ impl Completions {
             }
         }
 
-        completion_item.kind(kind).set_documentation(docs).add_to(self)
+        completion_item.kind(kind).set_details_from(ctx, details_source).add_to(self)
     }
 
     pub(crate) fn add_function(&mut self, ctx: &CompletionContext, func: hir::Function) {
@@ -202,8 +202,6 @@ impl Completions {
         let params = func.params(ctx.db);
 
         let name = name.unwrap_or_else(|| func_name.to_string());
-        let ast_node = func.source(ctx.db).value;
-        let detail = function_label(&ast_node);
 
         let mut builder =
             CompletionItem::new(CompletionKind::Reference, ctx.source_range(), name.clone())
@@ -212,9 +210,8 @@ impl Completions {
                 } else {
                     CompletionItemKind::Function
                 })
-                .set_documentation(func.docs(ctx.db))
-                .set_deprecated(is_deprecated(func, ctx.db))
-                .detail(detail);
+                .details_from(ctx, func)
+                .set_deprecated(is_deprecated(func, ctx.db));
 
         // Add `<>` for generic types
         if ctx.use_item_syntax.is_none()
@@ -243,7 +240,7 @@ impl Completions {
 
         CompletionItem::new(CompletionKind::Reference, ctx.source_range(), name.text().to_string())
             .kind(CompletionItemKind::Const)
-            .set_documentation(constant.docs(ctx.db))
+            .details_from(ctx, constant)
             .set_deprecated(is_deprecated(constant, ctx.db))
             .detail(detail)
             .add_to(self);
@@ -259,7 +256,7 @@ impl Completions {
 
         CompletionItem::new(CompletionKind::Reference, ctx.source_range(), name.text().to_string())
             .kind(CompletionItemKind::TypeAlias)
-            .set_documentation(type_alias.docs(ctx.db))
+            .details_from(ctx, type_alias)
             .set_deprecated(is_deprecated(type_alias, ctx.db))
             .detail(detail)
             .add_to(self);
@@ -275,7 +272,7 @@ impl Completions {
             .to_string();
         CompletionItem::new(CompletionKind::Reference, ctx.source_range(), name.to_string())
             .kind(CompletionItemKind::EnumVariant)
-            .set_documentation(variant.docs(ctx.db))
+            .details_from(ctx, variant)
             .set_deprecated(is_deprecated)
             .detail(detail)
             .add_to(self);
@@ -292,6 +289,7 @@ mod tests {
     use test_utils::covers;
 
     use crate::completion::{do_completion, CompletionItem, CompletionKind};
+    use crate::mock_analysis::single_file_with_position;
 
     fn do_reference_completion(code: &str) -> Vec<CompletionItem> {
         do_completion(code, CompletionKind::Reference)
@@ -670,4 +668,26 @@ mod tests {
         "###
         )
     }
+
+    #[test]
+    fn computes_details_on_resolve() {
+        let (analysis, position) = single_file_with_position(
+            r"
+            /// Does the thing.
+            fn frobnicate() {}
+            fn main() { frob<|> }
+            ",
+        );
+        let items = analysis.unresolved_completions(position).unwrap().unwrap();
+        let item = items.iter().find(|it| it.lookup() == "frobnicate").unwrap();
+        assert!(item.documentation().is_none());
+        assert!(item.detail().is_none());
+
+        let resolved = analysis
+            .resolve_completion(position, item.import_to_add(), item.details_source())
+            .unwrap();
+        assert_eq!(resolved.documentation.unwrap().as_str(), "Does the thing.");
+        assert_eq!(resolved.detail.as_ref().map(String::as_str), Some("fn frobnicate()"));
+        assert!(resolved.import_edit.is_none());
+    }
 }
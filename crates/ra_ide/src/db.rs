@@ -4,9 +4,10 @@ use std::sync::Arc;
 
 use ra_db::{
     salsa::{self, Database, Durability},
-    Canceled, CheckCanceled, CrateId, FileId, FileLoader, FileLoaderDelegate, RelativePath,
-    SourceDatabase, SourceRootId,
+    Canceled, CheckCanceled, CrateId, ExternSourceId, FileId, FileLoader, FileLoaderDelegate,
+    RelativePath, SourceDatabase, SourceRootId,
 };
+use ra_syntax::{Parse, SourceFile};
 use rustc_hash::FxHashMap;
 
 use crate::{
@@ -29,6 +30,9 @@ pub(crate) struct RootDatabase {
     runtime: salsa::Runtime<RootDatabase>,
     pub(crate) feature_flags: Arc<FeatureFlags>,
     pub(crate) debug_data: Arc<DebugData>,
+    /// Syntax trees produced by incremental reparsing of the files changed by
+    /// the last change, along with the text they correspond to.
+    pub(crate) reparsed_files: Arc<FxHashMap<FileId, (Arc<String>, Parse<SourceFile>)>>,
     pub(crate) last_gc: crate::wasm_shims::Instant,
     pub(crate) last_gc_check: crate::wasm_shims::Instant,
 }
@@ -47,6 +51,21 @@ impl FileLoader for RootDatabase {
     fn relevant_crates(&self, file_id: FileId) -> Arc<Vec<CrateId>> {
         FileLoaderDelegate(self).relevant_crates(file_id)
     }
+    fn resolve_extern_path(
+        &self,
+        extern_id: ExternSourceId,
+        relative_path: &RelativePath,
+    ) -> Option<FileId> {
+        FileLoaderDelegate(self).resolve_extern_path(extern_id, relative_path)
+    }
+    fn reparsed_file(&self, file_id: FileId, text: &Arc<String>) -> Option<Parse<SourceFile>> {
+        let (reparsed_text, parse) = self.reparsed_files.get(&file_id)?;
+        if Arc::ptr_eq(reparsed_text, text) {
+            Some(parse.clone())
+        } else {
+            None
+        }
+    }
 }
 
 impl salsa::Database for RootDatabase {
@@ -84,6 +103,7 @@ impl RootDatabase {
             last_gc_check: crate::wasm_shims::Instant::now(),
             feature_flags: Arc::new(feature_flags),
             debug_data: Default::default(),
+            reparsed_files: Default::default(),
         };
         db.set_crate_graph_with_durability(Default::default(), Durability::HIGH);
         db.set_local_roots_with_durability(Default::default(), Durability::HIGH);
@@ -104,6 +124,7 @@ impl salsa::ParallelDatabase for RootDatabase {
             last_gc_check: self.last_gc_check,
             feature_flags: Arc::clone(&self.feature_flags),
             debug_data: Arc::clone(&self.debug_data),
+            reparsed_files: Arc::clone(&self.reparsed_files),
         })
     }
 }
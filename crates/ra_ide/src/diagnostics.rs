@@ -1,6 +1,6 @@
 //! FIXME: write short doc here
 
-use std::cell::RefCell;
+use std::{cell::RefCell, iter::successors};
 
 use hir::diagnostics::{AstDiagnostic, Diagnostic as _, DiagnosticSink};
 use itertools::Itertools;
@@ -8,12 +8,17 @@ use ra_db::{RelativePath, SourceDatabase, SourceDatabaseExt};
 use ra_prof::profile;
 use ra_syntax::{
     algo,
-    ast::{self, make, AstNode},
-    Location, SyntaxNode, TextRange, T,
+    ast::{self, edit::IndentLevel, make, AstNode},
+    Direction, Location, NodeOrToken, SmolStr,
+    SyntaxKind::{COMMENT, WHITESPACE},
+    SyntaxNode, TextRange, T,
 };
 use ra_text_edit::{TextEdit, TextEditBuilder};
 
-use crate::{db::RootDatabase, Diagnostic, FileId, FileSystemEdit, SourceChange, SourceFileEdit};
+use crate::{
+    db::RootDatabase, imports_locator::ImportsLocator, Diagnostic, FileId, FileSystemEdit,
+    SourceChange, SourceFileEdit,
+};
 
 #[derive(Debug, Copy, Clone)]
 pub enum Severity {
@@ -30,7 +35,15 @@ pub(crate) fn diagnostics(db: &RootDatabase, file_id: FileId) -> Vec<Diagnostic>
         range: location_to_range(err.location()),
         message: format!("Syntax Error: {}", err),
         severity: Severity::Error,
-        fix: None,
+        code: err.code(),
+        fixes: Vec::new(),
+    }));
+    res.extend(parse.tree().semantic_errors().iter().map(|err| Diagnostic {
+        range: location_to_range(err.location()),
+        message: format!("Syntax Error: {}", err),
+        severity: Severity::Error,
+        code: err.code(),
+        fixes: Vec::new(),
     }));
 
     for node in parse.tree().syntax().descendants() {
@@ -43,7 +56,8 @@ pub(crate) fn diagnostics(db: &RootDatabase, file_id: FileId) -> Vec<Diagnostic>
             message: d.message(),
             range: d.highlight_range(),
             severity: Severity::Error,
-            fix: None,
+            code: None,
+            fixes: Vec::new(),
         })
     })
     .on::<hir::diagnostics::UnresolvedModule, _>(|d| {
@@ -60,7 +74,17 @@ pub(crate) fn diagnostics(db: &RootDatabase, file_id: FileId) -> Vec<Diagnostic>
             range: d.highlight_range(),
             message: d.message(),
             severity: Severity::Error,
-            fix: Some(fix),
+            code: None,
+            fixes: vec![fix],
+        })
+    })
+    .on::<hir::diagnostics::InactiveCode, _>(|d| {
+        res.borrow_mut().push(Diagnostic {
+            range: d.highlight_range(),
+            message: d.message(),
+            severity: Severity::WeakWarning,
+            code: None,
+            fixes: Vec::new(),
         })
     })
     .on::<hir::diagnostics::MissingFields, _>(|d| {
@@ -93,7 +117,72 @@ pub(crate) fn diagnostics(db: &RootDatabase, file_id: FileId) -> Vec<Diagnostic>
             range: d.highlight_range(),
             message: d.message(),
             severity: Severity::Error,
-            fix,
+            code: None,
+            fixes: fix.into_iter().collect(),
+        })
+    })
+    .on::<hir::diagnostics::TypeMismatch, _>(|d| {
+        if !d.fully_known && db.feature_flags.get("diagnostics.type-mismatch.conservative") {
+            return;
+        }
+        res.borrow_mut().push(Diagnostic {
+            range: d.highlight_range(),
+            message: d.message(),
+            severity: Severity::Error,
+            code: None,
+            fixes: Vec::new(),
+        })
+    })
+    .on::<hir::diagnostics::UnresolvedImport, _>(|d| {
+        let fixes = if d.file == file_id.into() {
+            let use_tree = d.node.to_node(db.parse(file_id).tree().syntax());
+            fixes_for_unresolved_import(db, file_id, &use_tree)
+        } else {
+            Vec::new()
+        };
+        res.borrow_mut().push(Diagnostic {
+            range: d.highlight_range(),
+            message: d.message(),
+            severity: Severity::Error,
+            code: None,
+            fixes,
+        })
+    })
+    .on::<hir::diagnostics::UnresolvedPath, _>(|d| {
+        let fixes = if d.file == file_id.into() {
+            fixes_for_unresolved_path(db, file_id, &d.ast(db))
+        } else {
+            Vec::new()
+        };
+        res.borrow_mut().push(Diagnostic {
+            range: d.highlight_range(),
+            message: d.message(),
+            severity: Severity::Error,
+            code: None,
+            fixes,
+        })
+    })
+    .on::<hir::diagnostics::MissingMatchArms, _>(|d| {
+        let fix = if d.file == file_id.into() {
+            missing_match_arms_fix(file_id, &d.ast(db), &d.missed_patterns)
+        } else {
+            None
+        };
+        res.borrow_mut().push(Diagnostic {
+            range: d.highlight_range(),
+            message: d.message(),
+            severity: Severity::Error,
+            code: None,
+            fixes: fix.into_iter().collect(),
+        })
+    })
+    .on::<hir::diagnostics::UnreachablePattern, _>(|d| {
+        res.borrow_mut().push(Diagnostic {
+            range: d.highlight_range(),
+            message: d.message(),
+            severity: Severity::WeakWarning,
+            code: None,
+            fixes: Vec::new(),
         })
     })
     .on::<hir::diagnostics::MissingOkInTailExpr, _>(|d| {
@@ -105,7 +194,8 @@ pub(crate) fn diagnostics(db: &RootDatabase, file_id: FileId) -> Vec<Diagnostic>
             range: d.highlight_range(),
             message: d.message(),
             severity: Severity::Error,
-            fix: Some(fix),
+            code: None,
+            fixes: vec![fix],
         })
     });
     let source_file = db.parse(file_id).tree();
@@ -117,6 +207,191 @@ pub(crate) fn diagnostics(db: &RootDatabase, file_id: FileId) -> Vec<Diagnostic>
     drop(sink);
     res.into_inner()
 }
+
+/// Offers to replace the path of an unresolved top-level `use` with the path
+/// of each item of the same name.
+fn fixes_for_unresolved_import(
+    db: &RootDatabase,
+    file_id: FileId,
+    use_tree: &ast::UseTree,
+) -> Vec<SourceChange> {
+    // Nested trees only hold a suffix of the path, so rewriting them is not
+    // enough to fix the import.
+    if use_tree.syntax().parent().and_then(ast::UseItem::cast).is_none() || use_tree.has_star() {
+        return Vec::new();
+    }
+    let path = match use_tree.path() {
+        Some(it) => it,
+        None => return Vec::new(),
+    };
+    let name_ref = match path.segment().and_then(|it| it.name_ref()) {
+        Some(it) => it,
+        None => return Vec::new(),
+    };
+    import_candidates(db, file_id, use_tree.syntax(), name_ref.text().as_str(), |_| true)
+        .into_iter()
+        .map(|candidate| {
+            let edit = TextEdit::replace(path.syntax().text_range(), candidate.to_string());
+            SourceChange::source_file_edit_from(format!("import {}", candidate), file_id, edit)
+        })
+        .collect()
+}
+
+/// Offers to add a `use` for each item which has the same name as the first
+/// segment of an unresolved path.
+fn fixes_for_unresolved_path(
+    db: &RootDatabase,
+    file_id: FileId,
+    expr: &ast::Expr,
+) -> Vec<SourceChange> {
+    let path = match expr {
+        ast::Expr::PathExpr(it) => it.path(),
+        _ => None,
+    };
+    let path = match path {
+        Some(it) => it,
+        None => return Vec::new(),
+    };
+    let is_qualified = path.qualifier().is_some();
+    let first_segment = successors(Some(path), |it| it.qualifier()).last();
+    let name_ref = match first_segment.and_then(|it| it.segment()).and_then(|it| it.name_ref()) {
+        Some(it) => it,
+        None => return Vec::new(),
+    };
+    let in_namespace = |def: &hir::ModuleDef| match def {
+        hir::ModuleDef::Function(_) | hir::ModuleDef::Const(_) | hir::ModuleDef::Static(_) => {
+            !is_qualified
+        }
+        hir::ModuleDef::EnumVariant(_) => !is_qualified,
+        _ => is_qualified,
+    };
+    import_candidates(db, file_id, expr.syntax(), name_ref.text().as_str(), in_namespace)
+        .into_iter()
+        .map(|candidate| {
+            let segments = candidate.to_string().split("::").map(SmolStr::new).collect::<Vec<_>>();
+            let mut builder = TextEditBuilder::default();
+            ra_assists::auto_import_text_edit(
+                expr.syntax(),
+                expr.syntax(),
+                &segments,
+                &mut builder,
+            );
+            SourceChange::source_file_edit_from(
+                format!("import {}", candidate),
+                file_id,
+                builder.finish(),
+            )
+        })
+        .collect()
+}
+
+/// Appends an arm with a unit body for each of the `patterns`. A match which
+/// fits on a single line is split into one arm per line.
+fn missing_match_arms_fix(
+    file_id: FileId,
+    arm_list: &ast::MatchArmList,
+    patterns: &[String],
+) -> Option<SourceChange> {
+    let l_curly = arm_list.syntax().first_token().filter(|it| it.kind() == T!['{'])?;
+    let r_curly = arm_list.syntax().last_token().filter(|it| it.kind() == T!['}'])?;
+    let indent = IndentLevel::from_node(arm_list.syntax()).0 as usize;
+    let arm_indent = "    ".repeat(indent + 1);
+
+    let mut buf = String::new();
+    if !arm_list.syntax().text().contains_char('\n') {
+        for element in arm_list.syntax().children_with_tokens() {
+            match element {
+                NodeOrToken::Node(node) => {
+                    buf.push_str(&format!("\n{}{}", arm_indent, node));
+                    match ast::MatchArm::cast(node).and_then(|it| it.expr()) {
+                        Some(ast::Expr::BlockExpr(_)) => (),
+                        _ => buf.push(','),
+                    }
+                }
+                NodeOrToken::Token(token) if token.kind() == COMMENT => {
+                    buf.push_str(&format!(" {}", token.text()));
+                }
+                NodeOrToken::Token(_) => (),
+            }
+        }
+        for pat in patterns {
+            buf.push_str(&format!("\n{}{} => (),", arm_indent, pat));
+        }
+        buf.push_str(&format!("\n{}", "    ".repeat(indent)));
+        let range = TextRange::from_to(l_curly.text_range().end(), r_curly.text_range().start());
+        let edit = TextEdit::replace(range, buf);
+        return Some(SourceChange::source_file_edit_from("add missing match arms", file_id, edit));
+    }
+
+    let offset = match arm_list.arms().last() {
+        Some(last_arm) => {
+            let comma = last_arm
+                .syntax()
+                .siblings_with_tokens(Direction::Next)
+                .skip(1)
+                .find(|it| !it.kind().is_trivia())
+                .filter(|it| it.kind() == T![,]);
+            match comma {
+                Some(comma) => comma.text_range().end(),
+                None => {
+                    match last_arm.expr() {
+                        Some(ast::Expr::BlockExpr(_)) => (),
+                        _ => buf.push(','),
+                    }
+                    last_arm.syntax().text_range().end()
+                }
+            }
+        }
+        None => arm_list.syntax().first_token()?.text_range().end(),
+    };
+    for pat in patterns {
+        buf.push_str(&format!("\n{}{} => (),", arm_indent, pat));
+    }
+    // Move a closing brace which follows the last arm onto its own line,
+    // swallowing the whitespace in front of it.
+    let mut end = offset;
+    match r_curly.prev_token() {
+        Some(ws) if ws.kind() == WHITESPACE && ws.text().contains('\n') => (),
+        prev => {
+            if let Some(ws) = prev.filter(|it| it.kind() == WHITESPACE) {
+                if ws.text_range().start() == offset {
+                    end = ws.text_range().end();
+                }
+            }
+            buf.push_str(&format!("\n{}", "    ".repeat(indent)));
+        }
+    }
+
+    let edit = TextEdit::replace(TextRange::from_to(offset, end), buf);
+    Some(SourceChange::source_file_edit_from("add missing match arms", file_id, edit))
+}
+
+/// Paths, as seen from the module containing `node`, of all items called
+/// `name` which are accepted by `filter`.
+fn import_candidates(
+    db: &RootDatabase,
+    file_id: FileId,
+    node: &SyntaxNode,
+    name: &str,
+    filter: impl Fn(&hir::ModuleDef) -> bool,
+) -> Vec<hir::ModPath> {
+    let src = hir::ModuleSource::from_child_node(db, hir::InFile::new(file_id.into(), node));
+    let module = match hir::Module::from_definition(db, hir::InFile::new(file_id.into(), src)) {
+        Some(it) => it,
+        None => return Vec::new(),
+    };
+    let mut candidates = ImportsLocator::new(db)
+        .find_imports(name)
+        .into_iter()
+        .filter(|def| filter(def))
+        .filter_map(|def| module.find_use_path(db, def))
+        .filter(|path| path.segments.len() > 1 || path.kind != hir::PathKind::Plain)
+        .collect::<Vec<_>>();
+    candidates.sort_by_key(|path| path.to_string());
+    candidates.dedup();
+    candidates
+}
+
 fn location_to_range(location: Location) -> TextRange {
     match location {
         Location::Offset(offset) => TextRange::offset_len(offset, 1.into()),
@@ -146,10 +421,11 @@ fn check_unnecessary_braces_in_use_statement(
             range,
             message: "Unnecessary braces in use statement".to_string(),
             severity: Severity::WeakWarning,
-            fix: Some(SourceChange::source_file_edit(
+            code: None,
+            fixes: vec![SourceChange::source_file_edit(
                 "Remove unnecessary braces",
                 SourceFileEdit { file_id, edit },
-            )),
+            )],
         });
     }
 
@@ -190,10 +466,11 @@ fn check_struct_shorthand_initialization(
                     range: record_field.syntax().text_range(),
                     message: "Shorthand struct initialization".to_string(),
                     severity: Severity::WeakWarning,
-                    fix: Some(SourceChange::source_file_edit(
+                    code: None,
+                    fixes: vec![SourceChange::source_file_edit(
                         "use struct shorthand initialization",
                         SourceFileEdit { file_id, edit },
-                    )),
+                    )],
                 });
             }
         }
@@ -229,9 +506,9 @@ mod tests {
         for node in parse.tree().syntax().descendants() {
             func(&mut diagnostics, FileId(0), &node);
         }
-        let diagnostic =
+        let mut diagnostic =
             diagnostics.pop().unwrap_or_else(|| panic!("no diagnostics for:\n{}\n", before));
-        let mut fix = diagnostic.fix.unwrap();
+        let mut fix = diagnostic.fixes.pop().unwrap();
         let edit = fix.source_file_edits.pop().unwrap().edit;
         let actual = edit.apply(&before);
         assert_eq_text!(after, &actual);
@@ -244,8 +521,8 @@ mod tests {
     ///  * that the contents of the file containing the cursor match `after` after the diagnostic fix is applied
     fn check_apply_diagnostic_fix_from_position(fixture: &str, after: &str) {
         let (analysis, file_position) = analysis_and_position(fixture);
-        let diagnostic = analysis.diagnostics(file_position.file_id).unwrap().pop().unwrap();
-        let mut fix = diagnostic.fix.unwrap();
+        let mut diagnostic = analysis.diagnostics(file_position.file_id).unwrap().pop().unwrap();
+        let mut fix = diagnostic.fixes.pop().unwrap();
         let edit = fix.source_file_edits.pop().unwrap().edit;
         let target_file_contents = analysis.file_text(file_position.file_id).unwrap();
         let actual = edit.apply(&target_file_contents);
@@ -281,8 +558,8 @@ mod tests {
 
     fn check_apply_diagnostic_fix(before: &str, after: &str) {
         let (analysis, file_id) = single_file(before);
-        let diagnostic = analysis.diagnostics(file_id).unwrap().pop().unwrap();
-        let mut fix = diagnostic.fix.unwrap();
+        let mut diagnostic = analysis.diagnostics(file_id).unwrap().pop().unwrap();
+        let mut fix = diagnostic.fixes.pop().unwrap();
         let edit = fix.source_file_edits.pop().unwrap().edit;
         let actual = edit.apply(&before);
         assert_eq_text!(after, &actual);
@@ -296,6 +573,17 @@ mod tests {
         assert_eq!(diagnostics.len(), 0);
     }
 
+    /// Takes a multi-file input fixture with annotated cursor position and checks that the
+    /// diagnostics for the file containing the cursor have exactly the `expected` messages, and
+    /// that none of them offers a fix.
+    fn check_no_fix_for_target_file(fixture: &str, expected: &[&str]) {
+        let (analysis, file_position) = analysis_and_position(fixture);
+        let diagnostics = analysis.diagnostics(file_position.file_id).unwrap();
+        let messages: Vec<&str> = diagnostics.iter().map(|it| it.message.as_str()).collect();
+        assert_eq!(messages, expected);
+        assert!(diagnostics.iter().all(|it| it.fixes.is_empty()), "{:#?}", diagnostics);
+    }
+
     fn check_no_diagnostic(content: &str) {
         let (analysis, file_id) = single_file(content);
         let diagnostics = analysis.diagnostics(file_id).unwrap();
@@ -422,7 +710,7 @@ mod tests {
                 pub enum Result<T, E> { Ok(T), Err(E) }
             }
         "#;
-        check_no_diagnostic_for_target_file(content);
+        check_no_fix_for_target_file(content, &["expected Result<String, i32>, found i32"]);
     }
 
     #[test]
@@ -448,9 +736,84 @@ mod tests {
                 pub enum Result<T, E> { Ok(T), Err(E) }
             }
         "#;
+        check_no_fix_for_target_file(content, &["expected SomeOtherEnum, found i32"]);
+    }
+
+    #[test]
+    fn test_wrap_return_type_not_applicable_when_tail_is_wrapped() {
+        let content = r#"
+            //- /main.rs
+            use std::{string::String, result::Result::{self, Ok, Err}};
+
+            fn foo() -> Result<i32, String> {
+                Ok(0<|>)
+            }
+
+            //- /std/lib.rs
+            pub mod string {
+                pub struct String { }
+            }
+            pub mod result {
+                pub enum Result<T, E> { Ok(T), Err(E) }
+            }
+        "#;
         check_no_diagnostic_for_target_file(content);
     }
 
+    #[test]
+    fn test_type_mismatch_diagnostic() {
+        let (analysis, file_id) = single_file(
+            r#"
+            fn foo() -> u32 {
+                let x: bool = 92u32;
+                true
+            }
+            "#,
+        );
+        let diagnostics = analysis.diagnostics(file_id).unwrap();
+        assert_debug_snapshot!(diagnostics, @r###"
+        [
+            Diagnostic {
+                message: "expected bool, found u32",
+                range: [61; 66),
+                fixes: [],
+                severity: Error,
+                code: None,
+            },
+            Diagnostic {
+                message: "expected u32, found bool",
+                range: [84; 88),
+                fixes: [],
+                severity: Error,
+                code: None,
+            },
+        ]
+        "###);
+    }
+
+    #[test]
+    fn test_type_mismatch_not_reported_for_partially_known_types() {
+        let (analysis, file_id) = single_file(
+            r#"
+            fn foo() {
+                let x: u32 = (unresolved(), 92);
+            }
+            "#,
+        );
+        let diagnostics = analysis.diagnostics(file_id).unwrap();
+        assert_debug_snapshot!(diagnostics, @r###"
+        [
+            Diagnostic {
+                message: "unresolved path `unresolved`",
+                range: [54; 64),
+                fixes: [],
+                severity: Error,
+                code: None,
+            },
+        ]
+        "###);
+    }
+
     #[test]
     fn test_fill_struct_fields_empty() {
         let before = r"
@@ -532,7 +895,20 @@ mod tests {
             }
         ";
 
-        check_no_diagnostic(content);
+        // `a` is not defined, but no fields are reported as missing.
+        let (analysis, file_id) = single_file(content);
+        let diagnostics = analysis.diagnostics(file_id).unwrap();
+        assert_debug_snapshot!(diagnostics, @r###"
+        [
+            Diagnostic {
+                message: "unresolved path `a`",
+                range: [194; 195),
+                fixes: [],
+                severity: Error,
+                code: None,
+            },
+        ]
+        "###);
     }
 
     #[test]
@@ -544,7 +920,7 @@ mod tests {
             Diagnostic {
                 message: "unresolved module",
                 range: [0; 8),
-                fix: Some(
+                fixes: [
                     SourceChange {
                         label: "create module",
                         source_file_edits: [],
@@ -558,8 +934,218 @@ mod tests {
                         ],
                         cursor_position: None,
                     },
+                ],
+                severity: Error,
+                code: None,
+            },
+        ]
+        "###);
+    }
+
+    #[test]
+    fn test_semantic_error_diagnostic_has_code() {
+        let (analysis, file_id) = single_file("fn foo() { break; }");
+        let diagnostics = analysis.diagnostics(file_id).unwrap();
+        assert_debug_snapshot!(diagnostics, @r###"
+        [
+            Diagnostic {
+                message: "Syntax Error: `break` outside of a loop",
+                range: [11; 16),
+                fixes: [],
+                severity: Error,
+                code: Some(
+                    "E0268",
                 ),
+            },
+        ]
+        "###);
+    }
+
+    #[test]
+    fn test_inactive_code_diagnostic() {
+        let (analysis, file_id) = single_file(
+            r#"#[cfg(feature = "foo")]
+fn foo() {}"#,
+        );
+        let diagnostics = analysis.diagnostics(file_id).unwrap();
+        assert_debug_snapshot!(diagnostics, @r###"
+        [
+            Diagnostic {
+                message: "code is inactive due to #[cfg] directives: feature = \"foo\" is disabled",
+                range: [0; 35),
+                fixes: [],
+                severity: WeakWarning,
+                code: None,
+            },
+        ]
+        "###);
+    }
+
+    #[test]
+    fn test_inactive_code_diagnostic_below_items() {
+        let text = r#"
+#[cfg(test)]
+use foo::{bar, baz};
+#[cfg(test)]
+foo!();
+struct S {
+    #[cfg(test)]
+    field: u32,
+}
+struct T(#[cfg(test)] u32, bool);
+fn main() {
+    #[cfg(test)]
+    let x = 1;
+    let s = S {
+        #[cfg(test)]
+        field: 1,
+    };
+    match s {
+        #[cfg(test)]
+        _ => (),
+        S {} => (),
+    }
+    #[cfg(test)]
+    main()
+}
+"#;
+        let (analysis, file_id) = single_file(text);
+        let mut diagnostics = analysis.diagnostics(file_id).unwrap();
+        diagnostics.retain(|it| it.message.starts_with("code is inactive"));
+        diagnostics.sort_by_key(|it| it.range.start());
+        let inactive: Vec<_> = diagnostics
+            .iter()
+            .map(|it| &text[it.range.start().to_usize()..it.range.end().to_usize()])
+            .collect();
+        assert_eq!(
+            inactive,
+            vec![
+                "#[cfg(test)]\nuse foo::{bar, baz};",
+                "#[cfg(test)]\nfoo!();",
+                "#[cfg(test)]\n    field: u32",
+                "#[cfg(test)] u32",
+                "#[cfg(test)]\n    let x = 1;",
+                "#[cfg(test)]\n        field: 1",
+                "#[cfg(test)]\n        _ => ()",
+                "#[cfg(test)]\n    main()",
+            ]
+        );
+        assert!(diagnostics.iter().all(|it| it.message.ends_with("test is disabled")));
+    }
+
+    #[test]
+    fn test_fill_missing_match_arms() {
+        let before = r#"
+            enum E { A, B, C }
+
+            fn main(e: E) {
+                match e {
+                    E::A => (),
+                }
+            }
+        "#;
+        let after = r#"
+            enum E { A, B, C }
+
+            fn main(e: E) {
+                match e {
+                    E::A => (),
+                    E::B => (),
+                    E::C => (),
+                }
+            }
+        "#;
+        check_apply_diagnostic_fix(before, after);
+    }
+
+    #[test]
+    fn test_fill_missing_match_arms_without_trailing_comma() {
+        let before = r#"
+            enum E { A, B }
+
+            fn main(e: E) {
+                match e { E::A => () }
+            }
+        "#;
+        let after = r#"
+            enum E { A, B }
+
+            fn main(e: E) {
+                match e {
+                    E::A => (),
+                    E::B => (),
+                }
+            }
+        "#;
+        check_apply_diagnostic_fix(before, after);
+    }
+
+    #[test]
+    fn test_unresolved_import_fix() {
+        let before = r"
+            use bar::S;
+
+            mod foo {
+                pub struct S;
+            }
+        ";
+        let after = r"
+            use foo::S;
+
+            mod foo {
+                pub struct S;
+            }
+        ";
+        check_apply_diagnostic_fix(before, after);
+    }
+
+    #[test]
+    fn test_unresolved_path_fix() {
+        let before = r"
+            mod foo {
+                pub fn bar() {}
+            }
+
+            fn main() {
+                bar();
+            }
+        ";
+        let after = r"
+            use foo::bar;
+
+            mod foo {
+                pub fn bar() {}
+            }
+
+            fn main() {
+                bar();
+            }
+        ";
+        check_apply_diagnostic_fix(before, after);
+    }
+
+    #[test]
+    fn test_unresolved_path_no_fix_for_wrong_namespace() {
+        let (analysis, file_id) = single_file(
+            r"
+            mod foo {
+                pub fn bar() {}
+            }
+
+            fn main() {
+                bar::baz();
+            }
+            ",
+        );
+        let diagnostics = analysis.diagnostics(file_id).unwrap();
+        assert_debug_snapshot!(diagnostics, @r###"
+        [
+            Diagnostic {
+                message: "unresolved path `bar::baz`",
+                range: [110; 118),
+                fixes: [],
                 severity: Error,
+                code: None,
             },
         ]
         "###);
@@ -289,4 +289,27 @@ fn some_thing() -> u32 {
         assert_eq!(res.name, "foo");
         assert_snapshot!(res.expansion, @r###"0"###);
     }
+
+    #[test]
+    fn macro_expand_module_path() {
+        let res = check_expand_macro(
+            r#"
+        //- /lib.rs
+        //- /foo/lib.rs
+        #[rustc_builtin_macro]
+        macro_rules! module_path {}
+
+        mod bar {
+            mod baz {
+                fn main() {
+                    let res = module_pa<|>th!();
+                }
+            }
+        }
+        "#,
+        );
+
+        assert_eq!(res.name, "module_path");
+        assert_snapshot!(res.expansion, @r###""foo::bar::baz""###);
+    }
 }
@@ -53,8 +53,18 @@ impl Default for FeatureFlags {
     fn default() -> FeatureFlags {
         FeatureFlags::new(&[
             ("lsp.diagnostics", true),
+            ("diagnostics.type-mismatch.conservative", true),
             ("completion.insertion.add-call-parenthesis", true),
             ("completion.enable-postfix", true),
+            ("completion.enable-auto-import", true),
+            ("inlay-hints.type", true),
+            ("inlay-hints.parameter-names", true),
+            ("inlay-hints.chaining", true),
+            ("lens.run", true),
+            ("lens.debug", true),
+            ("lens.implementations", true),
+            ("lens.references", true),
+            ("lens.missing-members", true),
             ("notifications.workspace-loaded", true),
             ("notifications.cargo-toml-not-found", true),
         ])
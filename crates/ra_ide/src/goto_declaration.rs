@@ -0,0 +1,133 @@
+//! Go to declaration: like go to definition, but items of trait impls resolve
+//! to the corresponding declaration in the trait.
+
+use hir::{db::AstDatabase, AssocItem, AssocItemContainer};
+use ra_syntax::{ast, match_ast, AstNode};
+
+use crate::{
+    db::RootDatabase,
+    display::ToNav,
+    expand::descend_into_macros,
+    goto_definition::{goto_definition, pick_best},
+    references::{classify_name, classify_name_ref, NameKind},
+    FilePosition, NavigationTarget, RangeInfo,
+};
+
+pub(crate) fn goto_declaration(
+    db: &RootDatabase,
+    position: FilePosition,
+) -> Option<RangeInfo<Vec<NavigationTarget>>> {
+    let file = db.parse_or_expand(position.file_id.into())?;
+    let original_token = pick_best(file.token_at_offset(position.offset))?;
+    let token = descend_into_macros(db, position.file_id, original_token.clone());
+
+    let name_kind = match_ast! {
+        match (token.value.parent()) {
+            ast::NameRef(name_ref) => { classify_name_ref(db, token.with_value(&name_ref)) },
+            ast::Name(name) => { classify_name(db, token.with_value(&name)) },
+            _ => None,
+        }
+    }
+    .map(|it| it.kind);
+
+    if let Some(NameKind::AssocItem(item)) = name_kind {
+        if let Some(nav) = trait_declaration(db, item) {
+            return Some(RangeInfo::new(original_token.text_range(), vec![nav]));
+        }
+    }
+
+    goto_definition(db, position)
+}
+
+fn trait_declaration(db: &RootDatabase, item: AssocItem) -> Option<NavigationTarget> {
+    let imp = match item.container(db) {
+        AssocItemContainer::ImplBlock(it) => it,
+        AssocItemContainer::Trait(_) => return None,
+    };
+    let trait_ = imp.target_trait_def(db)?;
+    let name = item.name(db)?;
+    let decl = trait_.items(db).into_iter().find(|it| it.name(db).as_ref() == Some(&name))?;
+    Some(decl.to_nav(db))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mock_analysis::analysis_and_position;
+
+    fn check_goto(fixture: &str, expected: &str) {
+        let (analysis, pos) = analysis_and_position(fixture);
+
+        let mut navs = analysis.goto_declaration(pos).unwrap().unwrap().info;
+        assert_eq!(navs.len(), 1);
+        let nav = navs.pop().unwrap();
+        nav.assert_match(expected);
+    }
+
+    #[test]
+    fn goto_declaration_from_impl_method() {
+        check_goto(
+            "
+            //- /lib.rs
+            trait Trait {
+                fn foo(&self);
+            }
+            struct Bar;
+            impl Trait for Bar {
+                fn foo<|>(&self) {}
+            }
+            ",
+            "foo FN_DEF FileId(1) [18; 32) [21; 24)",
+        );
+    }
+
+    #[test]
+    fn goto_declaration_from_method_call() {
+        check_goto(
+            "
+            //- /lib.rs
+            trait Trait {
+                fn foo(&self);
+            }
+            struct Bar;
+            impl Trait for Bar {
+                fn foo(&self) {}
+            }
+            fn main() {
+                Bar.foo<|>();
+            }
+            ",
+            "foo FN_DEF FileId(1) [18; 32) [21; 24)",
+        );
+    }
+
+    #[test]
+    fn goto_declaration_of_inherent_method_is_definition() {
+        check_goto(
+            "
+            //- /lib.rs
+            struct Bar;
+            impl Bar {
+                fn foo(&self) {}
+            }
+            fn main() {
+                Bar.foo<|>();
+            }
+            ",
+            "foo FN_DEF FileId(1) [27; 43) [30; 33)",
+        );
+    }
+
+    #[test]
+    fn goto_declaration_on_reexport() {
+        check_goto(
+            "
+            //- /lib.rs
+            mod inner {
+                pub struct Foo;
+            }
+            pub use inner::Foo<|>;
+            ",
+            "Foo STRUCT_DEF FileId(1) [16; 31) [27; 30)",
+        );
+    }
+}
@@ -2,10 +2,10 @@
 
 use hir::{db::AstDatabase, InFile};
 use ra_syntax::{
-    ast::{self, DocCommentsOwner},
+    ast::{self, DocCommentsOwner, PathSegmentKind},
     match_ast, AstNode,
     SyntaxKind::*,
-    SyntaxNode, SyntaxToken, TokenAtOffset,
+    SyntaxNode, SyntaxToken, TokenAtOffset, T,
 };
 
 use crate::{
@@ -13,7 +13,7 @@ use crate::{
     display::{ShortLabel, ToNav},
     expand::descend_into_macros,
     references::{classify_name_ref, NameKind::*},
-    FilePosition, NavigationTarget, RangeInfo,
+    FileId, FilePosition, NavigationTarget, RangeInfo,
 };
 
 pub(crate) fn goto_definition(
@@ -22,6 +22,18 @@ pub(crate) fn goto_definition(
 ) -> Option<RangeInfo<Vec<NavigationTarget>>> {
     let file = db.parse_or_expand(position.file_id.into())?;
     let original_token = pick_best(file.token_at_offset(position.offset))?;
+
+    let keyword_target = match original_token.kind() {
+        LIFETIME => Some(lifetime_definition(position.file_id, &original_token)?),
+        T![self] | T![super] | T![crate] => {
+            Some(keyword_definition(db, position.file_id, &original_token)?)
+        }
+        _ => None,
+    };
+    if let Some(nav) = keyword_target {
+        return Some(RangeInfo::new(original_token.text_range(), vec![nav]));
+    }
+
     let token = descend_into_macros(db, position.file_id, original_token.clone());
 
     let nav_targets = match_ast! {
@@ -39,17 +51,107 @@ pub(crate) fn goto_definition(
     Some(RangeInfo::new(original_token.text_range(), nav_targets))
 }
 
-fn pick_best(tokens: TokenAtOffset<SyntaxToken>) -> Option<SyntaxToken> {
+pub(crate) fn pick_best(tokens: TokenAtOffset<SyntaxToken>) -> Option<SyntaxToken> {
     return tokens.max_by_key(priority);
     fn priority(n: &SyntaxToken) -> usize {
         match n.kind() {
-            IDENT | INT_NUMBER => 2,
+            IDENT | INT_NUMBER | LIFETIME | T![self] | T![super] | T![crate] => 2,
             kind if kind.is_trivia() => 0,
             _ => 1,
         }
     }
 }
 
+/// Resolves a label used by `break` or `continue` to the labeled loop or
+/// block, and a lifetime to the generic parameter declaring it.
+fn lifetime_definition(file_id: FileId, token: &SyntaxToken) -> Option<NavigationTarget> {
+    let parent = token.parent();
+    let declaration = match parent.kind() {
+        LABEL | LIFETIME_PARAM => parent,
+        BREAK_EXPR | CONTINUE_EXPR => parent
+            .ancestors()
+            .take_while(|it| it.kind() != FN_DEF && it.kind() != LAMBDA_EXPR)
+            .filter_map(|it| it.children().find(|child| child.kind() == LABEL))
+            .find(|label| label_name(label).map(|it| it.text() == token.text()).unwrap_or(false))?,
+        _ => parent.ancestors().find_map(|it| {
+            it.children()
+                .filter_map(ast::TypeParamList::cast)
+                .flat_map(|params| params.lifetime_params())
+                .find(|param| {
+                    param.lifetime_token().as_ref().map(|it| it.text()) == Some(token.text())
+                })
+                .map(|param| param.syntax().clone())
+        })?,
+    };
+    let name = label_name(&declaration)?;
+    Some(NavigationTarget::from_syntax(
+        file_id,
+        name.text().clone(),
+        Some(name.text_range()),
+        declaration.text_range(),
+        declaration.kind(),
+        None,
+        None,
+    ))
+}
+
+fn label_name(node: &SyntaxNode) -> Option<SyntaxToken> {
+    node.children_with_tokens().filter_map(|it| it.into_token()).find(|it| it.kind() == LIFETIME)
+}
+
+/// Resolves `self` to the self parameter, and `self`, `super` and `crate` in
+/// paths to the module they refer to.
+fn keyword_definition(
+    db: &RootDatabase,
+    file_id: FileId,
+    token: &SyntaxToken,
+) -> Option<NavigationTarget> {
+    if let Some(self_param) = ast::SelfParam::cast(token.parent()) {
+        return Some(self_param_target(file_id, &self_param));
+    }
+    let segment = ast::PathSegment::cast(token.parent())?;
+    let path = segment.parent_path();
+    if token.kind() == T![self] && path.qualifier().is_none() {
+        let is_value = path.syntax().parent().map(|it| it.kind() == PATH_EXPR).unwrap_or(false);
+        if is_value {
+            let self_param =
+                path.syntax().ancestors().find_map(ast::FnDef::cast)?.param_list()?.self_param()?;
+            return Some(self_param_target(file_id, &self_param));
+        }
+    }
+
+    let src = hir::ModuleSource::from_child_node(db, InFile::new(file_id.into(), segment.syntax()));
+    let mut module = hir::Module::from_definition(db, InFile::new(file_id.into(), src))?;
+    let mut segments = vec![segment];
+    let mut qualifier = path.qualifier();
+    while let Some(path) = qualifier {
+        segments.push(path.segment()?);
+        qualifier = path.qualifier();
+    }
+    for segment in segments.into_iter().rev() {
+        module = match segment.kind()? {
+            PathSegmentKind::SelfKw => module,
+            PathSegmentKind::SuperKw => module.parent(db)?,
+            PathSegmentKind::CrateKw => module.krate().root_module(db)?,
+            _ => return None,
+        };
+    }
+    Some(module.to_nav(db))
+}
+
+fn self_param_target(file_id: FileId, self_param: &ast::SelfParam) -> NavigationTarget {
+    let self_token = self_param.self_kw_token();
+    NavigationTarget::from_syntax(
+        file_id,
+        self_token.text().clone(),
+        Some(self_token.text_range()),
+        self_param.syntax().text_range(),
+        SELF_PARAM,
+        None,
+        None,
+    )
+}
+
 #[derive(Debug)]
 pub(crate) enum ReferenceResult {
     Exact(NavigationTarget),
@@ -928,4 +1030,77 @@ mod tests {
             "x: i32|x",
         )
     }
+
+    #[test]
+    fn goto_def_for_label() {
+        check_goto(
+            "
+            //- /lib.rs
+            fn main() {
+                'outer: loop {
+                    loop { break 'outer<|>; }
+                }
+            }
+            ",
+            "'outer LABEL FileId(1) [16; 23) [16; 22)",
+            "'outer:|'outer",
+        );
+    }
+
+    #[test]
+    fn goto_def_for_lifetime() {
+        check_goto(
+            "
+            //- /lib.rs
+            fn foo<'a>(x: &'a<|> str) {}
+            ",
+            "'a LIFETIME_PARAM FileId(1) [7; 9) [7; 9)",
+            "'a|'a",
+        );
+    }
+
+    #[test]
+    fn goto_def_for_self_param() {
+        check_goto(
+            "
+            //- /lib.rs
+            struct Foo;
+            impl Foo {
+                fn f(&self) { self<|>; }
+            }
+            ",
+            "self SELF_PARAM FileId(1) [32; 37) [33; 37)",
+            "&self|self",
+        );
+    }
+
+    #[test]
+    fn goto_def_for_super_keyword() {
+        check_goto(
+            "
+            //- /lib.rs
+            mod foo;
+            //- /foo.rs
+            mod bar;
+            //- /foo/bar.rs
+            use super<|>::Baz;
+            ",
+            "foo SOURCE_FILE FileId(2) [0; 9)",
+            "mod bar;\n",
+        );
+    }
+
+    #[test]
+    fn goto_def_for_crate_keyword() {
+        check_goto(
+            "
+            //- /lib.rs
+            mod foo;
+            //- /foo.rs
+            use crate<|>::Baz;
+            ",
+            "SOURCE_FILE FileId(1) [0; 9)",
+            "mod foo;\n",
+        );
+    }
 }
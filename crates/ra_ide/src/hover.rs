@@ -1,6 +1,7 @@
 //! FIXME: write short doc here
 
 use hir::{db::AstDatabase, Adt, HasSource, HirDisplay};
+use itertools::Itertools;
 use ra_db::SourceDatabase;
 use ra_syntax::{
     algo::find_covering_element,
@@ -92,6 +93,43 @@ fn hover_text(docs: Option<String>, desc: Option<String>) -> Option<String> {
     }
 }
 
+/// Shows the type of a local; for closures, also their `Fn*` kind and what they
+/// capture from their environment.
+fn local_hover_text(db: &RootDatabase, local: hir::Local) -> String {
+    let ty = local.ty(db);
+    let (kind, captures) = match (local.closure_kind(db), local.closure_captures(db)) {
+        (Some(kind), Some(captures)) => (kind, captures),
+        _ => return rust_code_markup(ty.display_truncated(db, None).to_string()),
+    };
+    let kind = match kind {
+        hir::FnTrait::FnOnce => "FnOnce",
+        hir::FnTrait::FnMut => "FnMut",
+        hir::FnTrait::Fn => "Fn",
+    };
+    let captures = if captures.is_empty() {
+        "nothing".to_string()
+    } else {
+        captures
+            .iter()
+            .map(|capture| {
+                let name = match capture.local().name(db) {
+                    Some(name) => name.to_string(),
+                    None => "_".to_string(),
+                };
+                let mode = match capture.kind() {
+                    hir::CaptureKind::ByRef(hir::Mutability::Shared) => "by reference",
+                    hir::CaptureKind::ByRef(hir::Mutability::Mut) => "by mutable reference",
+                    hir::CaptureKind::ByValue => "by value",
+                };
+                format!("`{}` {}", name, mode)
+            })
+            .join(", ")
+    };
+    let doc = format!("`{}` closure capturing {}", kind, captures);
+    // Closure signatures are short, so show them in full
+    rust_code_markup_with_doc(ty.display(db).to_string(), Some(doc))
+}
+
 fn hover_text_from_name_kind(db: &RootDatabase, name_kind: NameKind) -> Option<String> {
     return match name_kind {
         Macro(it) => {
@@ -128,7 +166,7 @@ fn hover_text_from_name_kind(db: &RootDatabase, name_kind: NameKind) -> Option<S
             hir::ModuleDef::TypeAlias(it) => from_def_source(db, it),
             hir::ModuleDef::BuiltinType(it) => Some(it.to_string()),
         },
-        Local(it) => Some(rust_code_markup(it.ty(db).display_truncated(db, None).to_string())),
+        Local(it) => Some(local_hover_text(db, it)),
         TypeParam(_) | SelfType(_) => {
             // FIXME: Hover for generic param
             None
@@ -221,11 +259,19 @@ pub(crate) fn type_of(db: &RootDatabase, frange: FileRange) -> Option<String> {
 
 #[cfg(test)]
 mod tests {
-    use crate::mock_analysis::{
-        analysis_and_position, single_file_with_position, single_file_with_range,
-    };
+    use std::sync::Arc;
+
+    use ra_cfg::CfgOptions;
+    use ra_db::{Env, ProcMacro, ProcMacroKind};
     use ra_syntax::TextRange;
 
+    use crate::{
+        mock_analysis::{
+            analysis_and_position, single_file_with_position, single_file_with_range, MockAnalysis,
+        },
+        AnalysisChange, CrateGraph, Edition,
+    };
+
     fn trim_markup(s: &str) -> &str {
         s.trim_start_matches("```rust\n").trim_end_matches("\n```")
     }
@@ -747,4 +793,199 @@ fn func(foo: i32) { if true { <|>foo; }; }
             &["u32"],
         );
     }
+
+    #[test]
+    fn hover_closure_shows_kind_and_captures() {
+        check_hover_result(
+            r#"
+            //- /main.rs
+            struct S;
+            fn main() {
+                let mut count = 0;
+                let s = S;
+                let inc<|> = |step: u32| { count += step; s };
+            }
+            "#,
+            &["|u32| -> S\n```\n\n`FnOnce` closure capturing `count` by mutable reference, `s` by value"],
+        );
+        check_hover_result(
+            r#"
+            //- /main.rs
+            fn main() {
+                let x = 92;
+                let f<|> = || x + 1;
+            }
+            "#,
+            &["|| -> i32\n```\n\n`Fn` closure capturing `x` by reference"],
+        );
+        check_hover_result(
+            r#"
+            //- /main.rs
+            fn main() {
+                let x = 92;
+                let f<|> = move || x + 1;
+            }
+            "#,
+            &["|| -> i32\n```\n\n`Fn` closure capturing `x` by value"],
+        );
+    }
+
+    #[test]
+    fn hover_closure_capture_kinds() {
+        check_hover_result(
+            r#"
+            //- /main.rs
+            struct S;
+            impl S {
+                fn get(&self) -> u32 { 0 }
+                fn bump(&mut self) {}
+            }
+            fn main() {
+                let mut s = S;
+                let f<|> = || { s.get(); s.bump(); };
+            }
+            "#,
+            &["|| -> ()\n```\n\n`FnMut` closure capturing `s` by mutable reference"],
+        );
+        check_hover_result(
+            r#"
+            //- /main.rs
+            fn main() {
+                let mut count = 0;
+                let mut inc = || count += 1;
+                let f<|> = || inc();
+            }
+            "#,
+            &["|| -> ()\n```\n\n`FnMut` closure capturing `inc` by mutable reference"],
+        );
+        check_hover_result(
+            r#"
+            //- /main.rs
+            fn main() {
+                let f<|> = |x: i32| x + 1;
+            }
+            "#,
+            &["|i32| -> i32\n```\n\n`Fn` closure capturing nothing"],
+        );
+    }
+
+    #[test]
+    fn hover_closure_calling_fn_trait_params() {
+        check_hover_result(
+            r#"
+            //- /main.rs
+            #[lang = "fn_once"]
+            trait FnOnce<Args> { type Output; }
+            #[lang = "fn_mut"]
+            trait FnMut<Args>: FnOnce<Args> {}
+            #[lang = "fn"]
+            trait Fn<Args>: FnMut<Args> {}
+            fn call<F: FnMut()>(mut f: F) {
+                let g<|> = || { f(); };
+            }
+            "#,
+            &["|| -> ()\n```\n\n`FnMut` closure capturing `f` by mutable reference"],
+        );
+        check_hover_result(
+            r#"
+            //- /main.rs
+            #[lang = "fn_once"]
+            trait FnOnce<Args> { type Output; }
+            #[lang = "fn_mut"]
+            trait FnMut<Args>: FnOnce<Args> {}
+            #[lang = "fn"]
+            trait Fn<Args>: FnMut<Args> {}
+            fn call<F: FnOnce()>(f: F) {
+                let g<|> = || { f(); };
+            }
+            "#,
+            &["|| -> ()\n```\n\n`FnOnce` closure capturing `f` by value"],
+        );
+        check_hover_result(
+            r#"
+            //- /main.rs
+            #[lang = "fn_once"]
+            trait FnOnce<Args> { type Output; }
+            #[lang = "fn_mut"]
+            trait FnMut<Args>: FnOnce<Args> {}
+            #[lang = "fn"]
+            trait Fn<Args>: FnMut<Args> {}
+            fn call<F: Fn()>(f: F) {
+                let g<|> = || { f(); };
+            }
+            "#,
+            &["|| -> ()\n```\n\n`Fn` closure capturing `f` by reference"],
+        );
+    }
+
+    /// Expands to `struct Generated;`, whatever the input.
+    #[derive(Debug)]
+    struct GenerateStruct;
+
+    impl tt::TokenExpander for GenerateStruct {
+        fn expand(
+            &self,
+            _subtree: &tt::Subtree,
+            _attrs: Option<&tt::Subtree>,
+        ) -> Result<tt::Subtree, tt::ExpansionError> {
+            let id = tt::TokenId::unspecified();
+            let ident = |text: &str| tt::Leaf::from(tt::Ident { text: text.into(), id }).into();
+            let semi = tt::Leaf::from(tt::Punct { char: ';', spacing: tt::Spacing::Alone, id });
+            let token_trees = vec![ident("struct"), ident("Generated"), semi.into()];
+            Ok(tt::Subtree { delimiter: None, token_trees })
+        }
+    }
+
+    #[test]
+    fn hover_item_generated_by_attribute_macro() {
+        let (mock, position) = MockAnalysis::with_files_and_position(
+            r#"
+            //- /main.rs
+            #[pm::generate]
+            fn foo() {}
+
+            fn main() {
+                let x<|> = Generated;
+            }
+
+            //- /pm/lib.rs
+            "#,
+        );
+        let main_file = mock.id_of("/main.rs");
+        let pm_file = mock.id_of("/pm/lib.rs");
+        let mut host = mock.analysis_host();
+
+        // The mock doesn't know about proc macros, so replace its crate graph.
+        let mut crate_graph = CrateGraph::default();
+        let main_crate = crate_graph.add_crate_root(
+            main_file,
+            Edition::Edition2018,
+            None,
+            CfgOptions::default(),
+            Env::default(),
+            Default::default(),
+            Vec::new(),
+        );
+        let proc_macro = ProcMacro {
+            name: "generate".into(),
+            kind: ProcMacroKind::Attr,
+            expander: Arc::new(GenerateStruct),
+        };
+        let pm_crate = crate_graph.add_crate_root(
+            pm_file,
+            Edition::Edition2018,
+            None,
+            CfgOptions::default(),
+            Env::default(),
+            Default::default(),
+            vec![proc_macro],
+        );
+        crate_graph.add_dep(main_crate, "pm".into(), pm_crate).unwrap();
+        let mut change = AnalysisChange::new();
+        change.set_crate_graph(crate_graph);
+        host.apply_change(change);
+
+        let hover = host.analysis().hover(position).unwrap().unwrap();
+        assert_eq!(trim_markup_opt(hover.info.first()), Some("Generated"));
+    }
 }
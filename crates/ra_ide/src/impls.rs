@@ -1,10 +1,19 @@
 //! FIXME: write short doc here
 
-use hir::{FromSource, ImplBlock};
+use hir::{AssocItem, FromSource, HasSource, ImplBlock, InFile, Name};
 use ra_db::SourceDatabase;
-use ra_syntax::{algo::find_node_at_offset, ast, AstNode};
+use ra_syntax::{algo::find_node_at_offset, ast, AstNode, TextRange};
 
-use crate::{db::RootDatabase, display::ToNav, FilePosition, NavigationTarget, RangeInfo};
+use crate::{db::RootDatabase, display::ToNav, FileId, FilePosition, NavigationTarget, RangeInfo};
+
+/// An `impl Trait for Type` block which lacks some of the items required by
+/// the trait.
+#[derive(Debug)]
+pub struct MissingTraitMembers {
+    pub range: TextRange,
+    pub trait_name: String,
+    pub members: Vec<String>,
+}
 
 pub(crate) fn goto_implementation(
     db: &RootDatabase,
@@ -82,9 +91,72 @@ fn impls_for_trait(
     Some(impls.into_iter().map(|imp| imp.to_nav(db)).collect())
 }
 
+pub(crate) fn missing_trait_members(
+    db: &RootDatabase,
+    file_id: FileId,
+) -> Vec<MissingTraitMembers> {
+    let parse = db.parse(file_id);
+    parse
+        .tree()
+        .syntax()
+        .descendants()
+        .filter_map(ast::ImplBlock::cast)
+        .filter_map(|impl_block| missing_members_of_impl(db, file_id, &impl_block))
+        .collect()
+}
+
+fn missing_members_of_impl(
+    db: &RootDatabase,
+    file_id: FileId,
+    impl_block: &ast::ImplBlock,
+) -> Option<MissingTraitMembers> {
+    let src = InFile::new(file_id.into(), impl_block.clone());
+    let impl_block_def = ImplBlock::from_source(db, src)?;
+    let trait_ = impl_block_def.target_trait_def(db)?;
+
+    let implemented = impl_block_def
+        .items(db)
+        .into_iter()
+        .filter_map(|it| assoc_item_name(db, it))
+        .collect::<Vec<_>>();
+    let members = trait_
+        .items(db)
+        .into_iter()
+        .filter(|&it| !has_default(db, it))
+        .filter_map(|it| assoc_item_name(db, it))
+        .filter(|name| !implemented.contains(name))
+        .map(|name| name.to_string())
+        .collect::<Vec<_>>();
+    if members.is_empty() {
+        return None;
+    }
+
+    Some(MissingTraitMembers {
+        range: impl_block.syntax().text_range(),
+        trait_name: trait_.name(db).to_string(),
+        members,
+    })
+}
+
+fn assoc_item_name(db: &RootDatabase, item: AssocItem) -> Option<Name> {
+    match item {
+        AssocItem::Function(it) => Some(it.name(db)),
+        AssocItem::Const(it) => it.name(db),
+        AssocItem::TypeAlias(it) => Some(it.name(db)),
+    }
+}
+
+fn has_default(db: &RootDatabase, item: AssocItem) -> bool {
+    match item {
+        AssocItem::Function(it) => it.source(db).value.body().is_some(),
+        AssocItem::Const(it) => it.source(db).value.body().is_some(),
+        AssocItem::TypeAlias(it) => it.type_ref(db).is_some(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::mock_analysis::analysis_and_position;
+    use crate::mock_analysis::{analysis_and_position, single_file};
 
     fn check_goto(fixture: &str, expected: &[&str]) {
         let (analysis, pos) = analysis_and_position(fixture);
@@ -203,4 +275,33 @@ mod tests {
             ],
         );
     }
+
+    #[test]
+    fn missing_trait_members_skips_defaults_and_implemented_items() {
+        let (analysis, file_id) = single_file(
+            "
+            trait Tr {
+                type Ty;
+                const C: u32;
+                fn required(&self);
+                fn provided(&self) {}
+            }
+            struct S;
+            impl Tr for S {
+                type Ty = ();
+            }
+            struct Complete;
+            impl Tr for Complete {
+                type Ty = ();
+                const C: u32 = 0;
+                fn required(&self) {}
+            }
+            impl S {}
+            ",
+        );
+        let missing = analysis.missing_trait_members(file_id).unwrap();
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].trait_name, "Tr");
+        assert_eq!(missing[0].members, vec!["C".to_string(), "required".to_string()]);
+    }
 }
@@ -0,0 +1,86 @@
+//! Searches the workspace and the libraries for items with a given name, to
+//! offer them as import candidates.
+
+use hir::ModuleDef;
+use ra_db::SourceDatabase;
+use ra_prof::profile;
+use ra_syntax::{ast, AstNode, SyntaxKind::NAME};
+
+use crate::{
+    db::RootDatabase,
+    references::{classify_name, NameDefinition, NameKind},
+    symbol_index::{self, FileSymbol},
+    Query,
+};
+
+pub(crate) struct ImportsLocator<'a> {
+    db: &'a RootDatabase,
+}
+
+impl<'a> ImportsLocator<'a> {
+    pub(crate) fn new(db: &'a RootDatabase) -> Self {
+        Self { db }
+    }
+
+    /// Finds all items named exactly `name_to_import`, both in the workspace
+    /// and in the libraries.
+    pub(crate) fn find_imports(&mut self, name_to_import: &str) -> Vec<ModuleDef> {
+        let _p = profile("search_for_imports");
+        self.search(name_to_import, true, 40)
+    }
+
+    /// Finds items whose name fuzzy-matches `name_fragment`, as typed in an
+    /// unfinished identifier. Returns at most `limit` items from the workspace
+    /// and as many from the libraries.
+    pub(crate) fn find_similar_imports(
+        &mut self,
+        name_fragment: &str,
+        limit: usize,
+    ) -> Vec<ModuleDef> {
+        let _p = profile("search_for_similar_imports");
+        self.search(name_fragment, false, limit)
+    }
+
+    fn search(&mut self, name: &str, exact: bool, limit: usize) -> Vec<ModuleDef> {
+        let db = self.db;
+        let make_query = |libs: bool| {
+            let mut query = Query::new(name.to_string());
+            if libs {
+                query.libs();
+            }
+            if exact {
+                query.exact();
+            }
+            query.limit(limit);
+            query
+        };
+
+        let project_results = symbol_index::world_symbols(db, make_query(false));
+        let lib_results = symbol_index::world_symbols(db, make_query(true));
+
+        project_results
+            .into_iter()
+            .chain(lib_results.into_iter())
+            .filter_map(|import_candidate| self.get_name_definition(&import_candidate))
+            .filter_map(|name_definition_to_import| match name_definition_to_import.kind {
+                NameKind::Def(module_def) => Some(module_def),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn get_name_definition(&mut self, import_candidate: &FileSymbol) -> Option<NameDefinition> {
+        let _p = profile("get_name_definition");
+        let file_id = import_candidate.file_id;
+        let candidate_node = import_candidate.ptr.to_node(self.db.parse(file_id).tree().syntax());
+        let candidate_name_node = if candidate_node.kind() != NAME {
+            candidate_node.children().find(|it| it.kind() == NAME)?
+        } else {
+            candidate_node
+        };
+        classify_name(
+            self.db,
+            hir::InFile { file_id: file_id.into(), value: &ast::Name::cast(candidate_name_node)? },
+        )
+    }
+}
@@ -1,18 +1,31 @@
-//! FIXME: write short doc here
+//! Inlay hints: the types of bindings, the names of the parameters at call
+//! sites and the types of the intermediate expressions of method chains.
 
 use hir::{HirDisplay, SourceAnalyzer};
 use once_cell::unsync::Lazy;
 use ra_prof::profile;
 use ra_syntax::{
-    ast::{self, AstNode, TypeAscriptionOwner},
-    match_ast, SmolStr, SourceFile, SyntaxKind, SyntaxNode, TextRange,
+    ast::{self, ArgListOwner, AstNode, TypeAscriptionOwner},
+    match_ast, Direction, NodeOrToken, SmolStr, SourceFile, SyntaxKind, SyntaxNode, TextRange, T,
 };
 
-use crate::{db::RootDatabase, FileId};
+use crate::{db::RootDatabase, FileId, FunctionSignature};
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum InlayKind {
     TypeHint,
+    ParameterHint,
+    ChainingHint,
+}
+
+impl InlayKind {
+    fn feature_flag(self) -> &'static str {
+        match self {
+            InlayKind::TypeHint => "inlay-hints.type",
+            InlayKind::ParameterHint => "inlay-hints.parameter-names",
+            InlayKind::ChainingHint => "inlay-hints.chaining",
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -32,6 +45,7 @@ pub(crate) fn inlay_hints(
         .descendants()
         .flat_map(|node| get_inlay_hints(db, file_id, &node, max_inlay_hint_length))
         .flatten()
+        .filter(|hint| db.feature_flags.get(hint.kind.feature_flag()))
         .collect()
 }
 
@@ -87,11 +101,142 @@ fn get_inlay_hints(
                         .collect(),
                 )
             },
+            ast::CallExpr(it) => {
+                get_param_name_hints(db, &analyzer, ast::Expr::from(it))
+            },
+            ast::MethodCallExpr(it) => {
+                let mut res = get_param_name_hints(db, &analyzer, ast::Expr::from(it.clone()))
+                    .unwrap_or_default();
+                res.extend(get_chaining_hint(db, &analyzer, it.expr()?, max_inlay_hint_length));
+                Some(res)
+            },
+            ast::FieldExpr(it) => {
+                get_chaining_hint(db, &analyzer, it.expr()?, max_inlay_hint_length)
+                    .map(|hint| vec![hint])
+            },
             _ => None,
         }
     }
 }
 
+fn get_param_name_hints(
+    db: &RootDatabase,
+    analyzer: &SourceAnalyzer,
+    expr: ast::Expr,
+) -> Option<Vec<InlayHint>> {
+    let (args, is_method_call) = match &expr {
+        ast::Expr::CallExpr(it) => (it.arg_list()?.args(), false),
+        ast::Expr::MethodCallExpr(it) => (it.arg_list()?.args(), true),
+        _ => return None,
+    };
+    let signature = get_fn_signature(db, analyzer, &expr)?;
+    // The receiver doesn't get a hint. It is not among the arguments of a
+    // method call, but it is the first one in UFCS calls like `S::method(s)`.
+    let has_self = signature.parameter_names.first().map(String::as_str) == Some("self");
+    let hints = signature
+        .parameter_names
+        .iter()
+        .skip(if has_self { 1 } else { 0 })
+        .zip(args.skip(if has_self && !is_method_call { 1 } else { 0 }))
+        .filter(|(param_name, arg)| should_show_param_hint(param_name, arg))
+        .map(|(param_name, arg)| InlayHint {
+            range: arg.syntax().text_range(),
+            kind: InlayKind::ParameterHint,
+            label: param_name.as_str().into(),
+        })
+        .collect();
+    Some(hints)
+}
+
+fn get_fn_signature(
+    db: &RootDatabase,
+    analyzer: &SourceAnalyzer,
+    expr: &ast::Expr,
+) -> Option<FunctionSignature> {
+    match expr {
+        ast::Expr::CallExpr(expr) => {
+            // Tuple structs and enum variants have no parameter names to show
+            match analyzer.type_of(db, &expr.expr()?)?.as_callable()? {
+                hir::CallableDef::FunctionId(it) => {
+                    Some(FunctionSignature::from_hir(db, it.into()))
+                }
+                hir::CallableDef::StructId(_) | hir::CallableDef::EnumVariantId(_) => None,
+            }
+        }
+        ast::Expr::MethodCallExpr(expr) => {
+            let function = analyzer.resolve_method_call(expr)?;
+            Some(FunctionSignature::from_hir(db, function))
+        }
+        _ => None,
+    }
+}
+
+/// Hints for anonymous or unused (`_`-prefixed) parameters, and for arguments
+/// which already spell out the name of the parameter (`foo(timeout)`,
+/// `foo(self.timeout)`, `foo(&timeout)`) are just noise.
+fn should_show_param_hint(param_name: &str, arg: &ast::Expr) -> bool {
+    if param_name.is_empty() || param_name.starts_with('_') {
+        return false;
+    }
+    match arg_name(arg) {
+        Some(arg_name) => !arg_name.text().eq_ignore_ascii_case(param_name),
+        None => true,
+    }
+}
+
+fn arg_name(arg: &ast::Expr) -> Option<ast::NameRef> {
+    match arg {
+        ast::Expr::PathExpr(it) => it.path()?.segment()?.name_ref(),
+        ast::Expr::FieldExpr(it) => it.name_ref(),
+        ast::Expr::MethodCallExpr(it) => it.name_ref(),
+        ast::Expr::RefExpr(it) => arg_name(&it.expr()?),
+        ast::Expr::ParenExpr(it) => arg_name(&it.expr()?),
+        _ => None,
+    }
+}
+
+/// Shows the type of `receiver`, if it is followed by a line break and then by
+/// the `.` of a method call or field access, as in
+///
+/// ```ignore
+/// let name = people
+///     .iter() // Iter<Person>
+///     .map(|it| it.name.clone()) // Map<...>
+///     .collect::<Vec<_>>();
+/// ```
+fn get_chaining_hint(
+    db: &RootDatabase,
+    analyzer: &SourceAnalyzer,
+    receiver: ast::Expr,
+    max_inlay_hint_length: Option<usize>,
+) -> Option<InlayHint> {
+    let mut tokens = receiver
+        .syntax()
+        .siblings_with_tokens(Direction::Next)
+        .skip(1)
+        .filter_map(NodeOrToken::into_token)
+        .filter(|token| match token.kind() {
+            SyntaxKind::COMMENT => false,
+            SyntaxKind::WHITESPACE => token.text().contains('\n'),
+            _ => true,
+        });
+    let next = tokens.next()?;
+    let next_next = tokens.next()?;
+    if next.kind() != SyntaxKind::WHITESPACE || next_next.kind() != T![.] {
+        return None;
+    }
+
+    let ty = analyzer.type_of(db, &receiver)?;
+    if ty.is_unknown() {
+        return None;
+    }
+    Some(InlayHint {
+        range: receiver.syntax().text_range(),
+        kind: InlayKind::ChainingHint,
+        label: ty.display_truncated(db, max_inlay_hint_length).to_string().into(),
+    })
+}
+
 fn get_pat_type_hints(
     db: &RootDatabase,
     analyzer: &SourceAnalyzer,
@@ -152,6 +297,8 @@ fn get_leaf_pats(root_pat: ast::Pat) -> Vec<ast::Pat> {
                 pats_to_process.extend(tuple_struct_pat.args())
             }
             ast::Pat::RefPat(ref_pat) => pats_to_process.extend(ref_pat.pat()),
+            ast::Pat::ParenPat(paren_pat) => pats_to_process.extend(paren_pat.pat()),
+            ast::Pat::OrPat(or_pat) => pats_to_process.extend(or_pat.pats()),
             _ => (),
         }
     }
@@ -605,4 +752,125 @@ fn main() {
         "###
         );
     }
+
+    #[test]
+    fn parameter_name_hints() {
+        let (analysis, file_id) = single_file(
+            r#"
+fn foo(timeout: u32, retry: bool, _ignored: i32) {}
+struct S;
+impl S {
+    fn method(&self, count: u32) {}
+}
+fn main() {
+    let timeout = 30;
+    foo(timeout, true, 1);
+    let s = S;
+    s.method(92);
+    S::method(&s, 92);
+}"#,
+        );
+
+        assert_debug_snapshot!(analysis.inlay_hints(file_id, None).unwrap(), @r###"
+        [
+            InlayHint {
+                range: [130; 137),
+                kind: TypeHint,
+                label: "u32",
+            },
+            InlayHint {
+                range: [161; 165),
+                kind: ParameterHint,
+                label: "retry",
+            },
+            InlayHint {
+                range: [179; 180),
+                kind: TypeHint,
+                label: "S",
+            },
+            InlayHint {
+                range: [199; 201),
+                kind: ParameterHint,
+                label: "count",
+            },
+            InlayHint {
+                range: [222; 224),
+                kind: ParameterHint,
+                label: "count",
+            },
+        ]
+        "###
+        );
+    }
+
+    #[test]
+    fn parameter_name_hints_skip_ufcs_receiver() {
+        let (analysis, file_id) = single_file(
+            r#"
+struct S;
+impl S {
+    fn consume(self, count: u32) {}
+    fn new(count: u32) -> S { S }
+}
+fn main() {
+    S::consume(S, 92);
+    S::new(92);
+}"#,
+        );
+
+        assert_debug_snapshot!(analysis.inlay_hints(file_id, None).unwrap(), @r###"
+        [
+            InlayHint {
+                range: [122; 124),
+                kind: ParameterHint,
+                label: "count",
+            },
+            InlayHint {
+                range: [138; 140),
+                kind: ParameterHint,
+                label: "count",
+            },
+        ]
+        "###
+        );
+    }
+
+    #[test]
+    fn chaining_hints() {
+        let (analysis, file_id) = single_file(
+            r#"
+struct A(B);
+impl A { fn into_b(self) -> B { self.0 } }
+struct B(C);
+impl B { fn into_c(self) -> C { self.0 } }
+struct C;
+
+fn main() {
+    let c = A(B(C))
+        .into_b()
+        .into_c();
+}"#,
+        );
+
+        assert_debug_snapshot!(analysis.inlay_hints(file_id, None).unwrap(), @r###"
+        [
+            InlayHint {
+                range: [144; 145),
+                kind: TypeHint,
+                label: "C",
+            },
+            InlayHint {
+                range: [148; 173),
+                kind: ChainingHint,
+                label: "B",
+            },
+            InlayHint {
+                range: [148; 155),
+                kind: ChainingHint,
+                label: "A",
+            },
+        ]
+        "###
+        );
+    }
 }
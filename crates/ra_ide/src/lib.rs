@@ -20,6 +20,7 @@ mod feature_flags;
 mod status;
 mod completion;
 mod runnables;
+mod goto_declaration;
 mod goto_definition;
 mod goto_type_definition;
 mod extend_selection;
@@ -37,6 +38,8 @@ mod folding_ranges;
 mod line_index;
 mod line_index_utils;
 mod join_lines;
+mod ssr;
+mod range_formatting;
 mod typing;
 mod matching_brace;
 mod display;
@@ -44,6 +47,7 @@ mod inlay_hints;
 mod wasm_shims;
 mod expand;
 mod expand_macro;
+mod imports_locator;
 
 #[cfg(test)]
 mod marks;
@@ -57,7 +61,7 @@ use ra_db::{
     salsa::{self, ParallelDatabase},
     CheckCanceled, Env, FileLoader, SourceDatabase,
 };
-use ra_syntax::{SourceFile, TextRange, TextUnit};
+use ra_syntax::{SmolStr, SourceFile, TextRange, TextUnit};
 
 use crate::{db::LineIndexDatabase, display::ToNav, symbol_index::FileSymbol};
 
@@ -65,25 +69,31 @@ pub use crate::{
     assists::{Assist, AssistId},
     call_hierarchy::CallItem,
     change::{AnalysisChange, LibraryData},
-    completion::{CompletionItem, CompletionItemKind, InsertTextFormat},
+    completion::{CompletionItem, CompletionItemKind, InsertTextFormat, ResolvedCompletion},
     diagnostics::Severity,
     display::{file_structure, FunctionSignature, NavigationTarget, StructureNode},
     expand_macro::ExpandedMacro,
     feature_flags::FeatureFlags,
     folding_ranges::{Fold, FoldKind},
     hover::HoverResult,
+    impls::MissingTraitMembers,
     inlay_hints::{InlayHint, InlayKind},
     line_index::{LineCol, LineIndex},
     line_index_utils::translate_offset_with_edit,
-    references::{Reference, ReferenceKind, ReferenceSearchResult, SearchScope},
+    range_formatting::FormattingRange,
+    references::{Reference, ReferenceKind, ReferenceSearchResult, RenameError, SearchScope},
     runnables::{Runnable, RunnableKind},
     source_change::{FileSystemEdit, SourceChange, SourceFileEdit},
-    syntax_highlighting::HighlightedRange,
+    ssr::SsrError,
+    syntax_highlighting::{
+        Highlight, HighlightModifier, HighlightModifiers, HighlightTag, HighlightedRange,
+    },
 };
 
 pub use hir::Documentation;
 pub use ra_db::{
-    Canceled, CrateGraph, CrateId, Edition, FileId, FilePosition, FileRange, SourceRootId,
+    Canceled, CrateGraph, CrateId, Edition, ExternSourceId, FileId, FilePosition, FileRange,
+    SourceRootId,
 };
 
 pub type Cancelable<T> = Result<T, Canceled>;
@@ -92,8 +102,10 @@ pub type Cancelable<T> = Result<T, Canceled>;
 pub struct Diagnostic {
     pub message: String,
     pub range: TextRange,
-    pub fix: Option<SourceChange>,
+    pub fixes: Vec<SourceChange>,
     pub severity: Severity,
+    /// Error code, like `E0268`, which can be looked up in rustc's error index.
+    pub code: Option<&'static str>,
 }
 
 #[derive(Debug)]
@@ -242,7 +254,15 @@ impl Analysis {
         // Default to enable test for single file.
         let mut cfg_options = CfgOptions::default();
         cfg_options.insert_atom("test".into());
-        crate_graph.add_crate_root(file_id, Edition::Edition2018, cfg_options, Env::default());
+        crate_graph.add_crate_root(
+            file_id,
+            Edition::Edition2018,
+            None,
+            cfg_options,
+            Env::default(),
+            Default::default(),
+            Vec::new(),
+        );
         change.add_file(source_root, file_id, "main.rs".into(), Arc::new(text));
         change.set_crate_graph(crate_graph);
         host.apply_change(change);
@@ -304,6 +324,13 @@ impl Analysis {
         self.with_db(|db| expand_macro::expand_macro(db, position))
     }
 
+    /// Returns the items which have to be reformatted to format the range.
+    pub fn formatting_range(&self, frange: FileRange) -> Cancelable<Option<FormattingRange>> {
+        self.with_db(|db| {
+            range_formatting::formatting_range(&db.parse(frange.file_id).tree(), frange.range)
+        })
+    }
+
     /// Returns an edit to remove all newlines in the range, cleaning up minor
     /// stuff like trailing commas.
     pub fn join_lines(&self, frange: FileRange) -> Cancelable<SourceChange> {
@@ -317,6 +344,18 @@ impl Analysis {
         })
     }
 
+    /// Replaces every match of a structural search pattern like
+    /// `foo($a, $b) ==>> bar($b, $a)` across the workspace.
+    pub fn structural_search_replace(
+        &self,
+        query: &str,
+    ) -> Cancelable<Result<SourceChange, SsrError>> {
+        self.with_db(|db| {
+            let edits = ssr::structural_search_replace(db, query)?;
+            Ok(SourceChange::source_file_edits("structural search replace", edits))
+        })
+    }
+
     /// Returns an edit which should be applied when opening a new line, fixing
     /// up minor stuff like continuing the comment.
     pub fn on_enter(&self, position: FilePosition) -> Cancelable<Option<SourceChange>> {
@@ -379,6 +418,15 @@ impl Analysis {
         self.with_db(|db| goto_definition::goto_definition(db, position))
     }
 
+    /// Returns the declarations from the symbol at `position`: items of trait
+    /// impls resolve to the trait's declaration.
+    pub fn goto_declaration(
+        &self,
+        position: FilePosition,
+    ) -> Cancelable<Option<RangeInfo<Vec<NavigationTarget>>>> {
+        self.with_db(|db| goto_declaration::goto_declaration(db, position))
+    }
+
     /// Returns the impls from the symbol at `position`.
     pub fn goto_implementation(
         &self,
@@ -452,14 +500,30 @@ impl Analysis {
         self.with_db(|db| db.crate_graph().crate_root(crate_id))
     }
 
+    /// Returns the trait impls in the file which don't implement all required
+    /// items of their trait.
+    pub fn missing_trait_members(&self, file_id: FileId) -> Cancelable<Vec<MissingTraitMembers>> {
+        self.with_db(|db| impls::missing_trait_members(db, file_id))
+    }
+
     /// Returns the set of possible targets to run for the current file.
     pub fn runnables(&self, file_id: FileId) -> Cancelable<Vec<Runnable>> {
         self.with_db(|db| runnables::runnables(db, file_id))
     }
 
+    /// Returns the innermost runnable containing the position.
+    pub fn runnable_at(&self, position: FilePosition) -> Cancelable<Option<Runnable>> {
+        self.with_db(|db| runnables::runnable_at(db, position))
+    }
+
     /// Computes syntax highlighting for the given file.
     pub fn highlight(&self, file_id: FileId) -> Cancelable<Vec<HighlightedRange>> {
-        self.with_db(|db| syntax_highlighting::highlight(db, file_id))
+        self.with_db(|db| syntax_highlighting::highlight(db, file_id, None))
+    }
+
+    /// Computes syntax highlighting for the given file range.
+    pub fn highlight_range(&self, frange: FileRange) -> Cancelable<Vec<HighlightedRange>> {
+        self.with_db(|db| syntax_highlighting::highlight(db, frange.file_id, Some(frange.range)))
     }
 
     /// Computes syntax highlighting for the given file.
@@ -472,6 +536,29 @@ impl Analysis {
         self.with_db(|db| completion::completions(db, position).map(Into::into))
     }
 
+    /// Computes completions at the given position, leaving out documentation,
+    /// signatures and imports to add. Use `resolve_completion` to compute them
+    /// for the item the user is looking at.
+    pub fn unresolved_completions(
+        &self,
+        position: FilePosition,
+    ) -> Cancelable<Option<Vec<CompletionItem>>> {
+        self.with_db(|db| completion::unresolved_completions(db, position).map(Into::into))
+    }
+
+    /// Computes what `unresolved_completions` left out of an item, from the
+    /// item's `import_to_add` and `details_source`.
+    pub fn resolve_completion(
+        &self,
+        position: FilePosition,
+        import_to_add: Option<&[SmolStr]>,
+        details_source: Option<FileRange>,
+    ) -> Cancelable<ResolvedCompletion> {
+        self.with_db(|db| {
+            completion::resolve_completion(db, position, import_to_add, details_source)
+        })
+    }
+
     /// Computes assists (aka code actions aka intentions) for the given
     /// position.
     pub fn assists(&self, frange: FileRange) -> Cancelable<Vec<Assist>> {
@@ -489,12 +576,12 @@ impl Analysis {
     }
 
     /// Returns the edit required to rename reference at the position to the new
-    /// name.
+    /// name, or an error if the rename can't be performed safely.
     pub fn rename(
         &self,
         position: FilePosition,
         new_name: &str,
-    ) -> Cancelable<Option<RangeInfo<SourceChange>>> {
+    ) -> Cancelable<Result<Option<RangeInfo<SourceChange>>, RenameError>> {
         self.with_db(|db| references::rename(db, position, new_name))
     }
 
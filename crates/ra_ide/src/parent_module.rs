@@ -93,8 +93,11 @@ mod tests {
         let crate_id = crate_graph.add_crate_root(
             root_file,
             Edition2018,
+            None,
             CfgOptions::default(),
             Env::default(),
+            Default::default(),
+            Vec::new(),
         );
         let mut change = AnalysisChange::new();
         change.set_crate_graph(crate_graph);
@@ -0,0 +1,204 @@
+//! Maps a selection to the items which should be reformatted.
+//!
+//! rustfmt can only format complete items, so for range formatting we widen
+//! the selection to cover every `ast::ModuleItem` it touches. Items nested in
+//! inline modules are formatted as if they were at the top level; the result
+//! is then reindented to fit back into the module.
+//!
+//! When rustfmt fails to parse the items, they are only reindented with
+//! `ra_fmt`.
+
+use ra_fmt::{leading_indent, reformat};
+use ra_syntax::{
+    algo::find_covering_element,
+    ast::{self, edit::IndentLevel, AstNode},
+    Direction, NodeOrToken, SmolStr, SourceFile, SyntaxKind, TextRange, TextUnit,
+};
+
+/// A slice of the file which can be formatted on its own.
+#[derive(Debug)]
+pub struct FormattingRange {
+    /// Range of the items to format, from the start of the first one to the
+    /// end of the last one.
+    pub range: TextRange,
+    indent: SmolStr,
+    reindented: String,
+}
+
+impl FormattingRange {
+    /// Reindents the formatter's output for the items, so that it can replace
+    /// `self.range`. Blank lines and lines inside string literals are left
+    /// as they are.
+    pub fn reindent(&self, formatted: &str) -> String {
+        let formatted = formatted.trim_end();
+        let literals: Vec<TextRange> = SourceFile::parse(formatted)
+            .tree()
+            .syntax()
+            .descendants_with_tokens()
+            .filter_map(|it| it.into_token())
+            .filter(|it| match it.kind() {
+                SyntaxKind::STRING
+                | SyntaxKind::RAW_STRING
+                | SyntaxKind::BYTE_STRING
+                | SyntaxKind::RAW_BYTE_STRING => true,
+                _ => false,
+            })
+            .map(|it| it.text_range())
+            .collect();
+
+        let mut buf = String::new();
+        let mut offset = TextUnit::from(0);
+        for (idx, line) in formatted.split('\n').enumerate() {
+            if idx > 0 {
+                buf.push('\n');
+                let in_literal = literals.iter().any(|it| it.start() < offset && offset < it.end());
+                if !line.is_empty() && !in_literal {
+                    buf.push_str(&self.indent);
+                }
+            }
+            buf.push_str(line);
+            offset += TextUnit::of_str(line) + TextUnit::of_char('\n');
+        }
+        buf
+    }
+
+    /// The items with only their indentation fixed, for when rustfmt can't
+    /// parse them.
+    pub fn fallback(&self) -> &str {
+        &self.reindented
+    }
+}
+
+pub(crate) fn formatting_range(file: &SourceFile, range: TextRange) -> Option<FormattingRange> {
+    let covering = find_covering_element(file.syntax(), range);
+    let container = covering.ancestors().find(|it| match it.kind() {
+        SyntaxKind::SOURCE_FILE => true,
+        SyntaxKind::ITEM_LIST => it.parent().map_or(false, |p| p.kind() == SyntaxKind::MODULE),
+        _ => false,
+    })?;
+
+    let mut items = container
+        .children()
+        .filter_map(ast::ModuleItem::cast)
+        .filter(|it| range.intersection(&it.syntax().text_range()).is_some());
+    let first = items.next()?;
+    let last = items.last().unwrap_or_else(|| first.clone());
+
+    let range =
+        TextRange::from_to(first.syntax().text_range().start(), last.syntax().text_range().end());
+    let indent = leading_indent(first.syntax()).unwrap_or_else(|| "".into());
+    let level = IndentLevel::from_node(first.syntax());
+    let reindented = first
+        .syntax()
+        .siblings_with_tokens(Direction::Next)
+        .take_while(|it| it.text_range().end() <= range.end())
+        .map(|it| match it {
+            NodeOrToken::Node(node) => match ast::ModuleItem::cast(node.clone()) {
+                Some(item) => reformat(item, level).syntax().to_string(),
+                None => node.to_string(),
+            },
+            NodeOrToken::Token(token) => token.text().to_string(),
+        })
+        .collect();
+    Some(FormattingRange { range, indent, reindented })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::extract_range;
+
+    use super::*;
+
+    fn check(before: &str, expected_text: &str, expected_indent: &str) {
+        let (range, before) = extract_range(before);
+        let file = SourceFile::parse(&before).tree();
+        let res = formatting_range(&file, range).unwrap();
+        assert_eq!(&before[res.range], expected_text);
+        assert_eq!(res.indent.as_str(), expected_indent);
+    }
+
+    #[test]
+    fn formats_selected_items() {
+        check(
+            r#"
+use std::fmt;
+
+fn <|>foo() {
+}
+
+struct  S<|> ;
+
+fn bar() {}
+"#,
+            "fn foo() {\n}\n\nstruct  S ;",
+            "",
+        );
+    }
+
+    #[test]
+    fn formats_whole_impl_for_method_selection() {
+        check(
+            r#"
+struct S;
+impl S {
+    fn foo(&self) { <|>1 + 1<|> }
+}
+"#,
+            "impl S {\n    fn foo(&self) { 1 + 1 }\n}",
+            "",
+        );
+    }
+
+    #[test]
+    fn formats_items_in_inline_module() {
+        check(
+            r#"
+mod m {
+    fn foo() {}
+    fn bar( )<|> {}<|>
+}
+"#,
+            "fn bar( ) {}",
+            "    ",
+        );
+    }
+
+    #[test]
+    fn reindents_formatted_text() {
+        let (range, before) = extract_range("mod m {\n    <|>fn foo( ) {}<|>\n}\n");
+        let file = SourceFile::parse(&before).tree();
+        let res = formatting_range(&file, range).unwrap();
+        assert_eq!(res.reindent("fn foo() {\n    1\n}\n"), "fn foo() {\n        1\n    }");
+    }
+
+    #[test]
+    fn reindent_leaves_blank_lines_empty() {
+        let (range, before) = extract_range("mod m {\n    <|>fn foo( ) {}<|>\n}\n");
+        let file = SourceFile::parse(&before).tree();
+        let res = formatting_range(&file, range).unwrap();
+        assert_eq!(
+            res.reindent("fn foo() {\n    1;\n\n    2\n}\n"),
+            "fn foo() {\n        1;\n\n        2\n    }"
+        );
+    }
+
+    #[test]
+    fn reindent_leaves_string_literals_alone() {
+        let (range, before) = extract_range("mod m {\n    <|>fn foo( ) {}<|>\n}\n");
+        let file = SourceFile::parse(&before).tree();
+        let res = formatting_range(&file, range).unwrap();
+        assert_eq!(
+            res.reindent("fn foo() {\n    let s = \"a\n\nb\";\n    let r = r#\"\nc\"#;\n}\n"),
+            "fn foo() {\n        let s = \"a\n\nb\";\n        let r = r#\"\nc\"#;\n    }"
+        );
+    }
+
+    #[test]
+    fn fallback_fixes_indentation_only() {
+        let (range, before) =
+            extract_range("mod m {\n    <|>fn foo( ) {\n1\n}\n\n    struct  S<|> ;\n}\n");
+        let file = SourceFile::parse(&before).tree();
+        let res = formatting_range(&file, range).unwrap();
+        assert_eq!(res.fallback(), "fn foo( ) {\n        1\n    }\n\n    struct  S ;");
+    }
+}
@@ -30,7 +30,7 @@ use crate::{
 pub(crate) use self::{
     classify::{classify_name, classify_name_ref},
     name_definition::{NameDefinition, NameKind},
-    rename::rename,
+    rename::{rename, RenameError},
 };
 
 pub use self::search_scope::SearchScope;
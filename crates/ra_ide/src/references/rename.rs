@@ -1,48 +1,90 @@
-//! FIXME: write short doc here
-
-use hir::ModuleSource;
+//! Renaming of items, locals, modules, lifetimes and labels.
+//!
+//! Most renames are driven by the reference search: every reference to the
+//! definition under the cursor is replaced by the new name. On top of that:
+//!
+//! * renaming a module also moves its file, together with the files of all of
+//!   its child modules;
+//! * lifetimes and labels are renamed syntactically within the item (or the
+//!   function body) that declares them;
+//! * renaming `self` turns the self parameter into an ordinary one.
+//!
+//! References produced by macro calls are renamed inside the call when the
+//! token can be mapped back to the call site. Otherwise the rename is refused.
+
+use std::{error::Error, fmt};
+
+use hir::{HasSource, InFile, ModuleDef, ModuleSource, Origin, SourceAnalyzer};
 use ra_db::{RelativePath, RelativePathBuf, SourceDatabase, SourceDatabaseExt};
-use ra_syntax::{algo::find_node_at_offset, ast, tokenize, AstNode, SyntaxKind, SyntaxNode};
+use ra_syntax::{
+    ast::{self, TypeAscriptionOwner},
+    tokenize, AstNode, SyntaxKind, SyntaxNode, SyntaxToken, TextUnit, T,
+};
 use ra_text_edit::TextEdit;
+use rustc_hash::FxHashSet;
 
 use crate::{
     db::RootDatabase, FileId, FilePosition, FileSystemEdit, RangeInfo, SourceChange,
     SourceFileEdit, TextRange,
 };
 
-use super::find_all_refs;
+use super::{
+    classify_name_ref, find_all_refs, find_name, process_definition, NameDefinition, NameKind,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenameError(String);
+
+impl fmt::Display for RenameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Error for RenameError {}
 
 pub(crate) fn rename(
     db: &RootDatabase,
     position: FilePosition,
     new_name: &str,
-) -> Option<RangeInfo<SourceChange>> {
+) -> Result<Option<RangeInfo<SourceChange>>, RenameError> {
+    let parse = db.parse(position.file_id);
+    let syntax = parse.tree().syntax().clone();
+
+    if let Some(lifetime) = find_token_at_offset(&syntax, position.offset, SyntaxKind::LIFETIME) {
+        return rename_lifetime_or_label(position.file_id, &lifetime, new_name);
+    }
+
     let tokens = tokenize(new_name);
     if tokens.len() != 1
         || (tokens[0].kind != SyntaxKind::IDENT && tokens[0].kind != SyntaxKind::UNDERSCORE)
     {
-        return None;
+        return Ok(None);
     }
 
-    let parse = db.parse(position.file_id);
-    if let Some((ast_name, ast_module)) =
-        find_name_and_module_at_offset(parse.tree().syntax(), position)
-    {
-        let range = ast_name.syntax().text_range();
-        rename_mod(db, &ast_name, &ast_module, position, new_name)
-            .map(|info| RangeInfo::new(range, info))
-    } else {
-        rename_reference(db, position, new_name)
+    if let Some(self_token) = find_token_at_offset(&syntax, position.offset, T![self]) {
+        if let Some(self_param) = self_param_for_token(&self_token) {
+            return Ok(rename_self_to_param(position.file_id, &self_token, &self_param, new_name));
+        }
+    }
+
+    let RangeInfo { range, info: (name, def) } = match find_name(db, &syntax, position) {
+        Some(it) => it,
+        None => return Ok(None),
+    };
+    match def.kind {
+        NameKind::Def(ModuleDef::Module(module)) => rename_mod(db, module, def, name, new_name)
+            .map(|it| it.map(|info| RangeInfo::new(range, info))),
+        _ => rename_reference(db, position, &def, &name, new_name),
     }
 }
 
-fn find_name_and_module_at_offset(
+fn find_token_at_offset(
     syntax: &SyntaxNode,
-    position: FilePosition,
-) -> Option<(ast::Name, ast::Module)> {
-    let ast_name = find_node_at_offset::<ast::Name>(syntax, position.offset)?;
-    let ast_module = ast::Module::cast(ast_name.syntax().parent()?)?;
-    Some((ast_name, ast_module))
+    offset: TextUnit,
+    kind: SyntaxKind,
+) -> Option<SyntaxToken> {
+    syntax.token_at_offset(offset).find(|token| token.kind() == kind)
 }
 
 fn source_edit_from_file_id_range(
@@ -55,60 +97,108 @@ fn source_edit_from_file_id_range(
 
 fn rename_mod(
     db: &RootDatabase,
-    ast_name: &ast::Name,
-    ast_module: &ast::Module,
-    position: FilePosition,
+    module: hir::Module,
+    def: NameDefinition,
+    name: String,
+    new_name: &str,
+) -> Result<Option<SourceChange>, RenameError> {
+    let declaration = match module.declaration_source(db) {
+        Some(it) => it,
+        None => return Ok(None),
+    };
+    let decl_name = match declaration.value.name() {
+        Some(it) => it,
+        None => return Ok(None),
+    };
+
+    let mut source_file_edits = vec![source_edit_from_file_id_range(
+        declaration.file_id.original_file(db),
+        decl_name.syntax().text_range(),
+        new_name,
+    )];
+    source_file_edits.extend(macro_call_edits(db, &def, &name, new_name)?);
+    let search_scope = def.search_scope(db);
+    source_file_edits.extend(process_definition(db, def, name, search_scope).into_iter().map(
+        |reference| {
+            source_edit_from_file_id_range(
+                reference.file_range.file_id,
+                reference.file_range.range,
+                new_name,
+            )
+        },
+    ));
+
+    let file_system_edits = move_module_files(db, module, new_name);
+    Ok(Some(SourceChange::from_edits("rename", source_file_edits, file_system_edits)))
+}
+
+/// Moves the file of `module`, if it has one, and the files of all modules
+/// nested below it into the directory named after `new_name`.
+fn move_module_files(
+    db: &RootDatabase,
+    module: hir::Module,
     new_name: &str,
-) -> Option<SourceChange> {
-    let mut source_file_edits = Vec::new();
+) -> Vec<FileSystemEdit> {
     let mut file_system_edits = Vec::new();
-    let module_src = hir::InFile { file_id: position.file_id.into(), value: ast_module.clone() };
-    if let Some(module) = hir::Module::from_declaration(db, module_src) {
-        let src = module.definition_source(db);
-        let file_id = src.file_id.original_file(db);
-        match src.value {
-            ModuleSource::SourceFile(..) => {
-                let mod_path: RelativePathBuf = db.file_relative_path(file_id);
-                // mod is defined in path/to/dir/mod.rs
-                let dst_path = if mod_path.file_stem() == Some("mod") {
-                    mod_path
-                        .parent()
-                        .and_then(|p| p.parent())
-                        .or_else(|| Some(RelativePath::new("")))
-                        .map(|p| p.join(new_name).join("mod.rs"))
-                } else {
-                    Some(mod_path.with_file_name(new_name).with_extension("rs"))
-                };
-                if let Some(path) = dst_path {
-                    let move_file = FileSystemEdit::MoveFile {
-                        src: file_id,
-                        dst_source_root: db.file_source_root(position.file_id),
-                        dst_path: path,
-                    };
-                    file_system_edits.push(move_file);
-                }
-            }
-            ModuleSource::Module(..) => {}
+    let src = module.definition_source(db);
+    let file_id = match src.value {
+        ModuleSource::SourceFile(..) => src.file_id.original_file(db),
+        ModuleSource::Module(..) => return file_system_edits,
+    };
+    let source_root = db.file_source_root(file_id);
+    let mod_path: RelativePathBuf = db.file_relative_path(file_id);
+
+    // mod is defined in path/to/dir/mod.rs, its children live in path/to/dir
+    // mod is defined in path/to/dir.rs, its children live in path/to/dir
+    let (dst_path, old_dir, new_dir) = if mod_path.file_stem() == Some("mod") {
+        let old_dir = mod_path.parent().unwrap_or_else(|| RelativePath::new("")).to_owned();
+        let new_dir = old_dir.parent().unwrap_or_else(|| RelativePath::new("")).join(new_name);
+        (new_dir.join("mod.rs"), old_dir, new_dir)
+    } else {
+        let new_dir = mod_path.with_file_name(new_name);
+        let old_dir = mod_path.with_file_name(mod_path.file_stem().unwrap_or_default());
+        (new_dir.with_extension("rs"), old_dir, new_dir)
+    };
+    file_system_edits.push(FileSystemEdit::MoveFile {
+        src: file_id,
+        dst_source_root: source_root,
+        dst_path,
+    });
+
+    let old_prefix = format!("{}/", old_dir);
+    let mut stack = module.children(db).collect::<Vec<_>>();
+    while let Some(child) = stack.pop() {
+        stack.extend(child.children(db));
+        let src = child.definition_source(db);
+        if let ModuleSource::Module(..) = src.value {
+            continue;
+        }
+        let child_file = src.file_id.original_file(db);
+        let child_path = db.file_relative_path(child_file);
+        if child_path.as_str().starts_with(&old_prefix) {
+            file_system_edits.push(FileSystemEdit::MoveFile {
+                src: child_file,
+                dst_source_root: db.file_source_root(child_file),
+                dst_path: new_dir.join(&child_path.as_str()[old_prefix.len()..]),
+            });
         }
     }
-
-    let edit = SourceFileEdit {
-        file_id: position.file_id,
-        edit: TextEdit::replace(ast_name.syntax().text_range(), new_name.into()),
-    };
-    source_file_edits.push(edit);
-
-    Some(SourceChange::from_edits("rename", source_file_edits, file_system_edits))
+    file_system_edits
 }
 
 fn rename_reference(
     db: &RootDatabase,
     position: FilePosition,
+    def: &NameDefinition,
+    name: &str,
     new_name: &str,
-) -> Option<RangeInfo<SourceChange>> {
-    let RangeInfo { range, info: refs } = find_all_refs(db, position, None)?;
+) -> Result<Option<RangeInfo<SourceChange>>, RenameError> {
+    let RangeInfo { range, info: refs } = match find_all_refs(db, position, None) {
+        Some(it) => it,
+        None => return Ok(None),
+    };
 
-    let edit = refs
+    let mut edit = refs
         .into_iter()
         .map(|reference| {
             source_edit_from_file_id_range(
@@ -120,10 +210,246 @@ fn rename_reference(
         .collect::<Vec<_>>();
 
     if edit.is_empty() {
+        return Ok(None);
+    }
+    edit.extend(macro_call_edits(db, def, name, new_name)?);
+
+    Ok(Some(RangeInfo::new(range, SourceChange::source_file_edits("rename", edit))))
+}
+
+/// Finds references to `def` that only exist after macro expansion and maps
+/// them back into the arguments of the macro calls.
+fn macro_call_edits(
+    db: &RootDatabase,
+    def: &NameDefinition,
+    name: &str,
+    new_name: &str,
+) -> Result<Vec<SourceFileEdit>, RenameError> {
+    let mut edits = Vec::new();
+    for (file_id, search_range) in def.search_scope(db) {
+        if !db.file_text(file_id).contains(name) {
+            continue;
+        }
+        let parse = db.parse(file_id);
+        let mut ranges = FxHashSet::default();
+        for macro_call in parse.tree().syntax().descendants().filter_map(ast::MacroCall::cast) {
+            if let Some(search_range) = search_range {
+                if !macro_call.syntax().text_range().is_subrange(&search_range) {
+                    continue;
+                }
+            }
+            let src = InFile::new(file_id.into(), &macro_call);
+            let analyzer = SourceAnalyzer::new(db, src.map(|it| it.syntax()), None);
+            if !expansion_may_mention(db, &analyzer, src, name) {
+                continue;
+            }
+            let expansion = match analyzer.expand(db, src) {
+                Some(it) => it,
+                None => continue,
+            };
+            let expanded_file = expansion.file_id();
+            let expanded = match db.parse_or_expand(expanded_file) {
+                Some(it) => it,
+                None => continue,
+            };
+            // FIXME: handle references produced by nested macro calls
+            for name_ref in expanded.descendants().filter_map(ast::NameRef::cast) {
+                if name_ref.text() != name {
+                    continue;
+                }
+                match classify_name_ref(db, InFile::new(expanded_file, &name_ref)) {
+                    Some(d) if d == *def => (),
+                    _ => continue,
+                }
+                let token = match name_ref.syntax().first_token() {
+                    Some(it) => it,
+                    None => continue,
+                };
+                let mapped = expanded_file
+                    .expansion_info(db)
+                    .and_then(|info| info.map_token_up(InFile::new(expanded_file, &token)));
+                match mapped {
+                    Some((token, Origin::Call)) if token.file_id == file_id.into() => {
+                        ranges.insert(token.value.text_range());
+                    }
+                    _ => {
+                        return Err(RenameError(format!(
+                            "Cannot rename `{}`: a reference is inside a macro expansion that cannot be mapped back to source",
+                            name
+                        )))
+                    }
+                }
+            }
+        }
+        let mut ranges = ranges.into_iter().collect::<Vec<_>>();
+        ranges.sort_by_key(|range| range.start());
+        edits.extend(
+            ranges
+                .into_iter()
+                .map(|range| source_edit_from_file_id_range(file_id, range, new_name)),
+        );
+    }
+    Ok(edits)
+}
+
+/// A macro call can only expand to a reference named `name` if the name is
+/// among its arguments or in the body of the macro, so other calls don't need
+/// to be expanded.
+fn expansion_may_mention(
+    db: &RootDatabase,
+    analyzer: &SourceAnalyzer,
+    macro_call: InFile<&ast::MacroCall>,
+    name: &str,
+) -> bool {
+    let mentions = |token_tree: Option<ast::TokenTree>| {
+        token_tree.map_or(false, |it| {
+            it.syntax()
+                .descendants_with_tokens()
+                .filter_map(|it| it.into_token())
+                .any(|it| it.kind() == SyntaxKind::IDENT && it.text() == name)
+        })
+    };
+    if mentions(macro_call.value.token_tree()) {
+        return true;
+    }
+    match analyzer.resolve_macro_call(db, macro_call) {
+        Some(def) => mentions(def.source(db).value.token_tree()),
+        None => false,
+    }
+}
+
+fn rename_lifetime_or_label(
+    file_id: FileId,
+    token: &SyntaxToken,
+    new_name: &str,
+) -> Result<Option<RangeInfo<SourceChange>>, RenameError> {
+    let new_name =
+        if new_name.starts_with('\'') { new_name.to_string() } else { format!("'{}", new_name) };
+    let tokens = tokenize(&new_name);
+    if tokens.len() != 1 || tokens[0].kind != SyntaxKind::LIFETIME {
+        return Ok(None);
+    }
+    for reserved in &["'static", "'_"] {
+        if token.text() == reserved || new_name == *reserved {
+            return Err(RenameError(format!("Cannot rename `{}` to `{}`", token.text(), new_name)));
+        }
+    }
+
+    let is_label = is_label_token(token);
+    let scope = if is_label { label_scope(token) } else { lifetime_scope(token) };
+    let scope = match scope {
+        Some(it) => it,
+        None => return Ok(None),
+    };
+
+    let edits = scope
+        .descendants_with_tokens()
+        .filter_map(|it| it.into_token())
+        .filter(|it| it.kind() == SyntaxKind::LIFETIME && it.text() == token.text())
+        .filter(|it| is_label_token(it) == is_label)
+        .filter(|it| {
+            let it_scope = if is_label { label_scope(it) } else { lifetime_scope(it) };
+            it_scope.as_ref() == Some(&scope)
+        })
+        .map(|it| source_edit_from_file_id_range(file_id, it.text_range(), &new_name))
+        .collect::<Vec<_>>();
+
+    Ok(Some(RangeInfo::new(token.text_range(), SourceChange::source_file_edits("rename", edits))))
+}
+
+fn is_label_token(token: &SyntaxToken) -> bool {
+    match token.parent().kind() {
+        SyntaxKind::LABEL | SyntaxKind::BREAK_EXPR | SyntaxKind::CONTINUE_EXPR => true,
+        _ => false,
+    }
+}
+
+/// Labels can't be referred to across function or closure boundaries.
+fn label_scope(token: &SyntaxToken) -> Option<SyntaxNode> {
+    token.parent().ancestors().find(|it| match it.kind() {
+        SyntaxKind::FN_DEF | SyntaxKind::LAMBDA_EXPR => true,
+        _ => false,
+    })
+}
+
+/// The item whose generic parameters declare the lifetime.
+fn lifetime_scope(token: &SyntaxToken) -> Option<SyntaxNode> {
+    token.parent().ancestors().find(|it| {
+        it.children().filter_map(ast::TypeParamList::cast).any(|params| {
+            params
+                .lifetime_params()
+                .filter_map(|param| param.lifetime_token())
+                .any(|lifetime| lifetime.text() == token.text())
+        })
+    })
+}
+
+fn self_param_for_token(token: &SyntaxToken) -> Option<ast::SelfParam> {
+    if let Some(self_param) = ast::SelfParam::cast(token.parent()) {
+        return Some(self_param);
+    }
+    let path = self_path_expr(token)?;
+    let fn_def = path.syntax().ancestors().find_map(ast::FnDef::cast)?;
+    fn_def.param_list()?.self_param()
+}
+
+/// Returns the path expression if `token` is a `self` used as a value.
+fn self_path_expr(token: &SyntaxToken) -> Option<ast::PathExpr> {
+    let segment = ast::PathSegment::cast(token.parent())?;
+    let path = ast::Path::cast(segment.syntax().parent()?)?;
+    if path.qualifier().is_some() {
         return None;
     }
+    ast::PathExpr::cast(path.syntax().parent()?)
+}
+
+fn rename_self_to_param(
+    file_id: FileId,
+    token: &SyntaxToken,
+    self_param: &ast::SelfParam,
+    new_name: &str,
+) -> Option<RangeInfo<SourceChange>> {
+    let param_start = self_param.syntax().text_range().start();
+    let self_range = self_param.self_kw_token().text_range();
+    let self_start = (self_range.start() - param_start).to_usize();
+    let self_end = (self_range.end() - param_start).to_usize();
+    let text = self_param.syntax().text().to_string();
+    let param_text = if self_param.ascribed_type().is_some() {
+        // `mut self: Box<Self>` -> `mut name: Box<Self>`
+        format!("{}{}{}", &text[..self_start], new_name, &text[self_end..])
+    } else {
+        let prefix = &text[..self_start];
+        match self_param.kind() {
+            // `mut self` -> `mut name: Self`
+            ast::SelfParamKind::Owned => format!("{}{}: Self", prefix, new_name),
+            // `&'a mut self` -> `name: &'a mut Self`
+            ast::SelfParamKind::Ref | ast::SelfParamKind::MutRef => {
+                format!("{}: {}Self", new_name, prefix)
+            }
+        }
+    };
+
+    let mut edits = vec![SourceFileEdit {
+        file_id,
+        edit: TextEdit::replace(self_param.syntax().text_range(), param_text),
+    }];
+
+    let fn_def = self_param.syntax().ancestors().find_map(ast::FnDef::cast)?;
+    let body = fn_def.body()?;
+    let usages = body
+        .syntax()
+        .descendants_with_tokens()
+        .filter_map(|it| it.into_token())
+        .filter(|it| it.kind() == T![self])
+        .filter(|it| self_path_expr(it).is_some())
+        .filter(|it| {
+            // `self` in nested functions refers to their own receiver
+            it.parent().ancestors().find_map(ast::FnDef::cast).as_ref() == Some(&fn_def)
+        })
+        .map(|it| source_edit_from_file_id_range(file_id, it.text_range(), new_name));
+    edits.extend(usages);
 
-    Some(RangeInfo::new(range, SourceChange::source_file_edits("rename", edit)))
+    Some(RangeInfo::new(token.text_range(), SourceChange::source_file_edits("rename", edits)))
 }
 
 #[cfg(test)]
@@ -175,7 +501,7 @@ mod tests {
     }",
         );
         let new_name = "invalid!";
-        let source_change = analysis.rename(position, new_name).unwrap();
+        let source_change = analysis.rename(position, new_name).unwrap().unwrap();
         assert!(source_change.is_none());
     }
 
@@ -270,7 +596,7 @@ mod tests {
             ",
         );
         let new_name = "foo2";
-        let source_change = analysis.rename(position, new_name).unwrap();
+        let source_change = analysis.rename(position, new_name).unwrap().unwrap();
         assert_debug_snapshot!(&source_change,
 @r###"
         Some(
@@ -322,7 +648,7 @@ mod tests {
             ",
         );
         let new_name = "foo2";
-        let source_change = analysis.rename(position, new_name).unwrap();
+        let source_change = analysis.rename(position, new_name).unwrap().unwrap();
         assert_debug_snapshot!(&source_change,
         @r###"
         Some(
@@ -364,9 +690,247 @@ mod tests {
                );
     }
 
+    #[test]
+    fn test_rename_mod_with_references_and_children() {
+        let (analysis, position) = analysis_and_position(
+            "
+            //- /lib.rs
+            mod fo<|>o;
+            fn main() { foo::bar(); }
+
+            //- /foo.rs
+            mod child;
+            pub fn bar() {}
+
+            //- /foo/child.rs
+            // empty
+            ",
+        );
+        let new_name = "foo2";
+        let source_change = analysis.rename(position, new_name).unwrap().unwrap();
+        assert_debug_snapshot!(&source_change,
+        @r###"
+        Some(
+            RangeInfo {
+                range: [4; 7),
+                info: SourceChange {
+                    label: "rename",
+                    source_file_edits: [
+                        SourceFileEdit {
+                            file_id: FileId(
+                                1,
+                            ),
+                            edit: TextEdit {
+                                atoms: [
+                                    AtomTextEdit {
+                                        delete: [4; 7),
+                                        insert: "foo2",
+                                    },
+                                ],
+                            },
+                        },
+                        SourceFileEdit {
+                            file_id: FileId(
+                                1,
+                            ),
+                            edit: TextEdit {
+                                atoms: [
+                                    AtomTextEdit {
+                                        delete: [21; 24),
+                                        insert: "foo2",
+                                    },
+                                ],
+                            },
+                        },
+                    ],
+                    file_system_edits: [
+                        MoveFile {
+                            src: FileId(
+                                2,
+                            ),
+                            dst_source_root: SourceRootId(
+                                0,
+                            ),
+                            dst_path: "foo2.rs",
+                        },
+                        MoveFile {
+                            src: FileId(
+                                3,
+                            ),
+                            dst_source_root: SourceRootId(
+                                0,
+                            ),
+                            dst_path: "foo2/child.rs",
+                        },
+                    ],
+                    cursor_position: None,
+                },
+            },
+        )
+        "###);
+    }
+
+    #[test]
+    fn test_rename_lifetime() {
+        test_rename(
+            r#"
+    struct Foo<'a> { s: &'a str }
+    fn foo<'a<|>>(x: &'a str) -> Foo<'a> { Foo { s: x } }"#,
+            "'b",
+            r#"
+    struct Foo<'a> { s: &'a str }
+    fn foo<'b>(x: &'b str) -> Foo<'b> { Foo { s: x } }"#,
+        );
+    }
+
+    #[test]
+    fn test_rename_lifetime_without_quote() {
+        test_rename(
+            r#"
+    impl<'a> Foo<'a<|>> {
+        fn get(&self) -> &'a str { self.s }
+    }"#,
+            "b",
+            r#"
+    impl<'b> Foo<'b> {
+        fn get(&self) -> &'b str { self.s }
+    }"#,
+        );
+    }
+
+    #[test]
+    fn test_rename_static_lifetime_is_refused() {
+        let (analysis, position) = single_file_with_position("fn foo(x: &'stat<|>ic str) {}");
+        assert!(analysis.rename(position, "'a").unwrap().is_err());
+    }
+
+    #[test]
+    fn test_rename_label() {
+        test_rename(
+            r#"
+    fn main() {
+        'outer<|>: loop {
+            let f = || 'outer: loop { break 'outer; };
+            loop {
+                break 'outer;
+            }
+            continue 'outer;
+        }
+    }"#,
+            "'done",
+            r#"
+    fn main() {
+        'done: loop {
+            let f = || 'outer: loop { break 'outer; };
+            loop {
+                break 'done;
+            }
+            continue 'done;
+        }
+    }"#,
+        );
+    }
+
+    #[test]
+    fn test_rename_self_ref_to_param() {
+        test_rename(
+            r#"
+    struct Foo { i: i32 }
+    impl Foo {
+        fn f(&self<|>) -> i32 {
+            fn inner(&self) {}
+            self.i
+        }
+    }"#,
+            "foo",
+            r#"
+    struct Foo { i: i32 }
+    impl Foo {
+        fn f(foo: &Self) -> i32 {
+            fn inner(&self) {}
+            foo.i
+        }
+    }"#,
+        );
+    }
+
+    #[test]
+    fn test_rename_self_usage_to_param() {
+        test_rename(
+            r#"
+    struct Foo { i: i32 }
+    impl Foo {
+        fn f(mut self) -> i32 {
+            self<|>.i += 1;
+            self.i
+        }
+    }"#,
+            "foo",
+            r#"
+    struct Foo { i: i32 }
+    impl Foo {
+        fn f(mut foo: Self) -> i32 {
+            foo.i += 1;
+            foo.i
+        }
+    }"#,
+        );
+    }
+
+    #[test]
+    fn test_rename_self_mut_ref_to_param() {
+        test_rename(
+            r#"
+    struct Foo { i: i32 }
+    impl Foo {
+        fn f(&mut self<|>) { self.i = 0; }
+    }"#,
+            "foo",
+            r#"
+    struct Foo { i: i32 }
+    impl Foo {
+        fn f(foo: &mut Self) { foo.i = 0; }
+    }"#,
+        );
+    }
+
+    #[test]
+    fn test_rename_local_inside_macro_call() {
+        test_rename(
+            r#"
+    macro_rules! id { ($e:expr) => { $e } }
+    fn main() {
+        let x<|> = 1;
+        id!(x);
+    }"#,
+            "y",
+            r#"
+    macro_rules! id { ($e:expr) => { $e } }
+    fn main() {
+        let y = 1;
+        id!(y);
+    }"#,
+        );
+    }
+
+    #[test]
+    fn test_rename_reference_from_macro_definition_is_refused() {
+        let (analysis, position) = single_file_with_position(
+            "
+    fn foo<|>() {}
+    macro_rules! m { () => { foo() } }
+    fn main() { m!(); }",
+        );
+        let err = analysis.rename(position, "bar").unwrap().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Cannot rename `foo`: a reference is inside a macro expansion that cannot be mapped back to source"
+        );
+    }
+
     fn test_rename(text: &str, new_name: &str, expected: &str) {
         let (analysis, position) = single_file_with_position(text);
-        let source_change = analysis.rename(position, new_name).unwrap();
+        let source_change = analysis.rename(position, new_name).unwrap().unwrap();
         let mut text_edit_builder = TextEditBuilder::default();
         let mut file_id: Option<FileId> = None;
         if let Some(change) = source_change {
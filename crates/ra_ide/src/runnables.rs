@@ -4,11 +4,11 @@ use hir::InFile;
 use itertools::Itertools;
 use ra_db::SourceDatabase;
 use ra_syntax::{
-    ast::{self, AstNode, AttrsOwner, ModuleItemOwner, NameOwner},
+    ast::{self, AstNode, AttrsOwner, DocCommentsOwner, ModuleItemOwner, NameOwner},
     match_ast, SyntaxNode, TextRange,
 };
 
-use crate::{db::RootDatabase, FileId};
+use crate::{db::RootDatabase, FileId, FilePosition};
 
 #[derive(Debug)]
 pub struct Runnable {
@@ -21,6 +21,7 @@ pub enum RunnableKind {
     Test { name: String },
     TestMod { path: String },
     Bench { name: String },
+    DocTest { test_id: String },
     Bin,
 }
 
@@ -29,30 +30,116 @@ pub(crate) fn runnables(db: &RootDatabase, file_id: FileId) -> Vec<Runnable> {
     parse.tree().syntax().descendants().filter_map(|i| runnable(db, file_id, i)).collect()
 }
 
+/// Returns the innermost runnable which contains the position, like the test
+/// function or the test module the cursor is in.
+pub(crate) fn runnable_at(db: &RootDatabase, position: FilePosition) -> Option<Runnable> {
+    runnables(db, position.file_id)
+        .into_iter()
+        .filter(|it| it.range.contains_inclusive(position.offset))
+        .min_by_key(|it| it.range.len())
+}
+
 fn runnable(db: &RootDatabase, file_id: FileId, item: SyntaxNode) -> Option<Runnable> {
     match_ast! {
         match item {
-            ast::FnDef(it) => { runnable_fn(it) },
+            ast::FnDef(it) => { runnable_fn(db, file_id, it) },
             ast::Module(it) => { runnable_mod(db, file_id, it) },
+            ast::StructDef(it) => { runnable_doctest(db, file_id, &it, it.name()?) },
+            ast::EnumDef(it) => { runnable_doctest(db, file_id, &it, it.name()?) },
+            ast::UnionDef(it) => { runnable_doctest(db, file_id, &it, it.name()?) },
+            ast::TraitDef(it) => { runnable_doctest(db, file_id, &it, it.name()?) },
+            ast::TypeAliasDef(it) => { runnable_doctest(db, file_id, &it, it.name()?) },
+            ast::ConstDef(it) => { runnable_doctest(db, file_id, &it, it.name()?) },
+            ast::StaticDef(it) => { runnable_doctest(db, file_id, &it, it.name()?) },
             _ => { None },
         }
     }
 }
 
-fn runnable_fn(fn_def: ast::FnDef) -> Option<Runnable> {
-    let name = fn_def.name()?.text().clone();
-    let kind = if name == "main" {
+fn runnable_fn(db: &RootDatabase, file_id: FileId, fn_def: ast::FnDef) -> Option<Runnable> {
+    let name = fn_def.name()?;
+    let kind = if name.text() == "main" {
         RunnableKind::Bin
     } else if fn_def.has_atom_attr("test") {
-        RunnableKind::Test { name: name.to_string() }
+        RunnableKind::Test { name: item_path(db, file_id, fn_def.syntax(), &name)? }
     } else if fn_def.has_atom_attr("bench") {
-        RunnableKind::Bench { name: name.to_string() }
+        RunnableKind::Bench { name: item_path(db, file_id, fn_def.syntax(), &name)? }
     } else {
-        return None;
+        return runnable_doctest(db, file_id, &fn_def, name);
     };
     Some(Runnable { range: fn_def.syntax().text_range(), kind })
 }
 
+fn runnable_doctest(
+    db: &RootDatabase,
+    file_id: FileId,
+    item: &impl DocCommentsOwner,
+    name: ast::Name,
+) -> Option<Runnable> {
+    if !has_runnable_doc_test(&item.doc_comment_text()?) {
+        return None;
+    }
+
+    let mut path = module_path(db, file_id, item.syntax())?;
+
+    // Doc tests of associated items are named after the type of the impl, or
+    // after the trait
+    let container = item.syntax().parent().and_then(|it| it.parent());
+    let container_name = match container {
+        Some(container) => match_ast! {
+            match container {
+                ast::ImplBlock(it) => {
+                    match it.target_type() {
+                        Some(ast::TypeRef::PathType(it)) => {
+                            Some(it.path()?.segment()?.name_ref()?.text().to_string())
+                        }
+                        Some(it) => Some(it.syntax().text().to_string()),
+                        None => None,
+                    }
+                },
+                ast::TraitDef(it) => { Some(it.name()?.text().to_string()) },
+                _ => { None },
+            }
+        },
+        None => None,
+    };
+    if let Some(container_name) = container_name {
+        if !path.is_empty() {
+            path.push_str("::");
+        }
+        path.push_str(&container_name);
+    }
+    if !path.is_empty() {
+        path.push_str("::");
+    }
+    path.push_str(name.text());
+
+    Some(Runnable {
+        range: item.syntax().text_range(),
+        kind: RunnableKind::DocTest { test_id: path },
+    })
+}
+
+const RUSTDOC_FENCE: &str = "```";
+const RUSTDOC_CODE_BLOCK_ATTRIBUTES_RUNNABLE: &[&str] =
+    &["", "rust", "should_panic", "no_run", "compile_fail", "edition2015", "edition2018"];
+
+/// Checks whether the documentation contains a code block which rustdoc
+/// compiles as a test: one without a language or with only rust attributes,
+/// which isn't `ignore`d.
+fn has_runnable_doc_test(doc: &str) -> bool {
+    doc.lines()
+        .map(|line| line.trim_start())
+        .filter(|line| line.starts_with(RUSTDOC_FENCE))
+        // Every other fence closes a block
+        .step_by(2)
+        .any(|fence| {
+            fence[RUSTDOC_FENCE.len()..]
+                .split(',')
+                .all(|attr| RUSTDOC_CODE_BLOCK_ATTRIBUTES_RUNNABLE.contains(&attr.trim()))
+        })
+}
+
 fn runnable_mod(db: &RootDatabase, file_id: FileId, module: ast::Module) -> Option<Runnable> {
     let has_test_function = module
         .item_list()?
@@ -66,18 +153,37 @@ fn runnable_mod(db: &RootDatabase, file_id: FileId, module: ast::Module) -> Opti
         return None;
     }
     let range = module.syntax().text_range();
-    let src = hir::ModuleSource::from_child_node(db, InFile::new(file_id.into(), &module.syntax()));
+    let path = module_path(db, file_id, module.syntax())?;
+    Some(Runnable { range, kind: RunnableKind::TestMod { path } })
+}
+
+/// The path of the module containing the node, relative to the crate root.
+fn module_path(db: &RootDatabase, file_id: FileId, node: &SyntaxNode) -> Option<String> {
+    let src = hir::ModuleSource::from_child_node(db, InFile::new(file_id.into(), node));
     let module = hir::Module::from_definition(db, InFile::new(file_id.into(), src))?;
+    Some(module.path_to_root(db).into_iter().rev().filter_map(|it| it.name(db)).join("::"))
+}
 
-    let path = module.path_to_root(db).into_iter().rev().filter_map(|it| it.name(db)).join("::");
-    Some(Runnable { range, kind: RunnableKind::TestMod { path } })
+/// The path of an item as the test harness names it, e.g. `tests::test_foo`.
+fn item_path(
+    db: &RootDatabase,
+    file_id: FileId,
+    node: &SyntaxNode,
+    name: &ast::Name,
+) -> Option<String> {
+    let mut path = module_path(db, file_id, node)?;
+    if !path.is_empty() {
+        path.push_str("::");
+    }
+    path.push_str(name.text());
+    Some(path)
 }
 
 #[cfg(test)]
 mod tests {
     use insta::assert_debug_snapshot;
 
-    use crate::mock_analysis::analysis_and_position;
+    use crate::{mock_analysis::analysis_and_position, RunnableKind};
 
     #[test]
     fn test_runnables() {
@@ -145,7 +251,7 @@ mod tests {
             Runnable {
                 range: [28; 57),
                 kind: Test {
-                    name: "test_foo1",
+                    name: "test_mod::test_foo1",
                 },
             },
         ]
@@ -180,7 +286,7 @@ mod tests {
             Runnable {
                 range: [46; 79),
                 kind: Test {
-                    name: "test_foo1",
+                    name: "foo::test_mod::test_foo1",
                 },
             },
         ]
@@ -217,7 +323,7 @@ mod tests {
             Runnable {
                 range: [68; 105),
                 kind: Test {
-                    name: "test_foo1",
+                    name: "foo::bar::test_mod::test_foo1",
                 },
             },
         ]
@@ -225,6 +331,106 @@ mod tests {
                 );
     }
 
+    #[test]
+    fn test_runnables_doc_test() {
+        let (analysis, pos) = analysis_and_position(
+            r#"
+        //- /lib.rs
+        <|> //empty
+        /// ```
+        /// let x = 5;
+        /// ```
+        fn foo() {}
+
+        /// ```text
+        /// not rust
+        /// ```
+        fn bar() {}
+
+        mod baz {
+            struct Baz;
+            impl Baz {
+                /// ```rust,should_panic
+                /// panic!();
+                /// ```
+                fn baz() {}
+            }
+        }
+        "#,
+        );
+        let runnables = analysis.runnables(pos.file_id).unwrap();
+        assert_debug_snapshot!(&runnables,
+        @r###"
+        [
+            Runnable {
+                range: [1; 51),
+                kind: DocTest {
+                    test_id: "foo",
+                },
+            },
+            Runnable {
+                range: [148; 230),
+                kind: DocTest {
+                    test_id: "baz::Baz::baz",
+                },
+            },
+        ]
+        "###
+                );
+    }
+
+    #[test]
+    fn test_runnables_doc_test_in_trait() {
+        let (analysis, pos) = analysis_and_position(
+            r#"
+        //- /lib.rs
+        <|> //empty
+        trait Foo {
+            /// ```
+            /// let x = 5;
+            /// ```
+            fn foo() {}
+        }
+        "#,
+        );
+        let runnables = analysis.runnables(pos.file_id).unwrap();
+        assert_debug_snapshot!(&runnables,
+        @r###"
+        [
+            Runnable {
+                range: [25; 79),
+                kind: DocTest {
+                    test_id: "Foo::foo",
+                },
+            },
+        ]
+        "###
+                );
+    }
+
+    #[test]
+    fn test_runnable_at_position() {
+        let (analysis, pos) = analysis_and_position(
+            r#"
+        //- /lib.rs
+        mod test_mod {
+            #[test]
+            fn test_foo1() {
+                <|>
+            }
+
+            #[test]
+            fn test_foo2() {}
+        }
+        "#,
+        );
+        let runnable = analysis.runnable_at(pos).unwrap().unwrap();
+        match runnable.kind {
+            RunnableKind::Test { name } => assert_eq!(name, "test_mod::test_foo1"),
+            kind => panic!("unexpected runnable {:?}", kind),
+        }
+    }
+
     #[test]
     fn test_runnables_no_test_function_in_module() {
         let (analysis, pos) = analysis_and_position(
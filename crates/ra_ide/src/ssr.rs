@@ -0,0 +1,465 @@
+//! Structural search and replace.
+//!
+//! A query looks like `foo($a, $b) ==>> bar($b, $a)`. Both sides are parsed as
+//! expressions (or, failing that, as types), with `$name` placeholders turned
+//! into ordinary identifiers. The search pattern is then matched against the
+//! syntax trees of every file in the workspace: a placeholder matches any
+//! expression, type or name, and all other nodes and tokens must be equal,
+//! ignoring trivia. Qualified paths in the pattern also match code paths which
+//! resolve to the same item, so `foo::bar()` finds `bar()` after a
+//! `use foo::bar;`.
+
+use std::{error::Error, fmt};
+
+use hir::{InFile, Module, ModuleSource, PathResolution, SourceAnalyzer};
+use ra_db::{SourceDatabase, SourceDatabaseExt};
+use ra_syntax::{
+    ast::{self, AstNode},
+    NodeOrToken, SmolStr, SourceFile, SyntaxElement,
+    SyntaxKind::{self, *},
+    SyntaxNode,
+};
+use ra_text_edit::TextEditBuilder;
+use rustc_hash::FxHashMap;
+
+use crate::{db::RootDatabase, symbol_index::SymbolsDatabase, FileId, SourceFileEdit};
+
+const PLACEHOLDER_PREFIX: &str = "__search_pattern_";
+
+#[derive(Debug, PartialEq)]
+pub struct SsrError(String);
+
+impl fmt::Display for SsrError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Parse error: {}", self.0)
+    }
+}
+
+impl Error for SsrError {}
+
+pub(crate) fn structural_search_replace(
+    db: &RootDatabase,
+    query: &str,
+) -> Result<Vec<SourceFileEdit>, SsrError> {
+    let query = SsrQuery::parse(query)?;
+
+    let mut files = Vec::new();
+    for &root in db.local_roots().iter() {
+        let sr = db.source_root(root);
+        files.extend(sr.walk());
+    }
+
+    let mut res = Vec::new();
+    for file_id in files {
+        let matcher = Matcher::new(db, file_id);
+        let matches = matcher.find_matches(&query.pattern);
+        if matches.is_empty() {
+            continue;
+        }
+        let mut edit = TextEditBuilder::default();
+        for m in matches {
+            edit.replace(m.place.text_range(), render_template(&query.template, &m.binding));
+        }
+        res.push(SourceFileEdit { file_id, edit: edit.finish() });
+    }
+    Ok(res)
+}
+
+#[derive(Debug)]
+struct SsrQuery {
+    pattern: SyntaxNode,
+    template: SyntaxNode,
+}
+
+impl SsrQuery {
+    fn parse(query: &str) -> Result<SsrQuery, SsrError> {
+        let mut it = query.split("==>>");
+        let pattern = it.next().expect("at least empty string").trim();
+        let template =
+            it.next().ok_or_else(|| SsrError("Cannot find delimiter `==>>`".into()))?.trim();
+        if it.next().is_some() {
+            return Err(SsrError("More than one delimiter found".into()));
+        }
+
+        let (pattern, pattern_vars) = substitute_placeholders(pattern)?;
+        let (template, template_vars) = substitute_placeholders(template)?;
+        if let Some(var) = template_vars.iter().find(|it| !pattern_vars.contains(it)) {
+            return Err(SsrError(format!("Placeholder `${}` is not defined in the pattern", var)));
+        }
+
+        Ok(SsrQuery { pattern: parse_fragment(&pattern)?, template: parse_fragment(&template)? })
+    }
+}
+
+/// Replaces `$name` with an identifier the parser accepts, returning the new
+/// text and the names of the placeholders.
+fn substitute_placeholders(text: &str) -> Result<(String, Vec<SmolStr>), SsrError> {
+    let mut res = String::with_capacity(text.len());
+    let mut vars = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '$' {
+            res.push(c);
+            continue;
+        }
+        let mut name = String::new();
+        while let Some(&c) = chars.peek() {
+            if !(c.is_alphanumeric() || c == '_') {
+                break;
+            }
+            name.push(c);
+            chars.next();
+        }
+        if name.is_empty() {
+            return Err(SsrError("Placeholder name must not be empty".into()));
+        }
+        res.push_str(PLACEHOLDER_PREFIX);
+        res.push_str(&name);
+        vars.push(name.into());
+    }
+    Ok((res, vars))
+}
+
+fn parse_fragment(text: &str) -> Result<SyntaxNode, SsrError> {
+    if text.is_empty() {
+        return Err(SsrError("Pattern and template must not be empty".into()));
+    }
+    let expr = parse_in_item::<ast::ConstDef>(&format!("const C: () = {};", text))
+        .and_then(|it| it.body())
+        .map(|it| it.syntax().clone());
+    let type_ref = || {
+        parse_in_item::<ast::TypeAliasDef>(&format!("type T = {};", text))
+            .and_then(|it| it.type_ref())
+            .map(|it| it.syntax().clone())
+    };
+    expr.or_else(type_ref)
+        .filter(|it| it.text() == text)
+        .ok_or_else(|| SsrError(format!("`{}` is not an expression or a type", text)))
+}
+
+fn parse_in_item<N: AstNode>(item_text: &str) -> Option<N> {
+    let parse = SourceFile::parse(item_text);
+    if !parse.errors().is_empty() {
+        return None;
+    }
+    parse.tree().syntax().descendants().find_map(N::cast)
+}
+
+/// Returns the name of the placeholder if `node` is one, that is, a single
+/// identifier starting with `PLACEHOLDER_PREFIX`.
+fn placeholder_name(node: &SyntaxNode) -> Option<SmolStr> {
+    match node.kind() {
+        PATH_EXPR | PATH_TYPE | NAME_REF | NAME => {}
+        _ => return None,
+    }
+    let text = node.text().to_string();
+    if !text.starts_with(PLACEHOLDER_PREFIX) {
+        return None;
+    }
+    let name = &text[PLACEHOLDER_PREFIX.len()..];
+    if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return None;
+    }
+    Some(name.into())
+}
+
+/// Checks whether `code` may be bound to the placeholder `pattern`.
+fn can_bind(pattern: &SyntaxNode, code: &SyntaxNode) -> bool {
+    match pattern.kind() {
+        PATH_EXPR => ast::Expr::can_cast(code.kind()),
+        PATH_TYPE => ast::TypeRef::can_cast(code.kind()),
+        NAME_REF | NAME => code.kind() == pattern.kind(),
+        _ => false,
+    }
+}
+
+#[derive(Debug)]
+struct Match {
+    place: SyntaxNode,
+    binding: FxHashMap<SmolStr, SyntaxNode>,
+}
+
+struct Matcher<'a> {
+    db: &'a RootDatabase,
+    file_id: FileId,
+    file: SourceFile,
+    root_module: Option<Module>,
+}
+
+impl<'a> Matcher<'a> {
+    fn new(db: &'a RootDatabase, file_id: FileId) -> Matcher<'a> {
+        let file = db.parse(file_id).tree();
+        let src = InFile::new(file_id.into(), ModuleSource::SourceFile(file.clone()));
+        let root_module =
+            Module::from_definition(db, src).and_then(|it| it.krate().root_module(db));
+        Matcher { db, file_id, file, root_module }
+    }
+
+    /// Finds all non-overlapping matches of `pattern`, preferring outer ones.
+    fn find_matches(&self, pattern: &SyntaxNode) -> Vec<Match> {
+        let mut res: Vec<Match> = Vec::new();
+        for node in self.file.syntax().descendants() {
+            if let Some(last) = res.last() {
+                if node.text_range().is_subrange(&last.place.text_range()) {
+                    continue;
+                }
+            }
+            let mut binding = FxHashMap::default();
+            if self.match_node(pattern, &node, &mut binding) {
+                res.push(Match { place: node, binding });
+            }
+        }
+        res
+    }
+
+    fn match_node(
+        &self,
+        pattern: &SyntaxNode,
+        code: &SyntaxNode,
+        binding: &mut FxHashMap<SmolStr, SyntaxNode>,
+    ) -> bool {
+        if let Some(name) = placeholder_name(pattern) {
+            if !can_bind(pattern, code) {
+                return false;
+            }
+            return match binding.get(&name) {
+                Some(bound) => bound.text() == code.text(),
+                None => {
+                    binding.insert(name, code.clone());
+                    true
+                }
+            };
+        }
+        if pattern.kind() != code.kind() {
+            return false;
+        }
+        if let (Some(pattern_path), Some(code_path)) =
+            (ast::Path::cast(pattern.clone()), ast::Path::cast(code.clone()))
+        {
+            if pattern_path.qualifier().is_some() && !has_placeholders(pattern) {
+                return without_whitespace(pattern) == without_whitespace(code)
+                    || self.resolves_to(&pattern_path, &code_path);
+            }
+        }
+
+        let mut pattern_children = non_trivia_children(pattern);
+        let mut code_children = non_trivia_children(code);
+        loop {
+            match (pattern_children.next(), code_children.next()) {
+                (None, None) => return true,
+                (Some(NodeOrToken::Node(p)), Some(NodeOrToken::Node(c))) => {
+                    if !self.match_node(&p, &c, binding) {
+                        return false;
+                    }
+                }
+                (Some(NodeOrToken::Token(p)), Some(NodeOrToken::Token(c))) => {
+                    if p.text() != c.text() {
+                        return false;
+                    }
+                }
+                _ => return false,
+            }
+        }
+    }
+
+    /// Checks whether `code` resolves to the item named by the qualified
+    /// `pattern` path, which is relative to the crate root.
+    fn resolves_to(&self, pattern: &ast::Path, code: &ast::Path) -> bool {
+        let root_module = match self.root_module {
+            Some(it) => it,
+            None => return false,
+        };
+        let analyzer =
+            SourceAnalyzer::new(self.db, InFile::new(self.file_id.into(), code.syntax()), None);
+        let def = match analyzer.resolve_path(self.db, code) {
+            Some(PathResolution::Def(def)) => def,
+            _ => return false,
+        };
+        let path = match root_module.find_use_path(self.db, def) {
+            Some(it) => it.to_string(),
+            None => return false,
+        };
+        let pattern = without_whitespace(pattern.syntax());
+        let pattern = pattern.trim_start_matches("::");
+        let pattern = pattern.trim_start_matches("crate::");
+        path == pattern
+    }
+}
+
+fn non_trivia_children(node: &SyntaxNode) -> impl Iterator<Item = SyntaxElement> {
+    node.children_with_tokens().filter(|it| !it.kind().is_trivia())
+}
+
+fn has_placeholders(node: &SyntaxNode) -> bool {
+    node.descendants().any(|it| placeholder_name(&it).is_some())
+}
+
+fn without_whitespace(node: &SyntaxNode) -> String {
+    node.text().to_string().chars().filter(|c| !c.is_whitespace()).collect()
+}
+
+fn render_template(template: &SyntaxNode, binding: &FxHashMap<SmolStr, SyntaxNode>) -> String {
+    let mut buf = String::new();
+    render_element(template.clone().into(), binding, &mut buf);
+    buf
+}
+
+fn render_element(
+    element: SyntaxElement,
+    binding: &FxHashMap<SmolStr, SyntaxNode>,
+    buf: &mut String,
+) {
+    let node = match element {
+        NodeOrToken::Token(token) => {
+            buf.push_str(token.text());
+            return;
+        }
+        NodeOrToken::Node(node) => node,
+    };
+    match placeholder_name(&node).and_then(|name| binding.get(&name)) {
+        Some(bound) if needs_parens(&node, bound) => {
+            buf.push('(');
+            buf.push_str(&bound.text().to_string());
+            buf.push(')');
+        }
+        Some(bound) => buf.push_str(&bound.text().to_string()),
+        None => {
+            for child in node.children_with_tokens() {
+                render_element(child, binding, buf);
+            }
+        }
+    }
+}
+
+/// Whether the expression bound to a placeholder must be parenthesized to keep
+/// its meaning in the position the template puts it in.
+fn needs_parens(placeholder: &SyntaxNode, bound: &SyntaxNode) -> bool {
+    let parent = match placeholder.parent() {
+        Some(it) => it,
+        None => return false,
+    };
+    is_compound_expr(bound.kind())
+        && match parent.kind() {
+            BIN_EXPR | PREFIX_EXPR | REF_EXPR | CAST_EXPR | TRY_EXPR | AWAIT_EXPR
+            | METHOD_CALL_EXPR | FIELD_EXPR | INDEX_EXPR | CALL_EXPR | RANGE_EXPR => true,
+            _ => false,
+        }
+}
+
+fn is_compound_expr(kind: SyntaxKind) -> bool {
+    match kind {
+        BIN_EXPR | RANGE_EXPR | CAST_EXPR | PREFIX_EXPR | REF_EXPR | LAMBDA_EXPR | BOX_EXPR
+        | RETURN_EXPR | BREAK_EXPR => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mock_analysis::{single_file, MockAnalysis};
+
+    use super::*;
+
+    fn check(query: &str, before: &str, after: &str) {
+        let (analysis, file_id) = single_file(before);
+        let change = analysis.structural_search_replace(query).unwrap().unwrap();
+        let mut actual = before.to_string();
+        for edit in change.source_file_edits {
+            assert_eq!(edit.file_id, file_id);
+            actual = edit.edit.apply(&actual);
+        }
+        assert_eq!(actual, after);
+    }
+
+    #[test]
+    fn parses_query() {
+        let query = SsrQuery::parse("foo($a, $b) ==>> bar($b, $a)").unwrap();
+        assert_eq!(query.pattern.text().to_string(), "foo(__search_pattern_a, __search_pattern_b)");
+        assert_eq!(
+            query.template.text().to_string(),
+            "bar(__search_pattern_b, __search_pattern_a)"
+        );
+    }
+
+    #[test]
+    fn reports_parse_errors() {
+        assert_eq!(
+            SsrQuery::parse("foo($a)").unwrap_err(),
+            SsrError("Cannot find delimiter `==>>`".into())
+        );
+        assert_eq!(
+            SsrQuery::parse("foo($a) ==>> bar($b)").unwrap_err(),
+            SsrError("Placeholder `$b` is not defined in the pattern".into())
+        );
+        assert_eq!(
+            SsrQuery::parse("foo($) ==>> bar()").unwrap_err(),
+            SsrError("Placeholder name must not be empty".into())
+        );
+        assert!(SsrQuery::parse("fn foo() {} ==>> bar()").is_err());
+    }
+
+    #[test]
+    fn swaps_arguments() {
+        check(
+            "foo($a, $b) ==>> bar($b, $a)",
+            "fn main() { foo(x, 1 + 2); foo(y,  z) }",
+            "fn main() { bar(1 + 2, x); bar(z, y) }",
+        );
+    }
+
+    #[test]
+    fn repeated_placeholders_must_match_same_code() {
+        check(
+            "$a == $a ==>> true",
+            "fn main() { let _ = x == x; let _ = x == y; }",
+            "fn main() { let _ = true; let _ = x == y; }",
+        );
+    }
+
+    #[test]
+    fn parenthesizes_compound_expressions() {
+        check(
+            "double($a) ==>> $a * 2",
+            "fn main() { double(x + 1); double(y) }",
+            "fn main() { (x + 1) * 2; y * 2 }",
+        );
+    }
+
+    #[test]
+    fn replaces_types() {
+        check(
+            "Option<Result<$t, E>> ==>> Maybe<$t>",
+            "fn foo(x: Option<Result<u32, E>>) -> Option<Result<(), E>> {}",
+            "fn foo(x: Maybe<u32>) -> Maybe<()> {}",
+        );
+    }
+
+    #[test]
+    fn matches_resolved_paths() {
+        let mock = MockAnalysis::with_files(
+            r#"
+//- /lib.rs
+mod foo;
+use foo::bar;
+fn main() { bar(1); foo::bar(2); baz::bar(3); }
+//- /foo.rs
+pub fn bar(_: u32) {}
+"#,
+        );
+        let file_id = mock.id_of("/lib.rs");
+        let analysis = mock.analysis();
+        let change = analysis.structural_search_replace("foo::bar($a) ==>> quux($a)").unwrap();
+        let change = change.unwrap();
+        assert_eq!(change.source_file_edits.len(), 1);
+        let edit = &change.source_file_edits[0];
+        assert_eq!(edit.file_id, file_id);
+        let text = analysis.file_text(file_id).unwrap();
+        assert_eq!(
+            edit.edit.apply(&text),
+            r#"mod foo;
+use foo::bar;
+fn main() { quux(1); quux(2); baz::bar(3); }
+"#
+        );
+    }
+}
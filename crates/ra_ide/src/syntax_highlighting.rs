@@ -5,7 +5,10 @@ use rustc_hash::{FxHashMap, FxHashSet};
 use hir::{InFile, Name};
 use ra_db::SourceDatabase;
 use ra_prof::profile;
-use ra_syntax::{ast, AstNode, Direction, SyntaxElement, SyntaxKind, SyntaxKind::*, TextRange, T};
+use ra_syntax::{
+    algo::find_covering_element, ast, AstNode, AstToken, Direction, NodeOrToken, SyntaxElement,
+    SyntaxKind, SyntaxKind::*, TextRange, T,
+};
 
 use crate::{
     db::RootDatabase,
@@ -16,39 +19,14 @@ use crate::{
     FileId,
 };
 
-pub mod tags {
-    pub(crate) const FIELD: &str = "field";
-    pub(crate) const FUNCTION: &str = "function";
-    pub(crate) const MODULE: &str = "module";
-    pub(crate) const CONSTANT: &str = "constant";
-    pub(crate) const MACRO: &str = "macro";
-
-    pub(crate) const VARIABLE: &str = "variable";
-    pub(crate) const VARIABLE_MUT: &str = "variable.mut";
-
-    pub(crate) const TYPE: &str = "type";
-    pub(crate) const TYPE_BUILTIN: &str = "type.builtin";
-    pub(crate) const TYPE_SELF: &str = "type.self";
-    pub(crate) const TYPE_PARAM: &str = "type.param";
-    pub(crate) const TYPE_LIFETIME: &str = "type.lifetime";
-
-    pub(crate) const LITERAL_BYTE: &str = "literal.byte";
-    pub(crate) const LITERAL_NUMERIC: &str = "literal.numeric";
-    pub(crate) const LITERAL_CHAR: &str = "literal.char";
-
-    pub(crate) const LITERAL_COMMENT: &str = "comment";
-    pub(crate) const LITERAL_STRING: &str = "string";
-    pub(crate) const LITERAL_ATTRIBUTE: &str = "attribute";
-
-    pub(crate) const KEYWORD: &str = "keyword";
-    pub(crate) const KEYWORD_UNSAFE: &str = "keyword.unsafe";
-    pub(crate) const KEYWORD_CONTROL: &str = "keyword.control";
-}
+mod tags;
+
+pub use tags::{Highlight, HighlightModifier, HighlightModifiers, HighlightTag};
 
 #[derive(Debug)]
 pub struct HighlightedRange {
     pub range: TextRange,
-    pub tag: &'static str,
+    pub highlight: Highlight,
     pub binding_hash: Option<u64>,
 }
 
@@ -67,10 +45,22 @@ fn is_control_keyword(kind: SyntaxKind) -> bool {
     }
 }
 
-pub(crate) fn highlight(db: &RootDatabase, file_id: FileId) -> Vec<HighlightedRange> {
+/// Highlights the whole file, or only the elements which intersect `range`.
+pub(crate) fn highlight(
+    db: &RootDatabase,
+    file_id: FileId,
+    range: Option<TextRange>,
+) -> Vec<HighlightedRange> {
     let _p = profile("highlight");
     let parse = db.parse(file_id);
     let root = parse.tree().syntax().clone();
+    let root = match range {
+        Some(range) => match find_covering_element(&root, range) {
+            NodeOrToken::Node(it) => it,
+            NodeOrToken::Token(it) => it.parent(),
+        },
+        None => root,
+    };
 
     fn calc_binding_hash(file_id: FileId, name: &Name, shadow_count: u32) -> u64 {
         fn hash<T: std::hash::Hash + std::fmt::Debug>(x: T) -> u64 {
@@ -94,17 +84,37 @@ pub(crate) fn highlight(db: &RootDatabase, file_id: FileId) -> Vec<HighlightedRa
         if highlighted.contains(&node) {
             continue;
         }
+        if let Some(range) = range {
+            if node.text_range().intersection(&range).is_none() {
+                continue;
+            }
+        }
         let mut binding_hash = None;
-        let tag = match node.kind() {
+        let mut highlight: Highlight = match node.kind() {
             FN_DEF => {
                 bindings_shadow_count.clear();
                 continue;
             }
-            COMMENT => tags::LITERAL_COMMENT,
-            STRING | RAW_STRING | RAW_BYTE_STRING | BYTE_STRING => tags::LITERAL_STRING,
-            ATTR => tags::LITERAL_ATTRIBUTE,
+            COMMENT => {
+                let is_doc = node
+                    .as_token()
+                    .cloned()
+                    .and_then(ast::Comment::cast)
+                    .map_or(false, |it| it.kind().doc.is_some());
+                if is_doc {
+                    HighlightTag::Comment | HighlightModifier::Documentation
+                } else {
+                    HighlightTag::Comment.into()
+                }
+            }
+            STRING | RAW_STRING | RAW_BYTE_STRING | BYTE_STRING => {
+                HighlightTag::StringLiteral.into()
+            }
+            ATTR => HighlightTag::Attribute.into(),
             // Special-case field init shorthand
-            NAME_REF if node.parent().and_then(ast::RecordField::cast).is_some() => tags::FIELD,
+            NAME_REF if node.parent().and_then(ast::RecordField::cast).is_some() => {
+                HighlightTag::Field.into()
+            }
             NAME_REF if node.ancestors().any(|it| it.kind() == ATTR) => continue,
             NAME_REF => {
                 let name_ref = node.as_node().cloned().and_then(ast::NameRef::cast).unwrap();
@@ -139,23 +149,30 @@ pub(crate) fn highlight(db: &RootDatabase, file_id: FileId) -> Vec<HighlightedRa
                     }
                 };
 
-                match name_kind {
+                let h = match name_kind {
                     Some(name_kind) => highlight_name(db, name_kind),
-                    None => name.syntax().parent().map_or(tags::FUNCTION, |x| match x.kind() {
-                        STRUCT_DEF | ENUM_DEF | TRAIT_DEF | TYPE_ALIAS_DEF => tags::TYPE,
-                        TYPE_PARAM => tags::TYPE_PARAM,
-                        RECORD_FIELD_DEF => tags::FIELD,
-                        _ => tags::FUNCTION,
-                    }),
-                }
+                    None => name
+                        .syntax()
+                        .parent()
+                        .map_or(HighlightTag::Function, |x| match x.kind() {
+                            STRUCT_DEF | ENUM_DEF | TRAIT_DEF | TYPE_ALIAS_DEF => {
+                                HighlightTag::Type
+                            }
+                            TYPE_PARAM => HighlightTag::TypeParam,
+                            RECORD_FIELD_DEF => HighlightTag::Field,
+                            _ => HighlightTag::Function,
+                        })
+                        .into(),
+                };
+                h | HighlightModifier::Declaration
             }
-            INT_NUMBER | FLOAT_NUMBER => tags::LITERAL_NUMERIC,
-            BYTE => tags::LITERAL_BYTE,
-            CHAR => tags::LITERAL_CHAR,
-            LIFETIME => tags::TYPE_LIFETIME,
-            T![unsafe] => tags::KEYWORD_UNSAFE,
-            k if is_control_keyword(k) => tags::KEYWORD_CONTROL,
-            k if k.is_keyword() => tags::KEYWORD,
+            INT_NUMBER | FLOAT_NUMBER => HighlightTag::NumericLiteral.into(),
+            BYTE => HighlightTag::ByteLiteral.into(),
+            CHAR => HighlightTag::CharLiteral.into(),
+            LIFETIME => HighlightTag::Lifetime.into(),
+            T![unsafe] => HighlightTag::Keyword | HighlightModifier::Unsafe,
+            k if is_control_keyword(k) => HighlightTag::Keyword | HighlightModifier::Control,
+            k if k.is_keyword() => HighlightTag::Keyword.into(),
             _ => {
                 if let Some(macro_call) = node.as_node().cloned().and_then(ast::MacroCall::cast) {
                     if let Some(path) = macro_call.path() {
@@ -172,7 +189,7 @@ pub(crate) fn highlight(db: &RootDatabase, file_id: FileId) -> Vec<HighlightedRa
                                 }
                                 res.push(HighlightedRange {
                                     range: TextRange::from_to(range_start, range_end),
-                                    tag: tags::MACRO,
+                                    highlight: HighlightTag::Macro.into(),
                                     binding_hash: None,
                                 })
                             }
@@ -182,7 +199,11 @@ pub(crate) fn highlight(db: &RootDatabase, file_id: FileId) -> Vec<HighlightedRa
                 continue;
             }
         };
-        res.push(HighlightedRange { range: node.text_range(), tag, binding_hash })
+        if highlight.tag != HighlightTag::Attribute && node.ancestors().any(|it| it.kind() == ATTR)
+        {
+            highlight |= HighlightModifier::Attribute;
+        }
+        res.push(HighlightedRange { range: node.text_range(), highlight, binding_hash })
     }
     res
 }
@@ -201,7 +222,7 @@ pub(crate) fn highlight_as_html(db: &RootDatabase, file_id: FileId, rainbow: boo
         )
     }
 
-    let mut ranges = highlight(db, file_id);
+    let mut ranges = highlight(db, file_id, None);
     ranges.sort_by_key(|it| it.range.start());
     // quick non-optimal heuristic to intersect token ranges and highlighted ranges
     let mut frontier = 0;
@@ -229,7 +250,11 @@ pub(crate) fn highlight_as_html(db: &RootDatabase, file_id: FileId, rainbow: boo
         if ranges.is_empty() {
             buf.push_str(&text);
         } else {
-            let classes = ranges.iter().map(|x| x.tag).collect::<Vec<_>>().join(" ");
+            let classes = ranges
+                .iter()
+                .map(|x| x.highlight.to_string().replace('.', " "))
+                .collect::<Vec<_>>()
+                .join(" ");
             let binding_hash = ranges.first().and_then(|x| x.binding_hash);
             let color = match (rainbow, binding_hash) {
                 (true, Some(hash)) => format!(
@@ -246,29 +271,29 @@ pub(crate) fn highlight_as_html(db: &RootDatabase, file_id: FileId, rainbow: boo
     buf
 }
 
-fn highlight_name(db: &RootDatabase, name_kind: NameKind) -> &'static str {
+fn highlight_name(db: &RootDatabase, name_kind: NameKind) -> Highlight {
     match name_kind {
-        Macro(_) => tags::MACRO,
-        Field(_) => tags::FIELD,
-        AssocItem(hir::AssocItem::Function(_)) => tags::FUNCTION,
-        AssocItem(hir::AssocItem::Const(_)) => tags::CONSTANT,
-        AssocItem(hir::AssocItem::TypeAlias(_)) => tags::TYPE,
-        Def(hir::ModuleDef::Module(_)) => tags::MODULE,
-        Def(hir::ModuleDef::Function(_)) => tags::FUNCTION,
-        Def(hir::ModuleDef::Adt(_)) => tags::TYPE,
-        Def(hir::ModuleDef::EnumVariant(_)) => tags::CONSTANT,
-        Def(hir::ModuleDef::Const(_)) => tags::CONSTANT,
-        Def(hir::ModuleDef::Static(_)) => tags::CONSTANT,
-        Def(hir::ModuleDef::Trait(_)) => tags::TYPE,
-        Def(hir::ModuleDef::TypeAlias(_)) => tags::TYPE,
-        Def(hir::ModuleDef::BuiltinType(_)) => tags::TYPE_BUILTIN,
-        SelfType(_) => tags::TYPE_SELF,
-        TypeParam(_) => tags::TYPE_PARAM,
+        Macro(_) => HighlightTag::Macro.into(),
+        Field(_) => HighlightTag::Field.into(),
+        AssocItem(hir::AssocItem::Function(_)) => HighlightTag::Function.into(),
+        AssocItem(hir::AssocItem::Const(_)) => HighlightTag::Constant.into(),
+        AssocItem(hir::AssocItem::TypeAlias(_)) => HighlightTag::Type.into(),
+        Def(hir::ModuleDef::Module(_)) => HighlightTag::Module.into(),
+        Def(hir::ModuleDef::Function(_)) => HighlightTag::Function.into(),
+        Def(hir::ModuleDef::Adt(_)) => HighlightTag::Type.into(),
+        Def(hir::ModuleDef::EnumVariant(_)) => HighlightTag::Constant.into(),
+        Def(hir::ModuleDef::Const(_)) => HighlightTag::Constant.into(),
+        Def(hir::ModuleDef::Static(_)) => HighlightTag::Variable | HighlightModifier::Static,
+        Def(hir::ModuleDef::Trait(_)) => HighlightTag::Type.into(),
+        Def(hir::ModuleDef::TypeAlias(_)) => HighlightTag::Type.into(),
+        Def(hir::ModuleDef::BuiltinType(_)) => HighlightTag::BuiltinType.into(),
+        SelfType(_) => HighlightTag::SelfType.into(),
+        TypeParam(_) => HighlightTag::TypeParam.into(),
         Local(local) => {
             if local.is_mut(db) || local.ty(db).is_mutable_reference() {
-                tags::VARIABLE_MUT
+                HighlightTag::Variable | HighlightModifier::Mutable
             } else {
-                tags::VARIABLE
+                HighlightTag::Variable.into()
             }
         }
     }
@@ -285,31 +310,32 @@ body                { margin: 0; }
 pre                 { color: #DCDCCC; background: #3F3F3F; font-size: 22px; padding: 0.4em; }
 
 .comment            { color: #7F9F7F; }
-.string             { color: #CC9393; }
+.documentation      { color: #629755; }
+.string_literal     { color: #CC9393; }
 .field              { color: #94BFF3; }
 .function           { color: #93E0E3; }
 .parameter          { color: #94BFF3; }
 .text               { color: #DCDCCC; }
 .type               { color: #7CB8BB; }
-.type\\.builtin     { color: #8CD0D3; }
-.type\\.param       { color: #20999D; }
+.builtin_type       { color: #8CD0D3; }
+.type_param         { color: #20999D; }
 .attribute          { color: #94BFF3; }
-.literal            { color: #BFEBBF; }
-.literal\\.numeric  { color: #6A8759; }
+.numeric_literal    { color: #BFEBBF; }
 .macro              { color: #94BFF3; }
 .variable           { color: #DCDCCC; }
-.variable\\.mut     { color: #DCDCCC; text-decoration: underline; }
+.mutable            { text-decoration: underline; }
 
 .keyword            { color: #F0DFAF; }
-.keyword\\.unsafe   { color: #DFAF8F; }
-.keyword\\.control  { color: #F0DFAF; font-weight: bold; }
+.keyword.unsafe     { color: #DFAF8F; }
+.keyword.control    { color: #F0DFAF; font-weight: bold; }
 </style>
 ";
 
 #[cfg(test)]
 mod tests {
-    use crate::mock_analysis::single_file;
-    use test_utils::{assert_eq_text, project_dir, read_text};
+    use test_utils::{assert_eq_text, extract_range, project_dir, read_text};
+
+    use crate::{mock_analysis::single_file, FileRange};
 
     #[test]
     fn test_highlighting() {
@@ -386,4 +412,33 @@ fn bar() {
         std::fs::write(dst_file, &actual_html).unwrap();
         assert_eq_text!(expected_html, actual_html);
     }
+
+    #[test]
+    fn ranged_highlighting_only_covers_the_range() {
+        let (range, text) = extract_range(
+            r#"
+fn foo() -> u32 {
+    let x = 92;
+    <|>let y = "hello";<|>
+    x
+}
+"#
+            .trim(),
+        );
+        let (analysis, file_id) = single_file(&text);
+        let highlights = analysis.highlight_range(FileRange { file_id, range }).unwrap();
+        let highlighted = highlights
+            .iter()
+            .map(|it| (&text[it.range], it.highlight.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            highlighted,
+            vec![
+                ("let", "keyword".to_string()),
+                ("y", "variable.declaration".to_string()),
+                ("\"hello\"", "string_literal".to_string()),
+            ]
+        );
+        assert!(highlights.iter().all(|it| it.range.is_subrange(&range)));
+    }
 }
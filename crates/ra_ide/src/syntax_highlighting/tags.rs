@@ -0,0 +1,184 @@
+//! Defines token tags we use for syntax highlighting.
+//! A tag is not unlike a CSS class.
+
+use std::{fmt, ops};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Highlight {
+    pub tag: HighlightTag,
+    pub modifiers: HighlightModifiers,
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HighlightModifiers(u32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HighlightTag {
+    Attribute,
+    BuiltinType,
+    ByteLiteral,
+    CharLiteral,
+    Comment,
+    Constant,
+    Field,
+    Function,
+    Keyword,
+    Lifetime,
+    Macro,
+    Module,
+    NumericLiteral,
+    SelfType,
+    StringLiteral,
+    Type,
+    TypeParam,
+    Variable,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum HighlightModifier {
+    /// Used with keywords like `if` and `break`.
+    Control = 0,
+    /// `foo` in `let mut foo = 92;` and all of its usages.
+    Mutable,
+    Unsafe,
+    /// The defining occurrence of a name, `foo` in `fn foo() {}`.
+    Declaration,
+    Static,
+    /// Doc comments, `/// foo` and `//! foo`.
+    Documentation,
+    /// Tokens inside of `#[...]`.
+    Attribute,
+}
+
+impl HighlightTag {
+    fn as_str(self) -> &'static str {
+        match self {
+            HighlightTag::Attribute => "attribute",
+            HighlightTag::BuiltinType => "builtin_type",
+            HighlightTag::ByteLiteral => "byte_literal",
+            HighlightTag::CharLiteral => "char_literal",
+            HighlightTag::Comment => "comment",
+            HighlightTag::Constant => "constant",
+            HighlightTag::Field => "field",
+            HighlightTag::Function => "function",
+            HighlightTag::Keyword => "keyword",
+            HighlightTag::Lifetime => "lifetime",
+            HighlightTag::Macro => "macro",
+            HighlightTag::Module => "module",
+            HighlightTag::NumericLiteral => "numeric_literal",
+            HighlightTag::SelfType => "self_type",
+            HighlightTag::StringLiteral => "string_literal",
+            HighlightTag::Type => "type",
+            HighlightTag::TypeParam => "type_param",
+            HighlightTag::Variable => "variable",
+        }
+    }
+}
+
+impl fmt::Display for HighlightTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl HighlightModifier {
+    const ALL: &'static [HighlightModifier] = &[
+        HighlightModifier::Control,
+        HighlightModifier::Mutable,
+        HighlightModifier::Unsafe,
+        HighlightModifier::Declaration,
+        HighlightModifier::Static,
+        HighlightModifier::Documentation,
+        HighlightModifier::Attribute,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            HighlightModifier::Control => "control",
+            HighlightModifier::Mutable => "mutable",
+            HighlightModifier::Unsafe => "unsafe",
+            HighlightModifier::Declaration => "declaration",
+            HighlightModifier::Static => "static",
+            HighlightModifier::Documentation => "documentation",
+            HighlightModifier::Attribute => "attribute",
+        }
+    }
+
+    fn mask(self) -> u32 {
+        1 << (self as u32)
+    }
+}
+
+impl fmt::Display for HighlightModifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for Highlight {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.tag)?;
+        for modifier in self.modifiers.iter() {
+            write!(f, ".{}", modifier)?
+        }
+        Ok(())
+    }
+}
+
+impl From<HighlightTag> for Highlight {
+    fn from(tag: HighlightTag) -> Highlight {
+        Highlight::new(tag)
+    }
+}
+
+impl Highlight {
+    pub(crate) fn new(tag: HighlightTag) -> Highlight {
+        Highlight { tag, modifiers: HighlightModifiers::default() }
+    }
+}
+
+impl ops::BitOr<HighlightModifier> for HighlightTag {
+    type Output = Highlight;
+
+    fn bitor(self, rhs: HighlightModifier) -> Highlight {
+        Highlight::new(self) | rhs
+    }
+}
+
+impl ops::BitOrAssign<HighlightModifier> for HighlightModifiers {
+    fn bitor_assign(&mut self, rhs: HighlightModifier) {
+        self.0 |= rhs.mask();
+    }
+}
+
+impl ops::BitOrAssign<HighlightModifier> for Highlight {
+    fn bitor_assign(&mut self, rhs: HighlightModifier) {
+        self.modifiers |= rhs;
+    }
+}
+
+impl ops::BitOr<HighlightModifier> for Highlight {
+    type Output = Highlight;
+
+    fn bitor(mut self, rhs: HighlightModifier) -> Highlight {
+        self |= rhs;
+        self
+    }
+}
+
+impl HighlightModifiers {
+    pub fn contains(self, modifier: HighlightModifier) -> bool {
+        self.0 & modifier.mask() != 0
+    }
+
+    pub fn iter(self) -> impl Iterator<Item = HighlightModifier> {
+        HighlightModifier::ALL.iter().copied().filter(move |it| self.contains(*it))
+    }
+}
+
+impl fmt::Debug for HighlightModifiers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
@@ -9,18 +9,27 @@ use lsp_types::{
     TypeDefinitionProviderCapability, WorkDoneProgressOptions,
 };
 
-pub fn server_capabilities() -> ServerCapabilities {
+use crate::semantic_tokens;
+
+pub fn server_capabilities() -> serde_json::Value {
+    let mut res = serde_json::to_value(lsp_capabilities()).unwrap();
+    // FIXME: move this into `lsp_capabilities` once `lsp_types` supports semantic tokens
+    res["semanticTokensProvider"] = serde_json::to_value(semantic_tokens::options()).unwrap();
+    res
+}
+
+fn lsp_capabilities() -> ServerCapabilities {
     ServerCapabilities {
         text_document_sync: Some(TextDocumentSyncCapability::Options(TextDocumentSyncOptions {
             open_close: Some(true),
-            change: Some(TextDocumentSyncKind::Full),
+            change: Some(TextDocumentSyncKind::Incremental),
             will_save: None,
             will_save_wait_until: None,
             save: Some(SaveOptions::default()),
         })),
         hover_provider: Some(true),
         completion_provider: Some(CompletionOptions {
-            resolve_provider: None,
+            resolve_provider: Some(true),
             trigger_characters: Some(vec![":".to_string(), ".".to_string()]),
             work_done_progress_options: WorkDoneProgressOptions { work_done_progress: None },
         }),
@@ -29,7 +38,7 @@ pub fn server_capabilities() -> ServerCapabilities {
             retrigger_characters: None,
             work_done_progress_options: WorkDoneProgressOptions { work_done_progress: None },
         }),
-        declaration_provider: None,
+        declaration_provider: Some(true),
         definition_provider: Some(true),
         type_definition_provider: Some(TypeDefinitionProviderCapability::Simple(true)),
         implementation_provider: Some(ImplementationProviderCapability::Simple(true)),
@@ -40,7 +49,7 @@ pub fn server_capabilities() -> ServerCapabilities {
         code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
         code_lens_provider: Some(CodeLensOptions { resolve_provider: Some(true) }),
         document_formatting_provider: Some(true),
-        document_range_formatting_provider: None,
+        document_range_formatting_provider: Some(true),
         document_on_type_formatting_provider: Some(DocumentOnTypeFormattingOptions {
             first_trigger_character: "=".to_string(),
             more_trigger_character: Some(vec![".".to_string(), ">".to_string()]),
@@ -40,16 +40,75 @@ pub(crate) fn runnable_args(
             res.push(name.to_string());
             res.push("--nocapture".to_string());
         }
+        RunnableKind::DocTest { test_id } => {
+            res.push("test".to_string());
+            res.push("--doc".to_string());
+            if let Some(spec) = spec {
+                spec.push_package_to(&mut res);
+            }
+            res.push("--".to_string());
+            res.push(test_id.to_string());
+            res.push("--nocapture".to_string());
+        }
         RunnableKind::Bin => {
             res.push("run".to_string());
             if let Some(spec) = spec {
-                spec.push_to(&mut res);
+                spec.push_run_target_to(&mut res);
             }
         }
     }
     Ok(res)
 }
 
+/// Splits the arguments of a runnable into the arguments for `cargo` which
+/// only build the binary and the arguments for the binary itself.
+///
+/// Returns `None` for doc tests, which are compiled by rustdoc on the fly.
+pub(crate) fn debug_runnable_args(
+    world: &WorldSnapshot,
+    file_id: FileId,
+    kind: &RunnableKind,
+) -> Result<Option<(Vec<String>, Vec<String>)>> {
+    let spec = CargoTargetSpec::for_file(world, file_id)?;
+    let mut cargo_args = Vec::new();
+    let mut executable_args = Vec::new();
+    match kind {
+        RunnableKind::Test { name } => {
+            cargo_args.push("test".to_string());
+            cargo_args.push("--no-run".to_string());
+            executable_args.push(name.to_string());
+            executable_args.push("--exact".to_string());
+            executable_args.push("--nocapture".to_string());
+        }
+        RunnableKind::TestMod { path } => {
+            cargo_args.push("test".to_string());
+            cargo_args.push("--no-run".to_string());
+            executable_args.push(path.to_string());
+            executable_args.push("--nocapture".to_string());
+        }
+        RunnableKind::Bench { name } => {
+            cargo_args.push("bench".to_string());
+            cargo_args.push("--no-run".to_string());
+            executable_args.push(name.to_string());
+            executable_args.push("--exact".to_string());
+            executable_args.push("--bench".to_string());
+            executable_args.push("--nocapture".to_string());
+        }
+        RunnableKind::DocTest { .. } => return Ok(None),
+        RunnableKind::Bin => {
+            cargo_args.push("build".to_string());
+            if let Some(spec) = spec {
+                spec.push_run_target_to(&mut cargo_args);
+            }
+            return Ok(Some((cargo_args, executable_args)));
+        }
+    }
+    if let Some(spec) = spec {
+        spec.push_to(&mut cargo_args);
+    }
+    Ok(Some((cargo_args, executable_args)))
+}
+
 pub struct CargoTargetSpec {
     pub package: String,
     pub target: String,
@@ -104,4 +163,19 @@ impl CargoTargetSpec {
             TargetKind::Other => (),
         }
     }
+
+    /// `cargo test --doc` doesn't accept a target, it always tests the library.
+    fn push_package_to(self, buf: &mut Vec<String>) {
+        buf.push("--package".to_string());
+        buf.push(self.package);
+    }
+
+    /// `cargo run` only accepts binary and example targets, a `main` anywhere
+    /// else is run through the default binary of the package.
+    fn push_run_target_to(self, buf: &mut Vec<String>) {
+        match self.target_kind {
+            TargetKind::Bin | TargetKind::Example => self.push_to(buf),
+            _ => self.push_package_to(buf),
+        }
+    }
 }
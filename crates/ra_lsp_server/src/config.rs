@@ -46,6 +46,14 @@ pub struct ServerConfig {
 
     /// Cargo feature configurations.
     pub cargo_features: CargoFeatures,
+
+    /// Command used to run the formatter, `rustfmt` by default.
+    pub rustfmt_command: String,
+    /// Extra arguments passed to the formatter.
+    pub rustfmt_args: Vec<String>,
+
+    /// Path to the proc macro server executable, enables proc macro expansion.
+    pub proc_macro_srv: Option<String>,
 }
 
 impl Default for ServerConfig {
@@ -63,6 +71,9 @@ impl Default for ServerConfig {
             with_sysroot: true,
             feature_flags: FxHashMap::default(),
             cargo_features: Default::default(),
+            rustfmt_command: "rustfmt".to_string(),
+            rustfmt_args: Vec::new(),
+            proc_macro_srv: None,
         }
     }
 }
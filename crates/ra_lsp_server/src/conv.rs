@@ -8,14 +8,15 @@ use lsp_types::{
 };
 use ra_ide::{
     translate_offset_with_edit, CompletionItem, CompletionItemKind, FileId, FilePosition,
-    FileRange, FileSystemEdit, Fold, FoldKind, InsertTextFormat, LineCol, LineIndex,
-    NavigationTarget, RangeInfo, Severity, SourceChange, SourceFileEdit,
+    FileRange, FileSystemEdit, Fold, FoldKind, Highlight, HighlightModifier, HighlightTag,
+    InsertTextFormat, LineCol, LineIndex, NavigationTarget, RangeInfo, Severity, SourceChange,
+    SourceFileEdit,
 };
 use ra_syntax::{SyntaxKind, TextRange, TextUnit};
 use ra_text_edit::{AtomTextEdit, TextEdit};
 use ra_vfs::LineEndings;
 
-use crate::{req, world::WorldSnapshot, Result};
+use crate::{req, semantic_tokens, world::WorldSnapshot, Result};
 
 pub trait Conv {
     type Output;
@@ -80,6 +81,47 @@ impl Conv for CompletionItemKind {
     }
 }
 
+impl Conv for Highlight {
+    type Output = (u32, u32);
+
+    fn conv(self) -> Self::Output {
+        let token_type = match self.tag {
+            HighlightTag::Attribute => semantic_tokens::ATTRIBUTE,
+            HighlightTag::BuiltinType => semantic_tokens::BUILTIN_TYPE,
+            HighlightTag::ByteLiteral | HighlightTag::NumericLiteral => semantic_tokens::NUMBER,
+            HighlightTag::CharLiteral | HighlightTag::StringLiteral => semantic_tokens::STRING,
+            HighlightTag::Comment => semantic_tokens::COMMENT,
+            HighlightTag::Constant => semantic_tokens::CONSTANT,
+            HighlightTag::Field => semantic_tokens::PROPERTY,
+            HighlightTag::Function => semantic_tokens::FUNCTION,
+            HighlightTag::Keyword => semantic_tokens::KEYWORD,
+            HighlightTag::Lifetime => semantic_tokens::LIFETIME,
+            HighlightTag::Macro => semantic_tokens::MACRO,
+            HighlightTag::Module => semantic_tokens::NAMESPACE,
+            HighlightTag::SelfType => semantic_tokens::SELF_TYPE,
+            HighlightTag::Type => semantic_tokens::TYPE,
+            HighlightTag::TypeParam => semantic_tokens::TYPE_PARAMETER,
+            HighlightTag::Variable => semantic_tokens::VARIABLE,
+        };
+
+        let mut token_modifiers = 0;
+        for modifier in self.modifiers.iter() {
+            let modifier = match modifier {
+                HighlightModifier::Attribute => semantic_tokens::ATTRIBUTE_MODIFIER,
+                HighlightModifier::Control => semantic_tokens::CONTROL_FLOW,
+                HighlightModifier::Declaration => semantic_tokens::DECLARATION,
+                HighlightModifier::Documentation => semantic_tokens::DOCUMENTATION,
+                HighlightModifier::Mutable => semantic_tokens::MUTABLE,
+                HighlightModifier::Static => semantic_tokens::STATIC,
+                HighlightModifier::Unsafe => semantic_tokens::UNSAFE,
+            };
+            token_modifiers |= semantic_tokens::modifier_mask(modifier);
+        }
+
+        (semantic_tokens::type_index(token_type), token_modifiers)
+    }
+}
+
 impl Conv for Severity {
     type Output = DiagnosticSeverity;
     fn conv(self) -> DiagnosticSeverity {
@@ -135,6 +177,12 @@ impl ConvWith<(&LineIndex, LineEndings)> for CompletionItem {
             res.tags = Some(vec![lsp_types::CompletionItemTag::Deprecated])
         }
 
+        if self.requires_import() {
+            // Items without `sortText` are sorted by label, and `~` sorts after
+            // any identifier, so this puts items which need a `use` last.
+            res.sort_text = Some(format!("~{}", self.lookup()));
+        }
+
         res.insert_text_format = Some(match self.insert_text_format() {
             InsertTextFormat::Snippet => lsp_types::InsertTextFormat::Snippet,
             InsertTextFormat::PlainText => lsp_types::InsertTextFormat::PlainText,
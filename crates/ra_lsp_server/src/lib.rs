@@ -27,6 +27,7 @@ mod conv;
 mod main_loop;
 mod markdown;
 pub mod req;
+mod semantic_tokens;
 mod config;
 mod world;
 
@@ -42,9 +42,11 @@ fn run_server() -> Result<()> {
     log::info!("lifecycle: server started");
 
     let (connection, io_threads) = Connection::stdio();
-    let server_capabilities = serde_json::to_value(ra_lsp_server::server_capabilities()).unwrap();
+    let server_capabilities = ra_lsp_server::server_capabilities();
 
     let initialize_params = connection.initialize(server_capabilities)?;
+    // Parsed by `main_loop`, as `lsp_types` drops the capabilities it doesn't know
+    let client_caps = initialize_params.get("capabilities").cloned().unwrap_or_default();
     let initialize_params: lsp_types::InitializeParams = serde_json::from_value(initialize_params)?;
 
     let cwd = std::env::current_dir()?;
@@ -74,12 +76,7 @@ fn run_server() -> Result<()> {
         })
         .unwrap_or_default();
 
-    ra_lsp_server::main_loop(
-        workspace_roots,
-        initialize_params.capabilities,
-        server_config,
-        connection,
-    )?;
+    ra_lsp_server::main_loop(workspace_roots, client_caps, server_config, connection)?;
 
     log::info!("shutting down IO...");
     io_threads.join()?;
@@ -5,15 +5,18 @@ mod handlers;
 mod subscriptions;
 pub(crate) mod pending_requests;
 
-use std::{error::Error, fmt, panic, path::PathBuf, sync::Arc, time::Instant};
+use std::{error::Error, fmt, panic, path::PathBuf, sync::Arc, thread, time::Instant};
 
 use crossbeam_channel::{select, unbounded, RecvError, Sender};
 use lsp_server::{Connection, ErrorCode, Message, Notification, Request, RequestId, Response};
-use lsp_types::{ClientCapabilities, NumberOrString};
+use lsp_types::{ClientCapabilities, NumberOrString, Position, TextDocumentContentChangeEvent};
 use ra_cargo_watch::{CheckOptions, CheckTask};
 use ra_ide::{Canceled, FeatureFlags, FileId, LibraryData, SourceRootId};
 use ra_prof::profile;
-use ra_vfs::{VfsTask, Watch};
+use ra_project_model::{ExternResources, ProjectWorkspace};
+use ra_syntax::{TextRange, TextUnit};
+use ra_text_edit::AtomTextEdit;
+use ra_vfs::{LineEndings, VfsTask, Watch};
 use relative_path::RelativePathBuf;
 use rustc_hash::FxHashSet;
 use serde::{de::DeserializeOwned, Serialize};
@@ -25,7 +28,7 @@ use crate::{
         subscriptions::Subscriptions,
     },
     req,
-    world::{Options, WorldSnapshot, WorldState},
+    world::{DocumentChange, Options, WorldSnapshot, WorldState},
     Result, ServerConfig,
 };
 
@@ -54,12 +57,19 @@ impl Error for LspError {}
 
 pub fn main_loop(
     ws_roots: Vec<PathBuf>,
-    client_caps: ClientCapabilities,
+    client_caps: serde_json::Value,
     config: ServerConfig,
     connection: Connection,
 ) -> Result<()> {
     log::info!("server_config: {:#?}", config);
 
+    // `lsp_types` doesn't know about `resolveSupport` yet
+    let resolve_completion_edits = client_caps
+        .pointer("/textDocument/completion/completionItem/resolveSupport/properties")
+        .and_then(|it| it.as_array())
+        .map_or(false, |properties| properties.iter().any(|it| it == "additionalTextEdits"));
+    let client_caps: ClientCapabilities = serde_json::from_value(client_caps)?;
+
     let mut loop_state = LoopState::default();
     let mut world_state = {
         let feature_flags = {
@@ -82,7 +92,7 @@ pub fn main_loop(
         let workspaces = {
             let mut loaded_workspaces = Vec::new();
             for ws_root in &ws_roots {
-                let workspace = ra_project_model::ProjectWorkspace::discover_with_sysroot(
+                let workspace = ProjectWorkspace::discover_with_sysroot(
                     ws_root.as_path(),
                     config.with_sysroot,
                     &config.cargo_features,
@@ -147,6 +157,7 @@ pub fn main_loop(
                     .and_then(|it| it.folding_range.as_ref())
                     .and_then(|it| it.line_folding_only)
                     .unwrap_or(false),
+                resolve_completion_edits,
                 max_inlay_hint_length: config.max_inlay_hint_length,
                 cargo_watch: CheckOptions {
                     enable: config.cargo_watch_enable,
@@ -154,6 +165,9 @@ pub fn main_loop(
                     command: config.cargo_watch_command,
                     all_targets: config.cargo_watch_all_targets,
                 },
+                rustfmt_command: config.rustfmt_command,
+                rustfmt_args: config.rustfmt_args,
+                proc_macro_srv: config.proc_macro_srv.map(PathBuf::from),
             }
         };
 
@@ -171,6 +185,36 @@ pub fn main_loop(
     let pool = ThreadPool::new(THREADPOOL_SIZE);
     let (task_sender, task_receiver) = unbounded::<Task>();
     let (libdata_sender, libdata_receiver) = unbounded::<LibraryData>();
+    let (proc_macro_sender, proc_macro_receiver) = unbounded::<Vec<ExternResources>>();
+
+    // Proc macros need the compiled dylibs, which are only known after
+    // `cargo check`. That can take minutes, so it is run in the background.
+    // The dylibs are known already if the check ran when loading the workspace,
+    // and there is nothing to load them into without a proc macro server.
+    if config.cargo_features.with_proc_macro
+        && world_state.options.proc_macro_srv.is_some()
+        && !config.cargo_features.load_out_dirs_from_check
+    {
+        let workspaces = Arc::clone(&world_state.workspaces);
+        let cargo_features = config.cargo_features.clone();
+        let proc_macro_sender = proc_macro_sender.clone();
+        thread::spawn(move || {
+            let resources = workspaces
+                .iter()
+                .map(|ws| match ws {
+                    ProjectWorkspace::Cargo { cargo, .. } => {
+                        ra_project_model::load_extern_resources(
+                            &cargo.manifest_path(),
+                            &cargo_features,
+                        )
+                    }
+                    ProjectWorkspace::Json { .. } => ExternResources::default(),
+                })
+                .collect();
+            // The server may have been shut down in the meantime.
+            let _ = proc_macro_sender.send(resources);
+        });
+    }
 
     log::info!("server initialized, serving requests");
     {
@@ -189,6 +233,7 @@ pub fn main_loop(
                     Err(RecvError) => Err("vfs died")?,
                 },
                 recv(libdata_receiver) -> data => Event::Lib(data.unwrap()),
+                recv(proc_macro_receiver) -> resources => Event::ProcMacros(resources.unwrap()),
                 recv(world_state.check_watcher.task_recv) -> task => match task {
                     Ok(task) => Event::CheckWatcher(task),
                     Err(RecvError) => Err("check watcher died")?,
@@ -239,6 +284,7 @@ enum Event {
     Vfs(VfsTask),
     Lib(LibraryData),
     CheckWatcher(CheckTask),
+    ProcMacros(Vec<ExternResources>),
 }
 
 impl fmt::Debug for Event {
@@ -277,6 +323,7 @@ impl fmt::Debug for Event {
             Event::Vfs(it) => fmt::Debug::fmt(it, f),
             Event::Lib(it) => fmt::Debug::fmt(it, f),
             Event::CheckWatcher(it) => fmt::Debug::fmt(it, f),
+            Event::ProcMacros(_) => f.write_str("ProcMacros"),
         }
     }
 }
@@ -336,6 +383,9 @@ fn loop_turn(
             world_state.maybe_collect_garbage();
             loop_state.in_flight_libraries -= 1;
         }
+        Event::ProcMacros(resources) => {
+            world_state.set_proc_macro_dylib_paths(&resources);
+        }
         Event::CheckWatcher(task) => match task {
             CheckTask::Update(uri) => {
                 // We manually send a diagnostic update when the watcher asks
@@ -486,12 +536,17 @@ fn on_request(
         .on::<req::DocumentSymbolRequest>(handlers::handle_document_symbol)?
         .on::<req::WorkspaceSymbol>(handlers::handle_workspace_symbol)?
         .on::<req::GotoDefinition>(handlers::handle_goto_definition)?
+        .on::<req::GotoDeclaration>(handlers::handle_goto_declaration)?
         .on::<req::GotoImplementation>(handlers::handle_goto_implementation)?
         .on::<req::GotoTypeDefinition>(handlers::handle_goto_type_definition)?
         .on::<req::ParentModule>(handlers::handle_parent_module)?
         .on::<req::Runnables>(handlers::handle_runnables)?
         .on::<req::DecorationsRequest>(handlers::handle_decorations)?
+        .on::<req::SemanticTokensFullRequest>(handlers::handle_semantic_tokens)?
+        .on::<req::SemanticTokensFullDeltaRequest>(handlers::handle_semantic_tokens_delta)?
+        .on::<req::SemanticTokensRangeRequest>(handlers::handle_semantic_tokens_range)?
         .on::<req::Completion>(handlers::handle_completion)?
+        .on::<req::ResolveCompletionItem>(handlers::handle_completion_resolve)?
         .on::<req::CodeActionRequest>(handlers::handle_code_action)?
         .on::<req::CodeLensRequest>(handlers::handle_code_lens)?
         .on::<req::CodeLensResolve>(handlers::handle_code_lens_resolve)?
@@ -502,8 +557,10 @@ fn on_request(
         .on::<req::Rename>(handlers::handle_rename)?
         .on::<req::References>(handlers::handle_references)?
         .on::<req::Formatting>(handlers::handle_formatting)?
+        .on::<req::RangeFormatting>(handlers::handle_range_formatting)?
         .on::<req::DocumentHighlightRequest>(handlers::handle_document_highlight)?
         .on::<req::InlayHints>(handlers::handle_inlay_hints)?
+        .on::<req::Ssr>(handlers::handle_ssr)?
         .on::<req::CallHierarchyPrepare>(handlers::handle_call_hierarchy_prepare)?
         .on::<req::CallHierarchyIncomingCalls>(handlers::handle_call_hierarchy_incoming)?
         .on::<req::CallHierarchyOutgoingCalls>(handlers::handle_call_hierarchy_outgoing)?
@@ -511,6 +568,69 @@ fn on_request(
     Ok(())
 }
 
+/// Applies the content changes from a `textDocument/didChange` notification to
+/// the `text` of the document. Returns the corresponding edits, or `None` if the
+/// whole text was replaced.
+///
+/// Like the VFS, `text` uses `\n` line endings, so that the edits match the
+/// text the analysis sees.
+fn apply_document_changes(
+    text: &mut String,
+    content_changes: Vec<TextDocumentContentChangeEvent>,
+) -> Option<Vec<AtomTextEdit>> {
+    let mut edits = Some(Vec::new());
+    for change in content_changes {
+        let change_text = normalize_line_endings(change.text);
+        match change.range {
+            Some(range) => {
+                let start = offset_at(text, range.start);
+                let end = offset_at(text, range.end).max(start);
+                text.replace_range(start..end, &change_text);
+                if let Some(edits) = &mut edits {
+                    let range =
+                        TextRange::from_to(TextUnit::from_usize(start), TextUnit::from_usize(end));
+                    edits.push(AtomTextEdit::replace(range, change_text));
+                }
+            }
+            None => {
+                *text = change_text;
+                edits = None;
+            }
+        }
+    }
+    edits
+}
+
+/// Converts an LSP position to an offset in `text`. Positions past the end of
+/// a line or of the text, which some clients send, are clamped.
+fn offset_at(text: &str, position: Position) -> usize {
+    let mut line_start = 0;
+    for _ in 0..position.line {
+        match text[line_start..].find('\n') {
+            Some(newline) => line_start += newline + 1,
+            None => return text.len(),
+        }
+    }
+    let line = &text[line_start..];
+    let line = &line[..line.find('\n').unwrap_or_else(|| line.len())];
+    let mut col_utf16 = 0;
+    for (offset, c) in line.char_indices() {
+        if col_utf16 >= position.character {
+            return line_start + offset;
+        }
+        col_utf16 += c.len_utf16() as u64;
+    }
+    line_start + line.len()
+}
+
+fn normalize_line_endings(text: String) -> String {
+    if text.contains('\r') {
+        text.replace("\r\n", "\n")
+    } else {
+        text
+    }
+}
+
 fn on_notification(
     msg_sender: &Sender<Message>,
     state: &mut WorldState,
@@ -540,6 +660,8 @@ fn on_notification(
         Ok(params) => {
             let uri = params.text_document.uri;
             let path = uri.to_file_path().map_err(|()| format!("invalid uri: {}", uri))?;
+            let text = normalize_line_endings(params.text_document.text.clone());
+            state.open_documents.insert(path.clone(), text);
             if let Some(file_id) =
                 state.vfs.write().add_file_overlay(&path, params.text_document.text)
             {
@@ -550,12 +672,25 @@ fn on_notification(
         Err(not) => not,
     };
     let not = match notification_cast::<req::DidChangeTextDocument>(not) {
-        Ok(mut params) => {
+        Ok(params) => {
             let uri = params.text_document.uri;
             let path = uri.to_file_path().map_err(|()| format!("invalid uri: {}", uri))?;
-            let text =
-                params.content_changes.pop().ok_or_else(|| "empty changes".to_string())?.text;
-            state.vfs.write().change_file_overlay(path.as_path(), text);
+            let text = state
+                .open_documents
+                .get_mut(&path)
+                .ok_or_else(|| format!("document is not open: {}", uri))?;
+            let edits = apply_document_changes(text, params.content_changes);
+            let mut vfs = state.vfs.write();
+            let mut overlay = text.clone();
+            if let Some(file) = vfs.path2file(&path) {
+                // The VFS takes the line endings of the document from the text.
+                if vfs.file_line_endings(file) == LineEndings::Dos {
+                    overlay = overlay.replace('\n', "\r\n");
+                }
+                let change = DocumentChange { text: text.clone(), edits };
+                state.pending_edits.entry(FileId(file.0)).or_default().push(change);
+            }
+            vfs.change_file_overlay(path.as_path(), overlay);
             return Ok(());
         }
         Err(not) => not,
@@ -571,9 +706,12 @@ fn on_notification(
         Ok(params) => {
             let uri = params.text_document.uri;
             let path = uri.to_file_path().map_err(|()| format!("invalid uri: {}", uri))?;
+            state.open_documents.remove(&path);
             if let Some(file_id) = state.vfs.write().remove_file_overlay(path.as_path()) {
                 subs.remove_sub(FileId(file_id.0));
+                state.pending_edits.remove(&FileId(file_id.0));
             }
+            state.semantic_tokens_cache.write().remove(&uri);
             let params =
                 req::PublishDiagnosticsParams { uri, diagnostics: Vec::new(), version: None };
             let not = notification_new::<req::PublishDiagnostics>(params);
@@ -810,3 +948,94 @@ where
 {
     Request::new(id, R::METHOD.to_string(), params)
 }
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::{Position, Range, TextDocumentContentChangeEvent};
+    use ra_syntax::TextRange;
+
+    use super::apply_document_changes;
+
+    fn change(
+        range: Option<((u64, u64), (u64, u64))>,
+        text: &str,
+    ) -> TextDocumentContentChangeEvent {
+        TextDocumentContentChangeEvent {
+            range: range.map(|((l1, c1), (l2, c2))| {
+                Range::new(Position::new(l1, c1), Position::new(l2, c2))
+            }),
+            range_length: None,
+            text: text.to_string(),
+        }
+    }
+
+    fn apply(
+        text: &mut String,
+        changes: Vec<TextDocumentContentChangeEvent>,
+    ) -> Option<Vec<(TextRange, String)>> {
+        let edits = apply_document_changes(text, changes)?;
+        Some(edits.into_iter().map(|it| (it.delete, it.insert)).collect())
+    }
+
+    #[test]
+    fn test_apply_document_changes() {
+        let mut text = String::new();
+        assert_eq!(apply(&mut text, vec![]), Some(vec![]));
+        assert_eq!(text, "");
+
+        let edits = apply(&mut text, vec![change(Some(((0, 0), (0, 0))), "fn main() {\n}\n")]);
+        assert_eq!(text, "fn main() {\n}\n");
+        assert_eq!(
+            edits,
+            Some(vec![(TextRange::offset_len(0.into(), 0.into()), "fn main() {\n}\n".into())])
+        );
+
+        let edits = apply(
+            &mut text,
+            vec![change(Some(((1, 0), (1, 0))), "    92\n"), change(Some(((0, 3), (0, 7))), "foo")],
+        );
+        assert_eq!(text, "fn foo() {\n    92\n}\n");
+        assert_eq!(
+            edits,
+            Some(vec![
+                (TextRange::offset_len(12.into(), 0.into()), "    92\n".into()),
+                (TextRange::from_to(3.into(), 7.into()), "foo".into()),
+            ])
+        );
+
+        let edits = apply(
+            &mut text,
+            vec![change(Some(((0, 0), (0, 2))), "pub fn"), change(None, "struct S;\n")],
+        );
+        assert_eq!(text, "struct S;\n");
+        assert_eq!(edits, None);
+    }
+
+    #[test]
+    fn test_apply_document_changes_normalizes_line_endings() {
+        let mut text = "fn main() {\n}\n".to_string();
+        let edits = apply(&mut text, vec![change(Some(((1, 0), (1, 0))), "    92\r\n")]);
+        assert_eq!(text, "fn main() {\n    92\n}\n");
+        assert_eq!(
+            edits,
+            Some(vec![(TextRange::offset_len(12.into(), 0.into()), "    92\n".into())])
+        );
+    }
+
+    #[test]
+    fn test_apply_document_changes_clamps_invalid_ranges() {
+        let mut text = "fn main() {}\n".to_string();
+        let edits = apply(
+            &mut text,
+            vec![change(Some(((0, 11), (0, 100))), "}"), change(Some(((5, 0), (6, 0))), "// 92")],
+        );
+        assert_eq!(text, "fn main() {}\n// 92");
+        assert_eq!(
+            edits,
+            Some(vec![
+                (TextRange::from_to(11.into(), 12.into()), "}".into()),
+                (TextRange::offset_len(13.into(), 0.into()), "// 92".into()),
+            ])
+        );
+    }
+}
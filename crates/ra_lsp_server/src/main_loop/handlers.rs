@@ -1,34 +1,44 @@
 //! This module is responsible for implementing handlers for Lanuage Server Protocol.
 //! The majority of requests are fulfilled by calling into the `ra_ide` crate.
 
-use std::{fmt::Write as _, io::Write as _};
+use std::{
+    fmt::Write as _,
+    io::Write as _,
+    sync::atomic::{AtomicU32, Ordering},
+};
 
 use lsp_server::ErrorCode;
 use lsp_types::{
     CallHierarchyIncomingCall, CallHierarchyIncomingCallsParams, CallHierarchyItem,
     CallHierarchyOutgoingCall, CallHierarchyOutgoingCallsParams, CallHierarchyPrepareParams,
     CodeAction, CodeActionResponse, CodeLens, Command, CompletionItem, Diagnostic,
-    DocumentFormattingParams, DocumentHighlight, DocumentSymbol, FoldingRange, FoldingRangeParams,
-    Hover, HoverContents, Location, MarkupContent, MarkupKind, Position, PrepareRenameResponse,
-    Range, RenameParams, SymbolInformation, TextDocumentIdentifier, TextEdit, WorkspaceEdit,
+    DocumentFormattingParams, DocumentHighlight, DocumentRangeFormattingParams, DocumentSymbol,
+    FoldingRange, FoldingRangeParams, Hover, HoverContents, Location, MarkupContent, MarkupKind,
+    NumberOrString, Position, PrepareRenameResponse, Range, RenameParams, SymbolInformation,
+    TextDocumentIdentifier, TextEdit, WorkspaceEdit,
 };
 use ra_ide::{
-    AssistId, FileId, FilePosition, FileRange, Query, RangeInfo, Runnable, RunnableKind,
-    SearchScope,
+    AssistId, FileId, FilePosition, FileRange, HighlightedRange, Query, RangeInfo, Runnable,
+    RunnableKind, SearchScope,
 };
 use ra_prof::profile;
-use ra_syntax::{AstNode, SyntaxKind, TextRange, TextUnit};
+use ra_project_model::TargetKind;
+use ra_syntax::{AstNode, SmolStr, SyntaxKind, TextRange, TextUnit};
 use rustc_hash::FxHashMap;
 use serde::{Deserialize, Serialize};
 use serde_json::to_value;
 
 use crate::{
-    cargo_target_spec::{runnable_args, CargoTargetSpec},
+    cargo_target_spec::{debug_runnable_args, runnable_args, CargoTargetSpec},
     conv::{
         to_call_hierarchy_item, to_location, Conv, ConvWith, FoldConvCtx, MapConvWith, TryConvWith,
         TryConvWithToVec,
     },
     req::{self, Decoration, InlayHint, InlayHintsParams, InlayKind},
+    semantic_tokens::{
+        self, SemanticTokens, SemanticTokensBuilder, SemanticTokensDelta,
+        SemanticTokensFullDeltaResult,
+    },
     world::WorldSnapshot,
     LspError, Result,
 };
@@ -147,6 +157,15 @@ pub fn handle_join_lines(
     world.analysis().join_lines(frange)?.try_conv_with(&world)
 }
 
+pub fn handle_ssr(world: WorldSnapshot, params: req::SsrParams) -> Result<req::SourceChange> {
+    let _p = profile("handle_ssr");
+    world
+        .analysis()
+        .structural_search_replace(&params.arg)?
+        .map_err(|err| LspError::new(ErrorCode::InvalidParams as i32, err.to_string()))?
+        .try_conv_with(&world)
+}
+
 pub fn handle_on_enter(
     world: WorldSnapshot,
     params: req::TextDocumentPositionParams,
@@ -292,6 +311,20 @@ pub fn handle_goto_definition(
     Ok(Some(res))
 }
 
+pub fn handle_goto_declaration(
+    world: WorldSnapshot,
+    params: req::TextDocumentPositionParams,
+) -> Result<Option<req::GotoDeclarationResponse>> {
+    let _p = profile("handle_goto_declaration");
+    let position = params.try_conv_with(&world)?;
+    let nav_info = match world.analysis().goto_declaration(position)? {
+        None => return Ok(None),
+        Some(it) => it,
+    };
+    let res = (position.file_id, nav_info).try_conv_with(&world)?;
+    Ok(Some(res))
+}
+
 pub fn handle_goto_implementation(
     world: WorldSnapshot,
     params: req::TextDocumentPositionParams,
@@ -339,12 +372,18 @@ pub fn handle_runnables(
     let offset = params.position.map(|it| it.conv_with(&line_index));
     let mut res = Vec::new();
     let workspace_root = world.workspace_root_for(file_id);
-    for runnable in world.analysis().runnables(file_id)? {
-        if let Some(offset) = offset {
-            if !runnable.range.contains_inclusive(offset) {
-                continue;
-            }
+    let mut runnables = file_runnables(&world, file_id)?;
+    if let Some(offset) = offset {
+        runnables.retain(|it| it.range.contains_inclusive(offset));
+        // The innermost runnable at the cursor is most likely the one to run, so it goes first
+        let innermost =
+            runnables.iter().enumerate().min_by_key(|(_, it)| it.range.len()).map(|(idx, _)| idx);
+        if let Some(idx) = innermost {
+            let runnable = runnables.remove(idx);
+            runnables.insert(0, runnable);
         }
+    }
+    for runnable in runnables {
         res.push(to_lsp_runnable(&world, file_id, runnable)?);
     }
     let mut check_args = vec!["check".to_string()];
@@ -371,6 +410,23 @@ pub fn handle_runnables(
     Ok(res)
 }
 
+/// The runnables of the file which cargo can run: doc tests only exist in
+/// library targets.
+fn file_runnables(world: &WorldSnapshot, file_id: FileId) -> Result<Vec<Runnable>> {
+    let mut runnables = world.analysis().runnables(file_id)?;
+    let is_lib = match CargoTargetSpec::for_file(world, file_id)? {
+        Some(spec) => spec.target_kind == TargetKind::Lib,
+        None => true,
+    };
+    if !is_lib {
+        runnables.retain(|it| match it.kind {
+            RunnableKind::DocTest { .. } => false,
+            _ => true,
+        });
+    }
+    Ok(runnables)
+}
+
 pub fn handle_decorations(
     world: WorldSnapshot,
     params: TextDocumentIdentifier,
@@ -408,16 +464,106 @@ pub fn handle_completion(
         return Ok(None);
     }
 
-    let items = match world.analysis().completions(position)? {
+    let items = match world.analysis().unresolved_completions(position)? {
         None => return Ok(None),
         Some(items) => items,
     };
     let line_index = world.analysis().file_line_index(position.file_id)?;
     let line_endings = world.file_line_endings(position.file_id);
-    let items: Vec<CompletionItem> =
-        items.into_iter().map(|item| item.conv_with((&line_index, line_endings))).collect();
+    let mut res = Vec::with_capacity(items.len());
+    for item in items {
+        let mut import_to_add = item.import_to_add().map(|it| it.to_vec());
+        let details_source = item.details_source();
+        let mut completion = item.conv_with((&line_index, line_endings));
+
+        // Clients which can't resolve edits get the `use` right away
+        if !world.options.resolve_completion_edits {
+            if let Some(path) = import_to_add.take() {
+                let resolved =
+                    world.analysis().resolve_completion(position, Some(path.as_slice()), None)?;
+                if let Some(edit) = resolved.import_edit {
+                    let edits = completion.additional_text_edits.get_or_insert_with(Vec::new);
+                    edits.extend(
+                        edit.as_atoms()
+                            .iter()
+                            .map(|atom| atom.conv_with((&line_index, line_endings))),
+                    );
+                }
+            }
+        }
 
-    Ok(Some(items.into()))
+        if import_to_add.is_some() || details_source.is_some() {
+            let details_source = match details_source {
+                Some(it) => {
+                    let line_index = world.analysis().file_line_index(it.file_id)?;
+                    Some(to_location(it.file_id, it.range, &world, &line_index)?)
+                }
+                None => None,
+            };
+            let data = CompletionResolveData {
+                position: params.text_document_position.clone(),
+                import_to_add: import_to_add
+                    .map(|path| path.iter().map(|it| it.to_string()).collect()),
+                details_source,
+            };
+            completion.data = Some(to_value(data).unwrap());
+        }
+        res.push(completion);
+    }
+
+    Ok(Some(res.into()))
+}
+
+/// What `handle_completion_resolve` needs to compute the rest of an item: see
+/// `CompletionItem::import_to_add` and `CompletionItem::details_source`.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CompletionResolveData {
+    position: req::TextDocumentPositionParams,
+    import_to_add: Option<Vec<String>>,
+    details_source: Option<Location>,
+}
+
+pub fn handle_completion_resolve(
+    world: WorldSnapshot,
+    mut original_item: CompletionItem,
+) -> Result<CompletionItem> {
+    let _p = profile("handle_completion_resolve");
+    let data = match original_item.data.clone() {
+        Some(it) => it,
+        None => return Ok(original_item),
+    };
+    let data: CompletionResolveData = serde_json::from_value(data)?;
+    let position = data.position.try_conv_with(&world)?;
+    let details_source = match data.details_source {
+        Some(location) => {
+            let text_document = TextDocumentIdentifier::new(location.uri);
+            Some((&text_document, location.range).try_conv_with(&world)?)
+        }
+        None => None,
+    };
+    let import_to_add: Option<Vec<SmolStr>> =
+        data.import_to_add.map(|path| path.into_iter().map(SmolStr::from).collect());
+
+    let resolved = world.analysis().resolve_completion(
+        position,
+        import_to_add.as_ref().map(|it| it.as_slice()),
+        details_source,
+    )?;
+    if original_item.documentation.is_none() {
+        original_item.documentation = resolved.documentation.map(|it| it.conv());
+    }
+    if original_item.detail.is_none() {
+        original_item.detail = resolved.detail;
+    }
+    if let Some(edit) = resolved.import_edit {
+        let line_index = world.analysis().file_line_index(position.file_id)?;
+        let line_endings = world.file_line_endings(position.file_id);
+        let edits = original_item.additional_text_edits.get_or_insert_with(Vec::new);
+        edits
+            .extend(edit.as_atoms().iter().map(|atom| atom.conv_with((&line_index, line_endings))));
+    }
+    Ok(original_item)
 }
 
 pub fn handle_folding_range(
@@ -487,7 +633,10 @@ pub fn handle_prepare_rename(
     let _p = profile("handle_prepare_rename");
     let position = params.try_conv_with(&world)?;
 
-    let optional_change = world.analysis().rename(position, "dummy")?;
+    let optional_change = world
+        .analysis()
+        .rename(position, "dummy")?
+        .map_err(|err| LspError::new(ErrorCode::InvalidParams as i32, err.to_string()))?;
     let range = match optional_change {
         None => return Ok(None),
         Some(it) => it.range,
@@ -511,7 +660,10 @@ pub fn handle_rename(world: WorldSnapshot, params: RenameParams) -> Result<Optio
         .into());
     }
 
-    let optional_change = world.analysis().rename(position, &*params.new_name)?;
+    let optional_change = world
+        .analysis()
+        .rename(position, &*params.new_name)?
+        .map_err(|err| LspError::new(ErrorCode::InvalidParams as i32, err.to_string()))?;
     let change = match optional_change {
         None => return Ok(None),
         Some(it) => it.info,
@@ -562,13 +714,64 @@ pub fn handle_formatting(
     let _p = profile("handle_formatting");
     let file_id = params.text_document.try_conv_with(&world)?;
     let file = world.analysis().file_text(file_id)?;
-    let crate_ids = world.analysis().crate_for(file_id)?;
 
     let file_line_index = world.analysis().file_line_index(file_id)?;
     let end_position = TextUnit::of_str(&file).conv_with(&file_line_index);
 
+    let formatted = match run_rustfmt(&world, &params.text_document, file_id, &file)? {
+        Some(it) => it,
+        None => return Ok(None),
+    };
+
+    Ok(Some(vec![TextEdit {
+        range: Range::new(Position::new(0, 0), end_position),
+        new_text: formatted,
+    }]))
+}
+
+pub fn handle_range_formatting(
+    world: WorldSnapshot,
+    params: DocumentRangeFormattingParams,
+) -> Result<Option<Vec<TextEdit>>> {
+    let _p = profile("handle_range_formatting");
+    let file_id = params.text_document.try_conv_with(&world)?;
+    let line_index = world.analysis().file_line_index(file_id)?;
+    let range = params.range.conv_with(&line_index);
+
+    let target = match world.analysis().formatting_range(FileRange { file_id, range })? {
+        Some(it) => it,
+        None => return Ok(None),
+    };
+    let file = world.analysis().file_text(file_id)?;
+    let items = &file[target.range];
+
+    let new_text = match run_rustfmt(&world, &params.text_document, file_id, items)? {
+        Some(it) => target.reindent(&it),
+        None => {
+            // rustfmt could not parse the items, still fix their indentation
+            log::info!("rustfmt failed to parse the range, only reindenting it");
+            target.fallback().to_string()
+        }
+    };
+
+    Ok(Some(vec![TextEdit { range: target.range.conv_with(&line_index), new_text }]))
+}
+
+/// Pipes `text` through the configured rustfmt. The process is started in the
+/// directory of the document, so that rustfmt picks up the project's
+/// `rustfmt.toml`.
+fn run_rustfmt(
+    world: &WorldSnapshot,
+    text_document: &TextDocumentIdentifier,
+    file_id: FileId,
+    text: &str,
+) -> Result<Option<String>> {
+    let crate_ids = world.analysis().crate_for(file_id)?;
+
     use std::process;
-    let mut rustfmt = process::Command::new("rustfmt");
+    let command = &world.options.rustfmt_command;
+    let mut rustfmt = process::Command::new(command);
+    rustfmt.args(&world.options.rustfmt_args);
     if let Some(&crate_id) = crate_ids.first() {
         // Assume all crates are in the same edition
         let edition = world.analysis().crate_edition(crate_id)?;
@@ -576,14 +779,29 @@ pub fn handle_formatting(
     }
     rustfmt.stdin(process::Stdio::piped()).stdout(process::Stdio::piped());
 
-    if let Ok(path) = params.text_document.uri.to_file_path() {
+    if let Ok(path) = text_document.uri.to_file_path() {
         if let Some(parent) = path.parent() {
             rustfmt.current_dir(parent);
         }
     }
-    let mut rustfmt = rustfmt.spawn()?;
+    let mut rustfmt = match rustfmt.spawn() {
+        Ok(it) => it,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return Err(LspError::new(
+                -32900,
+                format!(
+                    "failed to run `{}`: the formatter was not found. \
+                     Install it with `rustup component add rustfmt` \
+                     or set `rust-analyzer.rustfmt.command`",
+                    command
+                ),
+            )
+            .into());
+        }
+        Err(err) => return Err(err.into()),
+    };
 
-    rustfmt.stdin.as_mut().unwrap().write_all(file.as_bytes())?;
+    rustfmt.stdin.as_mut().unwrap().write_all(text.as_bytes())?;
 
     let output = rustfmt.wait_with_output()?;
     let captured_stdout = String::from_utf8(output.stdout)?;
@@ -600,7 +818,7 @@ pub fn handle_formatting(
                 return Ok(None);
             }
             _ => {
-                // Something else happened - e.g. `rustfmt` is missing or caught a signal
+                // Something else happened - e.g. `rustfmt` caught a signal
                 return Err(LspError::new(
                     -32900,
                     format!(
@@ -615,10 +833,7 @@ pub fn handle_formatting(
         }
     }
 
-    Ok(Some(vec![TextEdit {
-        range: Range::new(Position::new(0, 0), end_position),
-        new_text: captured_stdout,
-    }]))
+    Ok(Some(captured_stdout))
 }
 
 pub fn handle_code_action(
@@ -636,9 +851,8 @@ pub fn handle_code_action(
 
     let fixes_from_diagnostics = diagnostics
         .into_iter()
-        .filter_map(|d| Some((d.range, d.fix?)))
-        .filter(|(diag_range, _fix)| diag_range.intersection(&range).is_some())
-        .map(|(_range, fix)| fix);
+        .filter(|d| d.range.intersection(&range).is_some())
+        .flat_map(|d| d.fixes);
 
     for source_edit in fixes_from_diagnostics {
         let title = source_edit.label.clone();
@@ -717,52 +931,111 @@ pub fn handle_code_lens(
     let file_id = params.text_document.try_conv_with(&world)?;
     let line_index = world.analysis().file_line_index(file_id)?;
 
+    let feature_flags = world.feature_flags();
     let mut lenses: Vec<CodeLens> = Default::default();
 
     // Gather runnables
-    for runnable in world.analysis().runnables(file_id)? {
-        let title = match &runnable.kind {
-            RunnableKind::Test { .. } | RunnableKind::TestMod { .. } => "▶️Run Test",
-            RunnableKind::Bench { .. } => "Run Bench",
-            RunnableKind::Bin => "Run",
-        }
-        .to_string();
-        let r = to_lsp_runnable(&world, file_id, runnable)?;
-        let lens = CodeLens {
-            range: r.range,
-            command: Some(Command {
-                title,
-                command: "rust-analyzer.runSingle".into(),
-                arguments: Some(vec![to_value(r).unwrap()]),
-            }),
-            data: None,
+    for runnable in file_runnables(&world, file_id)? {
+        let debug_lens = if feature_flags.get("lens.debug") {
+            to_lsp_debug_runnable(&world, file_id, &runnable)?.map(|r| CodeLens {
+                range: r.range,
+                command: Some(Command {
+                    title: "Debug".into(),
+                    command: "rust-analyzer.debugSingle".into(),
+                    arguments: Some(vec![to_value(r).unwrap()]),
+                }),
+                data: None,
+            })
+        } else {
+            None
         };
 
-        lenses.push(lens);
+        if feature_flags.get("lens.run") {
+            let title = match &runnable.kind {
+                RunnableKind::Test { .. } | RunnableKind::TestMod { .. } => "▶️Run Test",
+                RunnableKind::Bench { .. } => "Run Bench",
+                RunnableKind::DocTest { .. } => "▶️Run Doctest",
+                RunnableKind::Bin => "Run",
+            }
+            .to_string();
+            let r = to_lsp_runnable(&world, file_id, runnable)?;
+            let lens = CodeLens {
+                range: r.range,
+                command: Some(Command {
+                    title,
+                    command: "rust-analyzer.runSingle".into(),
+                    arguments: Some(vec![to_value(r).unwrap()]),
+                }),
+                data: None,
+            };
+
+            lenses.push(lens);
+        }
+        lenses.extend(debug_lens);
     }
 
+    let structure = world.analysis().file_structure(file_id)?;
+
     // Handle impls
-    lenses.extend(
-        world
-            .analysis()
-            .file_structure(file_id)?
-            .into_iter()
-            .filter(|it| match it.kind {
-                SyntaxKind::TRAIT_DEF | SyntaxKind::STRUCT_DEF | SyntaxKind::ENUM_DEF => true,
-                _ => false,
-            })
-            .map(|it| {
-                let range = it.node_range.conv_with(&line_index);
-                let pos = range.start;
-                let lens_params =
-                    req::TextDocumentPositionParams::new(params.text_document.clone(), pos);
-                CodeLens {
-                    range,
-                    command: None,
-                    data: Some(to_value(CodeLensResolveData::Impls(lens_params)).unwrap()),
-                }
-            }),
-    );
+    if feature_flags.get("lens.implementations") {
+        lenses.extend(
+            structure
+                .iter()
+                .filter(|it| match it.kind {
+                    SyntaxKind::TRAIT_DEF | SyntaxKind::STRUCT_DEF | SyntaxKind::ENUM_DEF => true,
+                    _ => false,
+                })
+                .map(|it| {
+                    let range = it.node_range.conv_with(&line_index);
+                    let pos = range.start;
+                    let lens_params =
+                        req::TextDocumentPositionParams::new(params.text_document.clone(), pos);
+                    CodeLens {
+                        range,
+                        command: None,
+                        data: Some(to_value(CodeLensResolveData::Impls(lens_params)).unwrap()),
+                    }
+                }),
+        );
+    }
+
+    // Handle references
+    if feature_flags.get("lens.references") {
+        lenses.extend(
+            structure
+                .iter()
+                .filter(|it| match it.kind {
+                    SyntaxKind::FN_DEF | SyntaxKind::CONST_DEF | SyntaxKind::STATIC_DEF => true,
+                    _ => false,
+                })
+                .map(|it| {
+                    let range = it.node_range.conv_with(&line_index);
+                    // The references are looked up from the name of the item
+                    let pos = it.navigation_range.start().conv_with(&line_index);
+                    let lens_params =
+                        req::TextDocumentPositionParams::new(params.text_document.clone(), pos);
+                    CodeLens {
+                        range,
+                        command: None,
+                        data: Some(to_value(CodeLensResolveData::References(lens_params)).unwrap()),
+                    }
+                }),
+        );
+    }
+
+    // Handle incomplete trait impls
+    if feature_flags.get("lens.missing-members") {
+        for missing in world.analysis().missing_trait_members(file_id)? {
+            let members = missing.members.iter().map(|it| format!("`{}`", it)).collect::<Vec<_>>();
+            let title = format!("missing from `{}`: {}", missing.trait_name, members.join(", "));
+            lenses.push(CodeLens {
+                range: missing.range.conv_with(&line_index),
+                // A command without an id is displayed as plain text
+                command: Some(Command { title, command: String::new(), arguments: None }),
+                data: None,
+            });
+        }
+    }
 
     Ok(Some(lenses))
 }
@@ -771,6 +1044,7 @@ pub fn handle_code_lens(
 #[serde(rename_all = "camelCase")]
 enum CodeLensResolveData {
     Impls(req::TextDocumentPositionParams),
+    References(req::TextDocumentPositionParams),
 }
 
 pub fn handle_code_lens_resolve(world: WorldSnapshot, code_lens: CodeLens) -> Result<CodeLens> {
@@ -810,6 +1084,39 @@ pub fn handle_code_lens_resolve(world: WorldSnapshot, code_lens: CodeLens) -> Re
             };
             Ok(CodeLens { range: code_lens.range, command: Some(cmd), data: None })
         }
+        Some(CodeLensResolveData::References(lens_params)) => {
+            let position = lens_params.try_conv_with(&world)?;
+            let locations: Vec<Location> = match world.analysis().find_all_refs(position, None)? {
+                Some(refs) => refs
+                    .references()
+                    .iter()
+                    .filter_map(|r| {
+                        let line_index =
+                            world.analysis().file_line_index(r.file_range.file_id).ok()?;
+                        to_location(r.file_range.file_id, r.file_range.range, &world, &line_index)
+                            .ok()
+                    })
+                    .collect(),
+                None => vec![],
+            };
+
+            let title = if locations.len() == 1 {
+                "1 reference".into()
+            } else {
+                format!("{} references", locations.len())
+            };
+
+            let cmd = Command {
+                title,
+                command: "rust-analyzer.showReferences".into(),
+                arguments: Some(vec![
+                    to_value(&lens_params.text_document.uri).unwrap(),
+                    to_value(code_lens.range.start).unwrap(),
+                    to_value(locations).unwrap(),
+                ]),
+            };
+            Ok(CodeLens { range: code_lens.range, command: Some(cmd), data: None })
+        }
         None => Ok(CodeLens {
             range: code_lens.range,
             command: Some(Command { title: "Error".into(), ..Default::default() }),
@@ -859,7 +1166,7 @@ pub fn publish_diagnostics(
         .map(|d| Diagnostic {
             range: d.range.conv_with(&line_index),
             severity: Some(d.severity.conv()),
-            code: None,
+            code: d.code.map(|it| NumberOrString::String(it.to_string())),
             source: Some("rust-analyzer".to_string()),
             message: d.message,
             related_information: None,
@@ -892,6 +1199,7 @@ fn to_lsp_runnable(
         RunnableKind::Test { name } => format!("test {}", name),
         RunnableKind::TestMod { path } => format!("test-mod {}", path),
         RunnableKind::Bench { name } => format!("bench {}", name),
+        RunnableKind::DocTest { test_id } => format!("doctest {}", test_id),
         RunnableKind::Bin => "run binary".to_string(),
     };
     Ok(req::Runnable {
@@ -907,6 +1215,38 @@ fn to_lsp_runnable(
         cwd: world.workspace_root_for(file_id).map(|root| root.to_string_lossy().to_string()),
     })
 }
+
+fn to_lsp_debug_runnable(
+    world: &WorldSnapshot,
+    file_id: FileId,
+    runnable: &Runnable,
+) -> Result<Option<req::DebugRunnable>> {
+    let (cargo_args, executable_args) = match debug_runnable_args(world, file_id, &runnable.kind)? {
+        Some(it) => it,
+        None => return Ok(None),
+    };
+    let line_index = world.analysis().file_line_index(file_id)?;
+    let label = match &runnable.kind {
+        RunnableKind::Test { name } => format!("debug test {}", name),
+        RunnableKind::TestMod { path } => format!("debug test-mod {}", path),
+        RunnableKind::Bench { name } => format!("debug bench {}", name),
+        RunnableKind::DocTest { test_id } => format!("debug doctest {}", test_id),
+        RunnableKind::Bin => "debug binary".to_string(),
+    };
+    Ok(Some(req::DebugRunnable {
+        range: runnable.range.conv_with(&line_index),
+        label,
+        cargo_args,
+        executable_args,
+        env: {
+            let mut m = FxHashMap::default();
+            m.insert("RUST_BACKTRACE".to_string(), "short".to_string());
+            m
+        },
+        cwd: world.workspace_root_for(file_id).map(|root| root.to_string_lossy().to_string()),
+    }))
+}
+
 fn highlight(world: &WorldSnapshot, file_id: FileId) -> Result<Vec<Decoration>> {
     let line_index = world.analysis().file_line_index(file_id)?;
     let res = world
@@ -915,13 +1255,87 @@ fn highlight(world: &WorldSnapshot, file_id: FileId) -> Result<Vec<Decoration>>
         .into_iter()
         .map(|h| Decoration {
             range: h.range.conv_with(&line_index),
-            tag: h.tag,
+            tag: h.highlight.to_string(),
             binding_hash: h.binding_hash.map(|x| x.to_string()),
         })
         .collect();
     Ok(res)
 }
 
+pub fn handle_semantic_tokens(
+    world: WorldSnapshot,
+    params: semantic_tokens::SemanticTokensParams,
+) -> Result<Option<SemanticTokens>> {
+    let _p = profile("handle_semantic_tokens");
+    let file_id = params.text_document.try_conv_with(&world)?;
+    let highlights = world.analysis().highlight(file_id)?;
+    let tokens = semantic_tokens(&world, file_id, highlights, Some(next_result_id()))?;
+    world.semantic_tokens_cache.write().insert(params.text_document.uri, tokens.clone());
+    Ok(Some(tokens))
+}
+
+pub fn handle_semantic_tokens_delta(
+    world: WorldSnapshot,
+    params: semantic_tokens::SemanticTokensDeltaParams,
+) -> Result<Option<SemanticTokensFullDeltaResult>> {
+    let _p = profile("handle_semantic_tokens_delta");
+    let file_id = params.text_document.try_conv_with(&world)?;
+    let highlights = world.analysis().highlight(file_id)?;
+    let tokens = semantic_tokens(&world, file_id, highlights, Some(next_result_id()))?;
+
+    let mut cache = world.semantic_tokens_cache.write();
+    let previous = cache.insert(params.text_document.uri, tokens.clone());
+    let res = match previous {
+        Some(previous) if previous.result_id.as_ref() == Some(&params.previous_result_id) => {
+            SemanticTokensFullDeltaResult::TokensDelta(SemanticTokensDelta {
+                edits: semantic_tokens::diff_tokens(&previous.data, &tokens.data),
+                result_id: tokens.result_id,
+            })
+        }
+        _ => SemanticTokensFullDeltaResult::Tokens(tokens),
+    };
+    Ok(Some(res))
+}
+
+pub fn handle_semantic_tokens_range(
+    world: WorldSnapshot,
+    params: semantic_tokens::SemanticTokensRangeParams,
+) -> Result<Option<SemanticTokens>> {
+    let _p = profile("handle_semantic_tokens_range");
+    let frange = (&params.text_document, params.range).try_conv_with(&world)?;
+    let highlights = world.analysis().highlight_range(frange)?;
+    let tokens = semantic_tokens(&world, frange.file_id, highlights, None)?;
+    Ok(Some(tokens))
+}
+
+fn next_result_id() -> String {
+    static NEXT_ID: AtomicU32 = AtomicU32::new(0);
+    NEXT_ID.fetch_add(1, Ordering::SeqCst).to_string()
+}
+
+fn semantic_tokens(
+    world: &WorldSnapshot,
+    file_id: FileId,
+    highlights: Vec<HighlightedRange>,
+    result_id: Option<String>,
+) -> Result<SemanticTokens> {
+    let line_index = world.analysis().file_line_index(file_id)?;
+    let text = world.analysis().file_text(file_id)?;
+    let tokens = highlights
+        .into_iter()
+        .map(|it| {
+            let (token_type, modifiers) = it.highlight.conv();
+            semantic_tokens::Token { range: it.range, token_type, modifiers }
+        })
+        .collect();
+
+    let mut builder = SemanticTokensBuilder::default();
+    for token in semantic_tokens::flatten(tokens) {
+        builder.push_multiline(&text, &line_index, token);
+    }
+    Ok(builder.build(result_id))
+}
+
 pub fn handle_inlay_hints(
     world: WorldSnapshot,
     params: InlayHintsParams,
@@ -938,6 +1352,8 @@ pub fn handle_inlay_hints(
             range: api_type.range.conv_with(&line_index),
             kind: match api_type.kind {
                 ra_ide::InlayKind::TypeHint => InlayKind::TypeHint,
+                ra_ide::InlayKind::ParameterHint => InlayKind::ParameterHint,
+                ra_ide::InlayKind::ChainingHint => InlayKind::ChainingHint,
             },
         })
         .collect())
@@ -4,6 +4,11 @@ use lsp_types::{Location, Position, Range, TextDocumentIdentifier, Url};
 use rustc_hash::FxHashMap;
 use serde::{Deserialize, Serialize};
 
+use crate::semantic_tokens::{
+    SemanticTokens, SemanticTokensDeltaParams, SemanticTokensFullDeltaResult, SemanticTokensParams,
+    SemanticTokensRangeParams,
+};
+
 pub use lsp_types::{
     notification::*, request::*, ApplyWorkspaceEditParams, CodeActionParams, CodeLens,
     CodeLensParams, CompletionParams, CompletionResponse, DiagnosticTag,
@@ -111,10 +116,34 @@ pub struct PublishDecorationsParams {
 #[serde(rename_all = "camelCase")]
 pub struct Decoration {
     pub range: Range,
-    pub tag: &'static str,
+    pub tag: String,
     pub binding_hash: Option<String>,
 }
 
+pub enum SemanticTokensFullRequest {}
+
+impl Request for SemanticTokensFullRequest {
+    type Params = SemanticTokensParams;
+    type Result = Option<SemanticTokens>;
+    const METHOD: &'static str = "textDocument/semanticTokens/full";
+}
+
+pub enum SemanticTokensFullDeltaRequest {}
+
+impl Request for SemanticTokensFullDeltaRequest {
+    type Params = SemanticTokensDeltaParams;
+    type Result = Option<SemanticTokensFullDeltaResult>;
+    const METHOD: &'static str = "textDocument/semanticTokens/full/delta";
+}
+
+pub enum SemanticTokensRangeRequest {}
+
+impl Request for SemanticTokensRangeRequest {
+    type Params = SemanticTokensRangeParams;
+    type Result = Option<SemanticTokens>;
+    const METHOD: &'static str = "textDocument/semanticTokens/range";
+}
+
 pub enum ParentModule {}
 
 impl Request for ParentModule {
@@ -138,6 +167,19 @@ pub struct JoinLinesParams {
     pub range: Range,
 }
 
+pub enum Ssr {}
+
+impl Request for Ssr {
+    type Params = SsrParams;
+    type Result = SourceChange;
+    const METHOD: &'static str = "rust-analyzer/ssr";
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SsrParams {
+    pub arg: String,
+}
+
 pub enum OnEnter {}
 
 impl Request for OnEnter {
@@ -172,6 +214,21 @@ pub struct Runnable {
     pub cwd: Option<String>,
 }
 
+/// Everything needed to build a runnable's binary and launch it under a
+/// debugger.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugRunnable {
+    pub range: Range,
+    pub label: String,
+    /// Arguments for `cargo` which build the binary without running it.
+    pub cargo_args: Vec<String>,
+    /// Arguments for the built binary, like the name of the test to run.
+    pub executable_args: Vec<String>,
+    pub env: FxHashMap<String, String>,
+    pub cwd: Option<String>,
+}
+
 #[derive(Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct SourceChange {
@@ -197,6 +254,8 @@ pub struct InlayHintsParams {
 #[derive(Debug, PartialEq, Eq, Deserialize, Serialize)]
 pub enum InlayKind {
     TypeHint,
+    ParameterHint,
+    ChainingHint,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
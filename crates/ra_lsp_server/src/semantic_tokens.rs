@@ -0,0 +1,380 @@
+//! Semantic Tokens helpers.
+//!
+//! Implements the `textDocument/semanticTokens` family of requests from the
+//! LSP 3.16. `lsp_types` doesn't have the protocol types yet, so they are
+//! defined here.
+
+use std::cmp::Reverse;
+
+use lsp_types::{Range, TextDocumentIdentifier};
+use ra_ide::LineIndex;
+use ra_syntax::{TextRange, TextUnit};
+use serde::{Deserialize, Serialize};
+
+use crate::conv::ConvWith;
+
+pub(crate) const ATTRIBUTE: &str = "attribute";
+pub(crate) const BUILTIN_TYPE: &str = "builtinType";
+pub(crate) const COMMENT: &str = "comment";
+pub(crate) const CONSTANT: &str = "constant";
+pub(crate) const FUNCTION: &str = "function";
+pub(crate) const KEYWORD: &str = "keyword";
+pub(crate) const LIFETIME: &str = "lifetime";
+pub(crate) const MACRO: &str = "macro";
+pub(crate) const NAMESPACE: &str = "namespace";
+pub(crate) const NUMBER: &str = "number";
+pub(crate) const PROPERTY: &str = "property";
+pub(crate) const SELF_TYPE: &str = "selfType";
+pub(crate) const STRING: &str = "string";
+pub(crate) const TYPE: &str = "type";
+pub(crate) const TYPE_PARAMETER: &str = "typeParameter";
+pub(crate) const VARIABLE: &str = "variable";
+
+pub(crate) const ATTRIBUTE_MODIFIER: &str = "attribute";
+pub(crate) const CONTROL_FLOW: &str = "controlFlow";
+pub(crate) const DECLARATION: &str = "declaration";
+pub(crate) const DOCUMENTATION: &str = "documentation";
+pub(crate) const MUTABLE: &str = "mutable";
+pub(crate) const STATIC: &str = "static";
+pub(crate) const UNSAFE: &str = "unsafe";
+
+pub(crate) const SUPPORTED_TYPES: &[&str] = &[
+    ATTRIBUTE,
+    BUILTIN_TYPE,
+    COMMENT,
+    CONSTANT,
+    FUNCTION,
+    KEYWORD,
+    LIFETIME,
+    MACRO,
+    NAMESPACE,
+    NUMBER,
+    PROPERTY,
+    SELF_TYPE,
+    STRING,
+    TYPE,
+    TYPE_PARAMETER,
+    VARIABLE,
+];
+
+pub(crate) const SUPPORTED_MODIFIERS: &[&str] =
+    &[ATTRIBUTE_MODIFIER, CONTROL_FLOW, DECLARATION, DOCUMENTATION, MUTABLE, STATIC, UNSAFE];
+
+/// Index of a token type in the legend.
+pub(crate) fn type_index(ty: &str) -> u32 {
+    SUPPORTED_TYPES.iter().position(|it| *it == ty).unwrap() as u32
+}
+
+/// Bit of a token modifier in the legend.
+pub(crate) fn modifier_mask(modifier: &str) -> u32 {
+    1 << SUPPORTED_MODIFIERS.iter().position(|it| *it == modifier).unwrap()
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticTokensLegend {
+    pub token_types: Vec<&'static str>,
+    pub token_modifiers: Vec<&'static str>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticTokensFullOptions {
+    pub delta: bool,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticTokensOptions {
+    pub legend: SemanticTokensLegend,
+    pub range: bool,
+    pub full: SemanticTokensFullOptions,
+}
+
+pub(crate) fn options() -> SemanticTokensOptions {
+    SemanticTokensOptions {
+        legend: SemanticTokensLegend {
+            token_types: SUPPORTED_TYPES.to_vec(),
+            token_modifiers: SUPPORTED_MODIFIERS.to_vec(),
+        },
+        range: true,
+        full: SemanticTokensFullOptions { delta: true },
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticTokensParams {
+    pub text_document: TextDocumentIdentifier,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticTokensDeltaParams {
+    pub text_document: TextDocumentIdentifier,
+    pub previous_result_id: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticTokensRangeParams {
+    pub text_document: TextDocumentIdentifier,
+    pub range: Range,
+}
+
+/// Tokens are encoded as groups of five integers: delta line, delta start
+/// character, length, token type index and token modifiers bitset.
+#[derive(Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticTokens {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result_id: Option<String>,
+    pub data: Vec<u32>,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticTokensEdit {
+    pub start: u32,
+    pub delete_count: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Vec<u32>>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticTokensDelta {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result_id: Option<String>,
+    pub edits: Vec<SemanticTokensEdit>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(untagged)]
+pub enum SemanticTokensFullDeltaResult {
+    Tokens(SemanticTokens),
+    TokensDelta(SemanticTokensDelta),
+}
+
+/// A highlighted range with the indices of its type and modifiers in the legend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Token {
+    pub(crate) range: TextRange,
+    pub(crate) token_type: u32,
+    pub(crate) modifiers: u32,
+}
+
+/// Highlighted ranges nest, e.g. an attribute contains the tokens inside of it,
+/// but semantic tokens must not overlap. Inner tokens win and inherit the
+/// modifiers of the ranges enclosing them (an attribute adds the attribute
+/// modifier), while the enclosing ranges keep only the parts not covered by
+/// inner tokens.
+pub(crate) fn flatten(mut tokens: Vec<Token>) -> Vec<Token> {
+    tokens.sort_by_key(|it| (it.range.start(), Reverse(it.range.end())));
+
+    let mut res = Vec::new();
+    // Enclosing tokens, each with the offset up to which it has been emitted
+    let mut stack: Vec<(Token, TextUnit)> = Vec::new();
+    for mut token in tokens {
+        while let Some(&(outer, emitted)) = stack.last() {
+            if token.range.is_subrange(&outer.range) {
+                break;
+            }
+            stack.pop();
+            push_part(&mut res, outer, emitted, outer.range.end());
+        }
+        if let Some((outer, emitted)) = stack.last_mut() {
+            push_part(&mut res, *outer, *emitted, token.range.start());
+            *emitted = token.range.end();
+            token.modifiers |= outer.modifiers;
+            if outer.token_type == type_index(ATTRIBUTE) {
+                token.modifiers |= modifier_mask(ATTRIBUTE_MODIFIER);
+            }
+        }
+        stack.push((token, token.range.start()));
+    }
+    while let Some((outer, emitted)) = stack.pop() {
+        push_part(&mut res, outer, emitted, outer.range.end());
+    }
+    res.sort_by_key(|it| it.range.start());
+    res
+}
+
+fn push_part(res: &mut Vec<Token>, token: Token, start: TextUnit, end: TextUnit) {
+    if start < end {
+        res.push(Token { range: TextRange::from_to(start, end), ..token });
+    }
+}
+
+/// Accumulates tokens, encoding each one relative to the previous.
+#[derive(Default)]
+pub(crate) struct SemanticTokensBuilder {
+    prev_line: u32,
+    prev_char: u32,
+    prev_end: Option<(u32, u32)>,
+    data: Vec<u32>,
+}
+
+impl SemanticTokensBuilder {
+    /// Push a new token onto the builder.
+    ///
+    /// Tokens must be pushed in order and must not span several lines. A token
+    /// which starts inside the previous one is dropped, as the protocol does not
+    /// allow overlapping tokens.
+    pub fn push(&mut self, range: Range, token_type: u32, token_modifiers: u32) {
+        let line = range.start.line as u32;
+        let start = range.start.character as u32;
+        if let Some(prev_end) = self.prev_end {
+            if (line, start) < prev_end {
+                return;
+            }
+        }
+
+        let mut push_line = line;
+        let mut push_char = start;
+        if !self.data.is_empty() {
+            push_line -= self.prev_line;
+            if push_line == 0 {
+                push_char -= self.prev_char;
+            }
+        }
+
+        // A token cannot be multiline
+        let token_len = range.end.character as u32 - start;
+
+        self.data.extend_from_slice(&[
+            push_line,
+            push_char,
+            token_len,
+            token_type,
+            token_modifiers,
+        ]);
+
+        self.prev_line = line;
+        self.prev_char = start;
+        self.prev_end = Some((line, start + token_len));
+    }
+
+    /// Push a token which may span several lines, like a comment or a string,
+    /// as one token per line.
+    pub fn push_multiline(&mut self, text: &str, line_index: &LineIndex, token: Token) {
+        let mut start = token.range.start();
+        for line in text[token.range].split('\n') {
+            let token_text = line.trim_end_matches('\r');
+            if !token_text.is_empty() {
+                let range = TextRange::offset_len(start, TextUnit::of_str(token_text));
+                self.push(range.conv_with(line_index), token.token_type, token.modifiers);
+            }
+            start += TextUnit::of_str(line) + TextUnit::of_char('\n');
+        }
+    }
+
+    pub fn build(self, result_id: Option<String>) -> SemanticTokens {
+        SemanticTokens { result_id, data: self.data }
+    }
+}
+
+/// Computes a single edit which turns `old` tokens into `new` ones.
+pub(crate) fn diff_tokens(old: &[u32], new: &[u32]) -> Vec<SemanticTokensEdit> {
+    let old_tokens = old.chunks(5);
+    let new_tokens = new.chunks(5);
+
+    let prefix = old_tokens.clone().zip(new_tokens.clone()).take_while(|(a, b)| a == b).count();
+    let max_suffix = old.len().min(new.len()) / 5 - prefix;
+    let suffix =
+        old_tokens.rev().zip(new_tokens.rev()).take(max_suffix).take_while(|(a, b)| a == b).count();
+
+    let start = prefix * 5;
+    let old_end = old.len() - suffix * 5;
+    let new_end = new.len() - suffix * 5;
+    if start == old_end && start == new_end {
+        return Vec::new();
+    }
+    vec![SemanticTokensEdit {
+        start: start as u32,
+        delete_count: (old_end - start) as u32,
+        data: Some(new[start..new_end].to_vec()),
+    }]
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::Position;
+
+    use super::*;
+
+    fn token(start: u32, end: u32, token_type: &str, modifiers: u32) -> Token {
+        Token {
+            range: TextRange::from_to(start.into(), end.into()),
+            token_type: type_index(token_type),
+            modifiers,
+        }
+    }
+
+    #[test]
+    fn encodes_tokens_relative_to_the_previous_one() {
+        let mut builder = SemanticTokensBuilder::default();
+        builder.push(Range::new(Position::new(0, 0), Position::new(0, 3)), 1, 0);
+        builder.push(Range::new(Position::new(0, 4), Position::new(0, 6)), 2, 1);
+        builder.push(Range::new(Position::new(2, 1), Position::new(2, 5)), 3, 0);
+        assert_eq!(builder.build(None).data, vec![0, 0, 3, 1, 0, 0, 4, 2, 2, 1, 2, 1, 4, 3, 0]);
+    }
+
+    #[test]
+    fn splits_multiline_tokens() {
+        let text = "/* a\n\nb */ x";
+        let line_index = LineIndex::new(text);
+        let mut builder = SemanticTokensBuilder::default();
+        builder.push_multiline(text, &line_index, token(0, 10, COMMENT, 0));
+        let comment = type_index(COMMENT);
+        assert_eq!(builder.build(None).data, vec![0, 0, 4, comment, 0, 2, 0, 4, comment, 0]);
+    }
+
+    #[test]
+    fn flattens_nested_tokens() {
+        let attribute = modifier_mask(ATTRIBUTE_MODIFIER);
+        let tokens =
+            vec![token(5, 8, STRING, 0), token(0, 10, ATTRIBUTE, 0), token(12, 14, KEYWORD, 0)];
+        assert_eq!(
+            flatten(tokens),
+            vec![
+                token(0, 5, ATTRIBUTE, 0),
+                token(5, 8, STRING, attribute),
+                token(8, 10, ATTRIBUTE, 0),
+                token(12, 14, KEYWORD, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn diff_insert_at_end() {
+        let old = [0, 0, 3, 0, 0, 0, 4, 2, 1, 0];
+        let new = [0, 0, 3, 0, 0, 0, 4, 2, 1, 0, 1, 0, 2, 3, 0];
+        let edits = diff_tokens(&old, &new);
+        assert_eq!(
+            edits,
+            vec![SemanticTokensEdit {
+                start: 10,
+                delete_count: 0,
+                data: Some(vec![1, 0, 2, 3, 0])
+            }]
+        );
+    }
+
+    #[test]
+    fn diff_remove_from_middle() {
+        let old = [0, 0, 3, 0, 0, 0, 4, 2, 1, 0, 1, 0, 2, 3, 0];
+        let new = [0, 0, 3, 0, 0, 1, 0, 2, 3, 0];
+        let edits = diff_tokens(&old, &new);
+        assert_eq!(
+            edits,
+            vec![SemanticTokensEdit { start: 5, delete_count: 5, data: Some(vec![]) }]
+        );
+    }
+
+    #[test]
+    fn diff_no_changes() {
+        let tokens = [0, 0, 3, 0, 0, 0, 4, 2, 1, 0];
+        assert!(diff_tokens(&tokens, &tokens).is_empty());
+    }
+}
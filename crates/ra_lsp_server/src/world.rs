@@ -16,16 +16,20 @@ use ra_cargo_watch::{
     url_from_path_with_drive_lowercasing, CheckOptions, CheckWatcher, CheckWatcherSharedState,
 };
 use ra_ide::{
-    Analysis, AnalysisChange, AnalysisHost, CrateGraph, FeatureFlags, FileId, LibraryData,
-    SourceRootId,
+    Analysis, AnalysisChange, AnalysisHost, CrateGraph, ExternSourceId, FeatureFlags, FileId,
+    LibraryData, SourceRootId,
 };
-use ra_project_model::{get_rustc_cfg_options, ProjectWorkspace};
+use ra_proc_macro::ProcMacroClient;
+use ra_project_model::{get_rustc_cfg_options, ExternResources, ProjectWorkspace};
+use ra_text_edit::AtomTextEdit;
 use ra_vfs::{LineEndings, RootEntry, Vfs, VfsChange, VfsFile, VfsRoot, VfsTask, Watch};
 use ra_vfs_glob::{Glob, RustPackageFilterBuilder};
 use relative_path::RelativePathBuf;
+use rustc_hash::FxHashMap;
 
 use crate::{
     main_loop::pending_requests::{CompletedRequest, LatestRequests},
+    semantic_tokens::SemanticTokens,
     LspError, Result,
 };
 
@@ -34,8 +38,13 @@ pub struct Options {
     pub publish_decorations: bool,
     pub supports_location_link: bool,
     pub line_folding_only: bool,
+    /// Whether the client can add the `use` of a completion item on resolve.
+    pub resolve_completion_edits: bool,
     pub max_inlay_hint_length: Option<usize>,
     pub cargo_watch: CheckOptions,
+    pub rustfmt_command: String,
+    pub rustfmt_args: Vec<String>,
+    pub proc_macro_srv: Option<PathBuf>,
 }
 
 /// `WorldState` is the primary mutable state of the language server
@@ -55,6 +64,25 @@ pub struct WorldState {
     pub task_receiver: Receiver<VfsTask>,
     pub latest_requests: Arc<RwLock<LatestRequests>>,
     pub check_watcher: CheckWatcher,
+    /// Last semantic tokens sent for each document, used to compute deltas.
+    pub semantic_tokens_cache: Arc<RwLock<FxHashMap<Url, SemanticTokens>>>,
+    /// Current text of the documents opened in the editor.
+    pub open_documents: FxHashMap<PathBuf, String>,
+    /// Changes to open documents which are not yet applied to the analysis,
+    /// one per `didChange` notification.
+    pub pending_edits: FxHashMap<FileId, Vec<DocumentChange>>,
+    extern_source_roots: FxHashMap<PathBuf, ExternSourceId>,
+    proc_macro_client: ProcMacroClient,
+}
+
+/// The edits made to an open document by a `didChange` notification.
+#[derive(Debug)]
+pub struct DocumentChange {
+    /// Text of the document after the change, with `\n` line endings.
+    pub text: String,
+    /// `None` means that the whole text was replaced, so the document can't be
+    /// reparsed incrementally.
+    pub edits: Option<Vec<AtomTextEdit>>,
 }
 
 /// An immutable snapshot of the world's state at a point in time.
@@ -65,6 +93,7 @@ pub struct WorldSnapshot {
     pub vfs: Arc<RwLock<Vfs>>,
     pub latest_requests: Arc<RwLock<LatestRequests>>,
     pub check_watcher: Arc<RwLock<CheckWatcherSharedState>>,
+    pub semantic_tokens_cache: Arc<RwLock<FxHashMap<Url, SemanticTokens>>>,
 }
 
 impl WorldState {
@@ -101,35 +130,33 @@ impl WorldState {
         let task_sender = Box::new(move |t| task_sender.send(t).unwrap());
         let (mut vfs, vfs_roots) = Vfs::new(roots, task_sender, watch);
         let roots_to_scan = vfs_roots.len();
+        let mut extern_source_roots = FxHashMap::default();
         for r in vfs_roots {
             let vfs_root_path = vfs.root2path(r);
             let is_local = folder_roots.iter().any(|it| vfs_root_path.starts_with(it));
             change.add_root(SourceRootId(r.0), is_local);
             change.set_debug_root_path(SourceRootId(r.0), vfs_root_path.display().to_string());
+            extern_source_roots.insert(vfs_root_path, ExternSourceId(r.0));
         }
 
-        // FIXME: Read default cfgs from config
-        let default_cfg_options = {
-            let mut opts = get_rustc_cfg_options();
-            opts.insert_atom("test".into());
-            opts.insert_atom("debug_assertion".into());
-            opts
+        let proc_macro_client = match &options.proc_macro_srv {
+            None => ProcMacroClient::dummy(),
+            Some(srv) => match ProcMacroClient::extern_process(srv, &[] as &[&str]) {
+                Ok(client) => client,
+                Err(err) => {
+                    log::error!("failed to run proc macro server {}: {}", srv.display(), err);
+                    ProcMacroClient::dummy()
+                }
+            },
         };
 
-        // Create crate graph from all the workspaces
-        let mut crate_graph = CrateGraph::default();
-        let mut load = |path: &std::path::Path| {
-            let vfs_file = vfs.load(path);
-            vfs_file.map(|f| FileId(f.0))
-        };
-        for ws in workspaces.iter() {
-            let (graph, crate_names) = ws.to_crate_graph(&default_cfg_options, &mut load);
-            let shift = crate_graph.extend(graph);
-            for (crate_id, name) in crate_names {
-                change.set_debug_crate_name(crate_id.shift(shift), name)
-            }
-        }
-        change.set_crate_graph(crate_graph);
+        load_crate_graph(
+            &workspaces,
+            &extern_source_roots,
+            &proc_macro_client,
+            &mut vfs,
+            &mut change,
+        );
 
         // FIXME: Figure out the multi-workspace situation
         let check_watcher =
@@ -147,7 +174,33 @@ impl WorldState {
             task_receiver,
             latest_requests: Default::default(),
             check_watcher,
+            semantic_tokens_cache: Default::default(),
+            open_documents: FxHashMap::default(),
+            pending_edits: FxHashMap::default(),
+            extern_source_roots,
+            proc_macro_client,
+        }
+    }
+
+    /// Applies the proc-macro dylibs built in the background, one
+    /// `ExternResources` per workspace, and reloads the crate graph so that
+    /// the macros get expanded.
+    pub fn set_proc_macro_dylib_paths(&mut self, resources: &[ExternResources]) {
+        let workspaces = Arc::make_mut(&mut self.workspaces);
+        for (ws, resources) in workspaces.iter_mut().zip(resources) {
+            if let ProjectWorkspace::Cargo { cargo, .. } = ws {
+                cargo.set_proc_macro_dylib_paths(resources);
+            }
         }
+        let mut change = AnalysisChange::new();
+        load_crate_graph(
+            &self.workspaces,
+            &self.extern_source_roots,
+            &self.proc_macro_client,
+            &mut self.vfs.write(),
+            &mut change,
+        );
+        self.analysis_host.apply_change(change);
     }
 
     /// Returns a vec of libraries
@@ -186,7 +239,11 @@ impl WorldState {
                     change.remove_file(SourceRootId(root.0), FileId(file.0), path)
                 }
                 VfsChange::ChangeFile { file, text } => {
-                    change.change_file(FileId(file.0), text);
+                    let file_id = FileId(file.0);
+                    match self.take_pending_edits(file_id, &text) {
+                        Some(edits) => change.change_file_with_edits(file_id, text, edits),
+                        None => change.change_file(file_id, text),
+                    }
                 }
             }
         }
@@ -194,6 +251,32 @@ impl WorldState {
         Some(libs)
     }
 
+    /// Takes the edits of the `didChange` notifications which produced `text`.
+    /// The VFS may report several notifications as one change, in which case
+    /// their edits are concatenated.
+    fn take_pending_edits(&mut self, file_id: FileId, text: &str) -> Option<Vec<AtomTextEdit>> {
+        let changes = self.pending_edits.get_mut(&file_id)?;
+        let idx = match changes.iter().position(|it| it.text == text) {
+            Some(it) => it,
+            None => {
+                // We don't know which text the remaining edits start from.
+                self.pending_edits.remove(&file_id);
+                return None;
+            }
+        };
+        let mut edits = Some(Vec::new());
+        for change in changes.drain(..=idx) {
+            match (&mut edits, change.edits) {
+                (Some(edits), Some(change_edits)) => edits.extend(change_edits),
+                _ => edits = None,
+            }
+        }
+        if changes.is_empty() {
+            self.pending_edits.remove(&file_id);
+        }
+        edits
+    }
+
     pub fn add_lib(&mut self, data: LibraryData) {
         self.roots_to_scan -= 1;
         let mut change = AnalysisChange::new();
@@ -209,6 +292,7 @@ impl WorldState {
             vfs: Arc::clone(&self.vfs),
             latest_requests: Arc::clone(&self.latest_requests),
             check_watcher: self.check_watcher.shared.clone(),
+            semantic_tokens_cache: Arc::clone(&self.semantic_tokens_cache),
         }
     }
 
@@ -294,3 +378,39 @@ impl WorldSnapshot {
         self.analysis.feature_flags()
     }
 }
+
+/// Creates the crate graph from all the workspaces.
+fn load_crate_graph(
+    workspaces: &[ProjectWorkspace],
+    extern_source_roots: &FxHashMap<PathBuf, ExternSourceId>,
+    proc_macro_client: &ProcMacroClient,
+    vfs: &mut Vfs,
+    change: &mut AnalysisChange,
+) {
+    // FIXME: Read default cfgs from config
+    let default_cfg_options = {
+        let mut opts = get_rustc_cfg_options();
+        opts.insert_atom("test".into());
+        opts.insert_atom("debug_assertion".into());
+        opts
+    };
+
+    let mut crate_graph = CrateGraph::default();
+    let mut load = |path: &Path| {
+        let vfs_file = vfs.load(path);
+        vfs_file.map(|f| FileId(f.0))
+    };
+    for ws in workspaces.iter() {
+        let (graph, crate_names) = ws.to_crate_graph(
+            &default_cfg_options,
+            extern_source_roots,
+            proc_macro_client,
+            &mut load,
+        );
+        let shift = crate_graph.extend(graph);
+        for (crate_id, name) in crate_names {
+            change.set_debug_crate_name(crate_id.shift(shift), name)
+        }
+    }
+    change.set_crate_graph(crate_graph);
+}
@@ -8,8 +8,9 @@ use lsp_types::{
     WorkDoneProgressParams,
 };
 use ra_lsp_server::req::{
-    CodeActionParams, CodeActionRequest, Completion, CompletionParams, DidOpenTextDocument,
-    Formatting, OnEnter, Runnables, RunnablesParams,
+    CodeActionParams, CodeActionRequest, CodeLensParams, CodeLensRequest, CodeLensResolve,
+    Completion, CompletionParams, DidOpenTextDocument, Formatting, OnEnter, Runnables,
+    RunnablesParams,
 };
 use serde_json::json;
 use tempfile::TempDir;
@@ -184,6 +185,161 @@ fn main() {}
     );
 }
 
+#[test]
+fn test_code_lens_project() {
+    if skip_slow_tests() {
+        return;
+    }
+
+    let code = r#"
+//- foo/Cargo.toml
+[package]
+name = "foo"
+version = "0.0.0"
+
+//- foo/src/lib.rs
+pub fn foo() {}
+
+//- foo/tests/spam.rs
+trait Tr {
+    fn required(&self);
+}
+struct S;
+impl Tr for S {}
+
+#[test]
+fn test_eggs() {}
+"#;
+
+    let server = Project::with_fixture(code).root("foo").server();
+
+    server.wait_until_workspace_is_loaded();
+    let test_range = json!({
+      "end": { "character": 17, "line": 7 },
+      "start": { "character": 0, "line": 6 }
+    });
+    server.request::<CodeLensRequest>(
+        CodeLensParams {
+            text_document: server.doc_id("foo/tests/spam.rs"),
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        },
+        json!([
+          {
+            "range": test_range,
+            "command": {
+              "title": "▶️Run Test",
+              "command": "rust-analyzer.runSingle",
+              "arguments": [
+                {
+                  "args": [ "test", "--package", "foo", "--test", "spam", "--", "test_eggs", "--nocapture" ],
+                  "bin": "cargo",
+                  "env": { "RUST_BACKTRACE": "short" },
+                  "label": "test test_eggs",
+                  "range": test_range,
+                  "cwd": server.path().join("foo")
+                }
+              ]
+            }
+          },
+          {
+            "range": test_range,
+            "command": {
+              "title": "Debug",
+              "command": "rust-analyzer.debugSingle",
+              "arguments": [
+                {
+                  "cargoArgs": [ "test", "--no-run", "--package", "foo", "--test", "spam" ],
+                  "executableArgs": [ "test_eggs", "--exact", "--nocapture" ],
+                  "env": { "RUST_BACKTRACE": "short" },
+                  "label": "debug test test_eggs",
+                  "range": test_range,
+                  "cwd": server.path().join("foo")
+                }
+              ]
+            }
+          },
+          {
+            "range": {
+              "end": { "character": 1, "line": 2 },
+              "start": { "character": 0, "line": 0 }
+            },
+            "data": "{...}"
+          },
+          {
+            "range": {
+              "end": { "character": 9, "line": 3 },
+              "start": { "character": 0, "line": 3 }
+            },
+            "data": "{...}"
+          },
+          {
+            "range": {
+              "end": { "character": 23, "line": 1 },
+              "start": { "character": 4, "line": 1 }
+            },
+            "data": "{...}"
+          },
+          {
+            "range": test_range,
+            "data": "{...}"
+          },
+          {
+            "range": {
+              "end": { "character": 16, "line": 4 },
+              "start": { "character": 0, "line": 4 }
+            },
+            "command": {
+              "title": "missing from `Tr`: `required`",
+              "command": ""
+            }
+          }
+        ]),
+    );
+}
+
+#[test]
+fn test_code_lens_resolve_implementations() {
+    if skip_slow_tests() {
+        return;
+    }
+
+    let server = project(
+        r"
+//- lib.rs
+trait Tr {}
+struct S;
+impl Tr for S {}
+",
+    );
+    server.wait_until_workspace_is_loaded();
+    let range = json!({
+      "end": { "character": 11, "line": 0 },
+      "start": { "character": 0, "line": 0 }
+    });
+    let lens = serde_json::from_value(json!({
+      "range": range,
+      "data": {
+        "impls": {
+          "textDocument": server.doc_id("lib.rs"),
+          "position": { "character": 0, "line": 0 }
+        }
+      }
+    }))
+    .unwrap();
+    server.request::<CodeLensResolve>(
+        lens,
+        json!({
+          "range": range,
+          "command": {
+            "title": "1 implementation",
+            "command": "rust-analyzer.showReferences",
+            "arguments": "{...}"
+          }
+        }),
+    );
+}
+
 #[test]
 fn test_format_document() {
     if skip_slow_tests() {
@@ -102,20 +102,21 @@ impl Server {
         let _thread = jod_thread::Builder::new()
             .name("test server".to_string())
             .spawn(move || {
+                let client_caps = ClientCapabilities {
+                    workspace: None,
+                    text_document: Some(TextDocumentClientCapabilities {
+                        definition: Some(GotoCapability {
+                            dynamic_registration: None,
+                            link_support: Some(true),
+                        }),
+                        ..Default::default()
+                    }),
+                    window: None,
+                    experimental: None,
+                };
                 main_loop(
                     roots,
-                    ClientCapabilities {
-                        workspace: None,
-                        text_document: Some(TextDocumentClientCapabilities {
-                            definition: Some(GotoCapability {
-                                dynamic_registration: None,
-                                link_support: Some(true),
-                            }),
-                            ..Default::default()
-                        }),
-                        window: None,
-                        experimental: None,
-                    },
+                    serde_json::to_value(client_caps).unwrap(),
                     ServerConfig { with_sysroot, ..ServerConfig::default() },
                     connection,
                 )
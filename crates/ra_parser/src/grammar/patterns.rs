@@ -57,6 +57,33 @@ pub(super) fn pattern_r(p: &mut Parser, recovery_set: TokenSet) {
     }
 }
 
+/// Parses a pattern which may be an or-pattern, like `Some(A | B)`. Only used
+/// where a `|` can't be confused with the separator of a match arm or with a
+/// closure's parameter list.
+// test or_pattern
+// fn main() {
+//     match () {
+//         (_ | _) => (),
+//         &(_ | _) => (),
+//         (_ | _,) => (),
+//         [_ | _,] => (),
+//         Some(_ | _) => (),
+//         S { f: _ | _ } => (),
+//     }
+// }
+fn or_pattern(p: &mut Parser) {
+    let m = p.start();
+    pattern(p);
+    if !p.at(T![|]) {
+        m.abandon(p);
+        return;
+    }
+    while p.eat(T![|]) {
+        pattern(p);
+    }
+    m.complete(p, OR_PAT);
+}
+
 const PAT_RECOVERY_SET: TokenSet =
     token_set![LET_KW, IF_KW, WHILE_KW, LOOP_KW, MATCH_KW, R_PAREN, COMMA];
 
@@ -202,7 +229,7 @@ fn record_field_pat(p: &mut Parser) {
     }
 
     p.bump_any();
-    pattern(p);
+    or_pattern(p);
     m.complete(p, RECORD_FIELD_PAT);
 }
 
@@ -269,8 +296,33 @@ fn ref_pat(p: &mut Parser) -> CompletedMarker {
 fn tuple_pat(p: &mut Parser) -> CompletedMarker {
     assert!(p.at(T!['(']));
     let m = p.start();
-    tuple_pat_fields(p);
-    m.complete(p, TUPLE_PAT)
+    p.bump(T!['(']);
+    let mut has_comma = false;
+    let mut has_pat = false;
+    let mut has_rest = false;
+    while !p.at(EOF) && !p.at(T![')']) {
+        has_pat = true;
+        if !p.at_ts(PATTERN_FIRST) {
+            p.error("expected a pattern");
+            break;
+        }
+        has_rest |= p.at(T![..]);
+
+        or_pattern(p);
+        if !p.at(T![')']) {
+            has_comma = true;
+            p.expect(T![,]);
+        }
+    }
+    p.expect(T![')']);
+
+    // test paren_pat
+    // fn main() {
+    //     let (a) = ();
+    //     let (a,) = ();
+    // }
+    let kind = if has_pat && !has_comma && !has_rest { PAREN_PAT } else { TUPLE_PAT };
+    m.complete(p, kind)
 }
 
 // test slice_pat
@@ -293,7 +345,7 @@ fn pat_list(p: &mut Parser, ket: SyntaxKind) {
             break;
         }
 
-        pattern(p);
+        or_pattern(p);
         if !p.at(ket) {
             p.expect(T![,]);
         }
@@ -0,0 +1,128 @@
+//! Client-side Proc-Macro crate
+//!
+//! We separate proc-macro expanding logic to an extern program to allow
+//! different implementations (e.g. wasm or dylib loading). And this crate
+//! is used to provide basic infrastructure for communication between two
+//! processes: Client (RA itself), Server (the external program)
+//!
+//! Running the macros out of process also isolates us from them: a proc
+//! macro which panics, aborts or loops forever only takes the server down, in
+//! which case the client reports an error and spawns a fresh server.
+
+mod rpc;
+mod process;
+pub mod msg;
+
+use std::{
+    ffi::OsStr,
+    io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use process::{ProcMacroProcessSrv, ProcMacroProcessThread};
+use tt::{SmolStr, Subtree};
+
+pub use rpc::{ExpansionResult, ExpansionTask, ListMacrosResult, ListMacrosTask, ProcMacroKind};
+
+#[derive(Debug, Clone)]
+pub struct ProcMacroProcessExpander {
+    process: Arc<ProcMacroProcessSrv>,
+    dylib_path: PathBuf,
+    name: SmolStr,
+    kind: ProcMacroKind,
+}
+
+impl Eq for ProcMacroProcessExpander {}
+impl PartialEq for ProcMacroProcessExpander {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.kind == other.kind
+            && self.dylib_path == other.dylib_path
+            && Arc::ptr_eq(&self.process, &other.process)
+    }
+}
+
+impl ProcMacroProcessExpander {
+    pub fn name(&self) -> &SmolStr {
+        &self.name
+    }
+
+    pub fn kind(&self) -> ProcMacroKind {
+        self.kind
+    }
+}
+
+impl tt::TokenExpander for ProcMacroProcessExpander {
+    fn expand(
+        &self,
+        subtree: &Subtree,
+        attr: Option<&Subtree>,
+    ) -> Result<Subtree, tt::ExpansionError> {
+        let task = ExpansionTask {
+            macro_body: subtree.clone(),
+            macro_name: self.name.to_string(),
+            attributes: attr.cloned(),
+            lib: self.dylib_path.to_path_buf(),
+        };
+
+        let result: ExpansionResult = self.process.send_task(msg::Request::ExpansionMacro(task))?;
+        Ok(result.expansion)
+    }
+}
+
+#[derive(Debug)]
+enum ProcMacroClientKind {
+    Process { process: Arc<ProcMacroProcessSrv>, thread: ProcMacroProcessThread },
+    Dummy,
+}
+
+#[derive(Debug)]
+pub struct ProcMacroClient {
+    kind: ProcMacroClientKind,
+}
+
+impl ProcMacroClient {
+    /// Spawns the proc macro server at `process_path`.
+    pub fn extern_process(
+        process_path: &Path,
+        args: &[impl AsRef<OsStr>],
+    ) -> io::Result<ProcMacroClient> {
+        let (thread, process) = ProcMacroProcessSrv::run(process_path, args)?;
+        Ok(ProcMacroClient {
+            kind: ProcMacroClientKind::Process { process: Arc::new(process), thread },
+        })
+    }
+
+    /// A client which doesn't know about any proc macros.
+    pub fn dummy() -> ProcMacroClient {
+        ProcMacroClient { kind: ProcMacroClientKind::Dummy }
+    }
+
+    /// Asks the server which proc macros are exported by the compiled
+    /// proc-macro crate at `dylib_path`.
+    pub fn by_dylib_path(&self, dylib_path: &Path) -> Vec<ProcMacroProcessExpander> {
+        match &self.kind {
+            ProcMacroClientKind::Dummy => vec![],
+            ProcMacroClientKind::Process { process, .. } => {
+                let macros = match process.find_proc_macros(dylib_path) {
+                    Err(err) => {
+                        log::error!("failed to find proc macros in {:?}: {:?}", dylib_path, err);
+                        return vec![];
+                    }
+                    Ok(macros) => macros,
+                };
+
+                macros
+                    .into_iter()
+                    .map(|(name, kind)| ProcMacroProcessExpander {
+                        process: process.clone(),
+                        name: name.into(),
+                        kind,
+                        dylib_path: dylib_path.to_path_buf(),
+                    })
+                    .collect()
+            }
+        }
+    }
+}
@@ -0,0 +1,183 @@
+//! Handle process life-time and message passing for proc-macro client
+
+use std::{
+    convert::{TryFrom, TryInto},
+    ffi::{OsStr, OsString},
+    io::{self, BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+    sync::{Arc, Weak},
+};
+
+use crossbeam_channel::{bounded, Receiver, Sender};
+use tt::ExpansionError;
+
+use crate::msg::{ErrorCode, Message, Request, Response, ResponseError};
+use crate::rpc::{ListMacrosResult, ListMacrosTask, ProcMacroKind};
+
+#[derive(Debug, Default)]
+pub(crate) struct ProcMacroProcessSrv {
+    inner: Option<Weak<Sender<Task>>>,
+}
+
+#[derive(Debug)]
+pub(crate) struct ProcMacroProcessThread {
+    // XXX: drop order is significant
+    sender: Arc<Sender<Task>>,
+    handle: jod_thread::JoinHandle<()>,
+}
+
+struct Task {
+    req: Request,
+    result_tx: Sender<Option<Response>>,
+}
+
+struct Process {
+    path: PathBuf,
+    args: Vec<OsString>,
+    child: Child,
+}
+
+impl Drop for Process {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+    }
+}
+
+impl Process {
+    fn run(path: PathBuf, args: &[impl AsRef<OsStr>]) -> io::Result<Process> {
+        let args = args.iter().map(|it| it.as_ref().into()).collect::<Vec<_>>();
+        let child = mk_child(&path, &args)?;
+        Ok(Process { path, args, child })
+    }
+
+    fn restart(&mut self) -> io::Result<()> {
+        let _ = self.child.kill();
+        self.child = mk_child(&self.path, &self.args)?;
+        Ok(())
+    }
+
+    fn stdio(&mut self) -> Option<(ChildStdin, BufReader<ChildStdout>)> {
+        let stdin = self.child.stdin.take()?;
+        let stdout = self.child.stdout.take()?;
+        let read = BufReader::new(stdout);
+
+        Some((stdin, read))
+    }
+}
+
+fn mk_child(path: &Path, args: &[OsString]) -> io::Result<Child> {
+    Command::new(path)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+}
+
+impl ProcMacroProcessSrv {
+    pub fn run(
+        process_path: &Path,
+        args: &[impl AsRef<OsStr>],
+    ) -> io::Result<(ProcMacroProcessThread, ProcMacroProcessSrv)> {
+        let process = Process::run(process_path.to_path_buf(), args)?;
+
+        let (task_tx, task_rx) = bounded(0);
+        let handle = jod_thread::spawn(move || {
+            client_loop(task_rx, process);
+        });
+
+        let task_tx = Arc::new(task_tx);
+        let srv = ProcMacroProcessSrv { inner: Some(Arc::downgrade(&task_tx)) };
+        let thread = ProcMacroProcessThread { handle, sender: task_tx };
+
+        Ok((thread, srv))
+    }
+
+    pub fn find_proc_macros(
+        &self,
+        dylib_path: &Path,
+    ) -> Result<Vec<(String, ProcMacroKind)>, ExpansionError> {
+        let task = ListMacrosTask { lib: dylib_path.to_path_buf() };
+
+        let result: ListMacrosResult = self.send_task(Request::ListMacro(task))?;
+        Ok(result.macros)
+    }
+
+    pub fn send_task<R>(&self, req: Request) -> Result<R, ExpansionError>
+    where
+        R: TryFrom<Response, Error = &'static str>,
+    {
+        let sender = match &self.inner {
+            None => return Err(ExpansionError::Unknown("No sender is found.".to_string())),
+            Some(it) => it,
+        };
+
+        let (result_tx, result_rx) = bounded(0);
+        let sender = match sender.upgrade() {
+            None => return Err(ExpansionError::Unknown("Proc macro process is closed.".into())),
+            Some(it) => it,
+        };
+        sender
+            .send(Task { req, result_tx })
+            .map_err(|_| ExpansionError::Unknown("Proc macro thread is closed.".into()))?;
+        let res = result_rx
+            .recv()
+            .map_err(|_| ExpansionError::Unknown("Proc macro thread is closed.".into()))?;
+
+        match res {
+            Some(Response::Error(err)) => Err(ExpansionError::ExpansionError(err.message)),
+            Some(res) => res.try_into().map_err(|err| {
+                ExpansionError::Unknown(format!("Fail to get response, reason : {:#?} ", err))
+            }),
+            None => Err(ExpansionError::Unknown("Empty result".into())),
+        }
+    }
+}
+
+fn client_loop(task_rx: Receiver<Task>, mut process: Process) {
+    let (mut stdin, mut stdout) = match process.stdio() {
+        None => return,
+        Some(it) => it,
+    };
+
+    for task in task_rx {
+        let Task { req, result_tx } = task;
+
+        // The requesting side may have given up on the result already, e.g.
+        // because the analysis was cancelled, so failing to reply is fine.
+        match send_request(&mut stdin, &mut stdout, req) {
+            Ok(res) => {
+                let _ = result_tx.send(res);
+            }
+            Err(_err) => {
+                // The server is gone, most likely because a proc macro
+                // crashed it. Fail this request, but restart the server, so
+                // that other macros still work.
+                let res = Response::Error(ResponseError {
+                    code: ErrorCode::ServerErrorEnd,
+                    message: "Server closed".into(),
+                });
+                let _ = result_tx.send(res.into());
+                if process.restart().is_err() {
+                    break;
+                }
+                let stdio = match process.stdio() {
+                    None => break,
+                    Some(it) => it,
+                };
+                stdin = stdio.0;
+                stdout = stdio.1;
+            }
+        }
+    }
+}
+
+fn send_request(
+    mut writer: &mut impl Write,
+    mut reader: &mut impl BufRead,
+    req: Request,
+) -> io::Result<Option<Response>> {
+    req.write(&mut writer)?;
+    Ok(Response::read(&mut reader)?)
+}
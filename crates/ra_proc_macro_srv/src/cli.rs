@@ -0,0 +1,28 @@
+//! Driver for the proc macro server: reads requests from stdin and writes
+//! responses to stdout, one JSON message per line.
+
+use std::io;
+
+use ra_proc_macro::msg::{ErrorCode, Message, Request, Response, ResponseError};
+
+use crate::ProcMacroSrv;
+
+pub fn run() -> io::Result<()> {
+    let mut srv = ProcMacroSrv::default();
+
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    while let Some(req) = Request::read(&mut stdin.lock())? {
+        let res = match req {
+            Request::ListMacro(task) => srv.list_macros(&task).map(Response::ListMacro),
+            Request::ExpansionMacro(task) => srv.expand(&task).map(Response::ExpansionMacro),
+        };
+
+        let msg = res.unwrap_or_else(|message| {
+            Response::Error(ResponseError { code: ErrorCode::ExpansionError, message })
+        });
+        msg.write(&mut stdout.lock())?;
+    }
+
+    Ok(())
+}
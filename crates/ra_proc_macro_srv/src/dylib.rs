@@ -0,0 +1,143 @@
+//! Loading of compiled proc-macro crates.
+//!
+//! rustc marks the list of macros a proc-macro crate exports with a symbol
+//! named `__rustc_proc_macro_decls_<hash>__`, which we look up in the symbol
+//! table and then read through `libloading`.
+
+use std::{fs, io, path::Path};
+
+use goblin::{mach::Mach, Object};
+use libloading::Library;
+use ra_proc_macro::ProcMacroKind;
+
+use crate::{
+    proc_macro::bridge::{self, client::ProcMacro, server::SameThread},
+    rustc_server::{Rustc, TokenStream},
+};
+
+const NEW_REGISTRAR_SYMBOL: &str = "_rustc_proc_macro_decls_";
+
+fn invalid_data_err(e: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+fn is_derive_registrar_symbol(symbol: &str) -> bool {
+    symbol.contains(NEW_REGISTRAR_SYMBOL)
+}
+
+fn find_registrar_symbol(file: &Path) -> io::Result<Option<String>> {
+    let buffer = fs::read(file)?;
+    let object = Object::parse(&buffer).map_err(invalid_data_err)?;
+
+    let name = match object {
+        Object::Elf(elf) => {
+            let symbols = elf.dynstrtab.to_vec().map_err(invalid_data_err)?;
+            symbols.into_iter().find(|s| is_derive_registrar_symbol(s)).map(str::to_owned)
+        }
+        Object::PE(pe) => pe
+            .exports
+            .iter()
+            .flat_map(|s| s.name)
+            .find(|s| is_derive_registrar_symbol(s))
+            .map(str::to_owned),
+        Object::Mach(Mach::Binary(binary)) => {
+            let exports = binary.exports().map_err(invalid_data_err)?;
+            exports
+                .iter()
+                // Mach-O symbols have an extra leading underscore, which
+                // `dlsym` adds back itself.
+                .map(|s| if s.name.starts_with('_') { &s.name[1..] } else { &s.name[..] })
+                .find(|s| is_derive_registrar_symbol(s))
+                .map(str::to_owned)
+        }
+        _ => return Ok(None),
+    };
+    Ok(name)
+}
+
+/// A loaded proc-macro dylib.
+struct ProcMacroLibrary {
+    // Keeps the code behind `exported_macros` mapped.
+    _lib: Library,
+    exported_macros: Vec<ProcMacro>,
+}
+
+impl ProcMacroLibrary {
+    fn open(file: &Path) -> io::Result<ProcMacroLibrary> {
+        let symbol_name = find_registrar_symbol(file)?.ok_or_else(|| {
+            invalid_data_err(format!("Cannot find registrar symbol in file {}", file.display()))
+        })?;
+
+        let lib = Library::new(file)?;
+        let exported_macros = {
+            // The symbol is a `static &[ProcMacro]`, so its address is a
+            // `&&[ProcMacro]`.
+            let macros: libloading::Symbol<&&[ProcMacro]> =
+                unsafe { lib.get(symbol_name.as_bytes()) }?;
+            macros.to_vec()
+        };
+
+        Ok(ProcMacroLibrary { _lib: lib, exported_macros })
+    }
+}
+
+pub struct Expander {
+    lib: ProcMacroLibrary,
+}
+
+impl Expander {
+    pub fn new(lib: &Path) -> io::Result<Expander> {
+        // FIXME: Windows locks loaded dylibs, so we should load a copy to
+        // not break the next `cargo check`.
+        let lib = ProcMacroLibrary::open(lib)?;
+        Ok(Expander { lib })
+    }
+
+    pub fn expand(
+        &self,
+        macro_name: &str,
+        macro_body: &tt::Subtree,
+        attributes: Option<&tt::Subtree>,
+    ) -> Result<tt::Subtree, bridge::PanicMessage> {
+        let parsed_body = TokenStream::with_subtree(macro_body.clone());
+
+        let parsed_attributes =
+            attributes.map_or(TokenStream::new(), |attr| TokenStream::with_subtree(attr.clone()));
+
+        for proc_macro in &self.lib.exported_macros {
+            match proc_macro {
+                ProcMacro::CustomDerive { trait_name, client, .. } if *trait_name == macro_name => {
+                    let res = client.run(&SameThread, Rustc::default(), parsed_body);
+                    return res.map(|it| it.subtree);
+                }
+                ProcMacro::Bang { name, client } if *name == macro_name => {
+                    let res = client.run(&SameThread, Rustc::default(), parsed_body);
+                    return res.map(|it| it.subtree);
+                }
+                ProcMacro::Attr { name, client } if *name == macro_name => {
+                    let res =
+                        client.run(&SameThread, Rustc::default(), parsed_attributes, parsed_body);
+                    return res.map(|it| it.subtree);
+                }
+                _ => continue,
+            }
+        }
+
+        Err(bridge::PanicMessage::String(format!("Proc macro {} not found", macro_name)))
+    }
+
+    pub fn list_macros(&self) -> Vec<(String, ProcMacroKind)> {
+        self.lib
+            .exported_macros
+            .iter()
+            .map(|proc_macro| {
+                let kind = match proc_macro {
+                    ProcMacro::CustomDerive { .. } => ProcMacroKind::CustomDerive,
+                    ProcMacro::Bang { .. } => ProcMacroKind::FuncLike,
+                    ProcMacro::Attr { .. } => ProcMacroKind::Attr,
+                };
+                (proc_macro.name().to_string(), kind)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,74 @@
+//! Proc macro server: the process `ra_proc_macro` talks to.
+//!
+//! It loads compiled proc-macro crates and runs their macros on the token
+//! trees it receives. Proc macros are arbitrary code which may panic, abort
+//! or loop, that's why they don't run inside rust-analyzer itself.
+//!
+//! The macros are run by playing the compiler's part of the `proc_macro`
+//! bridge (see the `proc_macro` module) with a server which works on `ra_tt`
+//! token trees (see `rustc_server`).
+
+#[allow(dead_code)]
+#[doc(hidden)]
+mod proc_macro;
+
+#[doc(hidden)]
+mod rustc_server;
+
+mod dylib;
+
+pub mod cli;
+
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use ra_proc_macro::{ExpansionResult, ExpansionTask, ListMacrosResult, ListMacrosTask};
+
+use crate::dylib::Expander;
+
+#[derive(Default)]
+pub struct ProcMacroSrv {
+    expanders: HashMap<PathBuf, (Option<SystemTime>, Expander)>,
+}
+
+impl ProcMacroSrv {
+    pub fn expand(&mut self, task: &ExpansionTask) -> Result<ExpansionResult, String> {
+        let expander = self.expander(&task.lib)?;
+        match expander.expand(&task.macro_name, &task.macro_body, task.attributes.as_ref()) {
+            Ok(expansion) => Ok(ExpansionResult { expansion }),
+            Err(msg) => {
+                let msg = msg.as_str().unwrap_or("<unknown error>");
+                Err(format!("Cannot perform expansion for {}: error {}", task.macro_name, msg))
+            }
+        }
+    }
+
+    pub fn list_macros(&mut self, task: &ListMacrosTask) -> Result<ListMacrosResult, String> {
+        let expander = self.expander(&task.lib)?;
+        Ok(ListMacrosResult { macros: expander.list_macros() })
+    }
+
+    /// Loads the dylib at `path`, or reloads it if it was rebuilt since.
+    fn expander(&mut self, path: &Path) -> Result<&Expander, String> {
+        let modified = fs::metadata(path).and_then(|it| it.modified()).ok();
+        let load = || {
+            Expander::new(path)
+                .map_err(|err| format!("Cannot create expander for {}: {}", path.display(), err))
+        };
+        let entry = match self.expanders.entry(path.to_path_buf()) {
+            Entry::Occupied(entry) => {
+                let entry = entry.into_mut();
+                if entry.0 != modified {
+                    *entry = (modified, load()?);
+                }
+                entry
+            }
+            Entry::Vacant(entry) => entry.insert((modified, load()?)),
+        };
+        Ok(&entry.1)
+    }
+}
@@ -0,0 +1,9 @@
+//! The proc macro server binary, spawned by rust-analyzer when
+//! its `procMacroSrv` option points to it.
+
+fn main() {
+    if let Err(err) = ra_proc_macro_srv::cli::run() {
+        eprintln!("proc macro server failed: {}", err);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,37 @@
+//! A copy of the server side of `libproc_macro`'s bridge.
+//!
+//! A proc-macro dylib talks to its host only through the bridge, by passing
+//! serialized calls over C ABI buffers, so we can play the part of the
+//! compiler without linking to it. The bridge itself isn't stable though: it
+//! has to match the `proc_macro` the dylib was compiled against. This copy
+//! follows the one of rustc 1.42.
+
+pub mod bridge;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Delimiter {
+    Parenthesis,
+    Brace,
+    Bracket,
+    None,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Spacing {
+    Alone,
+    Joint,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Level {
+    Error,
+    Warning,
+    Note,
+    Help,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct LineColumn {
+    pub line: usize,
+    pub column: usize,
+}
@@ -0,0 +1,498 @@
+//! An implementation of the `proc_macro` server API on top of `ra_tt`.
+//!
+//! This is what a proc macro "sees" when it is run by us: token streams are
+//! plain `tt::Subtree`s, and since the macro runs outside of any compilation
+//! session, spans carry no information beyond the `tt::TokenId`s, and
+//! diagnostics are dropped.
+
+use std::{
+    collections::HashMap,
+    iter::FromIterator,
+    ops::{Bound, RangeBounds},
+    vec::IntoIter,
+};
+
+use ra_syntax::{ast, AstNode, SourceFile as ParsedFile};
+
+use crate::proc_macro::{
+    bridge::{self, server},
+    Delimiter, Level, LineColumn, Spacing,
+};
+
+type Group = tt::Subtree;
+type TokenTree = tt::TokenTree;
+type Punct = tt::Punct;
+type Literal = tt::Literal;
+type Span = tt::TokenId;
+
+#[derive(Debug, Clone, Default)]
+pub struct TokenStream {
+    pub subtree: tt::Subtree,
+}
+
+impl TokenStream {
+    pub fn new() -> Self {
+        TokenStream::default()
+    }
+
+    /// Wraps a macro input. A delimited subtree is a single group, like the
+    /// argument of a function-like macro would be.
+    pub fn with_subtree(subtree: tt::Subtree) -> Self {
+        if subtree.delimiter.is_some() {
+            TokenStream {
+                subtree: tt::Subtree { delimiter: None, token_trees: vec![subtree.into()] },
+            }
+        } else {
+            TokenStream { subtree }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.subtree.token_trees.is_empty()
+    }
+}
+
+impl FromIterator<TokenTree> for TokenStream {
+    fn from_iter<I: IntoIterator<Item = TokenTree>>(trees: I) -> Self {
+        let token_trees = trees.into_iter().collect();
+        TokenStream { subtree: tt::Subtree { delimiter: None, token_trees } }
+    }
+}
+
+impl Extend<TokenStream> for TokenStream {
+    fn extend<I: IntoIterator<Item = TokenStream>>(&mut self, streams: I) {
+        for stream in streams {
+            self.subtree.token_trees.extend(stream.subtree.token_trees);
+        }
+    }
+}
+
+pub struct TokenStreamBuilder {
+    acc: TokenStream,
+}
+
+#[derive(Clone)]
+pub struct TokenStreamIter {
+    trees: IntoIter<TokenTree>,
+}
+
+#[derive(Clone)]
+pub struct SourceFile;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IdentId(u32);
+
+/// Idents have to be `Copy` on the bridge, so we hand out ids instead.
+#[derive(Default)]
+struct IdentInterner {
+    idents: HashMap<(tt::SmolStr, Span), u32>,
+    ident_data: Vec<tt::Ident>,
+}
+
+impl IdentInterner {
+    fn intern(&mut self, ident: tt::Ident) -> IdentId {
+        let ident_data = &mut self.ident_data;
+        let id = *self.idents.entry((ident.text.clone(), ident.id)).or_insert_with(|| {
+            ident_data.push(ident);
+            ident_data.len() as u32 - 1
+        });
+        IdentId(id)
+    }
+
+    fn get(&self, id: IdentId) -> &tt::Ident {
+        &self.ident_data[id.0 as usize]
+    }
+}
+
+#[derive(Default)]
+pub struct Rustc {
+    ident_interner: IdentInterner,
+}
+
+impl Rustc {
+    fn to_bridge_tree(
+        &mut self,
+        tree: TokenTree,
+    ) -> bridge::TokenTree<Group, Punct, IdentId, Literal> {
+        match tree {
+            tt::TokenTree::Subtree(group) => bridge::TokenTree::Group(group),
+            tt::TokenTree::Leaf(tt::Leaf::Ident(ident)) => {
+                bridge::TokenTree::Ident(self.ident_interner.intern(ident))
+            }
+            tt::TokenTree::Leaf(tt::Leaf::Literal(literal)) => bridge::TokenTree::Literal(literal),
+            tt::TokenTree::Leaf(tt::Leaf::Punct(punct)) => bridge::TokenTree::Punct(punct),
+        }
+    }
+
+    fn from_bridge_tree(
+        &self,
+        tree: bridge::TokenTree<Group, Punct, IdentId, Literal>,
+    ) -> TokenTree {
+        match tree {
+            bridge::TokenTree::Group(group) => group.into(),
+            bridge::TokenTree::Ident(id) => {
+                tt::Leaf::from(self.ident_interner.get(id).clone()).into()
+            }
+            bridge::TokenTree::Literal(literal) => tt::Leaf::from(literal).into(),
+            bridge::TokenTree::Punct(punct) => tt::Leaf::from(punct).into(),
+        }
+    }
+}
+
+impl server::Types for Rustc {
+    type TokenStream = TokenStream;
+    type TokenStreamBuilder = TokenStreamBuilder;
+    type TokenStreamIter = TokenStreamIter;
+    type Group = Group;
+    type Punct = Punct;
+    type Ident = IdentId;
+    type Literal = Literal;
+    type SourceFile = SourceFile;
+    type MultiSpan = Vec<Span>;
+    type Diagnostic = ();
+    type Span = Span;
+}
+
+impl server::TokenStream for Rustc {
+    fn new(&mut self) -> Self::TokenStream {
+        TokenStream::new()
+    }
+
+    fn is_empty(&mut self, stream: &Self::TokenStream) -> bool {
+        stream.is_empty()
+    }
+
+    fn from_str(&mut self, src: &str) -> Self::TokenStream {
+        // Reuse the macro call machinery to lex and match the delimiters.
+        let file = ParsedFile::parse(&format!("m!{{{}}}", src)).tree();
+        let subtree = file
+            .syntax()
+            .descendants()
+            .find_map(ast::TokenTree::cast)
+            .and_then(|it| mbe::ast_to_token_tree(&it))
+            .map(|(subtree, _)| tt::Subtree { delimiter: None, ..subtree })
+            .unwrap_or_default();
+        TokenStream { subtree }
+    }
+
+    fn to_string(&mut self, stream: &Self::TokenStream) -> String {
+        stream.subtree.to_string()
+    }
+
+    fn from_token_tree(
+        &mut self,
+        tree: bridge::TokenTree<Self::Group, Self::Punct, Self::Ident, Self::Literal>,
+    ) -> Self::TokenStream {
+        let tree = self.from_bridge_tree(tree);
+        TokenStream::from_iter(std::iter::once(tree))
+    }
+
+    fn into_iter(&mut self, stream: Self::TokenStream) -> Self::TokenStreamIter {
+        TokenStreamIter { trees: stream.subtree.token_trees.into_iter() }
+    }
+}
+
+impl server::TokenStreamBuilder for Rustc {
+    fn new(&mut self) -> Self::TokenStreamBuilder {
+        TokenStreamBuilder { acc: TokenStream::new() }
+    }
+
+    fn push(&mut self, builder: &mut Self::TokenStreamBuilder, stream: Self::TokenStream) {
+        builder.acc.extend(std::iter::once(stream))
+    }
+
+    fn build(&mut self, builder: Self::TokenStreamBuilder) -> Self::TokenStream {
+        builder.acc
+    }
+}
+
+impl server::TokenStreamIter for Rustc {
+    fn next(
+        &mut self,
+        iter: &mut Self::TokenStreamIter,
+    ) -> Option<bridge::TokenTree<Self::Group, Self::Punct, Self::Ident, Self::Literal>> {
+        let tree = iter.trees.next()?;
+        Some(self.to_bridge_tree(tree))
+    }
+}
+
+fn delim_to_internal(d: Delimiter, id: Span) -> Option<tt::Delimiter> {
+    let kind = match d {
+        Delimiter::Parenthesis => tt::DelimiterKind::Parenthesis,
+        Delimiter::Brace => tt::DelimiterKind::Brace,
+        Delimiter::Bracket => tt::DelimiterKind::Bracket,
+        Delimiter::None => return None,
+    };
+    Some(tt::Delimiter { id, kind })
+}
+
+fn delim_to_external(d: Option<tt::Delimiter>) -> Delimiter {
+    match d.map(|it| it.kind) {
+        Some(tt::DelimiterKind::Parenthesis) => Delimiter::Parenthesis,
+        Some(tt::DelimiterKind::Brace) => Delimiter::Brace,
+        Some(tt::DelimiterKind::Bracket) => Delimiter::Bracket,
+        None => Delimiter::None,
+    }
+}
+
+fn spacing_to_internal(spacing: Spacing) -> tt::Spacing {
+    match spacing {
+        Spacing::Alone => tt::Spacing::Alone,
+        Spacing::Joint => tt::Spacing::Joint,
+    }
+}
+
+fn spacing_to_external(spacing: tt::Spacing) -> Spacing {
+    match spacing {
+        tt::Spacing::Alone => Spacing::Alone,
+        tt::Spacing::Joint => Spacing::Joint,
+    }
+}
+
+impl server::Group for Rustc {
+    fn new(&mut self, delimiter: Delimiter, stream: Self::TokenStream) -> Self::Group {
+        tt::Subtree {
+            delimiter: delim_to_internal(delimiter, tt::TokenId::unspecified()),
+            token_trees: stream.subtree.token_trees,
+        }
+    }
+
+    fn delimiter(&mut self, group: &Self::Group) -> Delimiter {
+        delim_to_external(group.delimiter)
+    }
+
+    fn stream(&mut self, group: &Self::Group) -> Self::TokenStream {
+        TokenStream::from_iter(group.token_trees.iter().cloned())
+    }
+
+    fn span(&mut self, group: &Self::Group) -> Self::Span {
+        group.delimiter.map(|it| it.id).unwrap_or_else(tt::TokenId::unspecified)
+    }
+
+    fn span_open(&mut self, group: &Self::Group) -> Self::Span {
+        server::Group::span(self, group)
+    }
+
+    fn span_close(&mut self, group: &Self::Group) -> Self::Span {
+        server::Group::span(self, group)
+    }
+
+    fn set_span(&mut self, group: &mut Self::Group, span: Self::Span) {
+        if let Some(delimiter) = &mut group.delimiter {
+            delimiter.id = span;
+        }
+    }
+}
+
+impl server::Punct for Rustc {
+    fn new(&mut self, ch: char, spacing: Spacing) -> Self::Punct {
+        tt::Punct {
+            char: ch,
+            spacing: spacing_to_internal(spacing),
+            id: tt::TokenId::unspecified(),
+        }
+    }
+
+    fn as_char(&mut self, punct: Self::Punct) -> char {
+        punct.char
+    }
+
+    fn spacing(&mut self, punct: Self::Punct) -> Spacing {
+        spacing_to_external(punct.spacing)
+    }
+
+    fn span(&mut self, punct: Self::Punct) -> Self::Span {
+        punct.id
+    }
+
+    fn with_span(&mut self, punct: Self::Punct, span: Self::Span) -> Self::Punct {
+        tt::Punct { id: span, ..punct }
+    }
+}
+
+impl server::Ident for Rustc {
+    fn new(&mut self, string: &str, span: Self::Span, is_raw: bool) -> Self::Ident {
+        let text = if is_raw { format!("r#{}", string) } else { string.to_string() };
+        self.ident_interner.intern(tt::Ident { text: text.into(), id: span })
+    }
+
+    fn span(&mut self, ident: Self::Ident) -> Self::Span {
+        self.ident_interner.get(ident).id
+    }
+
+    fn with_span(&mut self, ident: Self::Ident, span: Self::Span) -> Self::Ident {
+        let ident = tt::Ident { id: span, ..self.ident_interner.get(ident).clone() };
+        self.ident_interner.intern(ident)
+    }
+}
+
+fn literal(text: String) -> Literal {
+    tt::Literal { text: text.into(), id: tt::TokenId::unspecified() }
+}
+
+impl server::Literal for Rustc {
+    fn debug(&mut self, literal: &Self::Literal) -> String {
+        format!("{:?}", literal)
+    }
+
+    fn integer(&mut self, n: &str) -> Self::Literal {
+        literal(n.to_string())
+    }
+
+    fn typed_integer(&mut self, n: &str, kind: &str) -> Self::Literal {
+        literal(format!("{}{}", n, kind))
+    }
+
+    fn float(&mut self, n: &str) -> Self::Literal {
+        literal(n.to_string())
+    }
+
+    fn f32(&mut self, n: &str) -> Self::Literal {
+        literal(format!("{}f32", n))
+    }
+
+    fn f64(&mut self, n: &str) -> Self::Literal {
+        literal(format!("{}f64", n))
+    }
+
+    fn string(&mut self, string: &str) -> Self::Literal {
+        literal(format!("\"{}\"", string.escape_debug()))
+    }
+
+    fn character(&mut self, ch: char) -> Self::Literal {
+        literal(format!("'{}'", ch.escape_debug()))
+    }
+
+    fn byte_string(&mut self, bytes: &[u8]) -> Self::Literal {
+        let escaped = bytes
+            .iter()
+            .flat_map(|&b| std::ascii::escape_default(b))
+            .map(char::from)
+            .collect::<String>();
+        literal(format!("b\"{}\"", escaped))
+    }
+
+    fn span(&mut self, literal: &Self::Literal) -> Self::Span {
+        literal.id
+    }
+
+    fn set_span(&mut self, literal: &mut Self::Literal, span: Self::Span) {
+        literal.id = span;
+    }
+
+    fn subspan(
+        &mut self,
+        _literal: &Self::Literal,
+        _start: Bound<usize>,
+        _end: Bound<usize>,
+    ) -> Option<Self::Span> {
+        None
+    }
+}
+
+impl server::SourceFile for Rustc {
+    fn eq(&mut self, _file1: &Self::SourceFile, _file2: &Self::SourceFile) -> bool {
+        true
+    }
+
+    fn path(&mut self, _file: &Self::SourceFile) -> String {
+        String::new()
+    }
+
+    fn is_real(&mut self, _file: &Self::SourceFile) -> bool {
+        false
+    }
+}
+
+impl server::MultiSpan for Rustc {
+    fn new(&mut self) -> Self::MultiSpan {
+        Vec::new()
+    }
+
+    fn push(&mut self, spans: &mut Self::MultiSpan, span: Self::Span) {
+        spans.push(span)
+    }
+}
+
+impl server::Diagnostic for Rustc {
+    fn new(&mut self, _level: Level, _msg: &str, _spans: Self::MultiSpan) -> Self::Diagnostic {}
+
+    fn sub(
+        &mut self,
+        _diag: &mut Self::Diagnostic,
+        _level: Level,
+        _msg: &str,
+        _spans: Self::MultiSpan,
+    ) {
+    }
+
+    fn emit(&mut self, _diag: Self::Diagnostic) {}
+}
+
+impl server::Span for Rustc {
+    fn debug(&mut self, span: Self::Span) -> String {
+        format!("{:?}", span.0)
+    }
+
+    fn def_site(&mut self) -> Self::Span {
+        tt::TokenId::unspecified()
+    }
+
+    fn call_site(&mut self) -> Self::Span {
+        tt::TokenId::unspecified()
+    }
+
+    fn source_file(&mut self, _span: Self::Span) -> Self::SourceFile {
+        SourceFile
+    }
+
+    fn parent(&mut self, _span: Self::Span) -> Option<Self::Span> {
+        None
+    }
+
+    fn source(&mut self, span: Self::Span) -> Self::Span {
+        span
+    }
+
+    fn start(&mut self, _span: Self::Span) -> LineColumn {
+        LineColumn { line: 0, column: 0 }
+    }
+
+    fn end(&mut self, _span: Self::Span) -> LineColumn {
+        LineColumn { line: 0, column: 0 }
+    }
+
+    fn join(&mut self, first: Self::Span, _second: Self::Span) -> Option<Self::Span> {
+        Some(first)
+    }
+
+    fn resolved_at(&mut self, _span: Self::Span, at: Self::Span) -> Self::Span {
+        at
+    }
+
+    fn source_text(&mut self, _span: Self::Span) -> Option<String> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proc_macro::bridge::server::{Literal as _, TokenStream as _};
+
+    #[test]
+    fn test_rustc_server_from_str_round_trips() {
+        let mut rustc = Rustc::default();
+        let stream = rustc.from_str("struct T { a: u32 } fn f() -> &'static str { \"a\" }");
+        assert_eq!(rustc.to_string(&stream), "struct T {a : u32} fn f () -> & 'static str {\"a\"}");
+    }
+
+    #[test]
+    fn test_rustc_server_literals() {
+        let mut rustc = Rustc::default();
+        assert_eq!(rustc.typed_integer("1", "u8").text, "1u8");
+        assert_eq!(rustc.string("a\"b").text, "\"a\\\"b\"");
+        assert_eq!(rustc.character('\'').text, "'\\''");
+        assert_eq!(rustc.byte_string(b"a\n").text, "b\"a\\n\"");
+    }
+}
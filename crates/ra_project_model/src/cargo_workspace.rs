@@ -1,8 +1,11 @@
 //! FIXME: write short doc here
 
-use std::path::{Path, PathBuf};
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
 
-use cargo_metadata::{CargoOpt, MetadataCommand};
+use cargo_metadata::{CargoOpt, Message, MetadataCommand, PackageId};
 use ra_arena::{impl_arena_id, Arena, RawId};
 use ra_db::Edition;
 use rustc_hash::FxHashMap;
@@ -36,11 +39,29 @@ pub struct CargoFeatures {
     /// List of features to activate.
     /// This will be ignored if `cargo_all_features` is true.
     pub features: Vec<String>,
+
+    /// Build proc-macro crates with `cargo check`, so that the compiled
+    /// macros can be loaded by the proc macro server. Has no effect unless a
+    /// proc macro server is configured.
+    ///
+    /// This isn't done when loading the workspace, as the check can take a
+    /// while: see `CargoWorkspace::set_proc_macro_dylib_paths`.
+    pub with_proc_macro: bool,
+
+    /// Runs cargo check on launch to figure out the correct values of OUT_DIR,
+    /// and of the cfgs and env vars set by build scripts.
+    pub load_out_dirs_from_check: bool,
 }
 
 impl Default for CargoFeatures {
     fn default() -> Self {
-        CargoFeatures { no_default_features: false, all_features: true, features: Vec::new() }
+        CargoFeatures {
+            no_default_features: false,
+            all_features: true,
+            features: Vec::new(),
+            with_proc_macro: false,
+            load_out_dirs_from_check: false,
+        }
     }
 }
 
@@ -54,6 +75,7 @@ impl_arena_id!(Target);
 
 #[derive(Debug, Clone)]
 struct PackageData {
+    id: PackageId,
     name: String,
     manifest: PathBuf,
     targets: Vec<Target>,
@@ -61,6 +83,10 @@ struct PackageData {
     dependencies: Vec<PackageDependency>,
     edition: Edition,
     features: Vec<String>,
+    proc_macro_dylib_path: Option<PathBuf>,
+    out_dir: Option<PathBuf>,
+    cfgs: Vec<String>,
+    envs: Vec<(String, String)>,
 }
 
 #[derive(Debug, Clone)]
@@ -119,6 +145,23 @@ impl Package {
     pub fn features(self, ws: &CargoWorkspace) -> &[String] {
         &ws.packages[self].features
     }
+    /// Path to the compiled dylib of this package's proc-macro target, if it
+    /// has one and it was built.
+    pub fn proc_macro_dylib_path(self, ws: &CargoWorkspace) -> Option<&Path> {
+        ws.packages[self].proc_macro_dylib_path.as_deref()
+    }
+    /// `OUT_DIR` of the package's build script, if it was run.
+    pub fn out_dir(self, ws: &CargoWorkspace) -> Option<&Path> {
+        ws.packages[self].out_dir.as_deref()
+    }
+    /// Cfgs emitted by the build script via `cargo:rustc-cfg`.
+    pub fn cfgs(self, ws: &CargoWorkspace) -> &[String] {
+        &ws.packages[self].cfgs
+    }
+    /// Env vars emitted by the build script via `cargo:rustc-env`.
+    pub fn envs(self, ws: &CargoWorkspace) -> &[(String, String)] {
+        &ws.packages[self].envs
+    }
     pub fn targets<'a>(self, ws: &'a CargoWorkspace) -> impl Iterator<Item = Target> + 'a {
         ws.packages[self].targets.iter().cloned()
     }
@@ -172,6 +215,13 @@ impl CargoWorkspace {
             meta.current_dir(parent);
         }
         let meta = meta.exec().map_err(|e| format!("cargo metadata failed: {}", e))?;
+
+        let mut extern_resources = if cargo_features.load_out_dirs_from_check {
+            load_extern_resources(cargo_toml, cargo_features)
+        } else {
+            ExternResources::default()
+        };
+
         let mut pkg_by_id = FxHashMap::default();
         let mut packages = Arena::default();
         let mut targets = Arena::default();
@@ -183,6 +233,7 @@ impl CargoWorkspace {
             let is_member = ws_members.contains(&id);
             let edition = edition.parse::<Edition>()?;
             let pkg = packages.alloc(PackageData {
+                id: id.clone(),
                 name,
                 manifest: manifest_path,
                 targets: Vec::new(),
@@ -190,6 +241,10 @@ impl CargoWorkspace {
                 edition,
                 dependencies: Vec::new(),
                 features: Vec::new(),
+                proc_macro_dylib_path: extern_resources.proc_dylib_paths.remove(&id),
+                out_dir: extern_resources.out_dirs.remove(&id),
+                cfgs: extern_resources.cfgs.remove(&id).unwrap_or_default(),
+                envs: extern_resources.envs.remove(&id).unwrap_or_default(),
             });
             let pkg_data = &mut packages[pkg];
             pkg_by_id.insert(id, pkg);
@@ -225,4 +280,87 @@ impl CargoWorkspace {
     pub fn target_by_root(&self, root: &Path) -> Option<Target> {
         self.packages().filter_map(|pkg| pkg.targets(self).find(|it| it.root(self) == root)).next()
     }
+
+    pub fn manifest_path(&self) -> PathBuf {
+        self.workspace_root.join("Cargo.toml")
+    }
+
+    /// Records the proc-macro dylibs found by a later `load_extern_resources`
+    /// run, so that building proc macros doesn't block loading the workspace.
+    pub fn set_proc_macro_dylib_paths(&mut self, resources: &ExternResources) {
+        let paths: Vec<(Package, PathBuf)> = self
+            .packages
+            .iter()
+            .filter_map(|(pkg, data)| {
+                Some((pkg, resources.proc_dylib_paths.get(&data.id)?.clone()))
+            })
+            .collect();
+        for (pkg, path) in paths {
+            self.packages[pkg].proc_macro_dylib_path = Some(path);
+        }
+    }
+}
+
+/// Artifacts of a `cargo check` run which aren't part of `cargo metadata`.
+#[derive(Debug, Clone, Default)]
+pub struct ExternResources {
+    proc_dylib_paths: FxHashMap<PackageId, PathBuf>,
+    out_dirs: FxHashMap<PackageId, PathBuf>,
+    cfgs: FxHashMap<PackageId, Vec<String>>,
+    envs: FxHashMap<PackageId, Vec<(String, String)>>,
+}
+
+/// Runs `cargo check --message-format=json` and collects the paths of the
+/// compiled proc-macro dylibs, as well as the output of build scripts.
+///
+/// This is best-effort: if cargo fails, we just don't know about the
+/// artifacts.
+pub fn load_extern_resources(cargo_toml: &Path, cargo_features: &CargoFeatures) -> ExternResources {
+    let mut cmd = Command::new("cargo");
+    cmd.args(&["check", "--message-format=json", "--manifest-path"]).arg(cargo_toml);
+
+    if cargo_features.all_features {
+        cmd.arg("--all-features");
+    } else if cargo_features.no_default_features {
+        // FIXME: `NoDefaultFeatures` is mutual exclusive with `SomeFeatures`
+        // https://github.com/oli-obk/cargo_metadata/issues/79
+        cmd.arg("--no-default-features");
+    } else if !cargo_features.features.is_empty() {
+        cmd.arg("--features");
+        cmd.arg(cargo_features.features.join(" "));
+    }
+
+    let mut res = ExternResources::default();
+    let output = match cmd.output() {
+        Ok(it) => it,
+        Err(e) => {
+            log::error!("failed to run `cargo check`: {}", e);
+            return res;
+        }
+    };
+
+    for message in cargo_metadata::parse_messages(output.stdout.as_slice()) {
+        match message {
+            Ok(Message::BuildScriptExecuted(message)) => {
+                let package_id = message.package_id;
+                // `out_dir` is only reported since Rust 1.41.
+                if message.out_dir != PathBuf::default() {
+                    res.out_dirs.insert(package_id.clone(), message.out_dir);
+                }
+                let cfgs = message.cfgs.iter().map(|cfg| cfg.to_string_lossy().into_owned());
+                res.cfgs.insert(package_id.clone(), cfgs.collect());
+                res.envs.insert(package_id, message.env);
+            }
+            Ok(Message::CompilerArtifact(message)) => {
+                if message.target.kind.iter().any(|kind| kind == "proc-macro") {
+                    if let Some(filename) = message.filenames.get(0) {
+                        res.proc_dylib_paths.insert(message.package_id, filename.clone());
+                    }
+                }
+            }
+            Ok(_) => (),
+            Err(err) => log::error!("invalid json from cargo check, ignoring: {}", err),
+        }
+    }
+    res
 }
@@ -10,15 +10,23 @@ use std::{
     io::BufReader,
     path::{Path, PathBuf},
     process::Command,
+    sync::Arc,
 };
 
 use ra_cfg::CfgOptions;
-use ra_db::{CrateGraph, CrateId, Edition, Env, FileId};
+use ra_db::{
+    CrateGraph, CrateId, Edition, Env, ExternSource, ExternSourceId, FileId, ProcMacro,
+    ProcMacroKind,
+};
+use ra_proc_macro::{ProcMacroClient, ProcMacroProcessExpander};
 use rustc_hash::FxHashMap;
 use serde_json::from_reader;
 
 pub use crate::{
-    cargo_workspace::{CargoFeatures, CargoWorkspace, Package, Target, TargetKind},
+    cargo_workspace::{
+        load_extern_resources, CargoFeatures, CargoWorkspace, ExternResources, Package, Target,
+        TargetKind,
+    },
     json_project::JsonProject,
     sysroot::Sysroot,
 };
@@ -113,6 +121,10 @@ impl ProjectWorkspace {
                     let root = pkg.root(&cargo).to_path_buf();
                     let member = pkg.is_member(&cargo);
                     roots.push(PackageRoot::new(root, member));
+                    if let Some(out_dir) = pkg.out_dir(&cargo) {
+                        // Files generated by build scripts are never workspace members.
+                        roots.push(PackageRoot::new(out_dir.to_path_buf(), false));
+                    }
                 }
                 for krate in sysroot.crates() {
                     roots.push(PackageRoot::new(krate.root_dir(&sysroot).to_path_buf(), false))
@@ -134,6 +146,8 @@ impl ProjectWorkspace {
     pub fn to_crate_graph(
         &self,
         default_cfg_options: &CfgOptions,
+        extern_source_roots: &FxHashMap<PathBuf, ExternSourceId>,
+        proc_macro_client: &ProcMacroClient,
         load: &mut dyn FnMut(&Path) -> Option<FileId>,
     ) -> (CrateGraph, FxHashMap<CrateId, String>) {
         let mut crate_graph = CrateGraph::default();
@@ -158,13 +172,21 @@ impl ProjectWorkspace {
                             }
                             opts
                         };
+                        // Without `--crate-name`, rustc names the crate after its root file
+                        let name = krate
+                            .root_module
+                            .file_stem()
+                            .map(|it| it.to_string_lossy().replace('-', "_").into());
                         crates.insert(
                             crate_id,
                             crate_graph.add_crate_root(
                                 file_id,
                                 edition,
+                                name,
                                 cfg_options,
                                 Env::default(),
+                                Default::default(),
+                                Vec::new(),
                             ),
                         );
                     }
@@ -202,8 +224,11 @@ impl ProjectWorkspace {
                         let crate_id = crate_graph.add_crate_root(
                             file_id,
                             Edition::Edition2018,
+                            Some(krate.name(&sysroot).into()),
                             cfg_options,
                             Env::default(),
+                            Default::default(),
+                            Vec::new(),
                         );
                         sysroot_crates.insert(krate, crate_id);
                         names.insert(crate_id, krate.name(&sysroot).to_string());
@@ -240,13 +265,49 @@ impl ProjectWorkspace {
                             let cfg_options = {
                                 let mut opts = default_cfg_options.clone();
                                 opts.insert_features(pkg.features(&cargo).iter().map(Into::into));
+                                for cfg in pkg.cfgs(&cargo) {
+                                    parse_cfg(cfg).insert_into(&mut opts);
+                                }
                                 opts
                             };
+                            let env = {
+                                let mut env = Env::default();
+                                if let Some(out_dir) = pkg.out_dir(&cargo) {
+                                    // FIXME: non UTF-8 paths get mangled here
+                                    env.set("OUT_DIR", out_dir.to_string_lossy().to_string());
+                                }
+                                for (key, value) in pkg.envs(&cargo) {
+                                    env.set(key, value.clone());
+                                }
+                                env
+                            };
+                            let extern_source = {
+                                let mut extern_source = ExternSource::default();
+                                if let Some(out_dir) = pkg.out_dir(&cargo) {
+                                    if let Some(&extern_source_id) =
+                                        extern_source_roots.get(out_dir)
+                                    {
+                                        extern_source.set_extern_path(out_dir, extern_source_id);
+                                    }
+                                }
+                                extern_source
+                            };
+                            let proc_macro = match pkg.proc_macro_dylib_path(&cargo) {
+                                Some(dylib_path) if tgt.is_proc_macro(&cargo) => proc_macro_client
+                                    .by_dylib_path(dylib_path)
+                                    .into_iter()
+                                    .map(to_proc_macro)
+                                    .collect(),
+                                _ => Vec::new(),
+                            };
                             let crate_id = crate_graph.add_crate_root(
                                 file_id,
                                 edition,
+                                Some(tgt.name(&cargo).replace('-', "_").into()),
                                 cfg_options,
-                                Env::default(),
+                                env,
+                                extern_source,
+                                proc_macro,
                             );
                             names.insert(crate_id, pkg.name(&cargo).to_string());
                             if tgt.kind(&cargo) == TargetKind::Lib {
@@ -343,6 +404,15 @@ impl ProjectWorkspace {
     }
 }
 
+fn to_proc_macro(expander: ProcMacroProcessExpander) -> ProcMacro {
+    let kind = match expander.kind() {
+        ra_proc_macro::ProcMacroKind::CustomDerive => ProcMacroKind::CustomDerive,
+        ra_proc_macro::ProcMacroKind::FuncLike => ProcMacroKind::FuncLike,
+        ra_proc_macro::ProcMacroKind::Attr => ProcMacroKind::Attr,
+    };
+    ProcMacro { name: expander.name().clone(), kind, expander: Arc::new(expander) }
+}
+
 fn find_rust_project_json(path: &Path) -> Option<PathBuf> {
     if path.ends_with("rust-project.json") {
         return Some(path.to_path_buf());
@@ -396,14 +466,7 @@ pub fn get_rustc_cfg_options() -> CfgOptions {
     })() {
         Ok(rustc_cfgs) => {
             for line in rustc_cfgs.lines() {
-                match line.find('=') {
-                    None => cfg_options.insert_atom(line.into()),
-                    Some(pos) => {
-                        let key = &line[..pos];
-                        let value = line[pos + 1..].trim_matches('"');
-                        cfg_options.insert_key_value(key.into(), value.into());
-                    }
-                }
+                parse_cfg(line).insert_into(&mut cfg_options);
             }
         }
         Err(e) => log::error!("failed to get rustc cfgs: {}", e),
@@ -411,3 +474,84 @@ pub fn get_rustc_cfg_options() -> CfgOptions {
 
     cfg_options
 }
+
+/// A cfg as printed by `rustc --print cfg` or set by a build script with
+/// `cargo:rustc-cfg`.
+#[derive(Debug, PartialEq, Eq)]
+enum Cfg {
+    Atom(String),
+    KeyValue { key: String, value: String },
+}
+
+impl Cfg {
+    fn insert_into(self, cfg_options: &mut CfgOptions) {
+        match self {
+            Cfg::Atom(name) => cfg_options.insert_atom(name.into()),
+            Cfg::KeyValue { key, value } => cfg_options.insert_key_value(key.into(), value.into()),
+        }
+    }
+}
+
+/// Parses `name`, `key="value"` or `key=value`. Escapes in quoted values are
+/// processed like in a Rust string literal.
+fn parse_cfg(cfg: &str) -> Cfg {
+    let pos = match cfg.find('=') {
+        Some(it) => it,
+        None => return Cfg::Atom(cfg.to_string()),
+    };
+    let key = cfg[..pos].to_string();
+    let value = &cfg[pos + 1..];
+    let value = if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+        let quoted = &value[1..value.len() - 1];
+        unescape(quoted).unwrap_or_else(|| quoted.to_string())
+    } else {
+        value.to_string()
+    };
+    Cfg::KeyValue { key, value }
+}
+
+fn unescape(text: &str) -> Option<String> {
+    let mut res = String::with_capacity(text.len());
+    let mut has_error = false;
+    rustc_lexer::unescape::unescape_str(text, &mut |_range, c| match c {
+        Ok(c) => res.push(c),
+        Err(_) => has_error = true,
+    });
+    if has_error {
+        None
+    } else {
+        Some(res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_cfg, Cfg};
+
+    fn key_value(key: &str, value: &str) -> Cfg {
+        Cfg::KeyValue { key: key.to_string(), value: value.to_string() }
+    }
+
+    #[test]
+    fn parses_atoms() {
+        assert_eq!(parse_cfg("unix"), Cfg::Atom("unix".to_string()));
+        assert_eq!(parse_cfg("has_foo"), Cfg::Atom("has_foo".to_string()));
+    }
+
+    #[test]
+    fn parses_key_value_pairs() {
+        assert_eq!(parse_cfg(r#"target_os="linux""#), key_value("target_os", "linux"));
+        assert_eq!(parse_cfg(r#"feature="""#), key_value("feature", ""));
+        assert_eq!(parse_cfg("foo=bar"), key_value("foo", "bar"));
+        assert_eq!(parse_cfg(r#"foo="a=b""#), key_value("foo", "a=b"));
+    }
+
+    #[test]
+    fn unescapes_values() {
+        assert_eq!(parse_cfg(r#"foo="a\"b""#), key_value("foo", "a\"b"));
+        assert_eq!(parse_cfg(r#"foo="a\\b""#), key_value("foo", "a\\b"));
+        assert_eq!(parse_cfg(r#"foo="\u{e9}\x41\n""#), key_value("foo", "\u{e9}A\n"));
+        // Invalid escapes are kept as is.
+        assert_eq!(parse_cfg(r#"foo="\q""#), key_value("foo", "\\q"));
+    }
+}
@@ -59,6 +59,7 @@ impl ast::ItemList {
         res
     }
 
+    /// Appends `item`, reformatted to the indentation of the items.
     #[must_use]
     pub fn append_item(&self, item: ast::ImplItem) -> ast::ItemList {
         let (indent, position) = match self.impl_items().last() {
@@ -74,6 +75,7 @@ impl ast::ItemList {
                 None => return self.clone(),
             },
         };
+        let item = IndentLevel((indent.len() / 4) as u8).reformat(item);
         let ws = tokens::WsBuilder::new(&format!("\n{}", indent));
         let to_insert: ArrayVec<[SyntaxElement; 2]> =
             [ws.ws().into(), item.syntax().clone().into()].into();
@@ -129,6 +131,11 @@ impl ast::RecordFieldList {
         field: &ast::RecordField,
     ) -> ast::RecordFieldList {
         let is_multiline = self.syntax().text().contains_char('\n');
+        let field = if is_multiline {
+            IndentLevel(IndentLevel::from_node(self.syntax()).0 + 1).reformat(field.clone())
+        } else {
+            field.clone()
+        };
         let ws;
         let space = if is_multiline {
             ws = tokens::WsBuilder::new(&format!(
@@ -266,6 +273,56 @@ impl IndentLevel {
         IndentLevel(0)
     }
 
+    /// Re-indents a freshly built or edited `node`, so that it can be placed
+    /// at this level.
+    ///
+    /// Only whitespace containing newlines is changed: the contents of `{}`,
+    /// `()` and `[]` are indented one level deeper than the line the delimiter
+    /// is opened on, lines starting with a closing delimiter are aligned with
+    /// that line and method chains get one extra level. Trailing whitespace is
+    /// removed and runs of blank lines are collapsed into a single one.
+    /// Everything else, including comments and literals, is emitted as is.
+    pub fn reformat<N: AstNode>(self, node: N) -> N {
+        N::cast(self._reformat(node.syntax().clone())).unwrap()
+    }
+
+    fn _reformat(self, node: SyntaxNode) -> SyntaxNode {
+        // Levels of the contents of the open delimiters, relative to `self`.
+        let mut levels = vec![0usize];
+        let mut line_level = 0;
+        let mut replacements: FxHashMap<SyntaxElement, SyntaxElement> = FxHashMap::default();
+        for token in node.descendants_with_tokens().filter_map(|it| it.into_token()) {
+            match token.kind() {
+                T!['{'] | T!['('] | T!['['] => levels.push(line_level + 1),
+                T!['}'] | T![')'] | T![']'] => {
+                    if levels.len() > 1 {
+                        levels.pop();
+                    }
+                }
+                WHITESPACE if token.text().contains('\n') => {
+                    let level = *levels.last().unwrap();
+                    line_level = match token.next_token().map(|it| it.kind()) {
+                        Some(T!['}']) | Some(T![')']) | Some(T![']']) => level.saturating_sub(1),
+                        Some(T![.]) => level + 1,
+                        _ => level,
+                    };
+                    let newlines = token.text().matches('\n').count().min(2);
+                    let text = format!(
+                        "{}{:width$}",
+                        "\n".repeat(newlines),
+                        "",
+                        width = (self.0 as usize + line_level) * 4
+                    );
+                    if text != token.text().as_str() {
+                        replacements.insert(token.into(), make::tokens::whitespace(&text).into());
+                    }
+                }
+                _ => (),
+            }
+        }
+        algo::replace_descendants(&node, &replacements)
+    }
+
     pub fn increase_indent<N: AstNode>(self, node: N) -> N {
         N::cast(self._increase_indent(node.syntax().clone())).unwrap()
     }
@@ -386,3 +443,20 @@ fn test_increase_indent() {
         }"
     );
 }
+
+#[test]
+fn test_reformat_method_chain() {
+    let file = crate::SourceFile::parse("fn foo() {\nx\n.map(|it|\nit)\n.filter(|it| {\nit\n})\n}");
+    let fn_def = file.tree().syntax().descendants().find_map(ast::FnDef::cast).unwrap();
+    assert_eq!(
+        IndentLevel(1).reformat(fn_def).syntax().to_string(),
+        "fn foo() {
+        x
+            .map(|it|
+                it)
+            .filter(|it| {
+                it
+            })
+    }"
+    );
+}
@@ -49,6 +49,16 @@ pub enum PrefixOp {
     Neg,
 }
 
+impl PrefixOp {
+    pub fn text(self) -> &'static str {
+        match self {
+            PrefixOp::Deref => "*",
+            PrefixOp::Not => "!",
+            PrefixOp::Neg => "-",
+        }
+    }
+}
+
 impl ast::PrefixExpr {
     pub fn op_kind(&self) -> Option<PrefixOp> {
         match self.op_token()?.kind() {
@@ -143,6 +153,40 @@ impl BinOp {
             _ => false,
         }
     }
+
+    pub fn text(self) -> &'static str {
+        match self {
+            BinOp::BooleanOr => "||",
+            BinOp::BooleanAnd => "&&",
+            BinOp::EqualityTest => "==",
+            BinOp::NegatedEqualityTest => "!=",
+            BinOp::LesserEqualTest => "<=",
+            BinOp::GreaterEqualTest => ">=",
+            BinOp::LesserTest => "<",
+            BinOp::GreaterTest => ">",
+            BinOp::Addition => "+",
+            BinOp::Multiplication => "*",
+            BinOp::Subtraction => "-",
+            BinOp::Division => "/",
+            BinOp::Remainder => "%",
+            BinOp::LeftShift => "<<",
+            BinOp::RightShift => ">>",
+            BinOp::BitwiseXor => "^",
+            BinOp::BitwiseOr => "|",
+            BinOp::BitwiseAnd => "&",
+            BinOp::Assignment => "=",
+            BinOp::AddAssign => "+=",
+            BinOp::DivAssign => "/=",
+            BinOp::MulAssign => "*=",
+            BinOp::RemAssign => "%=",
+            BinOp::ShrAssign => ">>=",
+            BinOp::ShlAssign => "<<=",
+            BinOp::SubAssign => "-=",
+            BinOp::BitOrAssign => "|=",
+            BinOp::BitAndAssign => "&=",
+            BinOp::BitXorAssign => "^=",
+        }
+    }
 }
 impl ast::BinExpr {
     pub fn op_details(&self) -> Option<(SyntaxToken, BinOp)> {
@@ -215,6 +259,15 @@ pub enum RangeOp {
     Inclusive,
 }
 
+impl RangeOp {
+    pub fn text(self) -> &'static str {
+        match self {
+            RangeOp::Exclusive => "..",
+            RangeOp::Inclusive => "..=",
+        }
+    }
+}
+
 impl ast::RangeExpr {
     fn op_details(&self) -> Option<(usize, SyntaxToken, RangeOp)> {
         self.syntax().children_with_tokens().enumerate().find_map(|(ix, child)| {
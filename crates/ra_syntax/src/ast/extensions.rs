@@ -227,6 +227,12 @@ impl ast::FnDef {
     }
 }
 
+impl ast::LambdaExpr {
+    pub fn is_move(&self) -> bool {
+        self.syntax().children_with_tokens().any(|it| it.kind() == T![move])
+    }
+}
+
 impl ast::LetStmt {
     pub fn has_semi(&self) -> bool {
         match self.syntax().last_child_or_token() {
@@ -289,6 +295,40 @@ impl ast::BindPat {
     }
 }
 
+pub struct SlicePatComponents {
+    pub prefix: Vec<ast::Pat>,
+    pub slice: Option<ast::Pat>,
+    pub suffix: Vec<ast::Pat>,
+}
+
+impl ast::SlicePat {
+    /// Splits the elements around the rest pattern, which is either `..` or
+    /// `name @ ..`.
+    pub fn components(&self) -> SlicePatComponents {
+        let mut args = self.args();
+        let mut prefix = Vec::new();
+        let mut slice = None;
+        while let Some(arg) = args.next() {
+            let is_rest = match &arg {
+                ast::Pat::DotDotPat(_) => true,
+                ast::Pat::BindPat(it) => match it.pat() {
+                    Some(ast::Pat::DotDotPat(_)) => true,
+                    _ => false,
+                },
+                _ => false,
+            };
+            if is_rest {
+                slice = Some(arg);
+                break;
+            }
+            prefix.push(arg);
+        }
+        let suffix = args.collect();
+
+        SlicePatComponents { prefix, slice, suffix }
+    }
+}
+
 impl ast::PointerType {
     pub fn is_mut(&self) -> bool {
         self.syntax().children_with_tokens().any(|n| n.kind() == T![mut])
@@ -1,169 +1,78 @@
 //! This module contains free-standing functions for creating AST fragments out
 //! of smaller pieces.
-use itertools::Itertools;
+//!
+//! Builders for all node types are generated from the templates in
+//! `xtask/src/ast_src.rs`, the functions here are shortcuts for common cases.
+mod generated;
 
-use crate::{ast, AstNode, SourceFile};
+use std::iter;
 
-pub fn name(text: &str) -> ast::Name {
-    ast_from_text(&format!("mod {};", text))
-}
+use crate::{ast, AstNode, SourceFile};
 
-pub fn name_ref(text: &str) -> ast::NameRef {
-    ast_from_text(&format!("fn f() {{ {}; }}", text))
-}
+pub use self::generated::*;
 
 pub fn path_from_name_ref(name_ref: ast::NameRef) -> ast::Path {
-    path_from_text(&name_ref.syntax().to_string())
+    path(None, path_segment(name_ref, None))
 }
 pub fn path_qualified(qual: ast::Path, name_ref: ast::NameRef) -> ast::Path {
-    path_from_text(&format!("{}::{}", qual.syntax(), name_ref.syntax()))
-}
-fn path_from_text(text: &str) -> ast::Path {
-    ast_from_text(text)
-}
-
-pub fn record_field(name: ast::NameRef, expr: Option<ast::Expr>) -> ast::RecordField {
-    return match expr {
-        Some(expr) => from_text(&format!("{}: {}", name.syntax(), expr.syntax())),
-        None => from_text(&name.syntax().to_string()),
-    };
-
-    fn from_text(text: &str) -> ast::RecordField {
-        ast_from_text(&format!("fn f() {{ S {{ {}, }} }}", text))
-    }
+    path(Some(qual), path_segment(name_ref, None))
 }
 
 pub fn block_from_expr(e: ast::Expr) -> ast::Block {
-    return from_text(&format!("{{ {} }}", e.syntax()));
-
-    fn from_text(text: &str) -> ast::Block {
-        ast_from_text(&format!("fn f() {}", text))
-    }
+    block(iter::empty(), Some(e))
 }
 
 pub fn expr_unit() -> ast::Expr {
-    expr_from_text("()")
+    tuple_expr(iter::empty()).into()
 }
 pub fn expr_unimplemented() -> ast::Expr {
-    expr_from_text("unimplemented!()")
+    let path = path_from_name_ref(name_ref("unimplemented"));
+    macro_call(path, token_tree(iter::empty())).into()
 }
 pub fn expr_path(path: ast::Path) -> ast::Expr {
-    expr_from_text(&path.syntax().to_string())
+    path_expr(path).into()
 }
 pub fn expr_continue() -> ast::Expr {
-    expr_from_text("continue")
+    continue_expr().into()
 }
 pub fn expr_break() -> ast::Expr {
-    expr_from_text("break")
+    break_expr(None).into()
 }
 pub fn expr_return() -> ast::Expr {
-    expr_from_text("return")
+    return_expr(None).into()
 }
 pub fn expr_match(expr: ast::Expr, match_arm_list: ast::MatchArmList) -> ast::Expr {
-    expr_from_text(&format!("match {} {}", expr.syntax(), match_arm_list.syntax()))
-}
-fn expr_from_text(text: &str) -> ast::Expr {
-    ast_from_text(&format!("const C: () = {};", text))
+    match_expr(expr, match_arm_list).into()
 }
 
 pub fn bind_pat(name: ast::Name) -> ast::BindPat {
-    return from_text(name.text());
-
-    fn from_text(text: &str) -> ast::BindPat {
-        ast_from_text(&format!("fn f({}: ())", text))
-    }
-}
-
-pub fn placeholder_pat() -> ast::PlaceholderPat {
-    return from_text("_");
-
-    fn from_text(text: &str) -> ast::PlaceholderPat {
-        ast_from_text(&format!("fn f({}: ())", text))
-    }
-}
-
-pub fn tuple_struct_pat(
-    path: ast::Path,
-    pats: impl IntoIterator<Item = ast::Pat>,
-) -> ast::TupleStructPat {
-    let pats_str = pats.into_iter().map(|p| p.syntax().to_string()).join(", ");
-    return from_text(&format!("{}({})", path.syntax(), pats_str));
-
-    fn from_text(text: &str) -> ast::TupleStructPat {
-        ast_from_text(&format!("fn f({}: ())", text))
-    }
+    generated::bind_pat(false, false, name, None)
 }
 
-pub fn record_pat(path: ast::Path, pats: impl IntoIterator<Item = ast::Pat>) -> ast::RecordPat {
-    let pats_str = pats.into_iter().map(|p| p.syntax().to_string()).join(", ");
-    return from_text(&format!("{} {{ {} }}", path.syntax(), pats_str));
-
-    fn from_text(text: &str) -> ast::RecordPat {
-        ast_from_text(&format!("fn f({}: ())", text))
-    }
-}
-
-pub fn path_pat(path: ast::Path) -> ast::PathPat {
-    let path_str = path.syntax().text().to_string();
-    return from_text(path_str.as_str());
-    fn from_text(text: &str) -> ast::PathPat {
-        ast_from_text(&format!("fn f({}: ())", text))
-    }
+pub fn record_pat(path: ast::Path, pats: impl IntoIterator<Item = ast::BindPat>) -> ast::RecordPat {
+    generated::record_pat(path, record_field_pat_list(iter::empty(), pats))
 }
 
 pub fn match_arm(pats: impl IntoIterator<Item = ast::Pat>, expr: ast::Expr) -> ast::MatchArm {
-    let pats_str = pats.into_iter().map(|p| p.syntax().to_string()).join(" | ");
-    return from_text(&format!("{} => {}", pats_str, expr.syntax()));
-
-    fn from_text(text: &str) -> ast::MatchArm {
-        ast_from_text(&format!("fn f() {{ match () {{{}}} }}", text))
-    }
-}
-
-pub fn match_arm_list(arms: impl IntoIterator<Item = ast::MatchArm>) -> ast::MatchArmList {
-    let arms_str = arms.into_iter().map(|arm| format!("\n    {}", arm.syntax())).join(",");
-    return from_text(&format!("{},\n", arms_str));
-
-    fn from_text(text: &str) -> ast::MatchArmList {
-        ast_from_text(&format!("fn f() {{ match () {{{}}} }}", text))
-    }
+    generated::match_arm(pats, None, expr)
 }
 
 pub fn where_pred(
     path: ast::Path,
     bounds: impl IntoIterator<Item = ast::TypeBound>,
 ) -> ast::WherePred {
-    let bounds = bounds.into_iter().map(|b| b.syntax().to_string()).join(" + ");
-    return from_text(&format!("{}: {}", path.syntax(), bounds));
-
-    fn from_text(text: &str) -> ast::WherePred {
-        ast_from_text(&format!("fn f() where {} {{ }}", text))
-    }
-}
-
-pub fn where_clause(preds: impl IntoIterator<Item = ast::WherePred>) -> ast::WhereClause {
-    let preds = preds.into_iter().map(|p| p.syntax().to_string()).join(", ");
-    return from_text(preds.as_str());
-
-    fn from_text(text: &str) -> ast::WhereClause {
-        ast_from_text(&format!("fn f() where {} {{ }}", text))
-    }
+    generated::where_pred(path_type(path).into(), type_bound_list(bounds))
 }
 
 pub fn if_expression(condition: &ast::Expr, statement: &str) -> ast::IfExpr {
-    ast_from_text(&format!(
-        "fn f() {{ if !{} {{\n    {}\n}}\n}}",
-        condition.syntax().text(),
-        statement
-    ))
+    let not_cond = prefix_expr(ast::PrefixOp::Not, condition.clone());
+    let statement: ast::Stmt = ast_from_text(&format!("fn f() {{ {} }}", statement));
+    let then_branch = block_expr(false, block(iter::once(statement), None));
+    if_expr(generated::condition(None, not_cond.into()), then_branch, None)
 }
 
 pub fn let_stmt(pattern: ast::Pat, initializer: Option<ast::Expr>) -> ast::LetStmt {
-    let text = match initializer {
-        Some(it) => format!("let {} = {};", pattern.syntax(), it.syntax()),
-        None => format!("let {};", pattern.syntax()),
-    };
-    ast_from_text(&format!("fn f() {{ {} }}", text))
+    generated::let_stmt(pattern, None, initializer)
 }
 
 fn ast_from_text<N: AstNode>(text: &str) -> N {
@@ -171,6 +80,51 @@ fn ast_from_text<N: AstNode>(text: &str) -> N {
     parse.tree().syntax().descendants().find_map(N::cast).unwrap()
 }
 
+/// Parses `text` in `context`, where `$` marks the place of the node.
+fn ast_from_template<N: AstNode>(context: &str, text: &str) -> N {
+    ast_from_text(&context.replacen('$', text, 1))
+}
+
+/// Source with an instance of every node the generated builders accept.
+#[cfg(test)]
+const SAMPLES: &str = r#"
+pub const ONE: u32 = 1;
+fn foo(x: u32) -> u32 { x }
+extern crate foo as bar;
+use a::{b, c};
+struct S<T> where T: Clone { a: u32 }
+struct Tuple(u32);
+enum E { A, B }
+impl S {
+    fn bar(&self) {
+        let y = 1;
+        bar(1);
+        foo!(1);
+        S { a: 1 };
+        Vec::<u32>::new();
+        if let Some(x) = y {}
+        match x { 1 if true => (), S { a: x } => (), _ => () }
+        -y + 1;
+        0..10;
+    }
+}
+fn baz<'a>() { 'outer: loop {} }
+"#;
+
+#[cfg(test)]
+fn sample<N: AstNode>() -> N {
+    ast_from_text(SAMPLES)
+}
+
+#[cfg(test)]
+fn check_round_trip<N: AstNode>(node: N, context: &str) {
+    let text = node.syntax().to_string();
+    let parse = SourceFile::parse(&context.replacen('$', &text, 1));
+    assert!(parse.errors().is_empty(), "`{}` has errors: {:?}", text, parse.errors());
+    let reparsed: N = parse.tree().syntax().descendants().find_map(N::cast).unwrap();
+    assert_eq!(reparsed.syntax().to_string(), text);
+}
+
 pub mod tokens {
     use crate::{AstNode, Parse, SourceFile, SyntaxKind, SyntaxKind::*, SyntaxToken, T};
     use once_cell::sync::Lazy;
@@ -0,0 +1,1397 @@
+//! Generated file, do not edit by hand, see `crate/ra_tools/src/codegen`
+
+use crate::{
+    ast::{self, edit::IndentLevel, make::ast_from_template},
+    AstNode,
+};
+pub fn source_file(items: impl IntoIterator<Item = ast::ModuleItem>) -> ast::SourceFile {
+    let mut buf = String::new();
+    let items: Vec<String> = items.into_iter().map(|it| it.syntax().to_string()).collect();
+    buf.push_str(&items.join("\n\n"));
+    IndentLevel(0).reformat(ast_from_template("$", &buf))
+}
+pub fn fn_def(
+    visibility: Option<ast::Visibility>,
+    is_async: bool,
+    name: ast::Name,
+    type_param_list: Option<ast::TypeParamList>,
+    param_list: ast::ParamList,
+    ret_type: Option<ast::RetType>,
+    where_clause: Option<ast::WhereClause>,
+    body: ast::BlockExpr,
+) -> ast::FnDef {
+    let mut buf = String::new();
+    if let Some(it) = visibility {
+        buf.push_str(&it.syntax().to_string());
+        buf.push_str(" ");
+    }
+    if is_async {
+        buf.push_str("async ");
+    }
+    buf.push_str("fn ");
+    buf.push_str(&name.syntax().to_string());
+    if let Some(it) = type_param_list {
+        buf.push_str(&it.syntax().to_string());
+    }
+    buf.push_str(&param_list.syntax().to_string());
+    if let Some(it) = ret_type {
+        buf.push_str(" ");
+        buf.push_str(&it.syntax().to_string());
+    }
+    if let Some(it) = where_clause {
+        buf.push_str(" ");
+        buf.push_str(&it.syntax().to_string());
+    }
+    buf.push_str(" ");
+    buf.push_str(&body.syntax().to_string());
+    ast_from_template("$", &buf)
+}
+pub fn ret_type(type_ref: ast::TypeRef) -> ast::RetType {
+    let mut buf = String::new();
+    buf.push_str("-> ");
+    buf.push_str(&type_ref.syntax().to_string());
+    ast_from_template("fn f() $ {}", &buf)
+}
+pub fn struct_def(
+    visibility: Option<ast::Visibility>,
+    name: ast::Name,
+    type_param_list: Option<ast::TypeParamList>,
+    where_clause: Option<ast::WhereClause>,
+    record_field_def_list: ast::RecordFieldDefList,
+) -> ast::StructDef {
+    let mut buf = String::new();
+    if let Some(it) = visibility {
+        buf.push_str(&it.syntax().to_string());
+        buf.push_str(" ");
+    }
+    buf.push_str("struct ");
+    buf.push_str(&name.syntax().to_string());
+    if let Some(it) = type_param_list {
+        buf.push_str(&it.syntax().to_string());
+    }
+    if let Some(it) = where_clause {
+        buf.push_str(" ");
+        buf.push_str(&it.syntax().to_string());
+    }
+    buf.push_str(" ");
+    buf.push_str(&record_field_def_list.syntax().to_string());
+    ast_from_template("$", &buf)
+}
+pub fn union_def(
+    visibility: Option<ast::Visibility>,
+    name: ast::Name,
+    type_param_list: Option<ast::TypeParamList>,
+    where_clause: Option<ast::WhereClause>,
+    record_field_def_list: ast::RecordFieldDefList,
+) -> ast::UnionDef {
+    let mut buf = String::new();
+    if let Some(it) = visibility {
+        buf.push_str(&it.syntax().to_string());
+        buf.push_str(" ");
+    }
+    buf.push_str("union ");
+    buf.push_str(&name.syntax().to_string());
+    if let Some(it) = type_param_list {
+        buf.push_str(&it.syntax().to_string());
+    }
+    if let Some(it) = where_clause {
+        buf.push_str(" ");
+        buf.push_str(&it.syntax().to_string());
+    }
+    buf.push_str(" ");
+    buf.push_str(&record_field_def_list.syntax().to_string());
+    ast_from_template("$", &buf)
+}
+pub fn record_field_def_list(
+    fields: impl IntoIterator<Item = ast::RecordFieldDef>,
+) -> ast::RecordFieldDefList {
+    let mut buf = String::new();
+    buf.push_str("{ ");
+    let fields: Vec<String> = fields.into_iter().map(|it| it.syntax().to_string()).collect();
+    buf.push_str(&fields.join(", "));
+    buf.push_str(" }");
+    ast_from_template("struct S $", &buf)
+}
+pub fn record_field_def(
+    visibility: Option<ast::Visibility>,
+    name: ast::Name,
+    ascribed_type: ast::TypeRef,
+) -> ast::RecordFieldDef {
+    let mut buf = String::new();
+    if let Some(it) = visibility {
+        buf.push_str(&it.syntax().to_string());
+        buf.push_str(" ");
+    }
+    buf.push_str(&name.syntax().to_string());
+    buf.push_str(": ");
+    buf.push_str(&ascribed_type.syntax().to_string());
+    ast_from_template("struct S { $ }", &buf)
+}
+pub fn tuple_field_def_list(
+    fields: impl IntoIterator<Item = ast::TupleFieldDef>,
+) -> ast::TupleFieldDefList {
+    let mut buf = String::new();
+    buf.push_str("(");
+    let fields: Vec<String> = fields.into_iter().map(|it| it.syntax().to_string()).collect();
+    buf.push_str(&fields.join(", "));
+    buf.push_str(")");
+    ast_from_template("struct S$;", &buf)
+}
+pub fn tuple_field_def(
+    visibility: Option<ast::Visibility>,
+    type_ref: ast::TypeRef,
+) -> ast::TupleFieldDef {
+    let mut buf = String::new();
+    if let Some(it) = visibility {
+        buf.push_str(&it.syntax().to_string());
+        buf.push_str(" ");
+    }
+    buf.push_str(&type_ref.syntax().to_string());
+    ast_from_template("struct S($);", &buf)
+}
+pub fn enum_def(
+    visibility: Option<ast::Visibility>,
+    name: ast::Name,
+    type_param_list: Option<ast::TypeParamList>,
+    where_clause: Option<ast::WhereClause>,
+    variant_list: ast::EnumVariantList,
+) -> ast::EnumDef {
+    let mut buf = String::new();
+    if let Some(it) = visibility {
+        buf.push_str(&it.syntax().to_string());
+        buf.push_str(" ");
+    }
+    buf.push_str("enum ");
+    buf.push_str(&name.syntax().to_string());
+    if let Some(it) = type_param_list {
+        buf.push_str(&it.syntax().to_string());
+    }
+    if let Some(it) = where_clause {
+        buf.push_str(" ");
+        buf.push_str(&it.syntax().to_string());
+    }
+    buf.push_str(" ");
+    buf.push_str(&variant_list.syntax().to_string());
+    ast_from_template("$", &buf)
+}
+pub fn enum_variant_list(
+    variants: impl IntoIterator<Item = ast::EnumVariant>,
+) -> ast::EnumVariantList {
+    let mut buf = String::new();
+    buf.push_str("{ ");
+    let variants: Vec<String> = variants.into_iter().map(|it| it.syntax().to_string()).collect();
+    buf.push_str(&variants.join(", "));
+    buf.push_str(" }");
+    ast_from_template("enum E $", &buf)
+}
+pub fn enum_variant(
+    name: ast::Name,
+    tuple_field_def_list: Option<ast::TupleFieldDefList>,
+) -> ast::EnumVariant {
+    let mut buf = String::new();
+    buf.push_str(&name.syntax().to_string());
+    if let Some(it) = tuple_field_def_list {
+        buf.push_str(&it.syntax().to_string());
+    }
+    ast_from_template("enum E { $ }", &buf)
+}
+pub fn trait_def(
+    visibility: Option<ast::Visibility>,
+    name: ast::Name,
+    type_param_list: Option<ast::TypeParamList>,
+    type_bound_list: Option<ast::TypeBoundList>,
+    where_clause: Option<ast::WhereClause>,
+    item_list: ast::ItemList,
+) -> ast::TraitDef {
+    let mut buf = String::new();
+    if let Some(it) = visibility {
+        buf.push_str(&it.syntax().to_string());
+        buf.push_str(" ");
+    }
+    buf.push_str("trait ");
+    buf.push_str(&name.syntax().to_string());
+    if let Some(it) = type_param_list {
+        buf.push_str(&it.syntax().to_string());
+    }
+    if let Some(it) = type_bound_list {
+        buf.push_str(": ");
+        buf.push_str(&it.syntax().to_string());
+    }
+    if let Some(it) = where_clause {
+        buf.push_str(" ");
+        buf.push_str(&it.syntax().to_string());
+    }
+    buf.push_str(" ");
+    buf.push_str(&item_list.syntax().to_string());
+    ast_from_template("$", &buf)
+}
+pub fn module(
+    visibility: Option<ast::Visibility>,
+    name: ast::Name,
+    item_list: ast::ItemList,
+) -> ast::Module {
+    let mut buf = String::new();
+    if let Some(it) = visibility {
+        buf.push_str(&it.syntax().to_string());
+        buf.push_str(" ");
+    }
+    buf.push_str("mod ");
+    buf.push_str(&name.syntax().to_string());
+    buf.push_str(" ");
+    buf.push_str(&item_list.syntax().to_string());
+    ast_from_template("$", &buf)
+}
+pub fn item_list(impl_items: impl IntoIterator<Item = ast::ImplItem>) -> ast::ItemList {
+    let mut buf = String::new();
+    buf.push_str("{ ");
+    let impl_items: Vec<String> =
+        impl_items.into_iter().map(|it| it.syntax().to_string()).collect();
+    buf.push_str(&impl_items.join(" "));
+    buf.push_str(" }");
+    ast_from_template("impl S $", &buf)
+}
+pub fn const_def(
+    visibility: Option<ast::Visibility>,
+    name: ast::Name,
+    ascribed_type: ast::TypeRef,
+    body: ast::Expr,
+) -> ast::ConstDef {
+    let mut buf = String::new();
+    if let Some(it) = visibility {
+        buf.push_str(&it.syntax().to_string());
+        buf.push_str(" ");
+    }
+    buf.push_str("const ");
+    buf.push_str(&name.syntax().to_string());
+    buf.push_str(": ");
+    buf.push_str(&ascribed_type.syntax().to_string());
+    buf.push_str(" = ");
+    buf.push_str(&body.syntax().to_string());
+    buf.push_str(";");
+    ast_from_template("$", &buf)
+}
+pub fn static_def(
+    visibility: Option<ast::Visibility>,
+    mutable: bool,
+    name: ast::Name,
+    ascribed_type: ast::TypeRef,
+    body: ast::Expr,
+) -> ast::StaticDef {
+    let mut buf = String::new();
+    if let Some(it) = visibility {
+        buf.push_str(&it.syntax().to_string());
+        buf.push_str(" ");
+    }
+    buf.push_str("static ");
+    if mutable {
+        buf.push_str("mut ");
+    }
+    buf.push_str(&name.syntax().to_string());
+    buf.push_str(": ");
+    buf.push_str(&ascribed_type.syntax().to_string());
+    buf.push_str(" = ");
+    buf.push_str(&body.syntax().to_string());
+    buf.push_str(";");
+    ast_from_template("$", &buf)
+}
+pub fn type_alias_def(
+    visibility: Option<ast::Visibility>,
+    name: ast::Name,
+    type_param_list: Option<ast::TypeParamList>,
+    where_clause: Option<ast::WhereClause>,
+    type_ref: ast::TypeRef,
+) -> ast::TypeAliasDef {
+    let mut buf = String::new();
+    if let Some(it) = visibility {
+        buf.push_str(&it.syntax().to_string());
+        buf.push_str(" ");
+    }
+    buf.push_str("type ");
+    buf.push_str(&name.syntax().to_string());
+    if let Some(it) = type_param_list {
+        buf.push_str(&it.syntax().to_string());
+    }
+    if let Some(it) = where_clause {
+        buf.push_str(" ");
+        buf.push_str(&it.syntax().to_string());
+    }
+    buf.push_str(" = ");
+    buf.push_str(&type_ref.syntax().to_string());
+    buf.push_str(";");
+    ast_from_template("$", &buf)
+}
+pub fn impl_block(
+    type_param_list: Option<ast::TypeParamList>,
+    target_trait: Option<ast::TypeRef>,
+    target_type: ast::TypeRef,
+    where_clause: Option<ast::WhereClause>,
+    item_list: ast::ItemList,
+) -> ast::ImplBlock {
+    let mut buf = String::new();
+    buf.push_str("impl");
+    if let Some(it) = type_param_list {
+        buf.push_str(&it.syntax().to_string());
+    }
+    buf.push_str(" ");
+    if let Some(it) = target_trait {
+        buf.push_str(&it.syntax().to_string());
+        buf.push_str(" for ");
+    }
+    buf.push_str(&target_type.syntax().to_string());
+    if let Some(it) = where_clause {
+        buf.push_str(" ");
+        buf.push_str(&it.syntax().to_string());
+    }
+    buf.push_str(" ");
+    buf.push_str(&item_list.syntax().to_string());
+    ast_from_template("$", &buf)
+}
+pub fn use_item(visibility: Option<ast::Visibility>, use_tree: ast::UseTree) -> ast::UseItem {
+    let mut buf = String::new();
+    if let Some(it) = visibility {
+        buf.push_str(&it.syntax().to_string());
+        buf.push_str(" ");
+    }
+    buf.push_str("use ");
+    buf.push_str(&use_tree.syntax().to_string());
+    buf.push_str(";");
+    ast_from_template("$", &buf)
+}
+pub fn use_tree(path: ast::Path, use_tree_list: Option<ast::UseTreeList>) -> ast::UseTree {
+    let mut buf = String::new();
+    buf.push_str(&path.syntax().to_string());
+    if let Some(it) = use_tree_list {
+        buf.push_str("::");
+        buf.push_str(&it.syntax().to_string());
+    }
+    ast_from_template("use $;", &buf)
+}
+pub fn use_tree_list(use_trees: impl IntoIterator<Item = ast::UseTree>) -> ast::UseTreeList {
+    let mut buf = String::new();
+    buf.push_str("{");
+    let use_trees: Vec<String> = use_trees.into_iter().map(|it| it.syntax().to_string()).collect();
+    buf.push_str(&use_trees.join(", "));
+    buf.push_str("}");
+    ast_from_template("use a::$;", &buf)
+}
+pub fn alias(name: ast::Name) -> ast::Alias {
+    let mut buf = String::new();
+    buf.push_str("as ");
+    buf.push_str(&name.syntax().to_string());
+    ast_from_template("use a $;", &buf)
+}
+pub fn extern_crate_item(
+    visibility: Option<ast::Visibility>,
+    name_ref: ast::NameRef,
+    alias: Option<ast::Alias>,
+) -> ast::ExternCrateItem {
+    let mut buf = String::new();
+    if let Some(it) = visibility {
+        buf.push_str(&it.syntax().to_string());
+        buf.push_str(" ");
+    }
+    buf.push_str("extern crate ");
+    buf.push_str(&name_ref.syntax().to_string());
+    if let Some(it) = alias {
+        buf.push_str(" ");
+        buf.push_str(&it.syntax().to_string());
+    }
+    buf.push_str(";");
+    ast_from_template("$", &buf)
+}
+pub fn macro_call(path: ast::Path, token_tree: ast::TokenTree) -> ast::MacroCall {
+    let mut buf = String::new();
+    buf.push_str(&path.syntax().to_string());
+    buf.push_str("!");
+    buf.push_str(&token_tree.syntax().to_string());
+    ast_from_template("const C: () = $;", &buf)
+}
+pub fn token_tree(exprs: impl IntoIterator<Item = ast::Expr>) -> ast::TokenTree {
+    let mut buf = String::new();
+    buf.push_str("(");
+    let exprs: Vec<String> = exprs.into_iter().map(|it| it.syntax().to_string()).collect();
+    buf.push_str(&exprs.join(", "));
+    buf.push_str(")");
+    ast_from_template("const C: () = m!$;", &buf)
+}
+pub fn attr(path: ast::Path, token_tree: Option<ast::TokenTree>) -> ast::Attr {
+    let mut buf = String::new();
+    buf.push_str("#[");
+    buf.push_str(&path.syntax().to_string());
+    if let Some(it) = token_tree {
+        buf.push_str(&it.syntax().to_string());
+    }
+    buf.push_str("]");
+    ast_from_template("$ fn f() {}", &buf)
+}
+pub fn visibility(path: Option<ast::Path>) -> ast::Visibility {
+    let mut buf = String::new();
+    buf.push_str("pub");
+    if let Some(it) = path {
+        buf.push_str("(in ");
+        buf.push_str(&it.syntax().to_string());
+        buf.push_str(")");
+    }
+    ast_from_template("$ fn f() {}", &buf)
+}
+pub fn name(text: &str) -> ast::Name {
+    let mut buf = String::new();
+    buf.push_str(text);
+    ast_from_template("mod $;", &buf)
+}
+pub fn name_ref(text: &str) -> ast::NameRef {
+    let mut buf = String::new();
+    buf.push_str(text);
+    ast_from_template("fn f() { $; }", &buf)
+}
+pub fn label(lifetime: &str) -> ast::Label {
+    let mut buf = String::new();
+    buf.push_str(lifetime);
+    buf.push_str(":");
+    ast_from_template("const C: () = $ loop {};", &buf)
+}
+pub fn paren_type(type_ref: ast::TypeRef) -> ast::ParenType {
+    let mut buf = String::new();
+    buf.push_str("(");
+    buf.push_str(&type_ref.syntax().to_string());
+    buf.push_str(")");
+    ast_from_template("type T = $;", &buf)
+}
+pub fn tuple_type(fields: impl IntoIterator<Item = ast::TypeRef>) -> ast::TupleType {
+    let mut buf = String::new();
+    buf.push_str("(");
+    let fields: Vec<String> = fields.into_iter().map(|it| it.syntax().to_string()).collect();
+    buf.push_str(&fields.join(", "));
+    if fields.len() == 1 {
+        buf.push_str(",");
+    }
+    buf.push_str(")");
+    ast_from_template("type T = $;", &buf)
+}
+pub fn never_type() -> ast::NeverType {
+    let mut buf = String::new();
+    buf.push_str("!");
+    ast_from_template("type T = $;", &buf)
+}
+pub fn path_type(path: ast::Path) -> ast::PathType {
+    let mut buf = String::new();
+    buf.push_str(&path.syntax().to_string());
+    ast_from_template("type T = $;", &buf)
+}
+pub fn reference_type(mutable: bool, type_ref: ast::TypeRef) -> ast::ReferenceType {
+    let mut buf = String::new();
+    buf.push_str("&");
+    if mutable {
+        buf.push_str("mut ");
+    }
+    buf.push_str(&type_ref.syntax().to_string());
+    ast_from_template("type T = $;", &buf)
+}
+pub fn array_type(type_ref: ast::TypeRef, expr: ast::Expr) -> ast::ArrayType {
+    let mut buf = String::new();
+    buf.push_str("[");
+    buf.push_str(&type_ref.syntax().to_string());
+    buf.push_str("; ");
+    buf.push_str(&expr.syntax().to_string());
+    buf.push_str("]");
+    ast_from_template("type T = $;", &buf)
+}
+pub fn slice_type(type_ref: ast::TypeRef) -> ast::SliceType {
+    let mut buf = String::new();
+    buf.push_str("[");
+    buf.push_str(&type_ref.syntax().to_string());
+    buf.push_str("]");
+    ast_from_template("type T = $;", &buf)
+}
+pub fn placeholder_type() -> ast::PlaceholderType {
+    let mut buf = String::new();
+    buf.push_str("_");
+    ast_from_template("type T = $;", &buf)
+}
+pub fn fn_pointer_type(
+    param_list: ast::ParamList,
+    ret_type: Option<ast::RetType>,
+) -> ast::FnPointerType {
+    let mut buf = String::new();
+    buf.push_str("fn");
+    buf.push_str(&param_list.syntax().to_string());
+    if let Some(it) = ret_type {
+        buf.push_str(" ");
+        buf.push_str(&it.syntax().to_string());
+    }
+    ast_from_template("type T = $;", &buf)
+}
+pub fn for_type(type_param_list: ast::TypeParamList, type_ref: ast::TypeRef) -> ast::ForType {
+    let mut buf = String::new();
+    buf.push_str("for");
+    buf.push_str(&type_param_list.syntax().to_string());
+    buf.push_str(" ");
+    buf.push_str(&type_ref.syntax().to_string());
+    ast_from_template("type T = $;", &buf)
+}
+pub fn impl_trait_type(type_bound_list: ast::TypeBoundList) -> ast::ImplTraitType {
+    let mut buf = String::new();
+    buf.push_str("impl ");
+    buf.push_str(&type_bound_list.syntax().to_string());
+    ast_from_template("type T = $;", &buf)
+}
+pub fn dyn_trait_type(type_bound_list: ast::TypeBoundList) -> ast::DynTraitType {
+    let mut buf = String::new();
+    buf.push_str("dyn ");
+    buf.push_str(&type_bound_list.syntax().to_string());
+    ast_from_template("type T = $;", &buf)
+}
+pub fn tuple_expr(exprs: impl IntoIterator<Item = ast::Expr>) -> ast::TupleExpr {
+    let mut buf = String::new();
+    buf.push_str("(");
+    let exprs: Vec<String> = exprs.into_iter().map(|it| it.syntax().to_string()).collect();
+    buf.push_str(&exprs.join(", "));
+    if exprs.len() == 1 {
+        buf.push_str(",");
+    }
+    buf.push_str(")");
+    ast_from_template("const C: () = $;", &buf)
+}
+pub fn array_expr(exprs: impl IntoIterator<Item = ast::Expr>) -> ast::ArrayExpr {
+    let mut buf = String::new();
+    buf.push_str("[");
+    let exprs: Vec<String> = exprs.into_iter().map(|it| it.syntax().to_string()).collect();
+    buf.push_str(&exprs.join(", "));
+    buf.push_str("]");
+    ast_from_template("const C: () = $;", &buf)
+}
+pub fn paren_expr(expr: ast::Expr) -> ast::ParenExpr {
+    let mut buf = String::new();
+    buf.push_str("(");
+    buf.push_str(&expr.syntax().to_string());
+    buf.push_str(")");
+    ast_from_template("const C: () = $;", &buf)
+}
+pub fn path_expr(path: ast::Path) -> ast::PathExpr {
+    let mut buf = String::new();
+    buf.push_str(&path.syntax().to_string());
+    ast_from_template("const C: () = $;", &buf)
+}
+pub fn lambda_expr(
+    is_move: bool,
+    params: impl IntoIterator<Item = ast::Param>,
+    body: ast::Expr,
+) -> ast::LambdaExpr {
+    let mut buf = String::new();
+    if is_move {
+        buf.push_str("move ");
+    }
+    buf.push_str("|");
+    let params: Vec<String> = params.into_iter().map(|it| it.syntax().to_string()).collect();
+    buf.push_str(&params.join(", "));
+    buf.push_str("| ");
+    buf.push_str(&body.syntax().to_string());
+    ast_from_template("const C: () = $;", &buf)
+}
+pub fn if_expr(
+    condition: ast::Condition,
+    then_branch: ast::BlockExpr,
+    else_branch: Option<ast::BlockExpr>,
+) -> ast::IfExpr {
+    let mut buf = String::new();
+    buf.push_str("if ");
+    buf.push_str(&condition.syntax().to_string());
+    buf.push_str(" ");
+    buf.push_str(&then_branch.syntax().to_string());
+    if let Some(it) = else_branch {
+        buf.push_str(" else ");
+        buf.push_str(&it.syntax().to_string());
+    }
+    ast_from_template("const C: () = $;", &buf)
+}
+pub fn loop_expr(label: Option<ast::Label>, loop_body: ast::BlockExpr) -> ast::LoopExpr {
+    let mut buf = String::new();
+    if let Some(it) = label {
+        buf.push_str(&it.syntax().to_string());
+        buf.push_str(" ");
+    }
+    buf.push_str("loop ");
+    buf.push_str(&loop_body.syntax().to_string());
+    ast_from_template("const C: () = $;", &buf)
+}
+pub fn for_expr(
+    label: Option<ast::Label>,
+    pat: ast::Pat,
+    iterable: ast::Expr,
+    loop_body: ast::BlockExpr,
+) -> ast::ForExpr {
+    let mut buf = String::new();
+    if let Some(it) = label {
+        buf.push_str(&it.syntax().to_string());
+        buf.push_str(" ");
+    }
+    buf.push_str("for ");
+    buf.push_str(&pat.syntax().to_string());
+    buf.push_str(" in ");
+    buf.push_str(&iterable.syntax().to_string());
+    buf.push_str(" ");
+    buf.push_str(&loop_body.syntax().to_string());
+    ast_from_template("const C: () = $;", &buf)
+}
+pub fn while_expr(
+    label: Option<ast::Label>,
+    condition: ast::Condition,
+    loop_body: ast::BlockExpr,
+) -> ast::WhileExpr {
+    let mut buf = String::new();
+    if let Some(it) = label {
+        buf.push_str(&it.syntax().to_string());
+        buf.push_str(" ");
+    }
+    buf.push_str("while ");
+    buf.push_str(&condition.syntax().to_string());
+    buf.push_str(" ");
+    buf.push_str(&loop_body.syntax().to_string());
+    ast_from_template("const C: () = $;", &buf)
+}
+pub fn continue_expr() -> ast::ContinueExpr {
+    let mut buf = String::new();
+    buf.push_str("continue");
+    ast_from_template("const C: () = $;", &buf)
+}
+pub fn break_expr(expr: Option<ast::Expr>) -> ast::BreakExpr {
+    let mut buf = String::new();
+    buf.push_str("break");
+    if let Some(it) = expr {
+        buf.push_str(" ");
+        buf.push_str(&it.syntax().to_string());
+    }
+    ast_from_template("const C: () = $;", &buf)
+}
+pub fn block_expr(is_unsafe: bool, block: ast::Block) -> ast::BlockExpr {
+    let mut buf = String::new();
+    if is_unsafe {
+        buf.push_str("unsafe ");
+    }
+    buf.push_str(&block.syntax().to_string());
+    ast_from_template("const C: () = $;", &buf)
+}
+pub fn block(
+    statements: impl IntoIterator<Item = ast::Stmt>,
+    expr: Option<ast::Expr>,
+) -> ast::Block {
+    let mut buf = String::new();
+    buf.push_str("{");
+    let statements: Vec<String> =
+        statements.into_iter().map(|it| it.syntax().to_string()).collect();
+    if !statements.is_empty() {
+        buf.push_str("\n");
+    }
+    for it in statements.iter() {
+        buf.push_str(it);
+        buf.push_str("\n");
+    }
+    if let Some(it) = expr {
+        buf.push_str(" ");
+        buf.push_str(&it.syntax().to_string());
+        buf.push_str(" ");
+    }
+    buf.push_str("}");
+    IndentLevel(0).reformat(ast_from_template("fn f() $", &buf))
+}
+pub fn return_expr(expr: Option<ast::Expr>) -> ast::ReturnExpr {
+    let mut buf = String::new();
+    buf.push_str("return");
+    if let Some(it) = expr {
+        buf.push_str(" ");
+        buf.push_str(&it.syntax().to_string());
+    }
+    ast_from_template("const C: () = $;", &buf)
+}
+pub fn match_expr(expr: ast::Expr, match_arm_list: ast::MatchArmList) -> ast::MatchExpr {
+    let mut buf = String::new();
+    buf.push_str("match ");
+    buf.push_str(&expr.syntax().to_string());
+    buf.push_str(" ");
+    buf.push_str(&match_arm_list.syntax().to_string());
+    ast_from_template("const C: () = $;", &buf)
+}
+pub fn match_arm_list(arms: impl IntoIterator<Item = ast::MatchArm>) -> ast::MatchArmList {
+    let mut buf = String::new();
+    buf.push_str("{\n");
+    let arms: Vec<String> = arms.into_iter().map(|it| it.syntax().to_string()).collect();
+    for it in arms.iter() {
+        buf.push_str(it);
+        buf.push_str(",\n");
+    }
+    buf.push_str("}");
+    IndentLevel(0).reformat(ast_from_template("fn f() { match () $ }", &buf))
+}
+pub fn match_arm(
+    pats: impl IntoIterator<Item = ast::Pat>,
+    guard: Option<ast::MatchGuard>,
+    expr: ast::Expr,
+) -> ast::MatchArm {
+    let mut buf = String::new();
+    let pats: Vec<String> = pats.into_iter().map(|it| it.syntax().to_string()).collect();
+    buf.push_str(&pats.join(" | "));
+    if let Some(it) = guard {
+        buf.push_str(" ");
+        buf.push_str(&it.syntax().to_string());
+    }
+    buf.push_str(" => ");
+    buf.push_str(&expr.syntax().to_string());
+    ast_from_template("fn f() { match () { $ } }", &buf)
+}
+pub fn match_guard(expr: ast::Expr) -> ast::MatchGuard {
+    let mut buf = String::new();
+    buf.push_str("if ");
+    buf.push_str(&expr.syntax().to_string());
+    ast_from_template("fn f() { match () { _ $ => (), } }", &buf)
+}
+pub fn record_lit(path: ast::Path, record_field_list: ast::RecordFieldList) -> ast::RecordLit {
+    let mut buf = String::new();
+    buf.push_str(&path.syntax().to_string());
+    buf.push_str(" ");
+    buf.push_str(&record_field_list.syntax().to_string());
+    ast_from_template("const C: () = $;", &buf)
+}
+pub fn record_field_list(
+    fields: impl IntoIterator<Item = ast::RecordField>,
+) -> ast::RecordFieldList {
+    let mut buf = String::new();
+    buf.push_str("{ ");
+    let fields: Vec<String> = fields.into_iter().map(|it| it.syntax().to_string()).collect();
+    buf.push_str(&fields.join(", "));
+    buf.push_str(" }");
+    ast_from_template("const C: () = S $;", &buf)
+}
+pub fn record_field(name_ref: ast::NameRef, expr: Option<ast::Expr>) -> ast::RecordField {
+    let mut buf = String::new();
+    buf.push_str(&name_ref.syntax().to_string());
+    if let Some(it) = expr {
+        buf.push_str(": ");
+        buf.push_str(&it.syntax().to_string());
+    }
+    ast_from_template("const C: () = S { $ };", &buf)
+}
+pub fn call_expr(expr: ast::Expr, arg_list: ast::ArgList) -> ast::CallExpr {
+    let mut buf = String::new();
+    buf.push_str(&expr.syntax().to_string());
+    buf.push_str(&arg_list.syntax().to_string());
+    ast_from_template("const C: () = $;", &buf)
+}
+pub fn arg_list(args: impl IntoIterator<Item = ast::Expr>) -> ast::ArgList {
+    let mut buf = String::new();
+    buf.push_str("(");
+    let args: Vec<String> = args.into_iter().map(|it| it.syntax().to_string()).collect();
+    buf.push_str(&args.join(", "));
+    buf.push_str(")");
+    ast_from_template("const C: () = f$;", &buf)
+}
+pub fn method_call_expr(
+    expr: ast::Expr,
+    name_ref: ast::NameRef,
+    type_arg_list: Option<ast::TypeArgList>,
+    arg_list: ast::ArgList,
+) -> ast::MethodCallExpr {
+    let mut buf = String::new();
+    buf.push_str(&expr.syntax().to_string());
+    buf.push_str(".");
+    buf.push_str(&name_ref.syntax().to_string());
+    if let Some(it) = type_arg_list {
+        buf.push_str(&it.syntax().to_string());
+    }
+    buf.push_str(&arg_list.syntax().to_string());
+    ast_from_template("const C: () = $;", &buf)
+}
+pub fn index_expr(base: ast::Expr, index: ast::Expr) -> ast::IndexExpr {
+    let mut buf = String::new();
+    buf.push_str(&base.syntax().to_string());
+    buf.push_str("[");
+    buf.push_str(&index.syntax().to_string());
+    buf.push_str("]");
+    ast_from_template("const C: () = $;", &buf)
+}
+pub fn field_expr(expr: ast::Expr, name_ref: ast::NameRef) -> ast::FieldExpr {
+    let mut buf = String::new();
+    buf.push_str(&expr.syntax().to_string());
+    buf.push_str(".");
+    buf.push_str(&name_ref.syntax().to_string());
+    ast_from_template("const C: () = $;", &buf)
+}
+pub fn await_expr(expr: ast::Expr) -> ast::AwaitExpr {
+    let mut buf = String::new();
+    buf.push_str(&expr.syntax().to_string());
+    buf.push_str(".await");
+    ast_from_template("const C: () = $;", &buf)
+}
+pub fn try_expr(expr: ast::Expr) -> ast::TryExpr {
+    let mut buf = String::new();
+    buf.push_str(&expr.syntax().to_string());
+    buf.push_str("?");
+    ast_from_template("const C: () = $;", &buf)
+}
+pub fn cast_expr(expr: ast::Expr, type_ref: ast::TypeRef) -> ast::CastExpr {
+    let mut buf = String::new();
+    buf.push_str(&expr.syntax().to_string());
+    buf.push_str(" as ");
+    buf.push_str(&type_ref.syntax().to_string());
+    ast_from_template("const C: () = $;", &buf)
+}
+pub fn ref_expr(mutable: bool, expr: ast::Expr) -> ast::RefExpr {
+    let mut buf = String::new();
+    buf.push_str("&");
+    if mutable {
+        buf.push_str("mut ");
+    }
+    buf.push_str(&expr.syntax().to_string());
+    ast_from_template("const C: () = $;", &buf)
+}
+pub fn prefix_expr(op_kind: ast::PrefixOp, expr: ast::Expr) -> ast::PrefixExpr {
+    let mut buf = String::new();
+    buf.push_str(op_kind.text());
+    buf.push_str(&expr.syntax().to_string());
+    ast_from_template("const C: () = $;", &buf)
+}
+pub fn box_expr(expr: ast::Expr) -> ast::BoxExpr {
+    let mut buf = String::new();
+    buf.push_str("box ");
+    buf.push_str(&expr.syntax().to_string());
+    ast_from_template("const C: () = $;", &buf)
+}
+pub fn range_expr(
+    start: Option<ast::Expr>,
+    op_kind: ast::RangeOp,
+    end: Option<ast::Expr>,
+) -> ast::RangeExpr {
+    let mut buf = String::new();
+    if let Some(it) = start {
+        buf.push_str(&it.syntax().to_string());
+    }
+    buf.push_str(op_kind.text());
+    if let Some(it) = end {
+        buf.push_str(&it.syntax().to_string());
+    }
+    ast_from_template("const C: () = $;", &buf)
+}
+pub fn bin_expr(lhs: ast::Expr, op_kind: ast::BinOp, rhs: ast::Expr) -> ast::BinExpr {
+    let mut buf = String::new();
+    buf.push_str(&lhs.syntax().to_string());
+    buf.push_str(" ");
+    buf.push_str(op_kind.text());
+    buf.push_str(" ");
+    buf.push_str(&rhs.syntax().to_string());
+    ast_from_template("const C: () = $;", &buf)
+}
+pub fn or_pat(pats: impl IntoIterator<Item = ast::Pat>) -> ast::OrPat {
+    let mut buf = String::new();
+    let pats: Vec<String> = pats.into_iter().map(|it| it.syntax().to_string()).collect();
+    buf.push_str(&pats.join(" | "));
+    ast_from_template("fn f() { match () { [$] => (), } }", &buf)
+}
+pub fn paren_pat(pat: ast::Pat) -> ast::ParenPat {
+    let mut buf = String::new();
+    buf.push_str("(");
+    buf.push_str(&pat.syntax().to_string());
+    buf.push_str(")");
+    ast_from_template("fn f() { match () { $ => (), } }", &buf)
+}
+pub fn ref_pat(mutable: bool, pat: ast::Pat) -> ast::RefPat {
+    let mut buf = String::new();
+    buf.push_str("&");
+    if mutable {
+        buf.push_str("mut ");
+    }
+    buf.push_str(&pat.syntax().to_string());
+    ast_from_template("fn f() { match () { $ => (), } }", &buf)
+}
+pub fn box_pat(pat: ast::Pat) -> ast::BoxPat {
+    let mut buf = String::new();
+    buf.push_str("box ");
+    buf.push_str(&pat.syntax().to_string());
+    ast_from_template("fn f() { match () { $ => (), } }", &buf)
+}
+pub fn bind_pat(
+    is_ref: bool,
+    is_mutable: bool,
+    name: ast::Name,
+    pat: Option<ast::Pat>,
+) -> ast::BindPat {
+    let mut buf = String::new();
+    if is_ref {
+        buf.push_str("ref ");
+    }
+    if is_mutable {
+        buf.push_str("mut ");
+    }
+    buf.push_str(&name.syntax().to_string());
+    if let Some(it) = pat {
+        buf.push_str(" @ ");
+        buf.push_str(&it.syntax().to_string());
+    }
+    ast_from_template("fn f() { match () { $ => (), } }", &buf)
+}
+pub fn placeholder_pat() -> ast::PlaceholderPat {
+    let mut buf = String::new();
+    buf.push_str("_");
+    ast_from_template("fn f() { match () { $ => (), } }", &buf)
+}
+pub fn dot_dot_pat() -> ast::DotDotPat {
+    let mut buf = String::new();
+    buf.push_str("..");
+    ast_from_template("fn f() { let ($) = (); }", &buf)
+}
+pub fn path_pat(path: ast::Path) -> ast::PathPat {
+    let mut buf = String::new();
+    buf.push_str(&path.syntax().to_string());
+    ast_from_template("fn f() { match () { $ => (), } }", &buf)
+}
+pub fn slice_pat(args: impl IntoIterator<Item = ast::Pat>) -> ast::SlicePat {
+    let mut buf = String::new();
+    buf.push_str("[");
+    let args: Vec<String> = args.into_iter().map(|it| it.syntax().to_string()).collect();
+    buf.push_str(&args.join(", "));
+    buf.push_str("]");
+    ast_from_template("fn f() { match () { $ => (), } }", &buf)
+}
+pub fn range_pat(start: ast::LiteralPat, end: ast::LiteralPat) -> ast::RangePat {
+    let mut buf = String::new();
+    buf.push_str(&start.syntax().to_string());
+    buf.push_str("..=");
+    buf.push_str(&end.syntax().to_string());
+    ast_from_template("fn f() { match () { $ => (), } }", &buf)
+}
+pub fn literal_pat(literal: ast::Literal) -> ast::LiteralPat {
+    let mut buf = String::new();
+    buf.push_str(&literal.syntax().to_string());
+    ast_from_template("fn f() { match () { $ => (), } }", &buf)
+}
+pub fn record_pat(
+    path: ast::Path,
+    record_field_pat_list: ast::RecordFieldPatList,
+) -> ast::RecordPat {
+    let mut buf = String::new();
+    buf.push_str(&path.syntax().to_string());
+    buf.push_str(" ");
+    buf.push_str(&record_field_pat_list.syntax().to_string());
+    ast_from_template("fn f() { match () { $ => (), } }", &buf)
+}
+pub fn record_field_pat_list(
+    record_field_pats: impl IntoIterator<Item = ast::RecordFieldPat>,
+    bind_pats: impl IntoIterator<Item = ast::BindPat>,
+) -> ast::RecordFieldPatList {
+    let mut buf = String::new();
+    buf.push_str("{ ");
+    let record_field_pats: Vec<String> =
+        record_field_pats.into_iter().map(|it| it.syntax().to_string()).collect();
+    for it in record_field_pats.iter() {
+        buf.push_str(it);
+        buf.push_str(", ");
+    }
+    let bind_pats: Vec<String> = bind_pats.into_iter().map(|it| it.syntax().to_string()).collect();
+    buf.push_str(&bind_pats.join(", "));
+    buf.push_str(" }");
+    ast_from_template("fn f() { match () { S $ => (), } }", &buf)
+}
+pub fn record_field_pat(name: ast::Name, pat: ast::Pat) -> ast::RecordFieldPat {
+    let mut buf = String::new();
+    buf.push_str(&name.syntax().to_string());
+    buf.push_str(": ");
+    buf.push_str(&pat.syntax().to_string());
+    ast_from_template("fn f() { match () { S { $ } => (), } }", &buf)
+}
+pub fn tuple_struct_pat(
+    path: ast::Path,
+    args: impl IntoIterator<Item = ast::Pat>,
+) -> ast::TupleStructPat {
+    let mut buf = String::new();
+    buf.push_str(&path.syntax().to_string());
+    buf.push_str("(");
+    let args: Vec<String> = args.into_iter().map(|it| it.syntax().to_string()).collect();
+    buf.push_str(&args.join(", "));
+    buf.push_str(")");
+    ast_from_template("fn f() { match () { $ => (), } }", &buf)
+}
+pub fn tuple_pat(args: impl IntoIterator<Item = ast::Pat>) -> ast::TuplePat {
+    let mut buf = String::new();
+    buf.push_str("(");
+    let args: Vec<String> = args.into_iter().map(|it| it.syntax().to_string()).collect();
+    buf.push_str(&args.join(", "));
+    if args.len() == 1 {
+        buf.push_str(",");
+    }
+    buf.push_str(")");
+    ast_from_template("fn f() { match () { $ => (), } }", &buf)
+}
+pub fn type_param_list(
+    lifetime_params: impl IntoIterator<Item = ast::LifetimeParam>,
+    type_params: impl IntoIterator<Item = ast::TypeParam>,
+) -> ast::TypeParamList {
+    let mut buf = String::new();
+    buf.push_str("<");
+    let lifetime_params: Vec<String> =
+        lifetime_params.into_iter().map(|it| it.syntax().to_string()).collect();
+    for it in lifetime_params.iter() {
+        buf.push_str(it);
+        buf.push_str(", ");
+    }
+    let type_params: Vec<String> =
+        type_params.into_iter().map(|it| it.syntax().to_string()).collect();
+    buf.push_str(&type_params.join(", "));
+    buf.push_str(">");
+    ast_from_template("fn f$() {}", &buf)
+}
+pub fn type_param(
+    name: ast::Name,
+    type_bound_list: Option<ast::TypeBoundList>,
+    default_type: Option<ast::TypeRef>,
+) -> ast::TypeParam {
+    let mut buf = String::new();
+    buf.push_str(&name.syntax().to_string());
+    if let Some(it) = type_bound_list {
+        buf.push_str(": ");
+        buf.push_str(&it.syntax().to_string());
+    }
+    if let Some(it) = default_type {
+        buf.push_str(" = ");
+        buf.push_str(&it.syntax().to_string());
+    }
+    ast_from_template("fn f<$>() {}", &buf)
+}
+pub fn const_param(name: ast::Name, ascribed_type: ast::TypeRef) -> ast::ConstParam {
+    let mut buf = String::new();
+    buf.push_str("const ");
+    buf.push_str(&name.syntax().to_string());
+    buf.push_str(": ");
+    buf.push_str(&ascribed_type.syntax().to_string());
+    ast_from_template("fn f<$>() {}", &buf)
+}
+pub fn lifetime_param(lifetime: &str) -> ast::LifetimeParam {
+    let mut buf = String::new();
+    buf.push_str(lifetime);
+    ast_from_template("fn f<$>() {}", &buf)
+}
+pub fn type_bound(type_ref: ast::TypeRef) -> ast::TypeBound {
+    let mut buf = String::new();
+    buf.push_str(&type_ref.syntax().to_string());
+    ast_from_template("fn f<T: $>() {}", &buf)
+}
+pub fn type_bound_list(bounds: impl IntoIterator<Item = ast::TypeBound>) -> ast::TypeBoundList {
+    let mut buf = String::new();
+    let bounds: Vec<String> = bounds.into_iter().map(|it| it.syntax().to_string()).collect();
+    buf.push_str(&bounds.join(" + "));
+    ast_from_template("fn f<T: $>() {}", &buf)
+}
+pub fn where_pred(type_ref: ast::TypeRef, type_bound_list: ast::TypeBoundList) -> ast::WherePred {
+    let mut buf = String::new();
+    buf.push_str(&type_ref.syntax().to_string());
+    buf.push_str(": ");
+    buf.push_str(&type_bound_list.syntax().to_string());
+    ast_from_template("fn f() where $ {}", &buf)
+}
+pub fn where_clause(predicates: impl IntoIterator<Item = ast::WherePred>) -> ast::WhereClause {
+    let mut buf = String::new();
+    buf.push_str("where ");
+    let predicates: Vec<String> =
+        predicates.into_iter().map(|it| it.syntax().to_string()).collect();
+    buf.push_str(&predicates.join(", "));
+    ast_from_template("fn f() $ {}", &buf)
+}
+pub fn expr_stmt(expr: ast::Expr) -> ast::ExprStmt {
+    let mut buf = String::new();
+    buf.push_str(&expr.syntax().to_string());
+    buf.push_str(";");
+    ast_from_template("fn f() { $ }", &buf)
+}
+pub fn let_stmt(
+    pat: ast::Pat,
+    ascribed_type: Option<ast::TypeRef>,
+    initializer: Option<ast::Expr>,
+) -> ast::LetStmt {
+    let mut buf = String::new();
+    buf.push_str("let ");
+    buf.push_str(&pat.syntax().to_string());
+    if let Some(it) = ascribed_type {
+        buf.push_str(": ");
+        buf.push_str(&it.syntax().to_string());
+    }
+    if let Some(it) = initializer {
+        buf.push_str(" = ");
+        buf.push_str(&it.syntax().to_string());
+    }
+    buf.push_str(";");
+    ast_from_template("fn f() { $ }", &buf)
+}
+pub fn condition(pat: Option<ast::Pat>, expr: ast::Expr) -> ast::Condition {
+    let mut buf = String::new();
+    if let Some(it) = pat {
+        buf.push_str("let ");
+        buf.push_str(&it.syntax().to_string());
+        buf.push_str(" = ");
+    }
+    buf.push_str(&expr.syntax().to_string());
+    ast_from_template("fn f() { if $ {} }", &buf)
+}
+pub fn param_list(
+    self_param: Option<ast::SelfParam>,
+    params: impl IntoIterator<Item = ast::Param>,
+) -> ast::ParamList {
+    let mut buf = String::new();
+    buf.push_str("(");
+    if let Some(it) = self_param {
+        buf.push_str(&it.syntax().to_string());
+        buf.push_str(", ");
+    }
+    let params: Vec<String> = params.into_iter().map(|it| it.syntax().to_string()).collect();
+    buf.push_str(&params.join(", "));
+    buf.push_str(")");
+    ast_from_template("fn f$ {}", &buf)
+}
+pub fn self_param(by_ref: bool, mutable: bool) -> ast::SelfParam {
+    let mut buf = String::new();
+    if by_ref {
+        buf.push_str("&");
+    }
+    if mutable {
+        buf.push_str("mut ");
+    }
+    buf.push_str("self");
+    ast_from_template("impl S { fn f($) {} }", &buf)
+}
+pub fn param(pat: ast::Pat, ascribed_type: ast::TypeRef) -> ast::Param {
+    let mut buf = String::new();
+    buf.push_str(&pat.syntax().to_string());
+    buf.push_str(": ");
+    buf.push_str(&ascribed_type.syntax().to_string());
+    ast_from_template("fn f($) {}", &buf)
+}
+pub fn path(qualifier: Option<ast::Path>, segment: ast::PathSegment) -> ast::Path {
+    let mut buf = String::new();
+    if let Some(it) = qualifier {
+        buf.push_str(&it.syntax().to_string());
+        buf.push_str("::");
+    }
+    buf.push_str(&segment.syntax().to_string());
+    ast_from_template("type T = $;", &buf)
+}
+pub fn path_segment(
+    name_ref: ast::NameRef,
+    type_arg_list: Option<ast::TypeArgList>,
+) -> ast::PathSegment {
+    let mut buf = String::new();
+    buf.push_str(&name_ref.syntax().to_string());
+    if let Some(it) = type_arg_list {
+        buf.push_str(&it.syntax().to_string());
+    }
+    ast_from_template("type T = $;", &buf)
+}
+pub fn type_arg_list(type_args: impl IntoIterator<Item = ast::TypeArg>) -> ast::TypeArgList {
+    let mut buf = String::new();
+    buf.push_str("::<");
+    let type_args: Vec<String> = type_args.into_iter().map(|it| it.syntax().to_string()).collect();
+    buf.push_str(&type_args.join(", "));
+    buf.push_str(">");
+    ast_from_template("type T = S$;", &buf)
+}
+pub fn type_arg(type_ref: ast::TypeRef) -> ast::TypeArg {
+    let mut buf = String::new();
+    buf.push_str(&type_ref.syntax().to_string());
+    ast_from_template("type T = S<$>;", &buf)
+}
+pub fn assoc_type_arg(name_ref: ast::NameRef, type_ref: ast::TypeRef) -> ast::AssocTypeArg {
+    let mut buf = String::new();
+    buf.push_str(&name_ref.syntax().to_string());
+    buf.push_str(" = ");
+    buf.push_str(&type_ref.syntax().to_string());
+    ast_from_template("type T = S<$>;", &buf)
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::make::{check_round_trip, sample};
+    #[test]
+    fn builders_round_trip() {
+        check_round_trip(source_file(vec![sample(), sample()]), "$");
+        check_round_trip(
+            fn_def(
+                Some(sample()),
+                true,
+                sample(),
+                Some(sample()),
+                sample(),
+                Some(sample()),
+                Some(sample()),
+                sample(),
+            ),
+            "$",
+        );
+        check_round_trip(ret_type(sample()), "fn f() $ {}");
+        check_round_trip(
+            struct_def(Some(sample()), sample(), Some(sample()), Some(sample()), sample()),
+            "$",
+        );
+        check_round_trip(
+            union_def(Some(sample()), sample(), Some(sample()), Some(sample()), sample()),
+            "$",
+        );
+        check_round_trip(record_field_def_list(vec![sample(), sample()]), "struct S $");
+        check_round_trip(record_field_def(Some(sample()), sample(), sample()), "struct S { $ }");
+        check_round_trip(tuple_field_def_list(vec![sample(), sample()]), "struct S$;");
+        check_round_trip(tuple_field_def(Some(sample()), sample()), "struct S($);");
+        check_round_trip(
+            enum_def(Some(sample()), sample(), Some(sample()), Some(sample()), sample()),
+            "$",
+        );
+        check_round_trip(enum_variant_list(vec![sample(), sample()]), "enum E $");
+        check_round_trip(enum_variant(sample(), Some(sample())), "enum E { $ }");
+        check_round_trip(
+            trait_def(
+                Some(sample()),
+                sample(),
+                Some(sample()),
+                Some(sample()),
+                Some(sample()),
+                sample(),
+            ),
+            "$",
+        );
+        check_round_trip(module(Some(sample()), sample(), sample()), "$");
+        check_round_trip(item_list(vec![sample(), sample()]), "impl S $");
+        check_round_trip(const_def(Some(sample()), sample(), sample(), sample()), "$");
+        check_round_trip(static_def(Some(sample()), true, sample(), sample(), sample()), "$");
+        check_round_trip(
+            type_alias_def(Some(sample()), sample(), Some(sample()), Some(sample()), sample()),
+            "$",
+        );
+        check_round_trip(
+            impl_block(Some(sample()), Some(sample()), sample(), Some(sample()), sample()),
+            "$",
+        );
+        check_round_trip(use_item(Some(sample()), sample()), "$");
+        check_round_trip(use_tree(sample(), Some(sample())), "use $;");
+        check_round_trip(use_tree_list(vec![sample(), sample()]), "use a::$;");
+        check_round_trip(alias(sample()), "use a $;");
+        check_round_trip(extern_crate_item(Some(sample()), sample(), Some(sample())), "$");
+        check_round_trip(macro_call(sample(), sample()), "const C: () = $;");
+        check_round_trip(token_tree(vec![sample(), sample()]), "const C: () = m!$;");
+        check_round_trip(attr(sample(), Some(sample())), "$ fn f() {}");
+        check_round_trip(visibility(Some(sample())), "$ fn f() {}");
+        check_round_trip(name("foo"), "mod $;");
+        check_round_trip(name_ref("foo"), "fn f() { $; }");
+        check_round_trip(label("'a"), "const C: () = $ loop {};");
+        check_round_trip(paren_type(sample()), "type T = $;");
+        check_round_trip(tuple_type(vec![sample(), sample()]), "type T = $;");
+        check_round_trip(never_type(), "type T = $;");
+        check_round_trip(path_type(sample()), "type T = $;");
+        check_round_trip(reference_type(true, sample()), "type T = $;");
+        check_round_trip(array_type(sample(), sample()), "type T = $;");
+        check_round_trip(slice_type(sample()), "type T = $;");
+        check_round_trip(placeholder_type(), "type T = $;");
+        check_round_trip(fn_pointer_type(sample(), Some(sample())), "type T = $;");
+        check_round_trip(for_type(sample(), sample()), "type T = $;");
+        check_round_trip(impl_trait_type(sample()), "type T = $;");
+        check_round_trip(dyn_trait_type(sample()), "type T = $;");
+        check_round_trip(tuple_expr(vec![sample(), sample()]), "const C: () = $;");
+        check_round_trip(array_expr(vec![sample(), sample()]), "const C: () = $;");
+        check_round_trip(paren_expr(sample()), "const C: () = $;");
+        check_round_trip(path_expr(sample()), "const C: () = $;");
+        check_round_trip(lambda_expr(true, vec![sample(), sample()], sample()), "const C: () = $;");
+        check_round_trip(if_expr(sample(), sample(), Some(sample())), "const C: () = $;");
+        check_round_trip(loop_expr(Some(sample()), sample()), "const C: () = $;");
+        check_round_trip(
+            for_expr(Some(sample()), sample(), sample(), sample()),
+            "const C: () = $;",
+        );
+        check_round_trip(while_expr(Some(sample()), sample(), sample()), "const C: () = $;");
+        check_round_trip(continue_expr(), "const C: () = $;");
+        check_round_trip(break_expr(Some(sample())), "const C: () = $;");
+        check_round_trip(block_expr(true, sample()), "const C: () = $;");
+        check_round_trip(block(vec![sample(), sample()], Some(sample())), "fn f() $");
+        check_round_trip(return_expr(Some(sample())), "const C: () = $;");
+        check_round_trip(match_expr(sample(), sample()), "const C: () = $;");
+        check_round_trip(match_arm_list(vec![sample(), sample()]), "fn f() { match () $ }");
+        check_round_trip(
+            match_arm(vec![sample(), sample()], Some(sample()), sample()),
+            "fn f() { match () { $ } }",
+        );
+        check_round_trip(match_guard(sample()), "fn f() { match () { _ $ => (), } }");
+        check_round_trip(record_lit(sample(), sample()), "const C: () = $;");
+        check_round_trip(record_field_list(vec![sample(), sample()]), "const C: () = S $;");
+        check_round_trip(record_field(sample(), Some(sample())), "const C: () = S { $ };");
+        check_round_trip(call_expr(sample(), sample()), "const C: () = $;");
+        check_round_trip(arg_list(vec![sample(), sample()]), "const C: () = f$;");
+        check_round_trip(
+            method_call_expr(sample(), sample(), Some(sample()), sample()),
+            "const C: () = $;",
+        );
+        check_round_trip(index_expr(sample(), sample()), "const C: () = $;");
+        check_round_trip(field_expr(sample(), sample()), "const C: () = $;");
+        check_round_trip(await_expr(sample()), "const C: () = $;");
+        check_round_trip(try_expr(sample()), "const C: () = $;");
+        check_round_trip(cast_expr(sample(), sample()), "const C: () = $;");
+        check_round_trip(ref_expr(true, sample()), "const C: () = $;");
+        check_round_trip(
+            prefix_expr(sample::<ast::PrefixExpr>().op_kind().unwrap(), sample()),
+            "const C: () = $;",
+        );
+        check_round_trip(box_expr(sample()), "const C: () = $;");
+        check_round_trip(
+            range_expr(
+                Some(sample()),
+                sample::<ast::RangeExpr>().op_kind().unwrap(),
+                Some(sample()),
+            ),
+            "const C: () = $;",
+        );
+        check_round_trip(
+            bin_expr(sample(), sample::<ast::BinExpr>().op_kind().unwrap(), sample()),
+            "const C: () = $;",
+        );
+        check_round_trip(or_pat(vec![sample(), sample()]), "fn f() { match () { [$] => (), } }");
+        check_round_trip(paren_pat(sample()), "fn f() { match () { $ => (), } }");
+        check_round_trip(ref_pat(true, sample()), "fn f() { match () { $ => (), } }");
+        check_round_trip(box_pat(sample()), "fn f() { match () { $ => (), } }");
+        check_round_trip(
+            bind_pat(true, true, sample(), Some(sample())),
+            "fn f() { match () { $ => (), } }",
+        );
+        check_round_trip(placeholder_pat(), "fn f() { match () { $ => (), } }");
+        check_round_trip(dot_dot_pat(), "fn f() { let ($) = (); }");
+        check_round_trip(path_pat(sample()), "fn f() { match () { $ => (), } }");
+        check_round_trip(slice_pat(vec![sample(), sample()]), "fn f() { match () { $ => (), } }");
+        check_round_trip(range_pat(sample(), sample()), "fn f() { match () { $ => (), } }");
+        check_round_trip(literal_pat(sample()), "fn f() { match () { $ => (), } }");
+        check_round_trip(record_pat(sample(), sample()), "fn f() { match () { $ => (), } }");
+        check_round_trip(
+            record_field_pat_list(vec![sample(), sample()], vec![sample(), sample()]),
+            "fn f() { match () { S $ => (), } }",
+        );
+        check_round_trip(
+            record_field_pat(sample(), sample()),
+            "fn f() { match () { S { $ } => (), } }",
+        );
+        check_round_trip(
+            tuple_struct_pat(sample(), vec![sample(), sample()]),
+            "fn f() { match () { $ => (), } }",
+        );
+        check_round_trip(tuple_pat(vec![sample(), sample()]), "fn f() { match () { $ => (), } }");
+        check_round_trip(
+            type_param_list(vec![sample(), sample()], vec![sample(), sample()]),
+            "fn f$() {}",
+        );
+        check_round_trip(type_param(sample(), Some(sample()), Some(sample())), "fn f<$>() {}");
+        check_round_trip(const_param(sample(), sample()), "fn f<$>() {}");
+        check_round_trip(lifetime_param("'a"), "fn f<$>() {}");
+        check_round_trip(type_bound(sample()), "fn f<T: $>() {}");
+        check_round_trip(type_bound_list(vec![sample(), sample()]), "fn f<T: $>() {}");
+        check_round_trip(where_pred(sample(), sample()), "fn f() where $ {}");
+        check_round_trip(where_clause(vec![sample(), sample()]), "fn f() $ {}");
+        check_round_trip(expr_stmt(sample()), "fn f() { $ }");
+        check_round_trip(let_stmt(sample(), Some(sample()), Some(sample())), "fn f() { $ }");
+        check_round_trip(condition(Some(sample()), sample()), "fn f() { if $ {} }");
+        check_round_trip(param_list(Some(sample()), vec![sample(), sample()]), "fn f$ {}");
+        check_round_trip(self_param(true, true), "impl S { fn f($) {} }");
+        check_round_trip(param(sample(), sample()), "fn f($) {}");
+        check_round_trip(path(Some(sample()), sample()), "type T = $;");
+        check_round_trip(path_segment(sample(), Some(sample())), "type T = $;");
+        check_round_trip(type_arg_list(vec![sample(), sample()]), "type T = S$;");
+        check_round_trip(type_arg(sample()), "type T = S<$>;");
+        check_round_trip(assoc_type_arg(sample(), sample()), "type T = S<$>;");
+    }
+}
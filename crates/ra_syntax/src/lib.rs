@@ -24,6 +24,7 @@ mod syntax_error;
 mod parsing;
 mod validation;
 mod ptr;
+mod query;
 #[cfg(test)]
 mod tests;
 
@@ -43,6 +44,7 @@ pub use crate::{
     ast::{AstNode, AstToken},
     parsing::{classify_literal, tokenize, Token},
     ptr::{AstPtr, SyntaxNodePtr},
+    query::{Query, QueryError, QueryMatch},
     syntax_error::{Location, SyntaxError, SyntaxErrorKind},
     syntax_node::{
         Direction, NodeOrToken, SyntaxElement, SyntaxNode, SyntaxToken, SyntaxTreeBuilder,
@@ -158,6 +160,13 @@ impl SourceFile {
         assert_eq!(root.kind(), SyntaxKind::SOURCE_FILE);
         Parse { green, errors: Arc::new(errors), _ty: PhantomData }
     }
+
+    /// Errors which rustc reports after parsing, like `break` outside of a
+    /// loop. These are not part of `parse`, as they are not syntax errors
+    /// strictly speaking, and not every user of the syntax tree wants them.
+    pub fn semantic_errors(&self) -> Vec<SyntaxError> {
+        validation::validate_semantics(self.syntax())
+    }
 }
 
 /// Matches a `SyntaxNode` against an `ast` type.
@@ -0,0 +1,524 @@
+//! A small S-expression language for querying syntax trees.
+//!
+//! A query is a pattern like
+//!
+//! ```text
+//! (METHOD_CALL_EXPR name_ref: "unwrap" (ARG_LIST) @args)
+//! ```
+//!
+//! which is compiled once into a `Query` and can then be run over any number
+//! of files. The pattern language is:
+//!
+//!   - `(KIND ...)` matches a node (or token) of the given `SyntaxKind`, `(_ ...)`
+//!     matches any kind.
+//!   - `field: pattern` inside of parentheses matches the first child of kind
+//!     `FIELD` against `pattern`, like the accessors of `ast` nodes do.
+//!   - any other pattern inside of parentheses matches one of the non-trivia
+//!     children; these must appear in the same order as in the pattern.
+//!   - `"text"` matches an element with exactly this text, `_` matches
+//!     anything.
+//!   - `pattern @name` captures the element matched by `pattern`.
+//!   - `(#eq? @name "text")` and `(#not-eq? @name @other)` are predicates on
+//!     captures, checked after the pattern they are part of has matched.
+
+use std::{error::Error, fmt};
+
+use rustc_hash::FxHashSet;
+
+use crate::{NodeOrToken, SmolStr, SyntaxElement, SyntaxKind, SyntaxNode, TextRange};
+
+#[derive(Debug, PartialEq)]
+pub struct QueryError(String);
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid query: {}", self.0)
+    }
+}
+
+impl Error for QueryError {}
+
+/// A compiled query, see the module docs for the syntax.
+#[derive(Debug, Clone)]
+pub struct Query {
+    pattern: Pattern,
+}
+
+/// A node matched by a `Query`, together with the ranges of the captures.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryMatch {
+    pub range: TextRange,
+    pub captures: Vec<(SmolStr, TextRange)>,
+}
+
+impl Query {
+    pub fn parse(text: &str) -> Result<Query, QueryError> {
+        let mut parser = Parser { tokens: tokenize(text)?, pos: 0 };
+        let pattern = parser.pattern()?;
+        if let Some(token) = parser.peek() {
+            return Err(QueryError(format!("unexpected {} after the pattern", token)));
+        }
+
+        let mut captures = FxHashSet::default();
+        pattern.collect_captures(&mut captures);
+        pattern.check_predicates(&captures)?;
+
+        Ok(Query { pattern })
+    }
+
+    /// Finds all nodes in `root` (including `root` itself) matching the
+    /// query, in preorder.
+    pub fn matches(&self, root: &SyntaxNode) -> Vec<QueryMatch> {
+        root.descendants()
+            .filter_map(|node| {
+                let mut captures = Vec::new();
+                if !match_pattern(&self.pattern, &node.clone().into(), &mut captures) {
+                    return None;
+                }
+                let captures =
+                    captures.into_iter().map(|(name, it)| (name, it.text_range())).collect();
+                Some(QueryMatch { range: node.text_range(), captures })
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Pattern {
+    kind: PatternKind,
+    capture: Option<SmolStr>,
+}
+
+#[derive(Debug, Clone)]
+enum PatternKind {
+    Any,
+    Text(String),
+    Node(NodePattern),
+}
+
+#[derive(Debug, Clone)]
+struct NodePattern {
+    /// `None` for `(_ ...)`.
+    kind: Option<SyntaxKind>,
+    fields: Vec<(SyntaxKind, Pattern)>,
+    children: Vec<Pattern>,
+    predicates: Vec<Predicate>,
+}
+
+#[derive(Debug, Clone)]
+struct Predicate {
+    capture: SmolStr,
+    value: PredicateValue,
+    negated: bool,
+}
+
+#[derive(Debug, Clone)]
+enum PredicateValue {
+    Capture(SmolStr),
+    Text(String),
+}
+
+impl Pattern {
+    fn children(&self) -> impl Iterator<Item = &Pattern> + '_ {
+        let node = match &self.kind {
+            PatternKind::Node(it) => Some(it),
+            _ => None,
+        };
+        node.into_iter()
+            .flat_map(|it| it.fields.iter().map(|(_, field)| field).chain(it.children.iter()))
+    }
+
+    fn collect_captures(&self, acc: &mut FxHashSet<SmolStr>) {
+        if let Some(capture) = &self.capture {
+            acc.insert(capture.clone());
+        }
+        self.children().for_each(|it| it.collect_captures(acc));
+    }
+
+    fn check_predicates(&self, captures: &FxHashSet<SmolStr>) -> Result<(), QueryError> {
+        if let PatternKind::Node(node) = &self.kind {
+            for predicate in node.predicates.iter() {
+                let mut names =
+                    Some(&predicate.capture).into_iter().chain(match &predicate.value {
+                        PredicateValue::Capture(it) => Some(it),
+                        PredicateValue::Text(_) => None,
+                    });
+                if let Some(name) = names.find(|it| !captures.contains(*it)) {
+                    return Err(QueryError(format!("unknown capture `@{}`", name)));
+                }
+            }
+        }
+        self.children().try_for_each(|it| it.check_predicates(captures))
+    }
+}
+
+type Captures = Vec<(SmolStr, SyntaxElement)>;
+
+fn match_pattern(pattern: &Pattern, element: &SyntaxElement, acc: &mut Captures) -> bool {
+    let start = acc.len();
+    let matched = match &pattern.kind {
+        PatternKind::Any => true,
+        PatternKind::Text(text) => element_text(element) == *text,
+        PatternKind::Node(node) => match_node(node, element, acc),
+    };
+    if !matched {
+        acc.truncate(start);
+        return false;
+    }
+    if let Some(capture) = &pattern.capture {
+        // Keep the captures in preorder, the outer one first.
+        acc.insert(start, (capture.clone(), element.clone()));
+    }
+    if let PatternKind::Node(node) = &pattern.kind {
+        if !node.predicates.iter().all(|it| check_predicate(it, acc)) {
+            acc.truncate(start);
+            return false;
+        }
+    }
+    true
+}
+
+fn match_node(pattern: &NodePattern, element: &SyntaxElement, acc: &mut Captures) -> bool {
+    if pattern.kind.map_or(false, |kind| kind != element.kind()) {
+        return false;
+    }
+    let node = match element {
+        NodeOrToken::Node(it) => it,
+        NodeOrToken::Token(_) => return pattern.fields.is_empty() && pattern.children.is_empty(),
+    };
+    for (kind, field) in pattern.fields.iter() {
+        let child = match node.children_with_tokens().find(|it| it.kind() == *kind) {
+            Some(it) => it,
+            None => return false,
+        };
+        if !match_pattern(field, &child, acc) {
+            return false;
+        }
+    }
+    let children =
+        node.children_with_tokens().filter(|it| !it.kind().is_trivia()).collect::<Vec<_>>();
+    match_children(&pattern.children, &children, acc)
+}
+
+fn match_children(patterns: &[Pattern], children: &[SyntaxElement], acc: &mut Captures) -> bool {
+    let (first, rest) = match patterns.split_first() {
+        Some(it) => it,
+        None => return true,
+    };
+    for (idx, child) in children.iter().enumerate() {
+        let start = acc.len();
+        if match_pattern(first, child, acc) && match_children(rest, &children[idx + 1..], acc) {
+            return true;
+        }
+        acc.truncate(start);
+    }
+    false
+}
+
+fn check_predicate(predicate: &Predicate, acc: &Captures) -> bool {
+    let capture_text = |name: &SmolStr| {
+        acc.iter().rev().find(|(it, _)| it == name).map(|(_, element)| element_text(element))
+    };
+    let lhs = capture_text(&predicate.capture);
+    let rhs = match &predicate.value {
+        PredicateValue::Capture(name) => capture_text(name),
+        PredicateValue::Text(text) => Some(text.clone()),
+    };
+    // Captures which are not bound yet, like the ones of later siblings, never
+    // satisfy a predicate.
+    match (lhs, rhs) {
+        (Some(lhs), Some(rhs)) => (lhs == rhs) != predicate.negated,
+        _ => false,
+    }
+}
+
+fn element_text(element: &SyntaxElement) -> String {
+    match element {
+        NodeOrToken::Node(it) => it.text().to_string(),
+        NodeOrToken::Token(it) => it.text().to_string(),
+    }
+}
+
+fn kind_by_name(name: &str) -> Option<SyntaxKind> {
+    (0..SyntaxKind::__LAST as u16).map(SyntaxKind::from).find(|kind| format!("{:?}", kind) == name)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    LParen,
+    RParen,
+    Colon,
+    At,
+    Str(String),
+    Word(String),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::LParen => write!(f, "`(`"),
+            Token::RParen => write!(f, "`)`"),
+            Token::Colon => write!(f, "`:`"),
+            Token::At => write!(f, "`@`"),
+            Token::Str(it) => write!(f, "{:?}", it),
+            Token::Word(it) => write!(f, "`{}`", it),
+        }
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, QueryError> {
+    let is_word_char = |c: char| c.is_alphanumeric() || "_#?!-".contains(c);
+
+    let mut res = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        let token = match c {
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            ':' => Token::Colon,
+            '@' => Token::At,
+            '"' => {
+                let mut buf = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c) => buf.push(c),
+                            None => return Err(QueryError("unterminated string".into())),
+                        },
+                        Some(c) => buf.push(c),
+                        None => return Err(QueryError("unterminated string".into())),
+                    }
+                }
+                Token::Str(buf)
+            }
+            c if c.is_whitespace() => continue,
+            c if is_word_char(c) => {
+                let mut buf = c.to_string();
+                while let Some(&c) = chars.peek() {
+                    if !is_word_char(c) {
+                        break;
+                    }
+                    buf.push(c);
+                    chars.next();
+                }
+                Token::Word(buf)
+            }
+            c => return Err(QueryError(format!("unexpected character `{}`", c))),
+        };
+        res.push(token);
+    }
+    Ok(res)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.nth(0)
+    }
+
+    fn nth(&self, n: usize) -> Option<&Token> {
+        self.tokens.get(self.pos + n)
+    }
+
+    fn bump(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), QueryError> {
+        match self.bump() {
+            Some(token) if token == expected => Ok(()),
+            token => Err(unexpected(&expected.to_string(), token)),
+        }
+    }
+
+    fn word(&mut self) -> Result<String, QueryError> {
+        match self.bump() {
+            Some(Token::Word(it)) => Ok(it),
+            token => Err(unexpected("a name", token)),
+        }
+    }
+
+    fn pattern(&mut self) -> Result<Pattern, QueryError> {
+        let kind = match self.bump() {
+            Some(Token::LParen) => PatternKind::Node(self.node_pattern()?),
+            Some(Token::Str(it)) => PatternKind::Text(it),
+            Some(Token::Word(ref it)) if it == "_" => PatternKind::Any,
+            token => return Err(unexpected("a pattern", token)),
+        };
+        let capture = if self.peek() == Some(&Token::At) {
+            self.bump();
+            Some(self.word()?.into())
+        } else {
+            None
+        };
+        Ok(Pattern { kind, capture })
+    }
+
+    /// Parses the rest of a `(KIND ...)` pattern, after the `(`.
+    fn node_pattern(&mut self) -> Result<NodePattern, QueryError> {
+        let kind = match self.word()?.as_str() {
+            "_" => None,
+            name => Some(parse_kind(name)?),
+        };
+        let mut res =
+            NodePattern { kind, fields: Vec::new(), children: Vec::new(), predicates: Vec::new() };
+        loop {
+            match (self.peek(), self.nth(1)) {
+                (Some(Token::RParen), _) => {
+                    self.bump();
+                    return Ok(res);
+                }
+                (Some(Token::LParen), Some(Token::Word(name))) if name.starts_with('#') => {
+                    self.bump();
+                    res.predicates.push(self.predicate()?);
+                }
+                (Some(Token::Word(name)), Some(Token::Colon)) => {
+                    let kind = parse_kind(&name.to_ascii_uppercase())?;
+                    self.bump();
+                    self.bump();
+                    res.fields.push((kind, self.pattern()?));
+                }
+                (None, _) => return Err(QueryError("unclosed `(`".into())),
+                _ => res.children.push(self.pattern()?),
+            }
+        }
+    }
+
+    /// Parses the rest of a `(#eq? ...)` predicate, after the `(`.
+    fn predicate(&mut self) -> Result<Predicate, QueryError> {
+        let negated = match self.word()?.as_str() {
+            "#eq?" => false,
+            "#not-eq?" => true,
+            name => return Err(QueryError(format!("unknown predicate `{}`", name))),
+        };
+        self.expect(Token::At)?;
+        let capture = self.word()?.into();
+        let value = match self.bump() {
+            Some(Token::At) => PredicateValue::Capture(self.word()?.into()),
+            Some(Token::Str(it)) => PredicateValue::Text(it),
+            token => return Err(unexpected("a capture or a string", token)),
+        };
+        self.expect(Token::RParen)?;
+        Ok(Predicate { capture, value, negated })
+    }
+}
+
+fn parse_kind(name: &str) -> Result<SyntaxKind, QueryError> {
+    kind_by_name(name).ok_or_else(|| QueryError(format!("unknown syntax kind `{}`", name)))
+}
+
+fn unexpected(expected: &str, found: Option<Token>) -> QueryError {
+    match found {
+        Some(token) => QueryError(format!("expected {}, found {}", expected, token)),
+        None => QueryError(format!("expected {}, found the end of the query", expected)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Write;
+
+    use test_utils::assert_eq_text;
+
+    use super::*;
+    use crate::{AstNode, SourceFile};
+
+    fn check(query: &str, code: &str, expected: &str) {
+        let query = Query::parse(query).unwrap();
+        let file = SourceFile::parse(code).tree();
+        let mut actual = String::new();
+        for m in query.matches(file.syntax()) {
+            writeln!(actual, "{:?} {}", m.range, &code[m.range]).unwrap();
+            for (name, range) in m.captures {
+                writeln!(actual, "  @{} {:?} {}", name, range, &code[range]).unwrap();
+            }
+        }
+        assert_eq_text!(expected.trim_start(), &actual);
+    }
+
+    fn check_error(query: &str, expected: &str) {
+        let err = Query::parse(query).unwrap_err();
+        assert_eq!(err.to_string(), expected);
+    }
+
+    #[test]
+    fn query_field_text() {
+        check(
+            r#"(METHOD_CALL_EXPR name_ref: "unwrap")"#,
+            "fn f() { a.unwrap(); b.expect(); c.unwrap().unwrap(); }",
+            r#"
+[9; 19) a.unwrap()
+[33; 52) c.unwrap().unwrap()
+[33; 43) c.unwrap()
+"#,
+        );
+    }
+
+    #[test]
+    fn query_captures_and_children() {
+        check(
+            r#"(CALL_EXPR (PATH_EXPR) @callee (ARG_LIST (LITERAL) @arg))"#,
+            "fn f() { foo(1); bar(); }",
+            r#"
+[9; 15) foo(1)
+  @callee [9; 12) foo
+  @arg [13; 14) 1
+"#,
+        );
+    }
+
+    #[test]
+    fn query_children_in_order() {
+        check(
+            r#"(BIN_EXPR (LITERAL) @lhs "+" (PATH_EXPR) @rhs)"#,
+            "fn f() { 1 + x; x + 1; 2 + y; }",
+            r#"
+[9; 14) 1 + x
+  @lhs [9; 10) 1
+  @rhs [13; 14) x
+[23; 28) 2 + y
+  @lhs [23; 24) 2
+  @rhs [27; 28) y
+"#,
+        );
+    }
+
+    #[test]
+    fn query_predicates() {
+        check(
+            r#"(BIN_EXPR _ @lhs "==" _ @rhs (#eq? @lhs @rhs))"#,
+            "fn f() { a == a; a == b; }",
+            r#"
+[9; 15) a == a
+  @lhs [9; 10) a
+  @rhs [14; 15) a
+"#,
+        );
+        check(
+            r#"(FN_DEF name: (NAME) @name (#not-eq? @name "main"))"#,
+            "fn main() {} fn foo() {}",
+            r#"
+[13; 24) fn foo() {}
+  @name [16; 19) foo
+"#,
+        );
+    }
+
+    #[test]
+    fn query_errors() {
+        check_error("(FOO_EXPR)", "Invalid query: unknown syntax kind `FOO_EXPR`");
+        check_error("(CALL_EXPR", "Invalid query: unclosed `(`");
+        check_error(
+            r#"(CALL_EXPR (#eq? @callee "foo"))"#,
+            "Invalid query: unknown capture `@callee`",
+        );
+        check_error("(CALL_EXPR) @", "Invalid query: expected a name, found the end of the query");
+    }
+}
@@ -4,7 +4,7 @@ use std::fmt;
 
 use ra_parser::ParseError;
 
-use crate::{validation::EscapeError, TextRange, TextUnit};
+use crate::{validation::EscapeError, SmolStr, TextRange, TextUnit};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct SyntaxError {
@@ -52,6 +52,11 @@ impl SyntaxError {
         self.location.clone()
     }
 
+    /// The rustc error code of this error, like `E0268`, if there is one.
+    pub fn code(&self) -> Option<&'static str> {
+        self.kind.code()
+    }
+
     pub fn offset(&self) -> TextUnit {
         match self.location {
             Location::Offset(offset) => offset,
@@ -84,6 +89,36 @@ pub enum SyntaxErrorKind {
     InvalidTupleIndexFormat,
     VisibilityNotAllowed,
     InclusiveRangeMissingEnd,
+    SelfParamOutsideAssocFn,
+    SelfValueOutsideMethod,
+    AsyncTraitFn,
+    DefaultOutsideImpl,
+    DuplicateParam(SmolStr),
+    BreakOutsideLoop,
+    ContinueOutsideLoop,
+    BreakInsideClosure,
+    ContinueInsideClosure,
+    TryInUnitFn,
+    LetChain,
+}
+
+impl SyntaxErrorKind {
+    /// The code under which rustc reports this error, see
+    /// https://doc.rust-lang.org/error-index.html
+    pub fn code(&self) -> Option<&'static str> {
+        use self::SyntaxErrorKind::*;
+        let code = match self {
+            SelfValueOutsideMethod => "E0424",
+            AsyncTraitFn => "E0706",
+            DuplicateParam(_) => "E0415",
+            BreakOutsideLoop | ContinueOutsideLoop => "E0268",
+            BreakInsideClosure | ContinueInsideClosure => "E0267",
+            TryInUnitFn => "E0277",
+            LetChain => "E0658",
+            _ => return None,
+        };
+        Some(code)
+    }
 }
 
 impl fmt::Display for SyntaxErrorKind {
@@ -107,6 +142,29 @@ impl fmt::Display for SyntaxErrorKind {
             InclusiveRangeMissingEnd => {
                 write!(f, "An inclusive range must have an end expression")
             }
+            SelfParamOutsideAssocFn => {
+                write!(f, "`self` parameter is only allowed in associated functions")
+            }
+            SelfValueOutsideMethod => write!(
+                f,
+                "`self` value is a keyword only available in methods with a `self` parameter"
+            ),
+            AsyncTraitFn => write!(f, "functions in traits cannot be declared `async`"),
+            DefaultOutsideImpl => {
+                write!(f, "`default` is only allowed on items in `impl` blocks")
+            }
+            DuplicateParam(name) => {
+                write!(f, "identifier `{}` is bound more than once in this parameter list", name)
+            }
+            BreakOutsideLoop => write!(f, "`break` outside of a loop"),
+            ContinueOutsideLoop => write!(f, "`continue` outside of a loop"),
+            BreakInsideClosure => write!(f, "`break` inside of a closure"),
+            ContinueInsideClosure => write!(f, "`continue` inside of a closure"),
+            TryInUnitFn => write!(
+                f,
+                "the `?` operator can only be used in a function that returns `Result` or `Option`"
+            ),
+            LetChain => write!(f, "`let` chains are unstable"),
         }
     }
 }
@@ -48,6 +48,28 @@ fn parser_tests() {
     });
 }
 
+#[test]
+fn validation_tests() {
+    dir_tests(&test_data_dir(), &["validation"], |text, path| {
+        let parse = SourceFile::parse(text);
+        assert_eq!(
+            parse.errors(),
+            &[] as &[crate::SyntaxError],
+            "There should be no syntax errors in the file {:?}",
+            path.display(),
+        );
+        let mut acc = String::new();
+        for err in parse.tree().semantic_errors() {
+            match err.code() {
+                Some(code) => writeln!(acc, "error {:?} {}: {}", err.location(), code, err),
+                None => writeln!(acc, "error {:?}: {}", err.location(), err),
+            }
+            .unwrap();
+        }
+        acc
+    });
+}
+
 #[test]
 fn parser_fuzz_tests() {
     for (_, text) in collect_tests(&test_data_dir(), &["parser/fuzz-failures"]) {
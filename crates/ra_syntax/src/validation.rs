@@ -1,9 +1,12 @@
 //! FIXME: write short doc here
 
 mod block;
+mod semantic;
 
 use rustc_lexer::unescape;
 
+pub(crate) use self::semantic::validate_semantics;
+
 use crate::{
     ast, match_ast, AstNode, SyntaxError, SyntaxErrorKind,
     SyntaxKind::{BYTE, BYTE_STRING, CHAR, CONST_DEF, FN_DEF, INT_NUMBER, STRING, TYPE_ALIAS_DEF},
@@ -0,0 +1,178 @@
+//! Checks for errors which rustc reports after parsing, but which can still
+//! be detected from the syntax tree alone, like `break` outside of a loop.
+
+use rustc_hash::FxHashSet;
+
+use crate::{
+    ast::{self, AstNode},
+    match_ast, SyntaxError,
+    SyntaxErrorKind::*,
+    SyntaxKind,
+    SyntaxKind::*,
+    SyntaxNode, T,
+};
+
+pub(crate) fn validate_semantics(root: &SyntaxNode) -> Vec<SyntaxError> {
+    let mut errors = Vec::new();
+    for node in root.descendants() {
+        match_ast! {
+            match node {
+                ast::FnDef(it) => { validate_fn_def(it, &mut errors) },
+                ast::TypeAliasDef(it) => { validate_default(it.syntax(), &mut errors) },
+                ast::ParamList(it) => { validate_param_names(it, &mut errors) },
+                ast::PathExpr(it) => { validate_self_value(it, &mut errors) },
+                ast::BreakExpr(it) => { validate_in_loop(it.syntax(), &mut errors) },
+                ast::ContinueExpr(it) => { validate_in_loop(it.syntax(), &mut errors) },
+                ast::TryExpr(it) => { validate_try_expr(it, &mut errors) },
+                ast::Condition(it) => { validate_condition(it, &mut errors) },
+                _ => (),
+            }
+        }
+    }
+    errors
+}
+
+/// The trait or impl block `item` is an associated item of.
+fn assoc_item_container(item: &SyntaxNode) -> Option<SyntaxNode> {
+    item.parent()
+        .filter(|it| it.kind() == ITEM_LIST)
+        .and_then(|it| it.parent())
+        .filter(|it| it.kind() == TRAIT_DEF || it.kind() == IMPL_BLOCK)
+}
+
+fn validate_fn_def(fn_def: ast::FnDef, errors: &mut Vec<SyntaxError>) {
+    let container = assoc_item_container(fn_def.syntax());
+    if container.is_none() {
+        if let Some(self_param) = fn_def.param_list().and_then(|it| it.self_param()) {
+            errors
+                .push(SyntaxError::new(SelfParamOutsideAssocFn, self_param.syntax().text_range()));
+        }
+    }
+    if fn_def.is_async() && container.map(|it| it.kind()) == Some(TRAIT_DEF) {
+        if let Some(async_token) =
+            fn_def.syntax().children_with_tokens().find(|it| it.kind() == T![async])
+        {
+            errors.push(SyntaxError::new(AsyncTraitFn, async_token.text_range()));
+        }
+    }
+    validate_default(fn_def.syntax(), errors);
+}
+
+fn validate_default(item: &SyntaxNode, errors: &mut Vec<SyntaxError>) {
+    let default_token = match item.children_with_tokens().find(|it| it.kind() == T![default]) {
+        Some(it) => it,
+        None => return,
+    };
+    if assoc_item_container(item).map(|it| it.kind()) != Some(IMPL_BLOCK) {
+        errors.push(SyntaxError::new(DefaultOutsideImpl, default_token.text_range()));
+    }
+}
+
+fn validate_param_names(param_list: ast::ParamList, errors: &mut Vec<SyntaxError>) {
+    // Parameter names of function pointer types are only documentation.
+    match param_list.syntax().parent() {
+        Some(parent) if parent.kind() == FN_DEF || parent.kind() == LAMBDA_EXPR => (),
+        _ => return,
+    }
+    let mut seen = FxHashSet::default();
+    let names = param_list
+        .params()
+        .filter_map(|param| param.pat())
+        .flat_map(|pat| pat.syntax().descendants().filter_map(ast::BindPat::cast))
+        .filter_map(|bind_pat| bind_pat.name());
+    for name in names {
+        let text = name.text().clone();
+        if !seen.insert(text.clone()) {
+            errors.push(SyntaxError::new(DuplicateParam(text), name.syntax().text_range()));
+        }
+    }
+}
+
+fn validate_self_value(path_expr: ast::PathExpr, errors: &mut Vec<SyntaxError>) {
+    let path = match path_expr.path() {
+        Some(it) => it,
+        None => return,
+    };
+    if path.qualifier().is_some() {
+        return;
+    }
+    let is_self = path
+        .segment()
+        .and_then(|it| it.kind())
+        .map(|it| it == ast::PathSegmentKind::SelfKw)
+        .unwrap_or(false);
+    if !is_self {
+        return;
+    }
+    // Closures capture `self`, so only the enclosing function matters.
+    let fn_def = match path_expr.syntax().ancestors().find_map(ast::FnDef::cast) {
+        Some(it) => it,
+        None => return,
+    };
+    if fn_def.param_list().and_then(|it| it.self_param()).is_none() {
+        errors.push(SyntaxError::new(SelfValueOutsideMethod, path_expr.syntax().text_range()));
+    }
+}
+
+fn validate_in_loop(expr: &SyntaxNode, errors: &mut Vec<SyntaxError>) {
+    let has_label = has_token(expr, LIFETIME);
+    let mut in_closure = false;
+    for ancestor in expr.ancestors().skip(1) {
+        match ancestor.kind() {
+            LOOP_EXPR | WHILE_EXPR | FOR_EXPR => return,
+            // A labeled block can be broken out of.
+            BLOCK_EXPR if has_label && ancestor.children().any(|it| it.kind() == LABEL) => return,
+            LAMBDA_EXPR => {
+                in_closure = true;
+                break;
+            }
+            FN_DEF | CONST_DEF | STATIC_DEF => break,
+            _ => (),
+        }
+    }
+    let kind = match (expr.kind() == BREAK_EXPR, in_closure) {
+        (true, false) => BreakOutsideLoop,
+        (false, false) => ContinueOutsideLoop,
+        (true, true) => BreakInsideClosure,
+        (false, true) => ContinueInsideClosure,
+    };
+    errors.push(SyntaxError::new(kind, expr.text_range()));
+}
+
+fn validate_try_expr(expr: ast::TryExpr, errors: &mut Vec<SyntaxError>) {
+    for ancestor in expr.syntax().ancestors().skip(1) {
+        match ancestor.kind() {
+            LAMBDA_EXPR => return,
+            // `try { .. }` and `async { .. }` blocks have their own return type.
+            TRY_EXPR if has_token(&ancestor, T![try]) => return,
+            BLOCK_EXPR if has_token(&ancestor, T![async]) => return,
+            _ => (),
+        }
+        if let Some(fn_def) = ast::FnDef::cast(ancestor) {
+            // Without a return type, the function returns `()`, which is
+            // neither a `Result` nor an `Option`.
+            if fn_def.ret_type().is_none() {
+                errors.push(SyntaxError::new(TryInUnitFn, expr.syntax().text_range()));
+            }
+            return;
+        }
+    }
+}
+
+fn validate_condition(condition: ast::Condition, errors: &mut Vec<SyntaxError>) {
+    if condition.pat().is_none() {
+        return;
+    }
+    if let Some(ast::Expr::BinExpr(bin_expr)) = condition.expr() {
+        match bin_expr.op_kind() {
+            Some(ast::BinOp::BooleanAnd) | Some(ast::BinOp::BooleanOr) => {
+                errors.push(SyntaxError::new(LetChain, bin_expr.syntax().text_range()))
+            }
+            _ => (),
+        }
+    }
+}
+
+fn has_token(node: &SyntaxNode, kind: SyntaxKind) -> bool {
+    node.children_with_tokens().any(|it| it.kind() == kind)
+}
@@ -0,0 +1,10 @@
+fn main() {
+    match () {
+        (_ | _) => (),
+        &(_ | _) => (),
+        (_ | _,) => (),
+        [_ | _,] => (),
+        Some(_ | _) => (),
+        S { f: _ | _ } => (),
+    }
+}
@@ -0,0 +1,4 @@
+fn main() {
+    let (a) = ();
+    let (a,) = ();
+}
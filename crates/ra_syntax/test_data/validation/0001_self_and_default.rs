@@ -0,0 +1,14 @@
+fn free(&self) {}
+default fn bar() {}
+trait Trait {
+    async fn baz(&self);
+}
+impl Trait for () {
+    default type Ty = ();
+    fn assoc() {
+        self;
+    }
+    fn method(&self) {
+        let f = || self;
+    }
+}
@@ -0,0 +1,3 @@
+fn foo(a: u32, b: u32, a: u32) {}
+fn bar((x, y): (u32, u32), Some(x): Option<u32>) {}
+fn baz(a: u32, b: u32) {}
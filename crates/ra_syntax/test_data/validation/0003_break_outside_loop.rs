@@ -0,0 +1,16 @@
+fn foo() {
+    break;
+    loop {
+        break;
+        let f = || continue;
+    }
+    'a: {
+        break 'a;
+    }
+    while true {
+        fn nested() {
+            continue;
+        }
+    }
+}
+const C: () = break;
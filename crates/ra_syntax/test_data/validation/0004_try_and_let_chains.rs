@@ -0,0 +1,16 @@
+fn foo() {
+    x?;
+    let f = || x?;
+    let b = try { x? };
+    let a = async { x? };
+}
+fn bar() -> Option<()> {
+    x?;
+    None
+}
+fn baz() {
+    if let Some(x) = a && b {}
+    while let Some(x) = a || b {}
+    if a && b {}
+    if let Some(x) = (a && b) {}
+}
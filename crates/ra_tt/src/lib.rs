@@ -14,9 +14,12 @@ macro_rules! impl_froms {
     }
 }
 
-use std::fmt;
+use std::{
+    fmt::{self, Debug},
+    panic::RefUnwindSafe,
+};
 
-use smol_str::SmolStr;
+pub use smol_str::SmolStr;
 
 /// Represents identity of the token.
 ///
@@ -178,3 +181,21 @@ impl Subtree {
 }
 
 pub mod buffer;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ExpansionError {
+    IOError(String),
+    JsonError(String),
+    Unknown(String),
+    ExpansionError(String),
+}
+
+/// A macro expander which lives outside of rust-analyzer, like a procedural
+/// macro loaded from a compiled dylib.
+///
+/// `attrs` is the argument of an attribute macro (the `(...)` in
+/// `#[attr(...)]`), and `None` for function-like macros and custom derives.
+pub trait TokenExpander: Debug + Send + Sync + RefUnwindSafe {
+    fn expand(&self, subtree: &Subtree, attrs: Option<&Subtree>)
+        -> Result<Subtree, ExpansionError>;
+}
@@ -39,6 +39,9 @@ export function createClient(config: Config): lc.LanguageClient {
             cargoWatchCommand: config.cargoWatchOptions.command,
             cargoWatchAllTargets:
                 config.cargoWatchOptions.allTargets,
+            rustfmtCommand: config.rustfmtCommand,
+            rustfmtArgs: config.rustfmtArgs,
+            procMacroSrv: config.procMacroSrv,
             excludeGlobs: config.excludeGlobs,
             useClientWatching: config.useClientWatching,
             featureFlags: config.featureFlags,
@@ -11,7 +11,8 @@ import { onEnter } from './on_enter';
 import { parentModule } from './parent_module';
 import { syntaxTree } from './syntax_tree';
 import { expandMacro } from './expand_macro';
-import { run, runSingle } from './runnables';
+import { run, runSingle, debugSingle } from './runnables';
+import { ssr } from './ssr';
 
 function collectGarbage(ctx: Ctx): Cmd {
     return async () => {
@@ -57,7 +58,9 @@ export {
     collectGarbage,
     run,
     runSingle,
+    debugSingle,
     showReferences,
     applySourceChange,
-    reload
+    reload,
+    ssr
 };
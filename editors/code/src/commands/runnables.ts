@@ -64,6 +64,34 @@ export function runSingle(ctx: Ctx): Cmd {
     };
 }
 
+export function debugSingle(ctx: Ctx): Cmd {
+    return async (config: DebugRunnable) => {
+        const editor = ctx.activeRustEditor;
+        if (!editor) return;
+
+        if (!vscode.extensions.getExtension('vadimcn.vscode-lldb')) {
+            vscode.window.showErrorMessage(
+                'Install the `vadimcn.vscode-lldb` extension for debugging',
+            );
+            return;
+        }
+
+        const debugConfig: vscode.DebugConfiguration = {
+            type: 'lldb',
+            request: 'launch',
+            name: config.label,
+            cargo: {
+                args: config.cargoArgs,
+            },
+            args: config.executableArgs,
+            cwd: config.cwd,
+            env: config.env,
+        };
+
+        return vscode.debug.startDebugging(undefined, debugConfig);
+    };
+}
+
 interface RunnablesParams {
     textDocument: lc.TextDocumentIdentifier;
     position?: lc.Position;
@@ -77,6 +105,14 @@ interface Runnable {
     cwd?: string;
 }
 
+interface DebugRunnable {
+    label: string;
+    cargoArgs: string[];
+    executableArgs: string[];
+    env: { [index: string]: string };
+    cwd?: string;
+}
+
 class RunnableQuickPick implements vscode.QuickPickItem {
     public label: string;
     public description?: string | undefined;
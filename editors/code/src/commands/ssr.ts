@@ -0,0 +1,24 @@
+import * as vscode from 'vscode';
+
+import { Ctx, Cmd } from '../ctx';
+import { applySourceChange, SourceChange } from '../source_change';
+
+export function ssr(ctx: Ctx): Cmd {
+    return async () => {
+        const client = ctx.client;
+        if (!client) return;
+
+        const options: vscode.InputBoxOptions = {
+            placeHolder: 'foo($a, $b) ==>> bar($b, $a)',
+            prompt: 'Enter a structural search replace rule',
+        };
+        const request = await vscode.window.showInputBox(options);
+        if (!request) return;
+
+        const change = await client.sendRequest<SourceChange>(
+            'rust-analyzer/ssr',
+            { arg: request },
+        );
+        await applySourceChange(ctx, change);
+    };
+}
@@ -13,6 +13,8 @@ export interface CargoFeatures {
     noDefaultFeatures: boolean;
     allFeatures: boolean;
     features: string[];
+    loadOutDirsFromCheck: boolean;
+    withProcMacro: boolean;
 }
 
 export class Config {
@@ -34,10 +36,15 @@ export class Config {
         command: '',
         allTargets: true,
     };
+    rustfmtCommand = 'rustfmt';
+    rustfmtArgs: string[] = [];
+    procMacroSrv: null | string = null;
     cargoFeatures: CargoFeatures = {
         noDefaultFeatures: false,
         allFeatures: true,
         features: [],
+        loadOutDirsFromCheck: false,
+        withProcMacro: false,
     };
 
     private prevEnhancedTyping: null | boolean = null;
@@ -114,6 +121,21 @@ export class Config {
             );
         }
 
+        if (config.has('rustfmt.command')) {
+            this.rustfmtCommand = config.get<string>(
+                'rustfmt.command',
+                'rustfmt',
+            );
+        }
+
+        if (config.has('rustfmt.args')) {
+            this.rustfmtArgs = config.get<string[]>('rustfmt.args', []);
+        }
+
+        if (config.has('procMacroSrv')) {
+            this.procMacroSrv = config.get('procMacroSrv') || null;
+        }
+
         if (config.has('lruCapacity')) {
             this.lruCapacity = config.get('lruCapacity') as number;
         }
@@ -157,6 +179,18 @@ export class Config {
                 [],
             );
         }
+        if (config.has('cargoFeatures.loadOutDirsFromCheck')) {
+            this.cargoFeatures.loadOutDirsFromCheck = config.get(
+                'cargoFeatures.loadOutDirsFromCheck',
+                false,
+            );
+        }
+        if (config.has('cargoFeatures.withProcMacro')) {
+            this.cargoFeatures.withProcMacro = config.get(
+                'cargoFeatures.withProcMacro',
+                false,
+            );
+        }
 
         if (
             this.prevCargoFeatures !== null &&
@@ -164,6 +198,10 @@ export class Config {
                 this.prevCargoFeatures.allFeatures ||
                 this.cargoFeatures.noDefaultFeatures !==
                 this.prevCargoFeatures.noDefaultFeatures ||
+                this.cargoFeatures.loadOutDirsFromCheck !==
+                this.prevCargoFeatures.loadOutDirsFromCheck ||
+                this.cargoFeatures.withProcMacro !==
+                this.prevCargoFeatures.withProcMacro ||
                 this.cargoFeatures.features.length !==
                 this.prevCargoFeatures.features.length ||
                 this.cargoFeatures.features.some(
@@ -129,13 +129,14 @@ class Highlighter {
         }
 
         for (const d of highlights) {
-            if (!byTag.get(d.tag)) {
+            const tag = resolveTag(d.tag, byTag);
+            if (!tag) {
                 continue;
             }
 
             if (rainbowTime && d.bindingHash) {
                 if (!colorfulIdents.has(d.bindingHash)) {
-                    const mut = d.tag.endsWith('.mut');
+                    const mut = d.tag.split('.').includes('mutable');
                     colorfulIdents.set(d.bindingHash, [[], mut]);
                 }
                 colorfulIdents
@@ -145,7 +146,7 @@ class Highlighter {
                     );
             } else {
                 byTag
-                    .get(d.tag)!
+                    .get(tag)!
                     .push(
                         client.protocol2CodeConverter.asRange(d.range),
                     );
@@ -171,6 +172,23 @@ class Highlighter {
     }
 }
 
+// Tags look like `variable.mutable.declaration`: drop trailing modifiers
+// until we find a tag we have a decoration for.
+function resolveTag(
+    tag: string,
+    byTag: Map<string, vscode.Range[]>,
+): string | undefined {
+    const parts = tag.split('.');
+    while (parts.length > 0) {
+        const candidate = parts.join('.');
+        if (byTag.has(candidate)) {
+            return candidate;
+        }
+        parts.pop();
+    }
+    return undefined;
+}
+
 function initDecorations(): Map<string, vscode.TextEditorDecorationType> {
     const theme = ColorTheme.load();
     const res = new Map();
@@ -227,20 +245,21 @@ const TAG_TO_SCOPES = new Map<string, string[]>([
     ["macro", ["entity.name.macro"]],
 
     ["variable", ["variable"]],
-    ["variable.mut", ["variable", "meta.mutable"]],
+    ["variable.mutable", ["variable", "meta.mutable"]],
 
     ["type", ["entity.name.type"]],
-    ["type.builtin", ["entity.name.type", "support.type.primitive"]],
-    ["type.self", ["entity.name.type.parameter.self"]],
-    ["type.param", ["entity.name.type.parameter"]],
-    ["type.lifetime", ["entity.name.type.lifetime"]],
+    ["builtin_type", ["entity.name.type", "support.type.primitive"]],
+    ["self_type", ["entity.name.type.parameter.self"]],
+    ["type_param", ["entity.name.type.parameter"]],
+    ["lifetime", ["entity.name.type.lifetime"]],
 
-    ["literal.byte", ["constant.character.byte"]],
-    ["literal.char", ["constant.character"]],
-    ["literal.numeric", ["constant.numeric"]],
+    ["byte_literal", ["constant.character.byte"]],
+    ["char_literal", ["constant.character"]],
+    ["numeric_literal", ["constant.numeric"]],
 
     ["comment", ["comment"]],
-    ["string", ["string.quoted"]],
+    ["comment.documentation", ["comment.line.documentation"]],
+    ["string_literal", ["string.quoted"]],
     ["attribute", ["meta.attribute"]],
 
     ["keyword", ["keyword"]],
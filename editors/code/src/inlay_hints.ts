@@ -28,7 +28,7 @@ interface InlayHintsParams {
 
 interface InlayHint {
     range: vscode.Range;
-    kind: string;
+    kind: 'TypeHint' | 'ParameterHint' | 'ChainingHint';
     label: string;
 }
 
@@ -38,6 +38,14 @@ const typeHintDecorationType = vscode.window.createTextEditorDecorationType({
     },
 });
 
+const parameterHintDecorationType = vscode.window.createTextEditorDecorationType(
+    {
+        before: {
+            color: new vscode.ThemeColor('rust_analyzer.inlayHint'),
+        },
+    },
+);
+
 class HintsUpdater {
     private pending: Map<string, vscode.CancellationTokenSource> = new Map();
     private ctx: Ctx;
@@ -55,7 +63,7 @@ class HintsUpdater {
         if (this.enabled) {
             await this.refresh();
         } else {
-            this.allEditors.forEach(it => this.setDecorations(it, []));
+            this.allEditors.forEach(it => this.setDecorations(it, [], []));
         }
     }
 
@@ -68,15 +76,27 @@ class HintsUpdater {
     private async refreshEditor(editor: vscode.TextEditor): Promise<void> {
         const newHints = await this.queryHints(editor.document.uri.toString());
         if (newHints == null) return;
-        const newDecorations = newHints.map(hint => ({
-            range: hint.range,
-            renderOptions: {
-                after: {
-                    contentText: `: ${hint.label}`,
+        const newTypeDecorations = newHints
+            .filter(hint => hint.kind !== 'ParameterHint')
+            .map(hint => ({
+                range: hint.range,
+                renderOptions: {
+                    after: {
+                        contentText: `: ${hint.label}`,
+                    },
+                },
+            }));
+        const newParameterDecorations = newHints
+            .filter(hint => hint.kind === 'ParameterHint')
+            .map(hint => ({
+                range: hint.range,
+                renderOptions: {
+                    before: {
+                        contentText: `${hint.label}: `,
+                    },
                 },
-            },
-        }));
-        this.setDecorations(editor, newDecorations);
+            }));
+        this.setDecorations(editor, newTypeDecorations, newParameterDecorations);
     }
 
     private get allEditors(): vscode.TextEditor[] {
@@ -87,11 +107,16 @@ class HintsUpdater {
 
     private setDecorations(
         editor: vscode.TextEditor,
-        decorations: vscode.DecorationOptions[],
+        typeDecorations: vscode.DecorationOptions[],
+        parameterDecorations: vscode.DecorationOptions[],
     ) {
         editor.setDecorations(
             typeHintDecorationType,
-            this.enabled ? decorations : [],
+            this.enabled ? typeDecorations : [],
+        );
+        editor.setDecorations(
+            parameterHintDecorationType,
+            this.enabled ? parameterDecorations : [],
         );
     }
 
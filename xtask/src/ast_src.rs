@@ -120,6 +120,8 @@ pub(crate) const KINDS_SRC: KindsSrc = KindsSrc {
         "FOR_TYPE",
         "IMPL_TRAIT_TYPE",
         "DYN_TRAIT_TYPE",
+        "OR_PAT",
+        "PAREN_PAT",
         "REF_PAT",
         "BOX_PAT",
         "BIND_PAT",
@@ -423,15 +425,17 @@ pub(crate) const AST_SRC: AstSrc = AstSrc {
             fields: [RecordField],
             spread: Expr,
          }
-        struct RecordField { NameRef, Expr }
+        struct RecordField: AttrsOwner { NameRef, Expr }
 
+        struct OrPat { pats: [Pat] }
+        struct ParenPat { Pat }
         struct RefPat { Pat }
         struct BoxPat { Pat }
         struct BindPat: NameOwner { Pat }
         struct PlaceholderPat { }
         struct DotDotPat { }
         struct PathPat {  Path }
-        struct SlicePat {}
+        struct SlicePat { args: [Pat] }
         struct RangePat {}
         struct LiteralPat { Literal }
 
@@ -469,8 +473,8 @@ pub(crate) const AST_SRC: AstSrc = AstSrc {
         struct TypeBoundList { bounds: [TypeBound] }
         struct WherePred: TypeBoundsOwner { TypeRef }
         struct WhereClause { predicates: [WherePred] }
-        struct ExprStmt { Expr }
-        struct LetStmt: TypeAscriptionOwner {
+        struct ExprStmt: AttrsOwner { Expr }
+        struct LetStmt: AttrsOwner, TypeAscriptionOwner {
             Pat,
             initializer: Expr,
         }
@@ -601,6 +605,8 @@ pub(crate) const AST_SRC: AstSrc = AstSrc {
         }
 
         enum Pat {
+            OrPat,
+            ParenPat,
             RefPat,
             BoxPat,
             BindPat,
@@ -619,3 +625,172 @@ pub(crate) const AST_SRC: AstSrc = AstSrc {
         enum Stmt { ExprStmt, LetStmt }
     },
 };
+
+/// Describes a typed builder in `ra_syntax::ast::make`.
+pub(crate) struct MakeSrc<'a> {
+    /// The node the builder creates, like `FnDef`.
+    pub(crate) node: &'a str,
+    /// Source text the node can be parsed in, with `$` in place of the node.
+    pub(crate) context: &'a str,
+    /// The text of the node. `{{` and `}}` are literal braces, and
+    /// `{prefix$name...}` is a parameter of the builder, named after the
+    /// accessor of a child of the node in `AST_SRC`, which gives its type:
+    ///
+    /// * `{$name}` is a required child,
+    /// * `{prefix$name?suffix}` is an optional child, rendered with `prefix`
+    ///   and `suffix` around it when present,
+    /// * `{prefix$name*sep}` is a list of children separated by `sep`,
+    ///   preceded by `prefix` unless the list is empty,
+    /// * `{prefix$name+sep}` is a list as well, but a single element is
+    ///   followed by a trailing separator, as in `(T,)`,
+    /// * `{prefix$name%sep}` is a list with `sep` after every element.
+    ///
+    /// Parameters the node has no generated accessor for spell out their type,
+    /// as in `{$lhs: Expr}`. The type may also be an operator, as in
+    /// `{$op_kind: BinOp}`, a token taken as text, as in `{$text: IDENT}`, or
+    /// `bool` for a flag: `{prefix$name: bool}` renders `prefix` if it is set.
+    ///
+    /// Builders take their parameters in the order of the template, and
+    /// re-indent the node if the template spans several lines.
+    pub(crate) template: &'a str,
+}
+
+macro_rules! make_nodes {
+    ($($node:ident in $context:literal => $template:literal),*$(,)?) => {
+        [$(
+            MakeSrc { node: stringify!($node), context: $context, template: $template }
+        ),*]
+    };
+}
+
+pub(crate) const MAKE_SRC: &[MakeSrc] = &make_nodes! {
+    SourceFile in "$" => "{$items*\n\n}",
+    FnDef in "$" =>
+        "{$visibility? }{async $is_async: bool}fn {$name}{$type_param_list?}{$param_list}{ $ret_type?}{ $where_clause?} {$body}",
+    RetType in "fn f() $ {}" => "-> {$type_ref}",
+    StructDef in "$" =>
+        "{$visibility? }struct {$name}{$type_param_list?}{ $where_clause?} {$record_field_def_list: RecordFieldDefList}",
+    UnionDef in "$" =>
+        "{$visibility? }union {$name}{$type_param_list?}{ $where_clause?} {$record_field_def_list}",
+    RecordFieldDefList in "struct S $" => "{{ {$fields*, } }}",
+    RecordFieldDef in "struct S { $ }" => "{$visibility? }{$name}: {$ascribed_type}",
+    TupleFieldDefList in "struct S$;" => "({$fields*, })",
+    TupleFieldDef in "struct S($);" => "{$visibility? }{$type_ref}",
+    EnumDef in "$" =>
+        "{$visibility? }enum {$name}{$type_param_list?}{ $where_clause?} {$variant_list}",
+    EnumVariantList in "enum E $" => "{{ {$variants*, } }}",
+    EnumVariant in "enum E { $ }" => "{$name}{$tuple_field_def_list: TupleFieldDefList?}",
+    TraitDef in "$" =>
+        "{$visibility? }trait {$name}{$type_param_list?}{: $type_bound_list?}{ $where_clause?} {$item_list}",
+    Module in "$" => "{$visibility? }mod {$name} {$item_list}",
+    ItemList in "impl S $" => "{{ {$impl_items* } }}",
+    ConstDef in "$" => "{$visibility? }const {$name}: {$ascribed_type} = {$body};",
+    StaticDef in "$" =>
+        "{$visibility? }static {mut $mutable: bool}{$name}: {$ascribed_type} = {$body};",
+    TypeAliasDef in "$" =>
+        "{$visibility? }type {$name}{$type_param_list?}{ $where_clause?} = {$type_ref};",
+    ImplBlock in "$" =>
+        "impl{$type_param_list?} {$target_trait: TypeRef? for }{$target_type: TypeRef}{ $where_clause?} {$item_list}",
+    UseItem in "$" => "{$visibility? }use {$use_tree};",
+    UseTree in "use $;" => "{$path}{::$use_tree_list?}",
+    UseTreeList in "use a::$;" => "{{{$use_trees*, }}}",
+    Alias in "use a $;" => "as {$name}",
+    ExternCrateItem in "$" => "{$visibility? }extern crate {$name_ref}{ $alias?};",
+    MacroCall in "const C: () = $;" => "{$path}!{$token_tree}",
+    TokenTree in "const C: () = m!$;" => "({$exprs: Expr*, })",
+    Attr in "$ fn f() {}" => "#[{$path}{$token_tree: TokenTree?}]",
+    Visibility in "$ fn f() {}" => "pub{(in $path: Path?)}",
+    Name in "mod $;" => "{$text: IDENT}",
+    NameRef in "fn f() { $; }" => "{$text: IDENT}",
+    Label in "const C: () = $ loop {};" => "{$lifetime: LIFETIME}:",
+
+    ParenType in "type T = $;" => "({$type_ref})",
+    TupleType in "type T = $;" => "({$fields+, })",
+    NeverType in "type T = $;" => "!",
+    PathType in "type T = $;" => "{$path}",
+    ReferenceType in "type T = $;" => "&{mut $mutable: bool}{$type_ref}",
+    ArrayType in "type T = $;" => "[{$type_ref}; {$expr}]",
+    SliceType in "type T = $;" => "[{$type_ref}]",
+    PlaceholderType in "type T = $;" => "_",
+    FnPointerType in "type T = $;" => "fn{$param_list}{ $ret_type?}",
+    ForType in "type T = $;" => "for{$type_param_list: TypeParamList} {$type_ref}",
+    ImplTraitType in "type T = $;" => "impl {$type_bound_list}",
+    DynTraitType in "type T = $;" => "dyn {$type_bound_list}",
+
+    TupleExpr in "const C: () = $;" => "({$exprs+, })",
+    ArrayExpr in "const C: () = $;" => "[{$exprs*, }]",
+    ParenExpr in "const C: () = $;" => "({$expr})",
+    PathExpr in "const C: () = $;" => "{$path}",
+    LambdaExpr in "const C: () = $;" => "{move $is_move: bool}|{$params: Param*, }| {$body}",
+    IfExpr in "const C: () = $;" =>
+        "if {$condition} {$then_branch: BlockExpr}{ else $else_branch: BlockExpr?}",
+    LoopExpr in "const C: () = $;" => "{$label: Label? }loop {$loop_body}",
+    ForExpr in "const C: () = $;" =>
+        "{$label: Label? }for {$pat} in {$iterable} {$loop_body}",
+    WhileExpr in "const C: () = $;" => "{$label: Label? }while {$condition} {$loop_body}",
+    ContinueExpr in "const C: () = $;" => "continue",
+    BreakExpr in "const C: () = $;" => "break{ $expr?}",
+    BlockExpr in "const C: () = $;" => "{unsafe $is_unsafe: bool}{$block}",
+    Block in "fn f() $" => "{{{\n$statements%\n}{ $expr? }}}",
+    ReturnExpr in "const C: () = $;" => "return{ $expr?}",
+    MatchExpr in "const C: () = $;" => "match {$expr} {$match_arm_list}",
+    MatchArmList in "fn f() { match () $ }" => "{{\n{$arms%,\n}}}",
+    MatchArm in "fn f() { match () { $ } }" => "{$pats* | }{ $guard?} => {$expr}",
+    MatchGuard in "fn f() { match () { _ $ => (), } }" => "if {$expr}",
+    RecordLit in "const C: () = $;" => "{$path} {$record_field_list}",
+    RecordFieldList in "const C: () = S $;" => "{{ {$fields*, } }}",
+    RecordField in "const C: () = S { $ };" => "{$name_ref}{: $expr?}",
+    CallExpr in "const C: () = $;" => "{$expr}{$arg_list}",
+    ArgList in "const C: () = f$;" => "({$args*, })",
+    MethodCallExpr in "const C: () = $;" => "{$expr}.{$name_ref}{$type_arg_list?}{$arg_list}",
+    IndexExpr in "const C: () = $;" => "{$base: Expr}[{$index: Expr}]",
+    FieldExpr in "const C: () = $;" => "{$expr}.{$name_ref}",
+    AwaitExpr in "const C: () = $;" => "{$expr}.await",
+    TryExpr in "const C: () = $;" => "{$expr}?",
+    CastExpr in "const C: () = $;" => "{$expr} as {$type_ref}",
+    RefExpr in "const C: () = $;" => "&{mut $mutable: bool}{$expr}",
+    PrefixExpr in "const C: () = $;" => "{$op_kind: PrefixOp}{$expr}",
+    BoxExpr in "const C: () = $;" => "box {$expr}",
+    RangeExpr in "const C: () = $;" => "{$start: Expr?}{$op_kind: RangeOp}{$end: Expr?}",
+    BinExpr in "const C: () = $;" => "{$lhs: Expr} {$op_kind: BinOp} {$rhs: Expr}",
+
+    OrPat in "fn f() { match () { [$] => (), } }" => "{$pats* | }",
+    ParenPat in "fn f() { match () { $ => (), } }" => "({$pat})",
+    RefPat in "fn f() { match () { $ => (), } }" => "&{mut $mutable: bool}{$pat}",
+    BoxPat in "fn f() { match () { $ => (), } }" => "box {$pat}",
+    BindPat in "fn f() { match () { $ => (), } }" =>
+        "{ref $is_ref: bool}{mut $is_mutable: bool}{$name}{ @ $pat?}",
+    PlaceholderPat in "fn f() { match () { $ => (), } }" => "_",
+    DotDotPat in "fn f() { let ($) = (); }" => "..",
+    PathPat in "fn f() { match () { $ => (), } }" => "{$path}",
+    SlicePat in "fn f() { match () { $ => (), } }" => "[{$args*, }]",
+    RangePat in "fn f() { match () { $ => (), } }" =>
+        "{$start: LiteralPat}..={$end: LiteralPat}",
+    LiteralPat in "fn f() { match () { $ => (), } }" => "{$literal}",
+    RecordPat in "fn f() { match () { $ => (), } }" => "{$path} {$record_field_pat_list}",
+    RecordFieldPatList in "fn f() { match () { S $ => (), } }" =>
+        "{{ {$record_field_pats%, }{$bind_pats*, } }}",
+    RecordFieldPat in "fn f() { match () { S { $ } => (), } }" => "{$name}: {$pat}",
+    TupleStructPat in "fn f() { match () { $ => (), } }" => "{$path}({$args*, })",
+    TuplePat in "fn f() { match () { $ => (), } }" => "({$args+, })",
+
+    TypeParamList in "fn f$() {}" => "<{$lifetime_params%, }{$type_params*, }>",
+    TypeParam in "fn f<$>() {}" => "{$name}{: $type_bound_list?}{ = $default_type?}",
+    ConstParam in "fn f<$>() {}" => "const {$name}: {$ascribed_type}",
+    LifetimeParam in "fn f<$>() {}" => "{$lifetime: LIFETIME}",
+    TypeBound in "fn f<T: $>() {}" => "{$type_ref}",
+    TypeBoundList in "fn f<T: $>() {}" => "{$bounds* + }",
+    WherePred in "fn f() where $ {}" => "{$type_ref}: {$type_bound_list}",
+    WhereClause in "fn f() $ {}" => "where {$predicates*, }",
+    ExprStmt in "fn f() { $ }" => "{$expr};",
+    LetStmt in "fn f() { $ }" => "let {$pat}{: $ascribed_type?}{ = $initializer?};",
+    Condition in "fn f() { if $ {} }" => "{let $pat? = }{$expr}",
+    ParamList in "fn f$ {}" => "({$self_param?, }{$params*, })",
+    SelfParam in "impl S { fn f($) {} }" => "{&$by_ref: bool}{mut $mutable: bool}self",
+    Param in "fn f($) {}" => "{$pat}: {$ascribed_type}",
+    Path in "type T = $;" => "{$qualifier?::}{$segment}",
+    PathSegment in "type T = $;" => "{$name_ref}{$type_arg_list?}",
+    TypeArgList in "type T = S$;" => "::<{$type_args*, }>",
+    TypeArg in "type T = S<$>;" => "{$type_ref}",
+    AssocTypeArg in "type T = S<$>;" => "{$name_ref} = {$type_ref}",
+};
@@ -6,6 +6,7 @@
 //! This module's submodules define specific bits that we generate.
 
 mod gen_syntax;
+mod gen_make;
 mod gen_parser_tests;
 mod gen_assists_docs;
 
@@ -14,8 +15,8 @@ use std::{fs, mem, path::Path};
 use crate::Result;
 
 pub use self::{
-    gen_assists_docs::generate_assists_docs, gen_parser_tests::generate_parser_tests,
-    gen_syntax::generate_syntax,
+    gen_assists_docs::generate_assists_docs, gen_make::generate_make,
+    gen_parser_tests::generate_parser_tests, gen_syntax::generate_syntax,
 };
 
 const GRAMMAR_DIR: &str = "crates/ra_parser/src/grammar";
@@ -24,6 +25,7 @@ const ERR_INLINE_TESTS_DIR: &str = "crates/ra_syntax/test_data/parser/inline/err
 
 pub const SYNTAX_KINDS: &str = "crates/ra_parser/src/syntax_kind/generated.rs";
 pub const AST: &str = "crates/ra_syntax/src/ast/generated.rs";
+pub const AST_MAKE: &str = "crates/ra_syntax/src/ast/make/generated.rs";
 
 const ASSISTS_DIR: &str = "crates/ra_assists/src/assists";
 const ASSISTS_TESTS: &str = "crates/ra_assists/src/doc_tests/generated.rs";
@@ -0,0 +1,357 @@
+//! Generates typed builders for `ra_syntax::ast::make` from the templates in
+//! `ast_src::MAKE_SRC`.
+//!
+//! The parameters of a builder are the children of the node its template
+//! mentions, typed as in `ast_src::AST_SRC` and ordered as in the template.
+//! Each builder renders its template to text and parses it in the template's
+//! context, so the result is always a real, parsed node. A test checking that
+//! every builder produces text without syntax errors is generated as well.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+
+use crate::{
+    ast_src::{AstNodeSrc, AstSrc, FieldSrc, MakeSrc, AST_SRC, MAKE_SRC},
+    codegen::{self, gen_syntax::to_lower_snake_case, update, Mode},
+    project_root, Result,
+};
+
+pub fn generate_make(mode: Mode) -> Result<()> {
+    let make_file = project_root().join(codegen::AST_MAKE);
+    let make = generate_builders(AST_SRC, MAKE_SRC)?;
+    update(make_file.as_path(), &make, mode)?;
+    Ok(())
+}
+
+/// Children the traits of `ra_syntax::ast::traits` give to the nodes
+/// implementing them.
+const TRAIT_FIELDS: &[(&str, &str, FieldSrc<&str>)] = &[
+    ("VisibilityOwner", "visibility", FieldSrc::Optional("Visibility")),
+    ("NameOwner", "name", FieldSrc::Optional("Name")),
+    ("TypeParamsOwner", "type_param_list", FieldSrc::Optional("TypeParamList")),
+    ("TypeParamsOwner", "where_clause", FieldSrc::Optional("WhereClause")),
+    ("TypeAscriptionOwner", "ascribed_type", FieldSrc::Optional("TypeRef")),
+    ("TypeBoundsOwner", "type_bound_list", FieldSrc::Optional("TypeBoundList")),
+    ("LoopBodyOwner", "loop_body", FieldSrc::Optional("BlockExpr")),
+    ("ArgListOwner", "arg_list", FieldSrc::Optional("ArgList")),
+    ("AttrsOwner", "attrs", FieldSrc::Many("Attr")),
+    ("ModuleItemOwner", "items", FieldSrc::Many("ModuleItem")),
+    ("FnDefOwner", "functions", FieldSrc::Many("FnDef")),
+];
+
+/// Operator enums from `ra_syntax::ast::expr_extensions`, rendered with their
+/// `text` method.
+const OPERATORS: &[&str] = &["BinOp", "PrefixOp", "RangeOp"];
+
+#[derive(Debug)]
+enum Segment<'a> {
+    Text(String),
+    Param { name: &'a str, ty: Option<&'a str>, kind: ParamKind<'a> },
+}
+
+#[derive(Debug)]
+enum ParamKind<'a> {
+    Required,
+    Optional { prefix: &'a str, suffix: &'a str },
+    Many { prefix: &'a str, sep: &'a str, list: ListKind },
+    Flag { text: &'a str },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ListKind {
+    Separated,
+    Trailing,
+    Terminated,
+}
+
+/// A child of a node: the name of its accessor, its type and whether there
+/// can be several of them.
+struct Field<'a> {
+    name: String,
+    ty: &'a str,
+    many: bool,
+}
+
+fn generate_builders(grammar: AstSrc<'_>, make: &[MakeSrc<'_>]) -> Result<String> {
+    let mut builders = Vec::new();
+    let mut checks = Vec::new();
+    for src in make {
+        let node_src = match grammar.nodes.iter().find(|it| it.name == src.node) {
+            Some(it) => it,
+            None => anyhow::bail!("`{}` is not a node of `AST_SRC`", src.node),
+        };
+        let fields = node_fields(node_src);
+        let segments = parse_template(src.template)?;
+        let fn_name = format_ident!("{}", to_lower_snake_case(src.node));
+        let node = format_ident!("{}", src.node);
+        let context = src.context;
+
+        let mut params = Vec::new();
+        let mut pushes = Vec::new();
+        for segment in segments.iter() {
+            let (name, ty, kind) = match segment {
+                Segment::Text(text) => {
+                    pushes.push(quote!(buf.push_str(#text);));
+                    continue;
+                }
+                Segment::Param { name, ty, kind } => (*name, *ty, kind),
+            };
+            let field = fields.iter().position(|it| it.name == name);
+            let ty = match (ty, field) {
+                (None, Some(pos)) => {
+                    let is_list = if let ParamKind::Many { .. } = kind { true } else { false };
+                    if fields[pos].many != is_list {
+                        anyhow::bail!(
+                            "`{}` of `{}` is rendered as a {}",
+                            name,
+                            src.node,
+                            if is_list { "list" } else { "single node" }
+                        );
+                    }
+                    fields[pos].ty
+                }
+                (Some(ty), None) => ty,
+                (None, None) => anyhow::bail!("`{}` has no `{}` in `AST_SRC`", src.node, name),
+                (Some(_), Some(_)) => {
+                    anyhow::bail!("the type of `{}` of `{}` comes from `AST_SRC`", name, src.node)
+                }
+            };
+            let (param, arg, push) = render_param(src.node, name, ty, kind)?;
+            params.push((param, arg));
+            pushes.push(push);
+        }
+        let args = params.iter().map(|(_, arg)| arg);
+        let params = params.iter().map(|(param, _)| param);
+
+        // Nodes spanning several lines are indented like the rest of the code.
+        let node_from_text = if src.template.contains('\n') {
+            quote!(IndentLevel(0).reformat(ast_from_template(#context, &buf)))
+        } else {
+            quote!(ast_from_template(#context, &buf))
+        };
+        builders.push(quote! {
+            pub fn #fn_name(#(#params),*) -> ast::#node {
+                let mut buf = String::new();
+                #(#pushes)*
+                #node_from_text
+            }
+        });
+        checks.push(quote!(check_round_trip(#fn_name(#(#args),*), #context);));
+    }
+
+    let make = quote! {
+        use crate::{
+            ast::{self, edit::IndentLevel, make::ast_from_template},
+            AstNode,
+        };
+
+        #(#builders)*
+
+        #[cfg(test)]
+        mod tests {
+            use super::*;
+            use crate::ast::make::{check_round_trip, sample};
+
+            #[test]
+            fn builders_round_trip() {
+                #(#checks)*
+            }
+        }
+    };
+
+    crate::reformat(make)
+}
+
+/// Children of `node`, including the ones of its traits.
+fn node_fields<'a>(node: &AstNodeSrc<'a>) -> Vec<Field<'a>> {
+    let trait_fields = node.traits.iter().flat_map(|trait_name| {
+        TRAIT_FIELDS
+            .iter()
+            .filter(move |(it, ..)| it == trait_name)
+            .map(|(_, name, field)| (*name, field))
+    });
+    let fields = node.fields.iter().map(|(name, field)| (*name, field));
+    trait_fields
+        .chain(fields)
+        .map(|(name, field)| match field {
+            FieldSrc::Shorthand => Field { name: to_lower_snake_case(name), ty: name, many: false },
+            FieldSrc::Optional(ty) => Field { name: name.to_string(), ty: *ty, many: false },
+            FieldSrc::Many(ty) => Field { name: name.to_string(), ty: *ty, many: true },
+        })
+        .collect()
+}
+
+/// Returns the parameter, its value in the round trip test and the code
+/// rendering it.
+fn render_param(
+    node: &str,
+    name: &str,
+    ty: &str,
+    kind: &ParamKind<'_>,
+) -> Result<(TokenStream, TokenStream, TokenStream)> {
+    let node = format_ident!("{}", node);
+    let name = format_ident!("{}", name);
+    if let ParamKind::Flag { text } = kind {
+        let push = quote! {
+            if #name {
+                buf.push_str(#text);
+            }
+        };
+        return Ok((quote!(#name: bool), quote!(true), push));
+    }
+    if ty.chars().all(|c| c.is_ascii_uppercase() || c == '_') {
+        let sample = match (ty, kind) {
+            ("IDENT", ParamKind::Required) => "foo",
+            ("LIFETIME", ParamKind::Required) => "'a",
+            _ => anyhow::bail!("unsupported `{}` token `{}` of `{}`", ty, name, node),
+        };
+        return Ok((quote!(#name: &str), quote!(#sample), quote!(buf.push_str(#name);)));
+    }
+    let is_operator = OPERATORS.contains(&ty);
+    let ty = format_ident!("{}", ty);
+    if is_operator {
+        if let ParamKind::Required = kind {
+            let arg = quote!(sample::<ast::#node>().#name().unwrap());
+            return Ok((quote!(#name: ast::#ty), arg, quote!(buf.push_str(#name.text());)));
+        }
+        anyhow::bail!("operator `{}` of `{}` must be required", name, node);
+    }
+
+    let res = match kind {
+        ParamKind::Required => (
+            quote!(#name: ast::#ty),
+            quote!(sample()),
+            quote!(buf.push_str(&#name.syntax().to_string());),
+        ),
+        ParamKind::Optional { prefix, suffix } => {
+            let prefix = push_str(prefix);
+            let suffix = push_str(suffix);
+            let push = quote! {
+                if let Some(it) = #name {
+                    #prefix
+                    buf.push_str(&it.syntax().to_string());
+                    #suffix
+                }
+            };
+            (quote!(#name: Option<ast::#ty>), quote!(Some(sample())), push)
+        }
+        ParamKind::Many { prefix, sep, list } => {
+            let prefix = push_str(prefix).map(|prefix| {
+                quote! {
+                    if !#name.is_empty() {
+                        #prefix
+                    }
+                }
+            });
+            let elements = match list {
+                ListKind::Separated | ListKind::Trailing => {
+                    quote!(buf.push_str(&#name.join(#sep));)
+                }
+                ListKind::Terminated => quote! {
+                    for it in #name.iter() {
+                        buf.push_str(it);
+                        buf.push_str(#sep);
+                    }
+                },
+            };
+            let trailing = if *list == ListKind::Trailing {
+                let trailing = sep.trim_end();
+                Some(quote! {
+                    if #name.len() == 1 {
+                        buf.push_str(#trailing);
+                    }
+                })
+            } else {
+                None
+            };
+            let push = quote! {
+                let #name: Vec<String> =
+                    #name.into_iter().map(|it| it.syntax().to_string()).collect();
+                #prefix
+                #elements
+                #trailing
+            };
+            (
+                quote!(#name: impl IntoIterator<Item = ast::#ty>),
+                quote!(vec![sample(), sample()]),
+                push,
+            )
+        }
+        ParamKind::Flag { .. } => unreachable!(),
+    };
+    Ok(res)
+}
+
+fn push_str(text: &str) -> Option<TokenStream> {
+    if text.is_empty() {
+        None
+    } else {
+        Some(quote!(buf.push_str(#text);))
+    }
+}
+
+fn parse_template(template: &str) -> Result<Vec<Segment<'_>>> {
+    let mut segments = Vec::new();
+    let mut text = String::new();
+    let mut rest = template;
+    while let Some(c) = rest.chars().next() {
+        if rest.starts_with("{{") || rest.starts_with("}}") {
+            text.push(c);
+            rest = &rest[2..];
+            continue;
+        }
+        if c == '}' {
+            anyhow::bail!("unmatched `}}` in template `{}`", template);
+        }
+        if c != '{' {
+            text.push(c);
+            rest = &rest[c.len_utf8()..];
+            continue;
+        }
+        let end = match rest.find('}') {
+            Some(it) => it,
+            None => anyhow::bail!("unclosed `{{` in template `{}`", template),
+        };
+        if !text.is_empty() {
+            segments.push(Segment::Text(std::mem::replace(&mut text, String::new())));
+        }
+        segments.push(parse_param(&rest[1..end], template)?);
+        rest = &rest[end + 1..];
+    }
+    if !text.is_empty() {
+        segments.push(Segment::Text(text));
+    }
+    Ok(segments)
+}
+
+/// Parses `prefix$name?suffix`, `prefix$name: Type?suffix` and friends, see
+/// `ast_src::MakeSrc`.
+fn parse_param<'a>(param: &'a str, template: &str) -> Result<Segment<'a>> {
+    let malformed = || anyhow::format_err!("malformed `{{{}}}` in template `{}`", param, template);
+    let dollar = param.find('$').ok_or_else(malformed)?;
+    let (prefix, param) = (&param[..dollar], &param[dollar + 1..]);
+    let name_len = param.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'));
+    let (name, rest) = param.split_at(name_len.unwrap_or(param.len()));
+    let (ty, rest) = if rest.starts_with(':') {
+        let rest = rest[1..].trim_start();
+        let ty_len = rest.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'));
+        let (ty, rest) = rest.split_at(ty_len.unwrap_or(rest.len()));
+        (Some(ty), rest)
+    } else {
+        (None, rest)
+    };
+    if name.is_empty() || ty == Some("") {
+        return Err(malformed());
+    }
+
+    let kind = match (ty, rest.chars().next()) {
+        (Some("bool"), None) => ParamKind::Flag { text: prefix },
+        (_, None) if prefix.is_empty() => ParamKind::Required,
+        (_, Some('?')) => ParamKind::Optional { prefix, suffix: &rest[1..] },
+        (_, Some('*')) => ParamKind::Many { prefix, sep: &rest[1..], list: ListKind::Separated },
+        (_, Some('+')) => ParamKind::Many { prefix, sep: &rest[1..], list: ListKind::Trailing },
+        (_, Some('%')) => ParamKind::Many { prefix, sep: &rest[1..], list: ListKind::Terminated },
+        _ => return Err(malformed()),
+    };
+    Ok(Segment::Param { name, ty, kind })
+}
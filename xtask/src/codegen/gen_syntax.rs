@@ -282,7 +282,7 @@ fn to_upper_snake_case(s: &str) -> String {
     buf
 }
 
-fn to_lower_snake_case(s: &str) -> String {
+pub(super) fn to_lower_snake_case(s: &str) -> String {
     let mut buf = String::with_capacity(s.len());
     let mut prev_is_upper = None;
     for c in s.chars() {
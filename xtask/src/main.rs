@@ -68,6 +68,7 @@ FLAGS:
         "codegen" => {
             args.finish()?;
             codegen::generate_syntax(Mode::Overwrite)?;
+            codegen::generate_make(Mode::Overwrite)?;
             codegen::generate_parser_tests(Mode::Overwrite)?;
             codegen::generate_assists_docs(Mode::Overwrite)?;
             Ok(())
@@ -11,6 +11,13 @@ fn generated_grammar_is_fresh() {
     }
 }
 
+#[test]
+fn generated_make_is_fresh() {
+    if let Err(error) = codegen::generate_make(Mode::Verify) {
+        panic!("{}. Please update it by running `cargo xtask codegen`", error);
+    }
+}
+
 #[test]
 fn generated_tests_are_fresh() {
     if let Err(error) = codegen::generate_parser_tests(Mode::Verify) {